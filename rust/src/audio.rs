@@ -0,0 +1,624 @@
+//! Decoding of the sound files and resampling to the rate of the audio device, replacing `SDL_LoadWAV_RW` and
+//! `SDL_ConvertAudio` in `SoundMan.cc`.
+//!
+//! The game uses WAV files with 8 and 16-bit PCM for most sounds and IMA ADPCM for the speech. Mods can use Ogg Vorbis
+//! and FLAC files as well, the format is detected by the signature of the file, not its extension. The samples are
+//! resampled with a windowed sinc filter, which also removes the frequencies above the new Nyquist frequency when
+//! downsampling. Files with 3 to 8 channels are downmixed to stereo, the sound mixer only plays mono and stereo.
+
+use std::f32::consts::FRAC_1_SQRT_2;
+use std::f64::consts::PI;
+use std::io::Cursor;
+
+use byteorder::{ByteOrder, LittleEndian};
+use claxon::FlacReader;
+use lewton::inside_ogg::OggStreamReader;
+
+pub const WAVE_FORMAT_PCM: u16 = 0x0001;
+pub const WAVE_FORMAT_IMA_ADPCM: u16 = 0x0011;
+
+/// Zero crossings of the sinc function on each side of the filter.
+const SINC_ZERO_CROSSINGS: usize = 16;
+/// Number of precomputed fractional positions of the filter, positions in between use the nearest one.
+const SINC_PHASES: usize = 256;
+
+static IMA_INDEX_TABLE: [i32; 16] = [-1, -1, -1, -1, 2, 4, 6, 8, -1, -1, -1, -1, 2, 4, 6, 8];
+
+static IMA_STEP_TABLE: [i32; 89] = [
+    7, 8, 9, 10, 11, 12, 13, 14, 16, 17, 19, 21, 23, 25, 28, 31, 34, 37, 41, 45, 50, 55, 60, 66, 73, 80, 88, 97, 107,
+    118, 130, 143, 157, 173, 190, 209, 230, 253, 279, 307, 337, 371, 408, 449, 494, 544, 598, 658, 724, 796, 876, 963,
+    1060, 1166, 1282, 1411, 1552, 1707, 1878, 2066, 2272, 2499, 2749, 3024, 3327, 3660, 4026, 4428, 4871, 5358, 5894,
+    6484, 7132, 7845, 8630, 9493, 10442, 11487, 12635, 13899, 15289, 16818, 18500, 20350, 22385, 24623, 27086, 29794,
+    32767,
+];
+
+/// The formats of sound files, see `detect_audio_format`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AudioFormat {
+    Wav,
+    OggVorbis,
+    Flac,
+}
+
+/// Decoded audio as signed 16-bit samples, interleaved if there is more than one channel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioData {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub samples: Vec<i16>,
+}
+
+impl AudioData {
+    /// Number of samples per channel.
+    pub fn frames(&self) -> usize {
+        self.samples.len() / self.channels as usize
+    }
+}
+
+/// The `fmt ` chunk of a WAV file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct WavFormat {
+    format: u16,
+    channels: u16,
+    sample_rate: u32,
+    block_align: u16,
+    bits_per_sample: u16,
+}
+
+/// Returns the `fmt ` and `data` chunks of a RIFF WAVE file.
+fn read_chunks(data: &[u8]) -> Result<(WavFormat, &[u8]), String> {
+    if data.len() < 12 || &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+        return Err(String::from("Not a WAV file"));
+    }
+
+    let mut format = None;
+    let mut position = 12;
+    while position + 8 <= data.len() {
+        let size = LittleEndian::read_u32(&data[position + 4..position + 8]) as usize;
+        // Some files have a wrong size for the last chunk
+        let chunk = &data[position + 8..data.len().min(position + 8 + size)];
+        match &data[position..position + 4] {
+            b"fmt " if chunk.len() >= 16 => format = Some(WavFormat {
+                format: LittleEndian::read_u16(&chunk[0..2]),
+                channels: LittleEndian::read_u16(&chunk[2..4]),
+                sample_rate: LittleEndian::read_u32(&chunk[4..8]),
+                block_align: LittleEndian::read_u16(&chunk[12..14]),
+                bits_per_sample: LittleEndian::read_u16(&chunk[14..16]),
+            }),
+            b"fmt " => return Err(String::from("Truncated fmt chunk")),
+            b"data" => return format.map(|f| (f, chunk)).ok_or_else(|| String::from("The data chunk comes before the fmt chunk")),
+            _ => {},
+        }
+        // Chunks are padded to an even size
+        position += 8 + size + (size & 1);
+    }
+    Err(String::from(if format.is_some() { "No data chunk" } else { "No fmt chunk" }))
+}
+
+fn decode_ima_nibble(nibble: u8, predictor: &mut i32, index: &mut i32) -> i16 {
+    let step = IMA_STEP_TABLE[*index as usize];
+    let mut diff = step >> 3;
+    if nibble & 1 != 0 { diff += step >> 2; }
+    if nibble & 2 != 0 { diff += step >> 1; }
+    if nibble & 4 != 0 { diff += step; }
+    if nibble & 8 != 0 { *predictor -= diff } else { *predictor += diff }
+
+    *predictor = (*predictor).clamp(i32::from(i16::MIN), i32::from(i16::MAX));
+    *index = (*index + IMA_INDEX_TABLE[nibble as usize]).clamp(0, 88);
+    *predictor as i16
+}
+
+/// Decodes the Microsoft IMA ADPCM blocks: a header with the first sample and the step index of every channel, then
+/// groups of 4 bytes with 8 samples per channel, low nibble first.
+fn decode_ima_adpcm(format: &WavFormat, data: &[u8]) -> Result<Vec<i16>, String> {
+    let channels = format.channels as usize;
+    let block_align = format.block_align as usize;
+    if format.bits_per_sample != 4 || block_align <= 4 * channels || (block_align - 4 * channels) % (4 * channels) != 0 {
+        return Err(format!("Invalid IMA ADPCM block size {} for {} channel(s)", block_align, channels));
+    }
+
+    let mut samples = Vec::with_capacity(data.len() * 2);
+    for block in data.chunks(block_align).filter(|b| b.len() > 4 * channels) {
+        let mut predictors = Vec::with_capacity(channels);
+        let mut indexes = Vec::with_capacity(channels);
+        for header in block[..4 * channels].chunks(4) {
+            predictors.push(i32::from(LittleEndian::read_i16(&header[0..2])));
+            indexes.push(i32::from(header[2]).min(88));
+        }
+        samples.extend(predictors.iter().map(|&p| p as i16));
+
+        // Every group holds 8 samples for each channel
+        for group in block[4 * channels..].chunks(4 * channels).filter(|g| g.len() == 4 * channels) {
+            let mut decoded = vec!(0i16; 8 * channels);
+            for (channel, bytes) in group.chunks(4).enumerate() {
+                for (i, &byte) in bytes.iter().enumerate() {
+                    decoded[(2 * i) * channels + channel] = decode_ima_nibble(byte & 0x0F, &mut predictors[channel], &mut indexes[channel]);
+                    decoded[(2 * i + 1) * channels + channel] = decode_ima_nibble(byte >> 4, &mut predictors[channel], &mut indexes[channel]);
+                }
+            }
+            samples.extend(decoded);
+        }
+    }
+    Ok(samples)
+}
+
+/// Decodes a WAV file with 8 or 16-bit PCM or IMA ADPCM samples.
+pub fn decode_wav(data: &[u8]) -> Result<AudioData, String> {
+    let (format, chunk) = read_chunks(data)?;
+    if format.channels == 0 || format.sample_rate == 0 {
+        return Err(format!("Invalid WAV format with {} channel(s) at {} Hz", format.channels, format.sample_rate));
+    }
+
+    let samples = match (format.format, format.bits_per_sample) {
+        (WAVE_FORMAT_PCM, 8) => chunk.iter().map(|&b| (i16::from(b) - 128) << 8).collect(),
+        (WAVE_FORMAT_PCM, 16) => chunk.chunks(2).filter(|c| c.len() == 2).map(LittleEndian::read_i16).collect(),
+        (WAVE_FORMAT_IMA_ADPCM, _) => decode_ima_adpcm(&format, chunk)?,
+        (f, bits) => return Err(format!("Unsupported WAV format 0x{:04x} with {} bits per sample", f, bits)),
+    };
+
+    // Drop an incomplete last frame
+    let mut samples: Vec<i16> = samples;
+    let channels = format.channels as usize;
+    samples.truncate(samples.len() / channels * channels);
+    Ok(AudioData { sample_rate: format.sample_rate, channels: format.channels, samples })
+}
+
+/// Detects the format of a sound file by its signature.
+pub fn detect_audio_format(data: &[u8]) -> Option<AudioFormat> {
+    if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WAVE" {
+        Some(AudioFormat::Wav)
+    } else if data.starts_with(b"OggS") {
+        Some(AudioFormat::OggVorbis)
+    } else if data.starts_with(b"fLaC") {
+        Some(AudioFormat::Flac)
+    } else {
+        None
+    }
+}
+
+/// Checks that a sound file is in a supported format without decoding it.
+pub fn check_audio_header(data: &[u8]) -> Result<AudioFormat, String> {
+    let format = detect_audio_format(data).ok_or_else(|| String::from("Not a WAV, Ogg Vorbis or FLAC file"))?;
+    if format == AudioFormat::Wav {
+        match read_chunks(data)? {
+            (WavFormat { format: WAVE_FORMAT_PCM, bits_per_sample: 8, .. }, _) |
+            (WavFormat { format: WAVE_FORMAT_PCM, bits_per_sample: 16, .. }, _) |
+            (WavFormat { format: WAVE_FORMAT_IMA_ADPCM, bits_per_sample: 4, .. }, _) => {},
+            (f, _) => return Err(format!("Unsupported WAV format 0x{:04x} with {} bits per sample", f.format, f.bits_per_sample)),
+        }
+        let channels = read_chunks(data)?.0.channels;
+        if channels > 2 && downmix_weights(false, channels).is_none() {
+            return Err(format!("Unsupported audio with {} channels", channels));
+        }
+    }
+    Ok(format)
+}
+
+/// Decodes the first logical stream of an Ogg Vorbis file.
+pub fn decode_ogg_vorbis(data: &[u8]) -> Result<AudioData, String> {
+    let mut reader = OggStreamReader::new(Cursor::new(data)).map_err(|e| format!("Invalid Ogg Vorbis file: {}", e))?;
+    let channels = u16::from(reader.ident_hdr.audio_channels);
+    let sample_rate = reader.ident_hdr.audio_sample_rate;
+    if channels == 0 || sample_rate == 0 {
+        return Err(format!("Invalid Ogg Vorbis file with {} channel(s) at {} Hz", channels, sample_rate));
+    }
+
+    let mut samples = Vec::new();
+    while let Some(packet) = reader.read_dec_packet_itl().map_err(|e| format!("Invalid Ogg Vorbis file: {}", e))? {
+        samples.extend(packet);
+    }
+    Ok(AudioData { sample_rate, channels, samples })
+}
+
+/// Decodes a FLAC file, converting the samples to 16 bits.
+pub fn decode_flac(data: &[u8]) -> Result<AudioData, String> {
+    let mut reader = FlacReader::new(Cursor::new(data)).map_err(|e| format!("Invalid FLAC file: {}", e))?;
+    let info = reader.streaminfo();
+    let shift = info.bits_per_sample as i32 - 16;
+
+    let samples = reader.samples()
+        .map(|s| s.map(|s| if shift > 0 { (s >> shift) as i16 } else { (s << -shift) as i16 }))
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("Invalid FLAC file: {}", e))?;
+    Ok(AudioData { sample_rate: info.sample_rate, channels: info.channels as u16, samples })
+}
+
+/// Decodes a WAV, Ogg Vorbis or FLAC file, files with more than two channels are downmixed to stereo.
+pub fn decode_audio(data: &[u8]) -> Result<AudioData, String> {
+    let format = detect_audio_format(data);
+    let audio = match format {
+        Some(AudioFormat::Wav) => decode_wav(data)?,
+        Some(AudioFormat::OggVorbis) => decode_ogg_vorbis(data)?,
+        Some(AudioFormat::Flac) => decode_flac(data)?,
+        None => return Err(String::from("Not a WAV, Ogg Vorbis or FLAC file")),
+    };
+    if audio.channels <= 2 {
+        return Ok(audio);
+    }
+    let weights = downmix_weights(format == Some(AudioFormat::OggVorbis), audio.channels)
+        .ok_or_else(|| format!("Unsupported audio with {} channels", audio.channels))?;
+    Ok(downmix_to_stereo(&audio, &weights))
+}
+
+/// The left and right weights of every channel of a 3 to 8 channel layout.
+///
+/// WAV and FLAC use the order of `WAVEFORMATEXTENSIBLE` (front left, front right, center, LFE, back, side),
+/// Vorbis puts the center between the front channels and the LFE last. The LFE channel is dropped.
+fn downmix_weights(vorbis_order: bool, channels: u16) -> Option<Vec<(f32, f32)>> {
+    const L: (f32, f32) = (1.0, 0.0);
+    const R: (f32, f32) = (0.0, 1.0);
+    const C: (f32, f32) = (FRAC_1_SQRT_2, FRAC_1_SQRT_2);
+    const SL: (f32, f32) = (FRAC_1_SQRT_2, 0.0);
+    const SR: (f32, f32) = (0.0, FRAC_1_SQRT_2);
+    const BC: (f32, f32) = (0.5, 0.5);
+    const LFE: (f32, f32) = (0.0, 0.0);
+    let layout: &[(f32, f32)] = match (vorbis_order, channels) {
+        (false, 3) => &[L, R, C],
+        (false, 4) => &[L, R, SL, SR],
+        (false, 5) => &[L, R, C, SL, SR],
+        (false, 6) => &[L, R, C, LFE, SL, SR],
+        (false, 7) => &[L, R, C, LFE, BC, SL, SR],
+        (false, 8) => &[L, R, C, LFE, SL, SR, SL, SR],
+        (true, 3) => &[L, C, R],
+        (true, 4) => &[L, R, SL, SR],
+        (true, 5) => &[L, C, R, SL, SR],
+        (true, 6) => &[L, C, R, SL, SR, LFE],
+        (true, 7) => &[L, C, R, SL, SR, BC, LFE],
+        (true, 8) => &[L, C, R, SL, SR, SL, SR, LFE],
+        _ => return None,
+    };
+    Some(layout.to_vec())
+}
+
+/// Mixes the channels to stereo with `weights`, scaled down so that the mix can not clip.
+fn downmix_to_stereo(audio: &AudioData, weights: &[(f32, f32)]) -> AudioData {
+    let left_gain: f32 = weights.iter().map(|w| w.0).sum();
+    let right_gain: f32 = weights.iter().map(|w| w.1).sum();
+    let scale = 1.0 / left_gain.max(right_gain);
+    let samples = audio.samples.chunks(weights.len()).filter(|f| f.len() == weights.len()).flat_map(|frame| {
+        let (left, right) = frame.iter().zip(weights).fold((0.0, 0.0), |(l, r), (&s, w)| {
+            (l + f32::from(s) * w.0, r + f32::from(s) * w.1)
+        });
+        vec!((left * scale).round() as i16, (right * scale).round() as i16)
+    }).collect();
+    AudioData { sample_rate: audio.sample_rate, channels: 2, samples }
+}
+
+/// Blackman window over `-1..1`.
+fn blackman(x: f64) -> f64 {
+    0.42 + 0.5 * (PI * x).cos() + 0.08 * (2.0 * PI * x).cos()
+}
+
+fn sinc(x: f64) -> f64 {
+    if x == 0.0 { 1.0 } else { (PI * x).sin() / (PI * x) }
+}
+
+/// The windowed sinc filter for every phase, each row normalized to a gain of 1.
+fn sinc_table(cutoff: f64, half_width: usize) -> Vec<Vec<f32>> {
+    (0..=SINC_PHASES).map(|phase| {
+        let fraction = phase as f64 / SINC_PHASES as f64;
+        // Tap `j` weighs the source sample at distance `fraction + half_width - 1 - j` before the output position
+        let row: Vec<f64> = (0..2 * half_width).map(|j| {
+            let distance = fraction + half_width as f64 - 1.0 - j as f64;
+            sinc(distance * cutoff) * blackman(distance / half_width as f64)
+        }).collect();
+        let sum: f64 = row.iter().sum();
+        row.into_iter().map(|v| (v / sum) as f32).collect()
+    }).collect()
+}
+
+/// Resamples the audio to `sample_rate`, keeping the number of channels.
+///
+/// The filter is computed once per phase, every output frame uses the phase nearest to its position.
+pub fn resample(audio: &AudioData, sample_rate: u32) -> AudioData {
+    if audio.sample_rate == sample_rate || audio.samples.is_empty() {
+        return AudioData { sample_rate, ..audio.clone() };
+    }
+
+    let (from, to) = (u64::from(audio.sample_rate), u64::from(sample_rate));
+    let cutoff = (to as f64 / from as f64).min(1.0);
+    let half_width = (SINC_ZERO_CROSSINGS as f64 / cutoff).ceil() as usize;
+    let table = sinc_table(cutoff, half_width);
+    let channels = audio.channels as usize;
+    let frames = audio.frames() as i64;
+    let output_frames = (frames as u64 * to / from) as usize;
+
+    let mut samples = Vec::with_capacity(output_frames * channels);
+    for n in 0..output_frames as u64 {
+        // Output frame `n` is at source position `n * from / to`
+        let (whole, remainder) = ((n * from / to) as i64, n * from % to);
+        let coefficients = &table[((remainder * SINC_PHASES as u64 + to / 2) / to) as usize];
+        let first = whole - half_width as i64 + 1;
+
+        // The filter reaches beyond the first and the last frame, where the input is silence
+        let (start, end) = (first.max(0), (first + 2 * half_width as i64).min(frames));
+        for channel in 0..channels {
+            let sum: f32 = (start..end)
+                .map(|frame| f32::from(audio.samples[frame as usize * channels + channel]) * coefficients[(frame - first) as usize])
+                .sum();
+            samples.push(sum.round().clamp(f32::from(i16::MIN), f32::from(i16::MAX)) as i16);
+        }
+    }
+    AudioData { sample_rate, channels: audio.channels, samples }
+}
+
+/// Decodes a sound file and resamples it to the rate of the audio device.
+pub fn load_audio(data: &[u8], sample_rate: u32) -> Result<AudioData, String> {
+    Ok(resample(&decode_audio(data)?, sample_rate))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::{check_audio_header, decode_audio, downmix_weights, decode_wav, detect_audio_format, load_audio, resample, AudioData, AudioFormat, IMA_INDEX_TABLE, IMA_STEP_TABLE, WAVE_FORMAT_IMA_ADPCM, WAVE_FORMAT_PCM};
+    use test_utils::BitWriter;
+
+    fn sine(frequency: f64, sample_rate: u32, frames: usize, amplitude: f64) -> Vec<i16> {
+        (0..frames).map(|i| (amplitude * (2.0 * PI * frequency * i as f64 / f64::from(sample_rate)).sin()).round() as i16).collect()
+    }
+
+    fn wav_file(format: u16, channels: u16, sample_rate: u32, block_align: u16, bits: u16, extra: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut fmt = Vec::new();
+        for value in &[format, channels] { fmt.extend_from_slice(&value.to_le_bytes()); }
+        fmt.extend_from_slice(&sample_rate.to_le_bytes());
+        fmt.extend_from_slice(&(sample_rate * u32::from(block_align)).to_le_bytes());
+        for value in &[block_align, bits] { fmt.extend_from_slice(&value.to_le_bytes()); }
+        fmt.extend_from_slice(extra);
+
+        let mut wav = b"RIFF\0\0\0\0WAVEfmt ".to_vec();
+        wav.extend_from_slice(&(fmt.len() as u32).to_le_bytes());
+        wav.extend(fmt);
+        wav.extend_from_slice(b"LIST\x03\0\0\0abc\0data");
+        wav.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        wav.extend_from_slice(data);
+        wav
+    }
+
+    /// Encodes mono IMA ADPCM blocks of `block_align` bytes, like the speech files of the game.
+    fn ima_adpcm(samples: &[i16], block_align: usize) -> Vec<u8> {
+        let per_block = (block_align - 4) * 2 + 1;
+        let mut data = Vec::new();
+        let mut index = 0i32;
+        for block in samples.chunks(per_block) {
+            let mut predictor = i32::from(block[0]);
+            data.extend_from_slice(&block[0].to_le_bytes());
+            data.extend_from_slice(&[index as u8, 0]);
+            let mut nibbles = Vec::new();
+            for &sample in &block[1..] {
+                let step = IMA_STEP_TABLE[index as usize];
+                let mut diff = i32::from(sample) - predictor;
+                let mut nibble = if diff < 0 { diff = -diff; 8 } else { 0 };
+                let mut delta = step >> 3;
+                for &(bit, part) in &[(4, step), (2, step >> 1), (1, step >> 2)] {
+                    if diff >= part { nibble |= bit; diff -= part; delta += part; }
+                }
+                predictor = (if nibble & 8 != 0 { predictor - delta } else { predictor + delta }).clamp(-32768, 32767);
+                index = (index + IMA_INDEX_TABLE[nibble as usize]).clamp(0, 88);
+                nibbles.push(nibble as u8);
+            }
+            nibbles.resize(per_block - 1, 0);
+            data.extend(nibbles.chunks(2).map(|n| n[0] | n[1] << 4));
+        }
+        data
+    }
+
+    fn max_error(a: &[i16], b: &[i16]) -> i32 {
+        a.iter().zip(b).map(|(&x, &y)| (i32::from(x) - i32::from(y)).abs()).max().unwrap()
+    }
+
+    #[test]
+    fn decode_wav_should_decode_pcm() {
+        let samples = sine(440.0, 22050, 100, 20000.0);
+        let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes().to_vec()).collect();
+
+        let audio = decode_wav(&wav_file(WAVE_FORMAT_PCM, 1, 22050, 2, 16, &[], &data)).unwrap();
+        assert_eq!(audio, AudioData { sample_rate: 22050, channels: 1, samples: samples.clone() });
+
+        let audio = decode_wav(&wav_file(WAVE_FORMAT_PCM, 2, 11025, 2, 8, &[], &[0, 128, 255, 64])).unwrap();
+        assert_eq!((audio.channels, audio.frames()), (2, 2));
+        assert_eq!(audio.samples, vec!(-32768, 0, 32512, -16384));
+    }
+
+    #[test]
+    fn decode_wav_should_decode_ima_adpcm() {
+        let samples = sine(300.0, 22050, 2000, 12000.0);
+        let block_align = 512;
+        let data = ima_adpcm(&samples, block_align);
+
+        let audio = decode_wav(&wav_file(WAVE_FORMAT_IMA_ADPCM, 1, 22050, block_align as u16, 4, &[2, 0, 0xF9, 0x03], &data)).unwrap();
+        assert_eq!(audio.samples.len(), 2 * 1017);
+        // The step size of the encoder adapts to the signal during the first samples
+        assert!(max_error(&audio.samples[50..2000], &samples[50..]) < 300, "{}", max_error(&audio.samples[50..2000], &samples[50..]));
+    }
+
+    #[test]
+    fn decode_wav_should_reject_other_files() {
+        assert_eq!(decode_wav(b"OggS"), Err(String::from("Not a WAV file")));
+        assert_eq!(decode_wav(&wav_file(0x55, 1, 22050, 1, 0, &[], b"")), Err(String::from("Unsupported WAV format 0x0055 with 0 bits per sample")));
+        assert_eq!(decode_wav(&wav_file(WAVE_FORMAT_IMA_ADPCM, 1, 22050, 4, 4, &[], b"")), Err(String::from("Invalid IMA ADPCM block size 4 for 1 channel(s)")));
+    }
+
+    #[test]
+    fn resample_should_keep_the_waveform() {
+        let audio = AudioData { sample_rate: 22050, channels: 1, samples: sine(1000.0, 22050, 2205, 10000.0) };
+
+        let resampled = resample(&audio, 44100);
+        assert_eq!((resampled.sample_rate, resampled.frames()), (44100, 4410));
+        // Away from the edges, where the filter has no input
+        let expected = sine(1000.0, 44100, 4410, 10000.0);
+        assert!(max_error(&resampled.samples[100..4300], &expected[100..4300]) < 20);
+    }
+
+    #[test]
+    fn resample_should_filter_frequencies_above_the_new_nyquist_frequency() {
+        let tone: Vec<i16> = sine(15000.0, 44100, 4410, 10000.0);
+        let stereo: Vec<i16> = tone.iter().flat_map(|&s| vec!(s, 1000)).collect();
+        let audio = AudioData { sample_rate: 44100, channels: 2, samples: stereo };
+
+        let resampled = resample(&audio, 22050);
+        assert_eq!(resampled.frames(), 2205);
+        let middle = &resampled.samples[200..4200];
+        assert!(middle.iter().step_by(2).all(|s| s.abs() < 100));
+        assert!(middle.iter().skip(1).step_by(2).all(|&s| (s - 1000).abs() <= 1));
+    }
+
+    #[test]
+    fn load_audio_should_resample_to_the_device_rate() {
+        let data: Vec<u8> = sine(440.0, 11025, 1000, 20000.0).iter().flat_map(|s| s.to_le_bytes().to_vec()).collect();
+
+        let audio = load_audio(&wav_file(WAVE_FORMAT_PCM, 1, 11025, 2, 16, &[], &data), 44100).unwrap();
+        assert_eq!((audio.sample_rate, audio.channels, audio.frames()), (44100, 1, 4000));
+    }
+
+    /// A FLAC file with 16-bit samples in verbatim subframes, one frame per `block_size` frames.
+    fn flac_file(channels: u16, sample_rate: u32, samples: &[i16], block_size: usize) -> Vec<u8> {
+        fn crc(data: &[u8], polynomial: u16, width: u32) -> u16 {
+            let top = 1u32 << (width - 1);
+            data.iter().fold(0u32, |mut crc, &byte| {
+                crc ^= u32::from(byte) << (width - 8);
+                for _ in 0..8 {
+                    crc = if crc & top != 0 { (crc << 1) ^ u32::from(polynomial) } else { crc << 1 };
+                }
+                crc & ((1 << width) - 1)
+            }) as u16
+        }
+
+        let frames = samples.len() / channels as usize;
+        let mut data = b"fLaC\x80\0\0\x22".to_vec();
+        data.extend_from_slice(&(block_size as u16).to_be_bytes());
+        data.extend_from_slice(&(block_size as u16).to_be_bytes());
+        data.extend_from_slice(&[0; 6]);
+        // Sample rate (20 bits), channels - 1 (3 bits), bits per sample - 1 (5 bits) and total samples (36 bits)
+        let info = (u64::from(sample_rate) << 44) | (u64::from(channels - 1) << 41) | (15 << 36) | frames as u64;
+        data.extend_from_slice(&info.to_be_bytes());
+        data.extend_from_slice(&[0; 16]);
+
+        for (number, block) in samples.chunks(block_size * channels as usize).enumerate() {
+            let mut frame = vec!(0xFF, 0xF8, 0x70, ((channels - 1) << 4 | 0x08) as u8, number as u8);
+            frame.extend_from_slice(&((block.len() / channels as usize - 1) as u16).to_be_bytes());
+            frame.push(crc(&frame, 0x07, 8) as u8);
+            for channel in 0..channels as usize {
+                frame.push(0x02);
+                frame.extend(block.iter().skip(channel).step_by(channels as usize).flat_map(|s| s.to_be_bytes().to_vec()));
+            }
+            let frame_crc = crc(&frame, 0x8005, 16);
+            frame.extend_from_slice(&frame_crc.to_be_bytes());
+            data.extend(frame);
+        }
+        data
+    }
+
+    fn ogg_page(header_type: u8, granule_position: u64, sequence: u32, packets: &[Vec<u8>]) -> Vec<u8> {
+        let mut page = b"OggS\0".to_vec();
+        page.push(header_type);
+        page.extend_from_slice(&granule_position.to_le_bytes());
+        page.extend_from_slice(&1u32.to_le_bytes());
+        page.extend_from_slice(&sequence.to_le_bytes());
+        page.extend_from_slice(&[0; 4]);
+        let lacing: Vec<u8> = packets.iter().flat_map(|p| {
+            let mut segments = vec!(255u8; p.len() / 255);
+            segments.push((p.len() % 255) as u8);
+            segments
+        }).collect();
+        page.push(lacing.len() as u8);
+        page.extend(lacing);
+        page.extend(packets.concat());
+
+        let checksum = page.iter().fold(0u32, |mut crc, &byte| {
+            crc ^= u32::from(byte) << 24;
+            for _ in 0..8 {
+                crc = if crc & 0x8000_0000 != 0 { (crc << 1) ^ 0x04C1_1DB7 } else { crc << 1 };
+            }
+            crc
+        });
+        page[22..26].copy_from_slice(&checksum.to_le_bytes());
+        page
+    }
+
+    /// An Ogg Vorbis file with silent packets of 256 samples: the setup has a single floor that every packet leaves unused.
+    fn silent_ogg_vorbis_file(channels: u8, sample_rate: u32, packets: usize) -> Vec<u8> {
+        let mut identification = b"\x01vorbis\0\0\0\0".to_vec();
+        identification.push(channels);
+        identification.extend_from_slice(&sample_rate.to_le_bytes());
+        identification.extend_from_slice(&[0; 12]);
+        identification.extend_from_slice(&[0x88, 1]);
+        let comment = b"\x03vorbis\x04\0\0\0test\0\0\0\0\x01".to_vec();
+
+        let mut setup = BitWriter::default();
+        // A codebook with two entries of length 1, without lookup
+        setup.write(0, 8).write(0x56_4342, 24).write(1, 16).write(2, 24).write(0, 1).write(0, 1).write(0, 5).write(0, 5).write(0, 4);
+        // Time domain transforms, floor 1 without partitions, residue 0 and mapping 0
+        setup.write(0, 6).write(0, 16);
+        setup.write(0, 6).write(1, 16).write(0, 5).write(0, 2).write(8, 4);
+        setup.write(0, 6).write(0, 16).write(0, 24).write(0, 24).write(0, 24).write(0, 6).write(0, 8).write(0, 3).write(0, 1);
+        setup.write(0, 6).write(0, 16).write(0, 1).write(0, 1).write(0, 2).write(0, 8).write(0, 8).write(0, 8);
+        // A mode with short blocks and the framing bit
+        setup.write(0, 6).write(0, 1).write(0, 16).write(0, 16).write(0, 8).write(1, 1);
+        let mut setup_packet = b"\x05vorbis".to_vec();
+        setup_packet.extend(setup.bytes);
+
+        let mut data = ogg_page(0x02, 0, 0, &[identification]);
+        data.extend(ogg_page(0, 0, 1, &[comment, setup_packet]));
+        data.extend(ogg_page(0x04, (packets as u64 - 1) * 128, 2, &vec!(vec!(0u8); packets)));
+        data
+    }
+
+    #[test]
+    fn detect_audio_format_should_use_the_signature() {
+        assert_eq!(detect_audio_format(&wav_file(WAVE_FORMAT_PCM, 1, 22050, 2, 16, &[], &[])), Some(AudioFormat::Wav));
+        assert_eq!(detect_audio_format(&silent_ogg_vorbis_file(1, 22050, 2)), Some(AudioFormat::OggVorbis));
+        assert_eq!(detect_audio_format(&flac_file(1, 22050, &[0], 1)), Some(AudioFormat::Flac));
+        assert_eq!(detect_audio_format(b"ID3\x03"), None);
+        assert_eq!(decode_audio(b"ID3\x03"), Err(String::from("Not a WAV, Ogg Vorbis or FLAC file")));
+    }
+
+    #[test]
+    fn check_audio_header_should_accept_the_decodable_formats() {
+        assert_eq!(check_audio_header(&wav_file(WAVE_FORMAT_IMA_ADPCM, 1, 22050, 512, 4, &[], &[])), Ok(AudioFormat::Wav));
+        assert_eq!(check_audio_header(&wav_file(3, 1, 22050, 4, 32, &[], &[])), Err(String::from("Unsupported WAV format 0x0003 with 32 bits per sample")));
+        assert_eq!(check_audio_header(b"RIFF\x04\0\0\0WAVE"), Err(String::from("No fmt chunk")));
+        assert_eq!(check_audio_header(&wav_file(WAVE_FORMAT_PCM, 9, 22050, 18, 16, &[], &[])), Err(String::from("Unsupported audio with 9 channels")));
+        assert_eq!(check_audio_header(&flac_file(1, 22050, &[0], 1)), Ok(AudioFormat::Flac));
+        assert_eq!(check_audio_header(b"MThd"), Err(String::from("Not a WAV, Ogg Vorbis or FLAC file")));
+    }
+
+    #[test]
+    fn decode_audio_should_decode_flac() {
+        let left = sine(440.0, 44100, 1000, 20000.0);
+        let right = sine(880.0, 44100, 1000, -8000.0);
+        let samples: Vec<i16> = left.iter().zip(&right).flat_map(|(&l, &r)| vec!(l, r)).collect();
+
+        let audio = decode_audio(&flac_file(2, 44100, &samples, 256)).unwrap();
+        assert_eq!(audio, AudioData { sample_rate: 44100, channels: 2, samples });
+        assert!(decode_audio(b"fLaC\0\0").unwrap_err().starts_with("Invalid FLAC file"));
+    }
+
+    #[test]
+    fn decode_audio_should_downmix_to_stereo() {
+        // Front left, front right, center, LFE, side left and side right
+        let samples: Vec<i16> = (0..300).flat_map(|_| vec!(1000, -2000, 4000, 30000, 500, 0)).collect();
+
+        let audio = decode_audio(&flac_file(6, 44100, &samples, 256)).unwrap();
+        assert_eq!((audio.sample_rate, audio.channels, audio.frames()), (44100, 2, 300));
+        assert!(audio.samples.chunks(2).all(|frame| frame == [1732, 343]), "{:?}", &audio.samples[..2]);
+
+        let silence = decode_audio(&silent_ogg_vorbis_file(6, 22050, 9)).unwrap();
+        assert_eq!((silence.channels, silence.frames()), (2, 8 * 128));
+        assert_eq!(downmix_weights(true, 6).unwrap()[1], downmix_weights(false, 6).unwrap()[2]);
+
+        let wav = wav_file(WAVE_FORMAT_PCM, 9, 22050, 18, 16, &[], &[0; 36]);
+        assert_eq!(decode_audio(&wav).unwrap_err(), "Unsupported audio with 9 channels");
+    }
+
+    #[test]
+    fn decode_audio_should_decode_ogg_vorbis() {
+        let audio = decode_audio(&silent_ogg_vorbis_file(2, 22050, 9)).unwrap();
+        assert_eq!((audio.sample_rate, audio.channels, audio.frames()), (22050, 2, 8 * 128));
+        assert!(audio.samples.iter().all(|&s| s == 0));
+
+        let resampled = load_audio(&silent_ogg_vorbis_file(1, 22050, 9), 44100).unwrap();
+        assert_eq!((resampled.sample_rate, resampled.frames()), (44100, 2 * 8 * 128));
+        assert!(decode_audio(b"OggS\0\0").unwrap_err().starts_with("Invalid Ogg Vorbis file"));
+    }
+}
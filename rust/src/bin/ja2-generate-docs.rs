@@ -0,0 +1,47 @@
+//! Writes the man page and the shell completion scripts for the game binary.
+//!
+//! Usage: ja2-generate-docs OUTPUT_DIR MODS_DIR VERSION DATE
+//!
+//! VERSION and DATE are shown in the header of the man page, CMake passes the version of the game and the build date.
+
+extern crate stracciatella;
+
+use std::env;
+use std::fs;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use stracciatella::docs;
+use stracciatella::mods::list_available_mods;
+
+fn write_file(path: &Path, contents: &str) -> Result<(), String> {
+    File::create(path)
+        .and_then(|mut f| f.write_all(contents.as_bytes()))
+        .map_err(|e| format!("Error writing {}: {}", path.display(), e))
+}
+
+fn run(output_dir: PathBuf, mods_dir: PathBuf, version: &str, date: &str) -> Result<(), String> {
+    let mods = list_available_mods(&mods_dir)?;
+
+    fs::create_dir_all(&output_dir).map_err(|e| format!("Error creating {}: {}", output_dir.display(), e))?;
+    write_file(&output_dir.join("ja2.6"), &docs::generate_manpage(version, date))?;
+    write_file(&output_dir.join("ja2.bash"), &docs::generate_bash_completion(&mods))?;
+    write_file(&output_dir.join("_ja2"), &docs::generate_zsh_completion(&mods))?;
+    write_file(&output_dir.join("ja2.fish"), &docs::generate_fish_completion(&mods))
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    if args.len() != 5 {
+        eprintln!("Usage: {} OUTPUT_DIR MODS_DIR VERSION DATE", args[0]);
+        process::exit(1);
+    }
+
+    if let Err(msg) = run(PathBuf::from(&args[1]), PathBuf::from(&args[2]), &args[3], &args[4]) {
+        eprintln!("{}", msg);
+        process::exit(1);
+    }
+}
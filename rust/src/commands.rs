@@ -0,0 +1,1048 @@
+//! Maintenance subcommands of the game binary, e.g. `ja2 config show`.
+//!
+//! They run entirely in Rust, the engine exits right after them without initializing SDL.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde_json;
+use serde_json::Value;
+
+use data;
+use edt;
+use extract;
+use image;
+use imp;
+use integrity;
+use items;
+use map;
+use mods;
+use music;
+use npc;
+use policy;
+use profile;
+use savegame;
+use settings;
+use smacker;
+use text;
+use EngineOptions;
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub enum Command {
+    #[default]
+    RunGame,
+    ConfigShow,
+    ConfigSet(String, String),
+    ConfigValidate,
+    ConfigSettings,
+    ConfigSettingsSet(String, String),
+    ModsList,
+    ModsInfo(String),
+    SavesList,
+    SavesSnapshot(PathBuf),
+    SavesCheck(PathBuf),
+    DataVerify,
+    DataChecksums,
+    DataExtract(PathBuf),
+    DataMusic(Option<PathBuf>),
+    EdtExport(PathBuf, PathBuf),
+    EdtImport(PathBuf, PathBuf),
+    ImageExport(PathBuf, PathBuf),
+    ImageExportLocalized(String, PathBuf, PathBuf),
+    MapInfo(PathBuf),
+    MapExport(PathBuf, PathBuf),
+    MapDiff(PathBuf, PathBuf, Option<PathBuf>),
+    NpcExport(PathBuf, PathBuf),
+    NpcImport(PathBuf, PathBuf),
+    PolicyValidate(Option<PathBuf>),
+    PolicyDiff(String, Option<PathBuf>),
+    PolicyImp(Option<PathBuf>),
+    ProfilesExport(PathBuf, PathBuf),
+    ProfilesImport(PathBuf, PathBuf),
+    TextRender(PathBuf, u16, String, PathBuf),
+    TextCheck(String, PathBuf, u32, usize),
+    VideoExport(PathBuf, PathBuf),
+}
+
+pub struct CommandDescription {
+    pub usage: &'static str,
+    pub description: &'static str,
+}
+
+/// All subcommands, used for the help text and the man page.
+pub static COMMANDS: &[CommandDescription] = &[
+    CommandDescription { usage: "config show", description: "Print the current configuration" },
+    CommandDescription { usage: "config set KEY VALUE", description: "Change a value in ja2.json, e.g. 'config set res 800x600'" },
+    CommandDescription { usage: "config validate", description: "Check the configuration for problems" },
+    CommandDescription { usage: "config settings", description: "Print the in-game options of Ja2.set, e.g. subtitles, speech and the volumes" },
+    CommandDescription { usage: "config settings KEY VALUE", description: "Change an in-game option in Ja2.set, e.g. 'config settings music_volume 100'" },
+    CommandDescription { usage: "mods list", description: "List the installed mods" },
+    CommandDescription { usage: "mods info MOD_NAME", description: "Show the description and files of an installed mod" },
+    CommandDescription { usage: "saves list", description: "List the saved games" },
+    CommandDescription { usage: "saves snapshot SAVE_FILE", description: "Store the current game policy and mods next to a saved game, for games saved before the game did this itself" },
+    CommandDescription { usage: "saves check SAVE_FILE", description: "Compare the game policy and mods of a saved game with the current ones" },
+    CommandDescription { usage: "data verify", description: "Check that the SLF libraries of every release are in the vanilla data directory, their contents are not checked" },
+    CommandDescription { usage: "data checksums", description: "Print the sizes and MD5 checksums of the SLF libraries in the vanilla data directory" },
+    CommandDescription { usage: "data extract OUTPUT_DIR", description: "Extract all SLF libraries, converting images to PNG and EDT files to JSON, with a manifest of their origin" },
+    CommandDescription { usage: "data music [MUSIC_JSON]", description: "Check that the files of music.json exist and are playable WAV, Ogg Vorbis or FLAC files, by default the one of the first mod or of the game" },
+    CommandDescription { usage: "edt export EDT_FILE JSON_FILE", description: "Export the quotes of an EDT file to the JSON dialog format" },
+    CommandDescription { usage: "edt import JSON_FILE EDT_FILE", description: "Compile a JSON dialog file to an EDT file, encoded for the configured resversion" },
+    CommandDescription { usage: "image export IMAGE_FILE PNG_FILE", description: "Convert an STI, PCX or TGA image to PNG, numbering the subimages of an STI" },
+    CommandDescription { usage: "image localized NAME DIR PNG_FILE", description: "Convert the graphic NAME of the configured resversion from a directory of game files to PNG, e.g. 'desktop'" },
+    CommandDescription { usage: "map info MAP_FILE", description: "Show a summary of the contents of a map" },
+    CommandDescription { usage: "map export MAP_FILE JSON_FILE", description: "Export a map to JSON" },
+    CommandDescription { usage: "map diff MAP_FILE OTHER_MAP_FILE [JSON_FILE]", description: "List the changes from one map to another, as JSON if a JSON file is given" },
+    CommandDescription { usage: "npc export NPC_FILE JSON_FILE", description: "Export the quote records of an .npc file to JSON" },
+    CommandDescription { usage: "npc import JSON_FILE NPC_FILE", description: "Write quote records from JSON to an .npc file" },
+    CommandDescription { usage: "policy validate [GAME_JSON]", description: "Check the ranges of the settings in game.json, by default the one shipped with the game" },
+    CommandDescription { usage: "policy diff PRESET [GAME_JSON]", description: "List the settings of game.json that differ from a preset, 'vanilla' or 'stracciatella default'" },
+    CommandDescription { usage: "policy imp [IMP_JSON]", description: "Check the items and pockets of the IMP starting kit, by default the one of the first mod or of the game" },
+    CommandDescription { usage: "profiles export PROF_DAT JSON_FILE", description: "Export the mercenary profiles of a prof.dat file to JSON" },
+    CommandDescription { usage: "profiles import JSON_FILE PROF_DAT", description: "Write mercenary profiles from JSON to a prof.dat file" },
+    CommandDescription { usage: "text render FONT_FILE WIDTH TEXT PNG_FILE", description: "Wrap a text to WIDTH pixels like the game and draw it with an STI font to PNG, using the glyphs of the configured resversion" },
+    CommandDescription { usage: "text check RESOURCE FONT_FILE WIDTH [LINES]", description: "Check that the strings of a resource in externalized/strings fit into LINES lines of WIDTH pixels in every language, e.g. 'text check ammo-calibre fonts/smallfont1.sti 60'" },
+    CommandDescription { usage: "video export SMK_FILE PNG_FILE", description: "Decode the frames of a Smacker video to numbered PNG files, e.g. to check a video without a display" },
+];
+
+/// The first words of all subcommands, e.g. `config`.
+pub fn command_groups() -> Vec<&'static str> {
+    let mut groups: Vec<&'static str> = COMMANDS.iter().filter_map(|c| c.usage.split(' ').next()).collect();
+    groups.dedup();
+    groups
+}
+
+impl Command {
+    /// Parses the free arguments of the command line into a command. No arguments means running the game.
+    pub fn from_args(args: &[String]) -> Result<Command, String> {
+        let words: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+
+        match words.as_slice() {
+            [] => Ok(Command::RunGame),
+            ["config", "show"] => Ok(Command::ConfigShow),
+            ["config", "set", key, value] => Ok(Command::ConfigSet(key.to_string(), value.to_string())),
+            ["config", "validate"] => Ok(Command::ConfigValidate),
+            ["config", "settings"] => Ok(Command::ConfigSettings),
+            ["config", "settings", key, value] => Ok(Command::ConfigSettingsSet(key.to_string(), value.to_string())),
+            ["mods", "list"] => Ok(Command::ModsList),
+            ["mods", "info", name] => Ok(Command::ModsInfo(name.to_string())),
+            ["saves", "list"] => Ok(Command::SavesList),
+            ["saves", "snapshot", save_file] => Ok(Command::SavesSnapshot(PathBuf::from(save_file))),
+            ["saves", "check", save_file] => Ok(Command::SavesCheck(PathBuf::from(save_file))),
+            ["data", "verify"] => Ok(Command::DataVerify),
+            ["data", "checksums"] => Ok(Command::DataChecksums),
+            ["data", "extract", output_dir] => Ok(Command::DataExtract(PathBuf::from(output_dir))),
+            ["data", "music"] => Ok(Command::DataMusic(None)),
+            ["data", "music", music_json] => Ok(Command::DataMusic(Some(PathBuf::from(music_json)))),
+            ["edt", "export", edt_file, json_file] => Ok(Command::EdtExport(PathBuf::from(edt_file), PathBuf::from(json_file))),
+            ["edt", "import", json_file, edt_file] => Ok(Command::EdtImport(PathBuf::from(json_file), PathBuf::from(edt_file))),
+            ["image", "export", image_file, png_file] => Ok(Command::ImageExport(PathBuf::from(image_file), PathBuf::from(png_file))),
+            ["image", "localized", name, dir, png_file] => Ok(Command::ImageExportLocalized(name.to_string(), PathBuf::from(dir), PathBuf::from(png_file))),
+            ["map", "info", map_file] => Ok(Command::MapInfo(PathBuf::from(map_file))),
+            ["map", "export", map_file, json_file] => Ok(Command::MapExport(PathBuf::from(map_file), PathBuf::from(json_file))),
+            ["map", "diff", map_file, other_map_file] => Ok(Command::MapDiff(PathBuf::from(map_file), PathBuf::from(other_map_file), None)),
+            ["map", "diff", map_file, other_map_file, json_file] => {
+                Ok(Command::MapDiff(PathBuf::from(map_file), PathBuf::from(other_map_file), Some(PathBuf::from(json_file))))
+            },
+            ["npc", "export", npc_file, json_file] => Ok(Command::NpcExport(PathBuf::from(npc_file), PathBuf::from(json_file))),
+            ["npc", "import", json_file, npc_file] => Ok(Command::NpcImport(PathBuf::from(json_file), PathBuf::from(npc_file))),
+            ["policy", "validate"] => Ok(Command::PolicyValidate(None)),
+            ["policy", "validate", game_json] => Ok(Command::PolicyValidate(Some(PathBuf::from(game_json)))),
+            ["policy", "diff", preset] => Ok(Command::PolicyDiff(preset.to_string(), None)),
+            ["policy", "diff", preset, game_json] => Ok(Command::PolicyDiff(preset.to_string(), Some(PathBuf::from(game_json)))),
+            ["policy", "imp"] => Ok(Command::PolicyImp(None)),
+            ["policy", "imp", imp_json] => Ok(Command::PolicyImp(Some(PathBuf::from(imp_json)))),
+            ["profiles", "export", prof_dat, json_file] => Ok(Command::ProfilesExport(PathBuf::from(prof_dat), PathBuf::from(json_file))),
+            ["profiles", "import", json_file, prof_dat] => Ok(Command::ProfilesImport(PathBuf::from(json_file), PathBuf::from(prof_dat))),
+            ["text", "render", font_file, width, text, png_file] => {
+                Ok(Command::TextRender(PathBuf::from(font_file), parse_number("width", width)?, text.to_string(), PathBuf::from(png_file)))
+            },
+            ["text", "check", resource, font_file, width] => {
+                Ok(Command::TextCheck(resource.to_string(), PathBuf::from(font_file), parse_number("width", width)?, 1))
+            },
+            ["text", "check", resource, font_file, width, lines] => {
+                Ok(Command::TextCheck(resource.to_string(), PathBuf::from(font_file), parse_number("width", width)?, parse_number("number of lines", lines)?))
+            },
+            ["video", "export", smk_file, png_file] => Ok(Command::VideoExport(PathBuf::from(smk_file), PathBuf::from(png_file))),
+            _ => Err(format!("Unknown arguments: '{}'.", args.join(" "))),
+        }
+    }
+
+    /// Only starting the game needs a configured data directory, the other commands report it themselves.
+    pub fn requires_data_dir(&self) -> bool {
+        *self == Command::RunGame
+    }
+}
+
+/// Parses a numeric argument of a command, e.g. a width in pixels.
+fn parse_number<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("Invalid {} '{}'.", name, value))
+}
+
+/// Builds the usage text shown with `-help`.
+pub fn usage_brief() -> String {
+    let mut brief = String::from("Usage: ja2 [options]\n       ja2 [options] COMMAND\n\nCommands:\n");
+    for command in COMMANDS {
+        brief.push_str(&format!("    {:<24}{}\n", command.usage, command.description));
+    }
+    brief
+}
+
+fn config_show(engine_options: &EngineOptions, out: &mut dyn Write) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(engine_options).map_err(|e| format!("Error serializing configuration: {}", e))?;
+    writeln!(out, "# {}", engine_options.stracciatella_home.join("ja2.json").display()).map_err(|e| e.to_string())?;
+    writeln!(out, "{}", json).map_err(|e| e.to_string())
+}
+
+/// Changes `key` in the stored `ja2.json`, so the values of command line switches like `--res` are not written.
+fn config_set(engine_options: &EngineOptions, key: &str, value: &str, out: &mut dyn Write) -> Result<(), String> {
+    let known = serde_json::to_value(EngineOptions::default()).map_err(|e| format!("Error serializing configuration: {}", e))?;
+    let known_keys: Vec<&str> = known.as_object().map(|m| m.keys().map(|k| k.as_str()).collect()).unwrap_or_default();
+    if !known_keys.contains(&key) {
+        return Err(format!("Unknown configuration key '{}', possible keys: {}", key, known_keys.join(", ")));
+    }
+
+    let path = engine_options.stracciatella_home.join("ja2.json");
+    let stored = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(ref e) if e.kind() == ::std::io::ErrorKind::NotFound => String::from(::DEFAULT_JSON_CONTENT),
+        Err(e) => return Err(format!("Error reading {}: {}", path.display(), e)),
+    };
+    let mut json: Value = serde_json::from_str(&stored).map_err(|e| format!("Error parsing {}: {}", path.display(), e))?;
+    {
+        let map = json.as_object_mut().ok_or_else(|| format!("{} is not a JSON object", path.display()))?;
+        // Values that are not valid JSON by themselves are taken as strings, so `config set res 800x600` works.
+        let parsed = serde_json::from_str(value).unwrap_or_else(|_| Value::String(String::from(value)));
+        map.insert(String::from(key), parsed);
+    }
+    serde_json::from_value::<EngineOptions>(json.clone()).map_err(|e| format!("Invalid value for '{}': {}", key, e))?;
+
+    let contents = serde_json::to_string_pretty(&json).map_err(|e| format!("Error serializing configuration: {}", e))?;
+    fs::write(&path, contents).map_err(|e| format!("Error writing {}: {}", path.display(), e))?;
+    writeln!(out, "Set '{}' to {}", key, value).map_err(|e| e.to_string())
+}
+
+/// Returns a list of human readable problems of the configuration.
+pub fn validate_config(engine_options: &EngineOptions, mods_dir: &Path) -> Vec<String> {
+    let mut problems = vec!();
+
+    match data::find_data_dir(&engine_options.vanilla_data_dir).and_then(|d| data::list_slf_libraries(&d)) {
+        Ok(ref libraries) if libraries.is_empty() => problems.push(String::from("The data folder does not contain any SLF libraries")),
+        Ok(_) => {},
+        Err(e) => problems.push(e),
+    }
+
+    let available_mods = mods::list_available_mods(mods_dir).unwrap_or_default();
+    for m in &engine_options.mods {
+        if !available_mods.contains(m) {
+            problems.push(format!("Mod '{}' is not installed in {}", m, mods_dir.display()));
+        }
+    }
+
+    let (x, y) = engine_options.resolution;
+    if x < 640 || y < 480 {
+        problems.push(format!("Resolution {}x{} is smaller than the minimum of 640x480", x, y));
+    }
+
+    problems.extend(engine_options.log.validate());
+
+    problems
+}
+
+fn config_validate(engine_options: &EngineOptions, mods_dir: &Path, out: &mut dyn Write) -> Result<(), String> {
+    let problems = validate_config(engine_options, mods_dir);
+
+    for problem in &problems {
+        writeln!(out, "- {}", problem).map_err(|e| e.to_string())?;
+    }
+    if !problems.is_empty() {
+        return Err(format!("Configuration has {} problem(s)", problems.len()));
+    }
+    writeln!(out, "Configuration is valid").map_err(|e| e.to_string())
+}
+
+/// Reads `Ja2.set`, a missing file means the game has not written it yet and uses the defaults.
+fn read_game_settings_or_default(engine_options: &EngineOptions) -> Result<(PathBuf, settings::GameSettings), String> {
+    let path = settings::game_settings_path(engine_options);
+    if !path.exists() {
+        return Ok((path, settings::GameSettings::default()));
+    }
+    let game_settings = settings::read_game_settings(&path)?;
+    Ok((path, game_settings))
+}
+
+fn config_settings(engine_options: &EngineOptions, out: &mut dyn Write) -> Result<(), String> {
+    let (path, game_settings) = read_game_settings_or_default(engine_options)?;
+    let json = serde_json::to_string_pretty(&game_settings).map_err(|e| format!("Error serializing settings: {}", e))?;
+
+    writeln!(out, "# {}", path.display()).map_err(|e| e.to_string())?;
+    if !path.exists() {
+        writeln!(out, "# Not written by the game yet, these are the defaults").map_err(|e| e.to_string())?;
+    } else if game_settings.is_outdated() {
+        writeln!(out, "# Written by an older version, the game will reset these settings").map_err(|e| e.to_string())?;
+    }
+    writeln!(out, "{}", json).map_err(|e| e.to_string())
+}
+
+fn config_settings_set(engine_options: &EngineOptions, key: &str, value: &str, out: &mut dyn Write) -> Result<(), String> {
+    let (path, mut game_settings) = read_game_settings_or_default(engine_options)?;
+
+    game_settings.set(key, value)?;
+    settings::write_game_settings(&path, &game_settings)?;
+    writeln!(out, "Set '{}' to {}", key, game_settings.get(key).unwrap_or_default()).map_err(|e| e.to_string())
+}
+
+fn mods_list(mods_dir: &Path, out: &mut dyn Write) -> Result<(), String> {
+    for m in mods::list_available_mods(mods_dir)? {
+        writeln!(out, "{}", m).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn mods_info(mods_dir: &Path, name: &str, out: &mut dyn Write) -> Result<(), String> {
+    let mod_dir = mods_dir.join(name);
+    if !mods::list_available_mods(mods_dir)?.iter().any(|m| m == name) {
+        return Err(format!("Mod '{}' is not installed in {}", name, mods_dir.display()));
+    }
+
+    writeln!(out, "Name: {}", name).map_err(|e| e.to_string())?;
+    writeln!(out, "Path: {}", mod_dir.display()).map_err(|e| e.to_string())?;
+    writeln!(out, "Files:").map_err(|e| e.to_string())?;
+    for file in mods::list_mod_files(&mod_dir)? {
+        writeln!(out, "    {}", file).map_err(|e| e.to_string())?;
+    }
+    if let Some(readme) = mods::find_mod_readme(&mod_dir) {
+        let text = fs::read(&readme).map_err(|e| format!("Error reading {}: {}", readme.display(), e))?;
+        writeln!(out).map_err(|e| e.to_string())?;
+        writeln!(out, "{}", String::from_utf8_lossy(&text).trim_end()).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn saves_list(engine_options: &EngineOptions, out: &mut dyn Write) -> Result<(), String> {
+    let dir = savegame::saved_games_dir(engine_options);
+
+    writeln!(out, "# {}", dir.display()).map_err(|e| e.to_string())?;
+    for path in savegame::list_saved_games(&dir)? {
+        let file_name = path.file_name().unwrap().to_string_lossy().into_owned();
+        match savegame::read_saved_game_header(&path) {
+            Ok(h) => writeln!(out, "{:<20} Day {:>3} {:02}:{:02}  {:<6} ${:<8} {}", file_name, h.day, h.hour, h.minute, h.sector_name(), h.balance, h.description),
+            Err(e) => writeln!(out, "{:<20} {}", file_name, e),
+        }.map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Save files are relative to the folder of the saved games, like in `saves list`.
+fn saves_snapshot(engine_options: &EngineOptions, extra_data_dir: &Path, save_file: &Path, out: &mut dyn Write) -> Result<(), String> {
+    let path = savegame::saved_games_dir(engine_options).join(save_file);
+    if !path.is_file() {
+        return Err(format!("Saved game {} does not exist", path.display()));
+    }
+    let snapshot = policy::PolicySnapshot::current(engine_options, extra_data_dir)?;
+
+    policy::write_policy_snapshot(&path, &snapshot)?;
+    writeln!(out, "Wrote {}", policy::snapshot_path(&path).display()).map_err(|e| e.to_string())
+}
+
+fn saves_check(engine_options: &EngineOptions, extra_data_dir: &Path, save_file: &Path, out: &mut dyn Write) -> Result<(), String> {
+    let path = savegame::saved_games_dir(engine_options).join(save_file);
+    let saved = policy::read_policy_snapshot(&path)?
+        .ok_or_else(|| format!("{} has no policy snapshot", path.display()))?;
+    let mismatch = policy::compare_snapshots(&saved, &policy::PolicySnapshot::current(engine_options, extra_data_dir)?);
+
+    if mismatch.is_empty() {
+        return writeln!(out, "{} uses the current rules", path.display()).map_err(|e| e.to_string());
+    }
+    write!(out, "{}", mismatch).map_err(|e| e.to_string())
+}
+
+fn data_verify(engine_options: &EngineOptions, out: &mut dyn Write) -> Result<(), String> {
+    let report = integrity::check_data_dir(&engine_options.vanilla_data_dir)?;
+
+    let sections = [
+        ("Missing", &report.missing),
+        ("Other libraries, not checked", &report.other),
+    ];
+    for &(title, libraries) in sections.iter().filter(|&&(_, l)| !l.is_empty()) {
+        writeln!(out, "{}:", title).map_err(|e| e.to_string())?;
+        for library in libraries {
+            writeln!(out, "    {}", library).map_err(|e| e.to_string())?;
+        }
+    }
+
+    if !report.is_ok() {
+        return Err(format!("{} missing libraries", report.missing.len()));
+    }
+    writeln!(out, "All {} libraries of every release exist", report.present.len()).map_err(|e| e.to_string())
+}
+
+fn data_checksums(engine_options: &EngineOptions, out: &mut dyn Write) -> Result<(), String> {
+    let checksums = integrity::hash_data_dir(&engine_options.vanilla_data_dir)?;
+    let json = serde_json::to_string_pretty(&checksums).map_err(|e| format!("Error serializing checksums: {}", e))?;
+
+    writeln!(out, "{}", json).map_err(|e| e.to_string())
+}
+
+fn data_extract(engine_options: &EngineOptions, output_dir: &Path, out: &mut dyn Write) -> Result<(), String> {
+    let manifest = extract::extract_data_dir(&engine_options.vanilla_data_dir, output_dir, engine_options.resource_version)?;
+
+    for entry in manifest.errors() {
+        writeln!(out, "{}: {}", entry.path, entry.error.as_deref().unwrap_or_default()).map_err(|e| e.to_string())?;
+    }
+    writeln!(out, "Extracted {} files from {} libraries to {}", manifest.files.len(), manifest.libraries.len(), output_dir.display()).map_err(|e| e.to_string())
+}
+
+fn data_music(engine_options: &EngineOptions, extra_data_dir: &Path, music_json: Option<&Path>, out: &mut dyn Write) -> Result<(), String> {
+    let path = music_json.map(Path::to_path_buf).unwrap_or_else(|| data::externalized_file(engine_options, extra_data_dir, "music.json"));
+    let problems = music::check_music_file(&path, &data::GameResources::new(engine_options, extra_data_dir)?)?;
+
+    for problem in &problems {
+        writeln!(out, "- {}", problem).map_err(|e| e.to_string())?;
+    }
+    if !problems.is_empty() {
+        return Err(format!("Music has {} problem(s)", problems.len()));
+    }
+    writeln!(out, "{} is valid", path.display()).map_err(|e| e.to_string())
+}
+
+fn edt_export(engine_options: &EngineOptions, edt_file: &Path, json_file: &Path, out: &mut dyn Write) -> Result<(), String> {
+    let quotes = edt::export_edt_to_json(edt_file, json_file, engine_options.resource_version)?;
+
+    writeln!(out, "Exported {} quotes to {}", quotes, json_file.display()).map_err(|e| e.to_string())
+}
+
+fn edt_import(engine_options: &EngineOptions, json_file: &Path, edt_file: &Path, out: &mut dyn Write) -> Result<(), String> {
+    let quotes = edt::compile_json_to_edt(json_file, edt_file, engine_options.resource_version)?;
+
+    writeln!(out, "Compiled {} quotes to {}", quotes, edt_file.display()).map_err(|e| e.to_string())
+}
+
+fn image_export(image_file: &Path, png_file: &Path, out: &mut dyn Write) -> Result<(), String> {
+    let images = image::read_image_file(image_file)?;
+
+    for written in image::write_pngs(png_file, &images)? {
+        writeln!(out, "Exported {}", written.display()).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn image_export_localized(engine_options: &EngineOptions, name: &str, dir: &Path, png_file: &Path, out: &mut dyn Write) -> Result<(), String> {
+    let file_name = image::mlg_file_name(name, engine_options.resource_version)
+        .ok_or_else(|| format!("Unknown localized graphic '{}', known are: {}", name, image::mlg_names().join(", ")))?;
+    let image_file = file_name.split('/').try_fold(dir.to_path_buf(), |path, part| data::find_case_insensitive(&path, part))
+        .ok_or_else(|| format!("{} not found in {}", file_name, dir.display()))?;
+
+    image_export(&image_file, png_file, out)
+}
+
+fn map_info(engine_options: &EngineOptions, map_file: &Path, out: &mut dyn Write) -> Result<(), String> {
+    let map = map::read_map_file(map_file, engine_options.resource_version)?;
+
+    write!(out, "{}", map::MapSummary::new(&map)).map_err(|e| e.to_string())
+}
+
+fn map_export(engine_options: &EngineOptions, map_file: &Path, json_file: &Path, out: &mut dyn Write) -> Result<(), String> {
+    let map = map::read_map_file(map_file, engine_options.resource_version)?;
+    let json = map::map_to_json(&map)?;
+
+    fs::write(json_file, json).map_err(|e| format!("Error writing {}: {}", json_file.display(), e))?;
+    writeln!(out, "Exported {} to {}", map_file.display(), json_file.display()).map_err(|e| e.to_string())
+}
+
+fn map_diff(engine_options: &EngineOptions, map_file: &Path, other_map_file: &Path, json_file: Option<&Path>, out: &mut dyn Write) -> Result<(), String> {
+    let before = map::read_map_file(map_file, engine_options.resource_version)?;
+    let after = map::read_map_file(other_map_file, engine_options.resource_version)?;
+    let diff = map::diff_maps(&before, &after);
+
+    match json_file {
+        Some(json_file) => {
+            fs::write(json_file, map::map_diff_to_json(&diff)?).map_err(|e| format!("Error writing {}: {}", json_file.display(), e))?;
+            writeln!(out, "Wrote {} changes to {}", diff.changes.len(), json_file.display()).map_err(|e| e.to_string())
+        },
+        None if diff.is_empty() => writeln!(out, "The maps are the same").map_err(|e| e.to_string()),
+        None => write!(out, "{}", diff).map_err(|e| e.to_string()),
+    }
+}
+
+fn npc_export(engine_options: &EngineOptions, npc_file: &Path, json_file: &Path, out: &mut dyn Write) -> Result<(), String> {
+    let records = npc::read_npc_file(npc_file, engine_options.resource_version)?;
+    let json = npc::npc_records_to_json(&records)?;
+
+    fs::write(json_file, json).map_err(|e| format!("Error writing {}: {}", json_file.display(), e))?;
+    writeln!(out, "Exported {} records to {}", records.len(), json_file.display()).map_err(|e| e.to_string())
+}
+
+fn npc_import(engine_options: &EngineOptions, json_file: &Path, npc_file: &Path, out: &mut dyn Write) -> Result<(), String> {
+    let json = fs::read_to_string(json_file).map_err(|e| format!("Error reading {}: {}", json_file.display(), e))?;
+    let records = npc::npc_records_from_json(&json).map_err(|e| format!("{}: {}", json_file.display(), e))?;
+
+    npc::write_npc_file(npc_file, &records, engine_options.resource_version)?;
+    writeln!(out, "Wrote {} records to {}", records.len(), npc_file.display()).map_err(|e| e.to_string())
+}
+
+fn policy_validate(game_json: &Path, out: &mut dyn Write) -> Result<(), String> {
+    let problems = policy::read_game_policy(game_json)?.validate();
+
+    for problem in &problems {
+        writeln!(out, "- {}", problem).map_err(|e| e.to_string())?;
+    }
+    if !problems.is_empty() {
+        return Err(format!("Game policy has {} problem(s)", problems.len()));
+    }
+    writeln!(out, "Game policy is valid").map_err(|e| e.to_string())
+}
+
+fn policy_diff(preset: &str, game_json: &Path, out: &mut dyn Write) -> Result<(), String> {
+    let preset_policy = policy::GamePolicy::preset(preset)
+        .ok_or_else(|| format!("Unknown preset '{}', possible presets: {}", preset, policy::PRESETS.join(", ")))?;
+    let changes = policy::diff_policies(&preset_policy, &policy::read_game_policy(game_json)?);
+
+    if changes.is_empty() {
+        return writeln!(out, "{} is the same as the preset '{}'", game_json.display(), preset).map_err(|e| e.to_string());
+    }
+    for change in changes {
+        writeln!(out, "{}", change).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Checks `imp.json` against the weapons and magazines the game would use with the configured mods.
+fn policy_imp(engine_options: &EngineOptions, extra_data_dir: &Path, imp_json: Option<&Path>, out: &mut dyn Write) -> Result<(), String> {
+    let path = imp_json.map(Path::to_path_buf).unwrap_or_else(|| data::externalized_file(engine_options, extra_data_dir, "imp.json"));
+    let problems = imp::validate_imp_file(&path, &items::read_items(engine_options, extra_data_dir)?)?;
+
+    for problem in &problems {
+        writeln!(out, "- {}", problem).map_err(|e| e.to_string())?;
+    }
+    if !problems.is_empty() {
+        return Err(format!("IMP policy has {} problem(s)", problems.len()));
+    }
+    writeln!(out, "{} is valid", path.display()).map_err(|e| e.to_string())
+}
+
+fn profiles_export(engine_options: &EngineOptions, prof_dat: &Path, json_file: &Path, out: &mut dyn Write) -> Result<(), String> {
+    let profiles = profile::read_profiles_file(prof_dat, engine_options.resource_version)?;
+    let json = profile::profiles_to_json(&profiles)?;
+
+    fs::write(json_file, json).map_err(|e| format!("Error writing {}: {}", json_file.display(), e))?;
+    writeln!(out, "Exported {} profiles to {}", profiles.len(), json_file.display()).map_err(|e| e.to_string())
+}
+
+fn profiles_import(engine_options: &EngineOptions, json_file: &Path, prof_dat: &Path, out: &mut dyn Write) -> Result<(), String> {
+    let json = fs::read_to_string(json_file).map_err(|e| format!("Error reading {}: {}", json_file.display(), e))?;
+    let profiles = profile::profiles_from_json(&json).map_err(|e| format!("{}: {}", json_file.display(), e))?;
+
+    profile::write_profiles_file(prof_dat, &profiles, engine_options.resource_version)?;
+    writeln!(out, "Wrote {} profiles to {}", profiles.len(), prof_dat.display()).map_err(|e| e.to_string())
+}
+
+fn text_render(engine_options: &EngineOptions, font_file: &Path, width: u16, text: &str, png_file: &Path, out: &mut dyn Write) -> Result<(), String> {
+    let font = text::read_font_file(font_file)?;
+    let layout = text::TextLayout::new(&font, engine_options.resource_version);
+
+    for line in layout.line_wrap(text, u32::from(width)) {
+        writeln!(out, "{:>4} {}", layout.string_width(&line), line).map_err(|e| e.to_string())?;
+    }
+    for c in layout.missing_glyphs(text) {
+        writeln!(out, "No glyph for '{}' U+{:04X}, drawn as '?'", c, c as u32).map_err(|e| e.to_string())?;
+    }
+    image::write_png(png_file, &layout.to_image(&layout.draw_wrapped(text, width, 0)))?;
+    writeln!(out, "Wrote {}", png_file.display()).map_err(|e| e.to_string())
+}
+
+fn text_check(extra_data_dir: &Path, resource: &str, font_file: &Path, width: u32, lines: usize, out: &mut dyn Write) -> Result<(), String> {
+    let font = text::read_font_file(font_file)?;
+    let problems = text::check_string_widths(&extra_data_dir.join("externalized/strings"), resource, &font, width, lines);
+
+    for problem in &problems {
+        writeln!(out, "- {}", problem).map_err(|e| e.to_string())?;
+    }
+    if !problems.is_empty() {
+        return Err(format!("Strings have {} problem(s)", problems.len()));
+    }
+    writeln!(out, "All strings of {} fit", resource).map_err(|e| e.to_string())
+}
+
+fn video_export(smk_file: &Path, png_file: &Path, out: &mut dyn Write) -> Result<(), String> {
+    let video = smacker::read_smacker_file(smk_file)?;
+    let (width, height) = (video.width, video.height);
+
+    let written = smacker::write_frame_pngs(video, png_file)?;
+    match (written.first(), written.last()) {
+        (Some(first), Some(last)) => writeln!(out, "Exported {} frames of {}x{} to {} - {}", written.len(), width, height, first.display(), last.display()),
+        _ => writeln!(out, "{} has no frames", smk_file.display()),
+    }.map_err(|e| e.to_string())
+}
+
+/// Runs `engine_options.command`, writing its output to `out`. `extra_data_dir` is where the mods are installed.
+pub fn run_command(engine_options: &EngineOptions, extra_data_dir: &Path, out: &mut dyn Write) -> Result<(), String> {
+    let mods_dir = extra_data_dir.join("mods");
+    let default_game_json = extra_data_dir.join("externalized/game.json");
+
+    match engine_options.command {
+        Command::RunGame => Err(String::from("Running the game is not a maintenance command")),
+        Command::ConfigShow => config_show(engine_options, out),
+        Command::ConfigSet(ref key, ref value) => config_set(engine_options, key, value, out),
+        Command::ConfigValidate => config_validate(engine_options, &mods_dir, out),
+        Command::ConfigSettings => config_settings(engine_options, out),
+        Command::ConfigSettingsSet(ref key, ref value) => config_settings_set(engine_options, key, value, out),
+        Command::ModsList => mods_list(&mods_dir, out),
+        Command::ModsInfo(ref name) => mods_info(&mods_dir, name, out),
+        Command::SavesList => saves_list(engine_options, out),
+        Command::SavesSnapshot(ref save_file) => saves_snapshot(engine_options, extra_data_dir, save_file, out),
+        Command::SavesCheck(ref save_file) => saves_check(engine_options, extra_data_dir, save_file, out),
+        Command::DataVerify => data_verify(engine_options, out),
+        Command::DataChecksums => data_checksums(engine_options, out),
+        Command::DataExtract(ref output_dir) => data_extract(engine_options, output_dir, out),
+        Command::DataMusic(ref music_json) => data_music(engine_options, extra_data_dir, music_json.as_deref(), out),
+        Command::EdtExport(ref edt_file, ref json_file) => edt_export(engine_options, edt_file, json_file, out),
+        Command::EdtImport(ref json_file, ref edt_file) => edt_import(engine_options, json_file, edt_file, out),
+        Command::ImageExport(ref image_file, ref png_file) => image_export(image_file, png_file, out),
+        Command::ImageExportLocalized(ref name, ref dir, ref png_file) => image_export_localized(engine_options, name, dir, png_file, out),
+        Command::MapInfo(ref map_file) => map_info(engine_options, map_file, out),
+        Command::MapExport(ref map_file, ref json_file) => map_export(engine_options, map_file, json_file, out),
+        Command::MapDiff(ref map_file, ref other_map_file, ref json_file) => map_diff(engine_options, map_file, other_map_file, json_file.as_deref(), out),
+        Command::NpcExport(ref npc_file, ref json_file) => npc_export(engine_options, npc_file, json_file, out),
+        Command::NpcImport(ref json_file, ref npc_file) => npc_import(engine_options, json_file, npc_file, out),
+        Command::PolicyValidate(ref game_json) => policy_validate(game_json.as_deref().unwrap_or(&default_game_json), out),
+        Command::PolicyDiff(ref preset, ref game_json) => policy_diff(preset, game_json.as_deref().unwrap_or(&default_game_json), out),
+        Command::PolicyImp(ref imp_json) => policy_imp(engine_options, extra_data_dir, imp_json.as_deref(), out),
+        Command::ProfilesExport(ref prof_dat, ref json_file) => profiles_export(engine_options, prof_dat, json_file, out),
+        Command::ProfilesImport(ref json_file, ref prof_dat) => profiles_import(engine_options, json_file, prof_dat, out),
+        Command::TextRender(ref font_file, width, ref text, ref png_file) => text_render(engine_options, font_file, width, text, png_file, out),
+        Command::TextCheck(ref resource, ref font_file, width, lines) => text_check(extra_data_dir, resource, font_file, width, lines, out),
+        Command::VideoExport(ref smk_file, ref png_file) => video_export(smk_file, png_file, out),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate tempdir;
+
+    use std::fs;
+    use std::path::PathBuf;
+
+    use serde_json;
+    use serde_json::Value;
+
+    use super::Command;
+
+    fn args(words: &[&str]) -> Vec<String> {
+        words.iter().map(|w| String::from(*w)).collect()
+    }
+
+    fn assets_dir() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../assets")
+    }
+
+    fn run(engine_options: &::EngineOptions) -> (Result<(), String>, String) {
+        let mut out: Vec<u8> = vec!();
+        let result = super::run_command(engine_options, &assets_dir(), &mut out);
+        (result, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn from_args_should_parse_all_commands() {
+        assert_eq!(Command::from_args(&args(&[])), Ok(Command::RunGame));
+        assert_eq!(Command::from_args(&args(&["config", "show"])), Ok(Command::ConfigShow));
+        assert_eq!(Command::from_args(&args(&["config", "set", "res", "800x600"])), Ok(Command::ConfigSet(String::from("res"), String::from("800x600"))));
+        assert_eq!(Command::from_args(&args(&["config", "validate"])), Ok(Command::ConfigValidate));
+        assert_eq!(Command::from_args(&args(&["config", "settings"])), Ok(Command::ConfigSettings));
+        assert_eq!(Command::from_args(&args(&["config", "settings", "speech", "off"])), Ok(Command::ConfigSettingsSet(String::from("speech"), String::from("off"))));
+        assert_eq!(Command::from_args(&args(&["mods", "list"])), Ok(Command::ModsList));
+        assert_eq!(Command::from_args(&args(&["mods", "info", "o-fortuna"])), Ok(Command::ModsInfo(String::from("o-fortuna"))));
+        assert_eq!(Command::from_args(&args(&["saves", "list"])), Ok(Command::SavesList));
+        assert_eq!(Command::from_args(&args(&["saves", "snapshot", "SaveGame01.sav"])), Ok(Command::SavesSnapshot(PathBuf::from("SaveGame01.sav"))));
+        assert_eq!(Command::from_args(&args(&["saves", "check", "SaveGame01.sav"])), Ok(Command::SavesCheck(PathBuf::from("SaveGame01.sav"))));
+        assert_eq!(Command::from_args(&args(&["data", "verify"])), Ok(Command::DataVerify));
+        assert_eq!(Command::from_args(&args(&["data", "checksums"])), Ok(Command::DataChecksums));
+        assert_eq!(Command::from_args(&args(&["data", "extract", "out"])), Ok(Command::DataExtract(PathBuf::from("out"))));
+        assert_eq!(Command::from_args(&args(&["data", "music"])), Ok(Command::DataMusic(None)));
+        assert_eq!(Command::from_args(&args(&["data", "music", "music.json"])), Ok(Command::DataMusic(Some(PathBuf::from("music.json")))));
+        assert_eq!(Command::from_args(&args(&["edt", "export", "009.edt", "009.edt.json"])), Ok(Command::EdtExport(PathBuf::from("009.edt"), PathBuf::from("009.edt.json"))));
+        assert_eq!(Command::from_args(&args(&["edt", "import", "009.edt.json", "009.edt"])), Ok(Command::EdtImport(PathBuf::from("009.edt.json"), PathBuf::from("009.edt"))));
+        assert_eq!(Command::from_args(&args(&["image", "export", "a.sti", "a.png"])), Ok(Command::ImageExport(PathBuf::from("a.sti"), PathBuf::from("a.png"))));
+        assert_eq!(
+            Command::from_args(&args(&["image", "localized", "desktop", "data", "desktop.png"])),
+            Ok(Command::ImageExportLocalized(String::from("desktop"), PathBuf::from("data"), PathBuf::from("desktop.png")))
+        );
+        assert_eq!(Command::from_args(&args(&["map", "info", "A9.dat"])), Ok(Command::MapInfo(PathBuf::from("A9.dat"))));
+        assert_eq!(Command::from_args(&args(&["map", "export", "A9.dat", "A9.json"])), Ok(Command::MapExport(PathBuf::from("A9.dat"), PathBuf::from("A9.json"))));
+        assert_eq!(Command::from_args(&args(&["map", "diff", "A9.dat", "B9.dat"])), Ok(Command::MapDiff(PathBuf::from("A9.dat"), PathBuf::from("B9.dat"), None)));
+        assert_eq!(Command::from_args(&args(&["map", "diff", "A9.dat", "B9.dat", "diff.json"])), Ok(Command::MapDiff(PathBuf::from("A9.dat"), PathBuf::from("B9.dat"), Some(PathBuf::from("diff.json")))));
+        assert_eq!(Command::from_args(&args(&["npc", "export", "057.npc", "057.json"])), Ok(Command::NpcExport(PathBuf::from("057.npc"), PathBuf::from("057.json"))));
+        assert_eq!(Command::from_args(&args(&["npc", "import", "057.json", "057.npc"])), Ok(Command::NpcImport(PathBuf::from("057.json"), PathBuf::from("057.npc"))));
+        assert_eq!(Command::from_args(&args(&["policy", "validate"])), Ok(Command::PolicyValidate(None)));
+        assert_eq!(Command::from_args(&args(&["policy", "diff", "vanilla", "game.json"])), Ok(Command::PolicyDiff(String::from("vanilla"), Some(PathBuf::from("game.json")))));
+        assert_eq!(Command::from_args(&args(&["policy", "imp"])), Ok(Command::PolicyImp(None)));
+        assert_eq!(Command::from_args(&args(&["policy", "imp", "imp.json"])), Ok(Command::PolicyImp(Some(PathBuf::from("imp.json")))));
+        assert_eq!(Command::from_args(&args(&["profiles", "export", "prof.dat", "prof.json"])), Ok(Command::ProfilesExport(PathBuf::from("prof.dat"), PathBuf::from("prof.json"))));
+        assert_eq!(Command::from_args(&args(&["profiles", "import", "prof.json", "prof.dat"])), Ok(Command::ProfilesImport(PathBuf::from("prof.json"), PathBuf::from("prof.dat"))));
+        assert_eq!(Command::from_args(&args(&["text", "render", "font.sti", "80", "Hello", "hello.png"])), Ok(Command::TextRender(PathBuf::from("font.sti"), 80, String::from("Hello"), PathBuf::from("hello.png"))));
+        assert_eq!(Command::from_args(&args(&["text", "check", "new-strings", "font.sti", "80"])), Ok(Command::TextCheck(String::from("new-strings"), PathBuf::from("font.sti"), 80, 1)));
+        assert_eq!(Command::from_args(&args(&["text", "check", "new-strings", "font.sti", "80", "3"])), Ok(Command::TextCheck(String::from("new-strings"), PathBuf::from("font.sti"), 80, 3)));
+        assert_eq!(Command::from_args(&args(&["text", "check", "new-strings", "font.sti", "wide"])), Err(String::from("Invalid width 'wide'.")));
+        assert_eq!(Command::from_args(&args(&["video", "export", "intro.smk", "intro.png"])), Ok(Command::VideoExport(PathBuf::from("intro.smk"), PathBuf::from("intro.png"))));
+    }
+
+    #[test]
+    fn from_args_should_fail_with_incomplete_commands() {
+        assert_eq!(Command::from_args(&args(&["config"])), Err(String::from("Unknown arguments: 'config'.")));
+        assert_eq!(Command::from_args(&args(&["mods", "info"])), Err(String::from("Unknown arguments: 'mods info'.")));
+    }
+
+    #[test]
+    fn command_groups_should_be_unique() {
+        assert_eq!(super::command_groups(), vec!("config", "mods", "saves", "data", "edt", "image", "map", "npc", "policy", "profiles", "text", "video"));
+    }
+
+    #[test]
+    fn mods_list_should_list_bundled_mods() {
+        let mut engine_options = ::EngineOptions::default();
+        engine_options.command = Command::ModsList;
+
+        let (result, out) = run(&engine_options);
+
+        assert_eq!(result, Ok(()));
+        assert!(out.lines().any(|l| l == "from-russia-with-love"));
+        assert!(out.lines().any(|l| l == "o-fortuna"));
+    }
+
+    #[test]
+    fn mods_info_should_show_files_and_readme() {
+        let mut engine_options = ::EngineOptions::default();
+        engine_options.command = Command::ModsInfo(String::from("from-russia-with-love"));
+
+        let (result, out) = run(&engine_options);
+
+        assert_eq!(result, Ok(()));
+        assert!(out.contains("    data/maps/A9.dat\n"));
+    }
+
+    #[test]
+    fn mods_info_should_fail_for_unknown_mod() {
+        let mut engine_options = ::EngineOptions::default();
+        engine_options.command = Command::ModsInfo(String::from("unknown"));
+
+        assert!(run(&engine_options).0.unwrap_err().starts_with("Mod 'unknown' is not installed"));
+    }
+
+    #[test]
+    fn config_validate_should_report_problems() {
+        let mut engine_options = ::EngineOptions::default();
+        engine_options.command = Command::ConfigValidate;
+        engine_options.mods = vec!(String::from("unknown"));
+        engine_options.resolution = (320, 200);
+
+        let (result, out) = run(&engine_options);
+
+        assert_eq!(result, Err(String::from("Configuration has 3 problem(s)")));
+        assert!(out.contains("- Vanilla data directory is not set\n"));
+        assert!(out.contains("- Mod 'unknown' is not installed"));
+        assert!(out.contains("- Resolution 320x200 is smaller than the minimum of 640x480\n"));
+    }
+
+    #[test]
+    fn config_set_should_write_the_changed_value() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        let mut engine_options = ::EngineOptions::default();
+        engine_options.stracciatella_home = PathBuf::from(temp_dir.path());
+        engine_options.command = Command::ConfigSet(String::from("res"), String::from("800x600"));
+
+        assert_eq!(run(&engine_options).0, Ok(()));
+        assert_eq!(::parse_json_config(PathBuf::from(temp_dir.path())).unwrap().resolution, (800, 600));
+
+        engine_options.command = Command::ConfigSet(String::from("fullscreen"), String::from("true"));
+        assert_eq!(run(&engine_options).0, Ok(()));
+        assert!(::parse_json_config(PathBuf::from(temp_dir.path())).unwrap().start_in_fullscreen);
+    }
+
+    #[test]
+    fn config_set_should_not_write_the_command_line_options() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        fs::write(temp_dir.path().join("ja2.json"), r#"{"data_dir": "/opt/ja2", "res": "640x480", "help": "kept"}"#).unwrap();
+        let mut engine_options = ::parse_json_config(PathBuf::from(temp_dir.path())).unwrap();
+        // Like `ja2 --res 1024x768 config set debug true`
+        engine_options.resolution = (1024, 768);
+        engine_options.command = Command::ConfigSet(String::from("debug"), String::from("true"));
+
+        assert_eq!(run(&engine_options).0, Ok(()));
+        let stored: Value = serde_json::from_str(&fs::read_to_string(temp_dir.path().join("ja2.json")).unwrap()).unwrap();
+        let expected: Value = serde_json::from_str(r#"{"data_dir": "/opt/ja2", "res": "640x480", "help": "kept", "debug": true}"#).unwrap();
+        assert_eq!(stored, expected);
+    }
+
+    #[test]
+    fn config_set_should_reject_invalid_values_and_keys() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        let mut engine_options = ::EngineOptions::default();
+        engine_options.stracciatella_home = PathBuf::from(temp_dir.path());
+
+        engine_options.command = Command::ConfigSet(String::from("res"), String::from("huge"));
+        assert_eq!(run(&engine_options).0, Err(String::from("Invalid value for 'res': Incorrect resolution format, should be WIDTHxHEIGHT.")));
+
+        engine_options.command = Command::ConfigSet(String::from("unknown"), String::from("1"));
+        assert!(run(&engine_options).0.unwrap_err().starts_with("Unknown configuration key 'unknown'"));
+        assert!(fs::read_dir(temp_dir.path()).unwrap().next().is_none());
+    }
+
+    #[test]
+    fn data_verify_should_fail_without_data_dir() {
+        let mut engine_options = ::EngineOptions::default();
+        engine_options.command = Command::DataVerify;
+
+        assert_eq!(run(&engine_options).0, Err(String::from("Vanilla data directory is not set")));
+    }
+
+    #[test]
+    fn data_verify_should_list_missing_libraries() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        fs::create_dir(temp_dir.path().join("data")).unwrap();
+        fs::File::create(temp_dir.path().join("data/maps.slf")).unwrap();
+        fs::File::create(temp_dir.path().join("data/german.slf")).unwrap();
+        let mut engine_options = ::EngineOptions::default();
+        engine_options.vanilla_data_dir = PathBuf::from(temp_dir.path());
+        engine_options.command = Command::DataVerify;
+
+        let (result, out) = run(&engine_options);
+
+        assert_eq!(result, Err(String::from("20 missing libraries")));
+        assert!(out.starts_with("Missing:\n    ambient.slf\n"));
+        assert!(out.ends_with("Other libraries, not checked:\n    german.slf\n"));
+    }
+
+    #[test]
+    fn edt_import_and_export_should_convert_dialogs() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        let edt_file = temp_dir.path().join("009.edt");
+        let mut engine_options = ::EngineOptions::default();
+        engine_options.resource_version = ::ResourceVersion::RUSSIAN;
+
+        engine_options.command = Command::EdtImport(assets_dir().join("mods/test-json-dialogs/data/mercedt/009.edt.json"), edt_file.clone());
+        let (result, out) = run(&engine_options);
+        assert_eq!(result, Ok(()));
+        assert!(out.starts_with("Compiled 200 quotes to "));
+
+        engine_options.command = Command::EdtExport(edt_file, temp_dir.path().join("009.edt.json"));
+        let (result, out) = run(&engine_options);
+        assert_eq!(result, Ok(()));
+        assert!(out.starts_with("Exported 200 quotes to "));
+    }
+
+    #[test]
+    fn profiles_import_should_fail_with_invalid_json() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        let json_file = temp_dir.path().join("prof.json");
+        fs::write(&json_file, "[{\"name\": 1}]").unwrap();
+        let mut engine_options = ::EngineOptions::default();
+        engine_options.command = Command::ProfilesImport(json_file, temp_dir.path().join("prof.dat"));
+
+        assert!(run(&engine_options).0.unwrap_err().contains("Error parsing profiles"));
+        assert!(!temp_dir.path().join("prof.dat").exists());
+    }
+
+    #[test]
+    fn npc_import_should_round_trip_an_exported_file() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        let npc_file = temp_dir.path().join("A9.npc");
+        let json_file = temp_dir.path().join("A9.json");
+        fs::write(&npc_file, vec!(0xFFu8; 1600)).unwrap();
+        let mut engine_options = ::EngineOptions::default();
+
+        engine_options.command = Command::NpcExport(npc_file.clone(), json_file.clone());
+        assert_eq!(run(&engine_options), (Ok(()), format!("Exported 50 records to {}\n", json_file.display())));
+        fs::remove_file(&npc_file).unwrap();
+        engine_options.command = Command::NpcImport(json_file, npc_file.clone());
+        assert_eq!(run(&engine_options).0, Ok(()));
+        assert_eq!(fs::read(&npc_file).unwrap()[0..19], [0xFF; 19]);
+    }
+
+    #[test]
+    fn map_info_should_summarize_a_map() {
+        let mut engine_options = ::EngineOptions::default();
+        engine_options.command = Command::MapInfo(assets_dir().join("mods/generous-rebels/data/Maps/A10_b1.dat"));
+
+        let (result, out) = run(&engine_options);
+        assert_eq!(result, Ok(()));
+        assert!(out.starts_with("Version:      5.00.25\nTileset:      "), "{}", out);
+        assert!(out.contains("Exit grids:   2\n"), "{}", out);
+    }
+
+    #[test]
+    fn map_diff_should_compare_two_maps() {
+        let map_file = assets_dir().join("mods/generous-rebels/data/Maps/A10_b1.dat");
+        let mut engine_options = ::EngineOptions::default();
+
+        engine_options.command = Command::MapDiff(map_file.clone(), map_file.clone(), None);
+        assert_eq!(run(&engine_options), (Ok(()), String::from("The maps are the same\n")));
+
+        engine_options.command = Command::MapDiff(map_file, assets_dir().join("mods/from-russia-with-love/data/maps/A9.dat"), None);
+        let (result, out) = run(&engine_options);
+        assert_eq!(result, Ok(()));
+        assert!(out.starts_with("~ header flags: 509 -> 381\n"), "{}", &out[..200]);
+    }
+
+    #[test]
+    fn image_export_should_number_the_subimages() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        let mut engine_options = ::EngineOptions::default();
+        engine_options.command = Command::ImageExport(assets_dir().join("externalized/sti/interface/LOADSCREENTAB.STI"), temp_dir.path().join("tab.png"));
+
+        let (result, out) = run(&engine_options);
+        assert_eq!(result, Ok(()));
+        assert_eq!(out.lines().count(), 4);
+        assert!(temp_dir.path().join("tab_3.png").exists());
+    }
+
+    #[test]
+    fn image_localized_should_find_the_file_of_the_resversion() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        let mut engine_options = ::EngineOptions::default();
+        engine_options.resource_version = ::ResourceVersion::GERMAN;
+
+        engine_options.command = Command::ImageExportLocalized(String::from("desktop"), temp_dir.path().to_path_buf(), temp_dir.path().join("desktop.png"));
+        assert_eq!(run(&engine_options).0, Err(format!("german/desktop_german.pcx not found in {}", temp_dir.path().display())));
+
+        engine_options.command = Command::ImageExportLocalized(String::from("deskt0p"), temp_dir.path().to_path_buf(), temp_dir.path().join("desktop.png"));
+        assert!(run(&engine_options).0.unwrap_err().starts_with("Unknown localized graphic 'deskt0p', known are: aimsymbol, "));
+    }
+
+    #[test]
+    fn data_extract_should_report_files_that_were_not_converted() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        fs::create_dir_all(temp_dir.path().join("data")).unwrap();
+        let slf = ::test_utils::slf_file("interface\\", &[("tab.sti", Some(b"STCI")), ("click.wav", Some(b"RIFF"))]);
+        fs::write(temp_dir.path().join("data/interface.slf"), slf).unwrap();
+        let mut engine_options = ::EngineOptions::default();
+        engine_options.vanilla_data_dir = temp_dir.path().to_path_buf();
+        engine_options.command = Command::DataExtract(temp_dir.path().join("out"));
+
+        let (result, out) = run(&engine_options);
+        assert_eq!(result, Ok(()));
+        assert_eq!(out, format!("interface/tab.sti: Unexpected end of STI data\nExtracted 2 files from 1 libraries to {}\n", temp_dir.path().join("out").display()));
+        assert_eq!(fs::read(temp_dir.path().join("out/interface/click.wav")).unwrap(), b"RIFF");
+    }
+
+    #[test]
+    fn config_settings_should_edit_ja2_set() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        let mut engine_options = ::EngineOptions::default();
+        engine_options.stracciatella_home = temp_dir.path().to_path_buf();
+
+        engine_options.command = Command::ConfigSettings;
+        let (result, out) = run(&engine_options);
+        assert_eq!(result, Ok(()));
+        assert!(out.contains("# Not written by the game yet, these are the defaults\n"), "{}", out);
+
+        engine_options.command = Command::ConfigSettingsSet(String::from("subtitles"), String::from("off"));
+        assert_eq!(run(&engine_options), (Ok(()), String::from("Set 'subtitles' to false\n")));
+        assert_eq!(fs::read(temp_dir.path().join("Ja2.set")).unwrap()[6], 0);
+
+        engine_options.command = Command::ConfigSettingsSet(String::from("speech"), String::from("off"));
+        assert_eq!(run(&engine_options).0, Err(String::from("At least one of subtitles and speech has to be on")));
+    }
+
+    #[test]
+    fn policy_commands_should_check_game_json() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        let game_json = temp_dir.path().join("game.json");
+        let json = fs::read_to_string(assets_dir().join("externalized/game.json")).unwrap();
+        fs::write(&game_json, json.replace("\"ms_per_game_cycle\": 25", "\"ms_per_game_cycle\": 0")).unwrap();
+        let mut engine_options = ::EngineOptions::default();
+
+        engine_options.command = Command::PolicyValidate(None);
+        assert_eq!(run(&engine_options), (Ok(()), String::from("Game policy is valid\n")));
+
+        engine_options.command = Command::PolicyValidate(Some(game_json.clone()));
+        assert_eq!(run(&engine_options), (Err(String::from("Game policy has 1 problem(s)")), String::from("- ms_per_game_cycle is 0, it has to be positive\n")));
+
+        engine_options.command = Command::PolicyDiff(String::from("stracciatella default"), Some(game_json));
+        assert_eq!(run(&engine_options), (Ok(()), String::from("ms_per_game_cycle: 25 -> 0\n")));
+    }
+
+    #[test]
+    fn saves_commands_should_snapshot_and_check_the_policy() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        fs::create_dir_all(temp_dir.path().join("SavedGames")).unwrap();
+        fs::write(temp_dir.path().join("SavedGames/SaveGame01.sav"), b"").unwrap();
+        let mut engine_options = ::EngineOptions::default();
+        engine_options.stracciatella_home = temp_dir.path().to_path_buf();
+        let save_path = temp_dir.path().join("SavedGames/SaveGame01.sav");
+
+        engine_options.command = Command::SavesCheck(PathBuf::from("SaveGame01.sav"));
+        assert_eq!(run(&engine_options).0, Err(format!("{} has no policy snapshot", save_path.display())));
+
+        engine_options.command = Command::SavesSnapshot(PathBuf::from("SaveGame01.sav"));
+        assert_eq!(run(&engine_options), (Ok(()), format!("Wrote {}.policy.json\n", save_path.display())));
+
+        engine_options.command = Command::SavesCheck(PathBuf::from("SaveGame01.sav"));
+        assert_eq!(run(&engine_options), (Ok(()), format!("{} uses the current rules\n", save_path.display())));
+
+        fs::rename(temp_dir.path().join("SavedGames"), temp_dir.path().join("SavedGames-unknown-mod")).unwrap();
+        engine_options.mods = vec!(String::from("unknown-mod"));
+        assert_eq!(run(&engine_options), (Ok(()), String::from("mods: none -> unknown-mod\n")));
+    }
+
+    #[test]
+    fn policy_imp_should_check_the_imp_json_of_the_first_mod() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        let imp_json = temp_dir.path().join("imp.json");
+        fs::write(&imp_json, r#"{ "inventory": ["CANTEEN"], "if_good_shooter": ["MP5K", "CLIP9_30"], "if_normal_shooter": ["BERETA_93R"] }"#).unwrap();
+        let mut engine_options = ::EngineOptions::default();
+
+        engine_options.command = Command::PolicyImp(None);
+        assert_eq!(run(&engine_options), (Ok(()), format!("{} is valid\n", assets_dir().join("externalized/imp.json").display())));
+
+        engine_options.command = Command::PolicyImp(Some(imp_json.clone()));
+        assert_eq!(run(&engine_options), (Err(String::from("IMP policy has 1 problem(s)")), format!("- {}: if_normal_shooter[0]: unknown item 'BERETA_93R'\n", imp_json.display())));
+    }
+
+    #[test]
+    fn data_music_should_check_the_music_json_of_the_first_mod() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        fs::create_dir_all(temp_dir.path().join("data")).unwrap();
+        let slf = ::test_utils::slf_file("music\\", &[("menumix1.wav", Some(b"RIFF"))]);
+        fs::write(temp_dir.path().join("data/music.slf"), slf).unwrap();
+        let mut engine_options = ::EngineOptions::default();
+        engine_options.vanilla_data_dir = temp_dir.path().to_path_buf();
+        engine_options.mods = vec!(String::from("o-fortuna"));
+        engine_options.command = Command::DataMusic(None);
+
+        let (result, out) = run(&engine_options);
+        let music_json = assets_dir().join("mods/o-fortuna/data/music.json");
+        assert_eq!(result, Err(String::from("Music has 15 problem(s)")));
+        assert!(out.starts_with(&format!("- {}: laptop[0]: music/marimbad 2.wav: not found\n", music_json.display())), "{}", out);
+        assert!(out.contains("main_menu[0]: music/o-fortuna.wav: not found\n"), "{}", out);
+    }
+
+    #[test]
+    fn video_export_should_write_every_frame() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        let smk_file = temp_dir.path().join("intro.smk");
+        fs::write(&smk_file, ::test_utils::test_video()).unwrap();
+        let mut engine_options = ::EngineOptions::default();
+        engine_options.command = Command::VideoExport(smk_file, temp_dir.path().join("intro.png"));
+
+        let (result, out) = run(&engine_options);
+        assert_eq!(result, Ok(()));
+        assert_eq!(out, format!("Exported 2 frames of 8x8 to {} - {}\n", temp_dir.path().join("intro_0.png").display(), temp_dir.path().join("intro_1.png").display()));
+        assert!(temp_dir.path().join("intro_1.png").is_file());
+    }
+
+    #[test]
+    fn text_render_should_wrap_and_draw_the_text() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        let font_file = temp_dir.path().join("font.sti");
+        fs::write(&font_file, ::test_utils::test_font()).unwrap();
+        let png_file = temp_dir.path().join("text.png");
+        let mut engine_options = ::EngineOptions::default();
+        engine_options.command = Command::TextRender(font_file, 20, String::from("Hello world \u{20AC}"), png_file.clone());
+
+        let (result, out) = run(&engine_options);
+        assert_eq!(result, Ok(()));
+        assert_eq!(out, format!("  20 Hello\n  20 world\n   4 \u{20AC}\nNo glyph for '\u{20AC}' U+20AC, drawn as '?'\nWrote {}\n", png_file.display()));
+        assert!(png_file.is_file());
+    }
+}
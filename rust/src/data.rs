@@ -0,0 +1,179 @@
+//! Access to the files of the original game in the vanilla data directory.
+
+use std::fs;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use slf;
+use EngineOptions;
+
+/// Name of the folder inside the vanilla data directory that holds the game resources.
+static BASE_DATA_DIR: &str = "data";
+
+/// Finds the entry `name` in `dir`, ignoring the case of the file name like the game does.
+pub fn find_case_insensitive(dir: &Path, name: &str) -> Option<PathBuf> {
+    let exact = dir.join(name);
+    if exact.exists() {
+        return Some(exact);
+    }
+
+    let lower_name = name.to_lowercase();
+    fs::read_dir(dir).ok()?
+        .filter_map(|e| e.ok())
+        .find(|e| e.file_name().to_string_lossy().to_lowercase() == lower_name)
+        .map(|e| e.path())
+}
+
+/// Returns the `Data` folder inside the vanilla data directory.
+pub fn find_data_dir(vanilla_data_dir: &Path) -> Result<PathBuf, String> {
+    if vanilla_data_dir == Path::new("") {
+        return Err(String::from("Vanilla data directory is not set"));
+    }
+    if !vanilla_data_dir.is_dir() {
+        return Err(format!("Vanilla data directory {} does not exist", vanilla_data_dir.display()));
+    }
+
+    match find_case_insensitive(vanilla_data_dir, BASE_DATA_DIR) {
+        Some(ref p) if p.is_dir() => Ok(p.clone()),
+        _ => Err(format!("Vanilla data directory {} does not contain a '{}' folder", vanilla_data_dir.display(), BASE_DATA_DIR)),
+    }
+}
+
+/// Lists all SLF libraries in `data_dir`, sorted by file name.
+pub fn list_slf_libraries(data_dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let entries = fs::read_dir(data_dir).map_err(|e| format!("Error reading {}: {}", data_dir.display(), e))?;
+    let mut libraries: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_file())
+        .filter(|p| p.extension().map(|e| e.to_string_lossy().to_lowercase() == "slf").unwrap_or(false))
+        .collect();
+
+    libraries.sort_by_key(|p| p.file_name().map(|n| n.to_string_lossy().to_lowercase()));
+    Ok(libraries)
+}
+
+/// The file of the externalized data that the game reads, e.g. `game.json`: the one in the data folder of the first
+/// mod if it has one, otherwise the one in `externalized`.
+pub fn externalized_file(engine_options: &EngineOptions, extra_data_dir: &Path, name: &str) -> PathBuf {
+    engine_options.mods.first()
+        .and_then(|m| find_case_insensitive(&extra_data_dir.join("mods").join(m).join("data"), name))
+        .unwrap_or_else(|| extra_data_dir.join("externalized").join(name))
+}
+
+/// Finds a relative path like `music/tensor a.wav` in `dir`, ignoring the case of every component.
+pub fn find_path_case_insensitive(dir: &Path, path: &str) -> Option<PathBuf> {
+    path.split('/').filter(|c| !c.is_empty()).try_fold(dir.to_path_buf(), |dir, name| find_case_insensitive(&dir, name))
+}
+
+/// Where a game resource was found.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResourceLocation {
+    File(PathBuf),
+    /// An entry of the SLF library at the path.
+    Library(PathBuf, slf::SlfEntry),
+}
+
+/// The places `openGameResForReading` looks for a resource, in its order: the data folder of the first mod, the
+/// externalized data, the vanilla `Data` folder and the SLF libraries in it.
+#[derive(Debug, Clone)]
+pub struct GameResources {
+    pub dirs: Vec<PathBuf>,
+    pub libraries: Vec<slf::SlfLibrary>,
+}
+
+impl GameResources {
+    pub fn new(engine_options: &EngineOptions, extra_data_dir: &Path) -> Result<GameResources, String> {
+        let data_dir = find_data_dir(&engine_options.vanilla_data_dir)?;
+        let mut dirs: Vec<PathBuf> = engine_options.mods.first().map(|m| extra_data_dir.join("mods").join(m).join("data")).into_iter().collect();
+        dirs.push(extra_data_dir.join("externalized"));
+        dirs.push(data_dir.clone());
+        let libraries = list_slf_libraries(&data_dir)?.iter().map(|p| slf::read_slf_library(p)).collect::<Result<_, _>>()?;
+
+        Ok(GameResources { dirs, libraries })
+    }
+
+    /// Finds a resource by its path inside the game data, e.g. `music/tensor a.wav`, ignoring the case.
+    pub fn find(&self, path: &str) -> Option<ResourceLocation> {
+        let path = path.replace('\\', "/");
+        if let Some(file) = self.dirs.iter().filter_map(|dir| find_path_case_insensitive(dir, &path)).find(|p| p.is_file()) {
+            return Some(ResourceLocation::File(file));
+        }
+
+        let lower_path = path.to_lowercase();
+        self.libraries.iter()
+            .filter_map(|library| library.entries.iter().find(|e| library.game_path(e).to_lowercase() == lower_path).map(|e| (library, e)))
+            .map(|(library, entry)| ResourceLocation::Library(library.path.clone(), entry.clone()))
+            .next()
+    }
+
+    pub fn read(&self, location: &ResourceLocation) -> Result<Vec<u8>, String> {
+        match *location {
+            ResourceLocation::File(ref path) => fs::read(path).map_err(|e| format!("Error reading {}: {}", path.display(), e)),
+            ResourceLocation::Library(ref path, ref entry) => {
+                let mut file = File::open(path).map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+                slf::read_slf_entry(&mut file, entry)
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate tempdir;
+
+    use std::fs;
+    use std::fs::File;
+    use std::path::Path;
+
+    use test_utils::slf_file;
+
+    #[test]
+    fn find_data_dir_should_ignore_case() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        fs::create_dir(temp_dir.path().join("DATA")).unwrap();
+
+        assert_eq!(super::find_data_dir(temp_dir.path()), Ok(temp_dir.path().join("DATA")));
+    }
+
+    #[test]
+    fn find_data_dir_should_fail_without_data_folder() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+
+        assert!(super::find_data_dir(temp_dir.path()).unwrap_err().contains("does not contain a 'data' folder"));
+        assert_eq!(super::find_data_dir(Path::new("")), Err(String::from("Vanilla data directory is not set")));
+    }
+
+    #[test]
+    fn list_slf_libraries_should_only_return_slf_files() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        File::create(temp_dir.path().join("Tilesets.slf")).unwrap();
+        File::create(temp_dir.path().join("ANIMS.SLF")).unwrap();
+        File::create(temp_dir.path().join("readme.txt")).unwrap();
+
+        let libraries = super::list_slf_libraries(temp_dir.path()).unwrap();
+
+        assert_eq!(libraries, vec!(temp_dir.path().join("ANIMS.SLF"), temp_dir.path().join("Tilesets.slf")));
+    }
+
+    #[test]
+    fn game_resources_should_prefer_files_to_libraries() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        let mut engine_options = ::EngineOptions::default();
+        engine_options.vanilla_data_dir = temp_dir.path().join("game");
+        engine_options.mods = vec!(String::from("a"));
+        fs::create_dir_all(temp_dir.path().join("game/Data/MUSIC")).unwrap();
+        fs::create_dir_all(temp_dir.path().join("extra/mods/a/data/music")).unwrap();
+        fs::write(temp_dir.path().join("game/Data/Music.slf"), slf_file("music\\", &[("b.wav", Some(b"lib b")), ("c.wav", Some(b"lib c"))])).unwrap();
+        fs::write(temp_dir.path().join("game/Data/MUSIC/B.WAV"), b"data b").unwrap();
+        fs::write(temp_dir.path().join("extra/mods/a/data/music/a.wav"), b"mod a").unwrap();
+
+        let resources = super::GameResources::new(&engine_options, &temp_dir.path().join("extra")).unwrap();
+        let read = |path: &str| resources.find(path).map(|l| resources.read(&l).unwrap());
+
+        assert_eq!(read("music/a.wav"), Some(b"mod a".to_vec()));
+        assert_eq!(read("music/b.wav"), Some(b"data b".to_vec()));
+        assert_eq!(read("Music\\C.wav"), Some(b"lib c".to_vec()));
+        assert_eq!(read("music/d.wav"), None);
+    }
+}
@@ -0,0 +1,227 @@
+//! Generation of the man page and shell completion scripts from `COMMAND_LINE_OPTIONS`.
+
+use std::fmt::Write;
+
+use commands::{command_groups, COMMANDS};
+use logging::LOG_LEVEL_NAMES;
+
+use CommandLineOption;
+use CommandLineOptionKind;
+use CommandLineOptionValues;
+use ResourceVersion;
+use COMMAND_LINE_OPTIONS;
+
+static BINARY_NAME: &str = "ja2";
+
+fn resource_version_names() -> Vec<String> {
+    ResourceVersion::all().iter().map(|v| v.to_string()).collect()
+}
+
+fn escape_roff(s: &str) -> String {
+    s.replace("\\", "\\\\").replace("-", "\\-")
+}
+
+fn escape_single_quoted(s: &str) -> String {
+    s.replace("'", "'\\''")
+}
+
+fn escape_zsh_description(s: &str) -> String {
+    escape_single_quoted(&s.replace("[", "\\[").replace("]", "\\]").replace(":", "\\:"))
+}
+
+fn escape_fish(s: &str) -> String {
+    s.replace("\\", "\\\\").replace("'", "\\'")
+}
+
+/// Generates the roff source of the `ja2(6)` man page, `version` and `date` are shown in its header.
+pub fn generate_manpage(version: &str, date: &str) -> String {
+    let mut out = String::new();
+
+    out.push_str(".\\\" Manpage for ja2.\n");
+    out.push_str(".\\\" Generated from the command line options of libstracciatella, do not edit by hand.\n");
+    out.push_str(".\\\" Goto https://github.com/ja2-stracciatella/ja2-stracciatella to report or correct errors or typos.\n");
+    writeln!(out, ".TH ja2 6 \"{}\" \"{}\" \"ja2 man page\"", escape_roff(date), escape_roff(version)).unwrap();
+    out.push_str(".SH NAME\nja2 \\- JA2 Stracciatella\n");
+    out.push_str(".SH SYNOPSIS\nja2 [OPTIONS]\n.br\nja2 [OPTIONS] COMMAND\n");
+    out.push_str(".SH DESCRIPTION\n");
+    out.push_str("JA2 Stracciatella is a cross-platform implementation of Jagged Alliance 2 game.\n");
+    out.push_str("This is only the game engine.  Game resources from the original JA2 are required to play the game.\n");
+    out.push_str(".TP\n");
+    out.push_str("When you first start the game, file \\fB~/.ja2/ja2.json\\fR is created.  You should edit this file, so that \\fBdata_dir\\fR points to the directory with game resources from the original JA2 game.\n");
+    out.push_str(".TP\n");
+    out.push_str("You can also use ja2\\-launcher to comfortably configure the game.\n");
+    out.push_str(".SH OPTIONS\n");
+
+    for option in COMMAND_LINE_OPTIONS {
+        out.push_str(".TP\n");
+        match option.kind {
+            CommandLineOptionKind::Flag => writeln!(out, "\\fB\\-{}\\fR", option.name).unwrap(),
+            _ => writeln!(out, "\\fB\\-{} {}\\fR", option.name, escape_roff(option.hint)).unwrap(),
+        }
+        writeln!(out, "{}", escape_roff(option.description)).unwrap();
+    }
+
+    out.push_str(".SH COMMANDS\n");
+    out.push_str("Maintenance commands run without starting the game.\n");
+    for command in COMMANDS {
+        out.push_str(".TP\n");
+        writeln!(out, "\\fB{}\\fR", escape_roff(command.usage)).unwrap();
+        writeln!(out, "{}", escape_roff(command.description)).unwrap();
+    }
+
+    out.push_str(".SH AUTHOR\nThe JA2 Stracciatella Team\n");
+    out
+}
+
+fn option_value_words(option: &CommandLineOption, mods: &[String]) -> Option<Vec<String>> {
+    match option.values {
+        CommandLineOptionValues::ResourceVersion => Some(resource_version_names()),
+        CommandLineOptionValues::Mod => Some(mods.to_vec()),
+        CommandLineOptionValues::LogLevel => Some(LOG_LEVEL_NAMES.iter().map(|l| l.to_string()).collect()),
+        _ => None,
+    }
+}
+
+/// Generates a bash completion script, `mods` are the names offered for `--mod`.
+pub fn generate_bash_completion(mods: &[String]) -> String {
+    let mut out = String::new();
+    let function_name = format!("_{}", BINARY_NAME);
+
+    out.push_str("# bash completion for ja2, generated by libstracciatella\n");
+    writeln!(out, "{}() {{", function_name).unwrap();
+    out.push_str("    local cur prev\n");
+    out.push_str("    cur=\"${COMP_WORDS[COMP_CWORD]}\"\n");
+    out.push_str("    prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n\n");
+    out.push_str("    case \"$prev\" in\n");
+
+    for option in COMMAND_LINE_OPTIONS.iter().filter(|o| o.kind != CommandLineOptionKind::Flag) {
+        writeln!(out, "        -{0}|--{0})", option.name).unwrap();
+        match (option.values, option_value_words(option, mods)) {
+            (_, Some(words)) => writeln!(out, "            COMPREPLY=( $(compgen -W '{}' -- \"$cur\") )", escape_single_quoted(&words.join(" "))).unwrap(),
+            (CommandLineOptionValues::Directory, None) => out.push_str("            COMPREPLY=( $(compgen -d -- \"$cur\") )\n"),
+            _ => out.push_str("            COMPREPLY=()\n"),
+        }
+        out.push_str("            return 0\n");
+        out.push_str("            ;;\n");
+    }
+
+    out.push_str("    esac\n\n");
+    for group in command_groups() {
+        let mut subcommands: Vec<&str> = COMMANDS.iter()
+            .filter(|c| c.usage.starts_with(&format!("{} ", group)))
+            .filter_map(|c| c.usage.split(' ').nth(1))
+            .collect();
+        subcommands.dedup();
+        writeln!(out, "    if [ \"$prev\" = \"{}\" ]; then", group).unwrap();
+        writeln!(out, "        COMPREPLY=( $(compgen -W '{}' -- \"$cur\") )", subcommands.join(" ")).unwrap();
+        out.push_str("        return 0\n");
+        out.push_str("    fi\n");
+    }
+    out.push('\n');
+    let switches: Vec<String> = COMMAND_LINE_OPTIONS.iter().map(|o| format!("-{}", o.name)).collect();
+    writeln!(out, "    COMPREPLY=( $(compgen -W '{} {}' -- \"$cur\") )", switches.join(" "), command_groups().join(" ")).unwrap();
+    out.push_str("    return 0\n");
+    out.push_str("}\n");
+    writeln!(out, "complete -F {} {}", function_name, BINARY_NAME).unwrap();
+    out
+}
+
+/// Generates a zsh completion script, `mods` are the names offered for `--mod`.
+pub fn generate_zsh_completion(mods: &[String]) -> String {
+    let mut out = String::new();
+
+    writeln!(out, "#compdef {}", BINARY_NAME).unwrap();
+    out.push_str("# zsh completion for ja2, generated by libstracciatella\n\n");
+    out.push_str("_arguments \\\n");
+
+    let lines: Vec<String> = COMMAND_LINE_OPTIONS.iter().map(|option| {
+        let repeat = if option.kind == CommandLineOptionKind::Multi { "*" } else { "" };
+        let description = escape_zsh_description(option.description);
+        let action = match (option.kind, option.values, option_value_words(option, mods)) {
+            (CommandLineOptionKind::Flag, _, _) => String::from(""),
+            (_, _, Some(words)) => format!(":{}:({})", option.hint, escape_single_quoted(&words.join(" "))),
+            (_, CommandLineOptionValues::Directory, None) => format!(":{}:_files -/", escape_single_quoted(option.hint)),
+            (_, _, None) => format!(":{}: ", option.hint),
+        };
+        format!("    '{}-{}[{}]{}'", repeat, option.name, description, action)
+    }).collect();
+
+    out.push_str(&lines.join(" \\\n"));
+    write!(out, " \\\n    '1::command:({})'", command_groups().join(" ")).unwrap();
+    out.push('\n');
+    out
+}
+
+/// Generates a fish completion script, `mods` are the names offered for `--mod`.
+pub fn generate_fish_completion(mods: &[String]) -> String {
+    let mut out = String::new();
+
+    out.push_str("# fish completion for ja2, generated by libstracciatella\n");
+    for option in COMMAND_LINE_OPTIONS {
+        write!(out, "complete -c {0} -o {1} -l {1} -d '{2}'", BINARY_NAME, option.name, escape_fish(option.description)).unwrap();
+        match (option.kind, option.values, option_value_words(option, mods)) {
+            (CommandLineOptionKind::Flag, _, _) => {},
+            (_, _, Some(words)) => write!(out, " -x -a '{}'", escape_fish(&words.join(" "))).unwrap(),
+            (_, CommandLineOptionValues::Directory, None) => out.push_str(" -x -a '(__fish_complete_directories)'"),
+            (_, _, None) => out.push_str(" -x"),
+        }
+        out.push('\n');
+    }
+    for group in command_groups() {
+        writeln!(out, "complete -c {} -f -n '__fish_use_subcommand' -a '{}'", BINARY_NAME, group).unwrap();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use COMMAND_LINE_OPTIONS;
+
+    fn mods() -> Vec<String> {
+        vec!(String::from("from-russia-with-love"), String::from("o-fortuna"))
+    }
+
+    #[test]
+    fn generate_manpage_should_document_every_option() {
+        let manpage = super::generate_manpage("0.16.x", "30 November 2017");
+
+        assert!(manpage.starts_with(".\\\" Manpage for ja2."));
+        assert!(manpage.contains(".TH ja2 6 \"30 November 2017\" \"0.16.x\" \"ja2 man page\"\n"));
+        for option in COMMAND_LINE_OPTIONS {
+            assert!(manpage.contains(&format!("\\fB\\-{}", option.name)), "{} is missing in man page", option.name);
+        }
+        assert!(manpage.contains("\\fB\\-res WIDTHxHEIGHT\\fR"));
+        assert!(manpage.contains(".SH COMMANDS\n"));
+        assert!(manpage.contains("\\fBconfig set KEY VALUE\\fR\n"));
+    }
+
+    #[test]
+    fn generate_bash_completion_should_complete_resversions_and_mods() {
+        let completion = super::generate_bash_completion(&mods());
+
+        assert!(completion.contains("compgen -W 'DUTCH ENGLISH FRENCH GERMAN ITALIAN POLISH RUSSIAN RUSSIAN_GOLD'"));
+        assert!(completion.contains("compgen -W 'from-russia-with-love o-fortuna'"));
+        assert!(completion.contains("-datadir|--datadir)\n            COMPREPLY=( $(compgen -d -- \"$cur\") )"));
+        assert!(completion.contains("    if [ \"$prev\" = \"config\" ]; then\n        COMPREPLY=( $(compgen -W 'show set validate settings' -- \"$cur\") )"));
+        assert!(completion.ends_with("complete -F _ja2 ja2\n"));
+    }
+
+    #[test]
+    fn generate_zsh_completion_should_escape_descriptions() {
+        let completion = super::generate_zsh_completion(&mods());
+
+        assert!(completion.starts_with("#compdef ja2\n"));
+        assert!(completion.contains("'*-mod[Start one of the game modifications. MOD_NAME is the name of modification, e.g. '\\''from-russia-with-love. See mods folder for possible options'\\''.]:MOD_NAME:(from-russia-with-love o-fortuna)'"));
+        assert!(completion.contains("'-resversion[Version of the game resources."));
+        assert!(completion.contains("'-fullscreen[Start the game in the fullscreen mode]'"));
+    }
+
+    #[test]
+    fn generate_fish_completion_should_have_one_line_per_option() {
+        let completion = super::generate_fish_completion(&mods());
+
+        assert_eq!(completion.lines().filter(|l| l.starts_with("complete -c ja2 -o ")).count(), COMMAND_LINE_OPTIONS.len());
+        assert!(completion.contains("complete -c ja2 -o window -l window -d 'Start the game in a window'\n"));
+        assert!(completion.contains(" -x -a 'DUTCH ENGLISH FRENCH GERMAN ITALIAN POLISH RUSSIAN RUSSIAN_GOLD'\n"));
+    }
+}
@@ -0,0 +1,416 @@
+//! Reading and writing of EDT files, the fixed-size UTF-16 text records in `BinaryData`, `MercEdt` and `NpcData`.
+//!
+//! The texts are "encrypted" with ROT-1 and some languages need encoding fixes on top, see `LoadEncryptedData` in
+//! `DefaultContentManager.cc`. Decoding is not injective: a few code points map to the same character, e.g. `!` can
+//! be stored as 33 or 34. Encoding always picks the form the original files use and fails for characters that cannot
+//! be stored at all, so only files stored in that form round-trip byte by byte.
+
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use serde_json;
+
+use ResourceVersion;
+
+/// Characters per quote in dialogue files.
+pub const DIALOGUESIZE: usize = 240;
+
+/// The encoding fixes needed for the data files of a resource version, `STRING_ENC_TYPE` in C++.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum StringEncoding {
+    Normal,
+    /// A few lines by Malice are CP437 read as CP1252.
+    English,
+    /// CP1250 read as CP1252, and a different centering code.
+    Polish,
+    /// CP1251 read as CP1252.
+    Russian,
+}
+
+impl From<ResourceVersion> for StringEncoding {
+    fn from(version: ResourceVersion) -> StringEncoding {
+        match version {
+            ResourceVersion::RUSSIAN | ResourceVersion::RUSSIAN_GOLD => StringEncoding::Russian,
+            ResourceVersion::POLISH => StringEncoding::Polish,
+            ResourceVersion::ENGLISH => StringEncoding::English,
+            _ => StringEncoding::Normal,
+        }
+    }
+}
+
+static ENGLISH_FIXES: [(u32, u32); 3] = [(128, 0x00C7), (130, 0x00E9), (135, 0x00E7)];
+
+static POLISH_FIXES: [(u32, u32); 17] = [
+    (143, 0x0179),
+    (163, 0x0141),
+    (165, 0x0104),
+    (175, 0x017B),
+    (179, 0x0142),
+    (182, 179), // not a char, but a format code (centering)
+    (185, 0x0105),
+    (191, 0x017C),
+    (198, 0x0106),
+    (202, 0x0118),
+    (209, 0x0143),
+    (230, 0x0107),
+    (234, 0x0119),
+    (241, 0x0144),
+    (338, 0x015A),
+    (339, 0x015B),
+    (376, 0x017A),
+];
+
+/// Cyrillic texts (by Ivan Dolvich) in the non-Russian versions as ranges of (stored, first character, length).
+static CYRILLIC_FIXES: [(u32, u32, u32); 4] = [
+    (0x044D, 0x0410, 6), // A to IE
+    (0x0453, 0x0401, 1), // IO
+    (0x0454, 0x0416, 20), // ZHE to SHCHA
+    (0x0468, 0x042B, 5), // YERU to YA
+];
+
+fn fix(table: &[(u32, u32)], c: u32) -> u32 {
+    table.iter().find(|&&(from, _)| from == c).map(|&(_, to)| to).unwrap_or(c)
+}
+
+fn unfix(table: &[(u32, u32)], c: u32) -> u32 {
+    table.iter().find(|&&(_, to)| to == c).map(|&(from, _)| from).unwrap_or(c)
+}
+
+fn decode_code(raw: u16, encoding: StringEncoding) -> u32 {
+    let raw = u32::from(raw);
+    let c = if raw > 33 { raw - 1 } else { raw };
+
+    match encoding {
+        StringEncoding::Russian => if (0xC0..=0xFF).contains(&c) { c + 0x0350 } else { c },
+        _ => {
+            let c = match encoding {
+                StringEncoding::English => fix(&ENGLISH_FIXES, c),
+                StringEncoding::Polish => fix(&POLISH_FIXES, c),
+                _ => c,
+            };
+            CYRILLIC_FIXES.iter()
+                .find(|&&(stored, _, len)| stored <= c && c < stored + len)
+                .map(|&(stored, first, _)| c - stored + first)
+                .unwrap_or(c)
+        }
+    }
+}
+
+/// Decodes a single stored code unit. Code units that are no valid character become U+FFFD.
+pub fn decode_char(raw: u16, encoding: StringEncoding) -> char {
+    ::std::char::from_u32(decode_code(raw, encoding)).unwrap_or('\u{FFFD}')
+}
+
+/// Encodes a character the way the original data files store it.
+pub fn encode_char(c: char, encoding: StringEncoding) -> Result<u16, String> {
+    let code = c as u32;
+    let unfixed = match encoding {
+        StringEncoding::Russian => if (0x0410..=0x044F).contains(&code) { code - 0x0350 } else { code },
+        _ => {
+            let cyrillic = CYRILLIC_FIXES.iter()
+                .find(|&&(_, first, len)| first <= code && code < first + len)
+                .map(|&(stored, first, _)| code - first + stored);
+            match (cyrillic, encoding) {
+                (Some(stored), _) => stored,
+                (None, StringEncoding::English) => unfix(&ENGLISH_FIXES, code),
+                (None, StringEncoding::Polish) => unfix(&POLISH_FIXES, code),
+                (None, _) => code,
+            }
+        }
+    };
+    let raw = if unfixed >= 33 { unfixed + 1 } else { unfixed };
+
+    if raw > 0xFFFF || code == 0 || decode_code(raw as u16, encoding) != code {
+        return Err(format!("Character '{}' (U+{:04X}) cannot be stored in an EDT file", c, code));
+    }
+    Ok(raw as u16)
+}
+
+/// Decodes a record of stored code units up to the first null, at most `raw.len() - 1` characters like the game.
+pub fn decode_string(raw: &[u16], encoding: StringEncoding) -> String {
+    let max_len = raw.len().saturating_sub(1);
+
+    raw.iter().take(max_len).take_while(|c| **c != 0).map(|c| decode_char(*c, encoding)).collect()
+}
+
+/// Encodes `text` into a record of `record_chars` code units, padded with nulls.
+pub fn encode_string(text: &str, record_chars: usize, encoding: StringEncoding) -> Result<Vec<u16>, String> {
+    let mut raw = text.chars().map(|c| encode_char(c, encoding)).collect::<Result<Vec<u16>, String>>()?;
+
+    if raw.len() >= record_chars {
+        return Err(format!("Text is {} characters long, at most {} fit into the record: {}", raw.len(), record_chars.saturating_sub(1), text));
+    }
+    raw.resize(record_chars, 0);
+    Ok(raw)
+}
+
+/// The record structure of an EDT file as the number of characters of each field.
+#[derive(Debug, PartialEq, Clone)]
+pub struct EdtLayout {
+    pub field_sizes: Vec<usize>,
+}
+
+impl EdtLayout {
+    /// One quote of `DIALOGUESIZE` characters per record, used by `MercEdt` and `NpcData`.
+    pub fn dialogue() -> EdtLayout {
+        EdtLayout { field_sizes: vec!(DIALOGUESIZE) }
+    }
+
+    /// Detects the layout from the file name. Unknown files are treated as dialogue.
+    pub fn for_file_name(file_name: &str) -> EdtLayout {
+        let field_sizes = match file_name.to_lowercase().as_str() {
+            "imptext.edt" => vec!(5 * 80),
+            "help.edt" => vec!(80 * 8),
+            "credits.edt" => vec!(80),
+            // short name, name and description
+            "itemdesc.edt" => vec!(80, 80, 240),
+            _ => vec!(DIALOGUESIZE),
+        };
+        EdtLayout { field_sizes }
+    }
+
+    pub fn for_path(path: &Path) -> EdtLayout {
+        EdtLayout::for_file_name(&path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default())
+    }
+
+    pub fn record_chars(&self) -> usize {
+        self.field_sizes.iter().sum()
+    }
+
+    pub fn record_bytes(&self) -> usize {
+        self.record_chars() * 2
+    }
+}
+
+/// Decodes all records of an EDT file. Every record has one string per field of the layout.
+pub fn decode_edt(data: &[u8], layout: &EdtLayout, version: ResourceVersion) -> Result<Vec<Vec<String>>, String> {
+    let record_bytes = layout.record_bytes();
+    if record_bytes == 0 || data.len() % record_bytes != 0 {
+        return Err(format!("Size {} is not a multiple of the record size {}", data.len(), record_bytes));
+    }
+    let encoding = StringEncoding::from(version);
+    let raw: Vec<u16> = data.chunks(2).map(|c| u16::from(c[0]) | (u16::from(c[1]) << 8)).collect();
+
+    Ok(raw.chunks(layout.record_chars()).map(|record| {
+        let mut offset = 0;
+        layout.field_sizes.iter().map(|size| {
+            let field = decode_string(&record[offset..offset + size], encoding);
+            offset += size;
+            field
+        }).collect()
+    }).collect())
+}
+
+/// Encodes records into an EDT file, the inverse of `decode_edt`.
+pub fn encode_edt(records: &[Vec<String>], layout: &EdtLayout, version: ResourceVersion) -> Result<Vec<u8>, String> {
+    let encoding = StringEncoding::from(version);
+    let mut data = Vec::with_capacity(records.len() * layout.record_bytes());
+
+    for (index, record) in records.iter().enumerate() {
+        if record.len() != layout.field_sizes.len() {
+            return Err(format!("Record {} has {} fields, expected {}", index, record.len(), layout.field_sizes.len()));
+        }
+        for (text, size) in record.iter().zip(layout.field_sizes.iter()) {
+            let raw = encode_string(text, *size, encoding).map_err(|e| format!("Record {}: {}", index, e))?;
+            for c in raw {
+                data.push(c as u8);
+                data.push((c >> 8) as u8);
+            }
+        }
+    }
+
+    Ok(data)
+}
+
+/// Reads an EDT file, the layout is detected from its name.
+pub fn read_edt_file(path: &Path, version: ResourceVersion) -> Result<Vec<Vec<String>>, String> {
+    let mut data = vec!();
+    File::open(path)
+        .and_then(|mut f| f.read_to_end(&mut data))
+        .map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+
+    decode_edt(&data, &EdtLayout::for_path(path), version).map_err(|e| format!("Error decoding {}: {}", path.display(), e))
+}
+
+/// Writes an EDT file, the layout is detected from its name.
+pub fn write_edt_file(path: &Path, records: &[Vec<String>], version: ResourceVersion) -> Result<(), String> {
+    let data = encode_edt(records, &EdtLayout::for_path(path), version).map_err(|e| format!("Error encoding {}: {}", path.display(), e))?;
+
+    File::create(path)
+        .and_then(|mut f| f.write_all(&data))
+        .map_err(|e| format!("Error writing {}: {}", path.display(), e))
+}
+
+fn single_field_records(records: &[Vec<String>]) -> Result<Vec<&str>, String> {
+    records.iter().enumerate().map(|(index, record)| match record.as_slice() {
+        [text] => Ok(text.as_str()),
+        _ => Err(format!("Record {} has {} fields, the JSON dialog format has one text per quote", index, record.len())),
+    }).collect()
+}
+
+/// Converts dialogue records to the JSON dialog format, an array with one string per quote like `mercedt/009.edt.json`.
+///
+/// Empty quotes stay in the array so the quote numbers do not change.
+pub fn dialogue_to_json(records: &[Vec<String>]) -> Result<String, String> {
+    let quotes = single_field_records(records)?;
+    let mut json = serde_json::to_string_pretty(&quotes).map_err(|e| format!("Error serializing quotes: {}", e))?;
+
+    json.push('\n');
+    Ok(json)
+}
+
+/// Parses the JSON dialog format into dialogue records.
+pub fn dialogue_from_json(json: &str) -> Result<Vec<Vec<String>>, String> {
+    let quotes: Vec<String> = serde_json::from_str(json).map_err(|e| format!("Error parsing JSON dialog: {}", e))?;
+
+    Ok(quotes.into_iter().map(|q| vec!(q)).collect())
+}
+
+/// Exports every quote of an EDT file to a JSON dialog file. Returns the number of quotes.
+pub fn export_edt_to_json(edt_path: &Path, json_path: &Path, version: ResourceVersion) -> Result<usize, String> {
+    let records = read_edt_file(edt_path, version)?;
+    let json = dialogue_to_json(&records).map_err(|e| format!("Error exporting {}: {}", edt_path.display(), e))?;
+
+    File::create(json_path)
+        .and_then(|mut f| f.write_all(json.as_bytes()))
+        .map_err(|e| format!("Error writing {}: {}", json_path.display(), e))?;
+    Ok(records.len())
+}
+
+/// Compiles a JSON dialog file back to an EDT file. Returns the number of quotes.
+pub fn compile_json_to_edt(json_path: &Path, edt_path: &Path, version: ResourceVersion) -> Result<usize, String> {
+    let mut json = String::new();
+    File::open(json_path)
+        .and_then(|mut f| f.read_to_string(&mut json))
+        .map_err(|e| format!("Error reading {}: {}", json_path.display(), e))?;
+    let records = dialogue_from_json(&json).map_err(|e| format!("{}: {}", json_path.display(), e))?;
+
+    write_edt_file(edt_path, &records, version)?;
+    Ok(records.len())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate tempdir;
+
+    use std::fs::File;
+    use std::io::Read;
+    use std::path::PathBuf;
+
+    use super::{EdtLayout, StringEncoding};
+    use ResourceVersion;
+
+    fn json_dialog_path() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../assets/mods/test-json-dialogs/data/mercedt/009.edt.json")
+    }
+
+    fn imp_text_path() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../assets/mods/imp-quiz-honest-answers/data/Binarydata/IMPTEXT.EDT")
+    }
+
+    #[test]
+    fn decode_char_should_undo_rot1() {
+        assert_eq!(super::decode_char(b'P' as u16, StringEncoding::Normal), 'O');
+        assert_eq!(super::decode_char(32, StringEncoding::Normal), ' ');
+        assert_eq!(super::decode_char(34, StringEncoding::Normal), '!');
+        assert_eq!(super::decode_char(33, StringEncoding::Normal), '!');
+    }
+
+    #[test]
+    fn decode_char_should_apply_the_language_fixes() {
+        assert_eq!(super::decode_char(0xC1, StringEncoding::Russian), 'А');
+        assert_eq!(super::decode_char(0xC1, StringEncoding::Normal), 'À');
+        assert_eq!(super::decode_char(129, StringEncoding::English), 'Ç');
+        assert_eq!(super::decode_char(164, StringEncoding::Polish), 'Ł');
+        assert_eq!(super::decode_char(183, StringEncoding::Polish), '\u{B3}');
+        assert_eq!(super::decode_char(0x044E, StringEncoding::Normal), 'А');
+        assert_eq!(super::decode_char(0x0454, StringEncoding::Polish), 'Ё');
+    }
+
+    #[test]
+    fn encode_char_should_be_the_inverse_of_decode_char() {
+        for encoding in &[StringEncoding::Normal, StringEncoding::English, StringEncoding::Polish, StringEncoding::Russian] {
+            for c in "Hello, World! Ąłę ÇéçАБВЁЖЩЫЯ абвя \u{B3}".chars() {
+                let raw = super::encode_char(c, *encoding);
+                if let Ok(raw) = raw {
+                    assert_eq!(super::decode_char(raw, *encoding), c, "{:?} {}", encoding, c);
+                }
+            }
+        }
+        assert_eq!(super::encode_char('!', StringEncoding::Normal), Ok(34));
+        assert_eq!(super::encode_char('Ł', StringEncoding::Polish), Ok(164));
+        assert!(super::encode_char('э', StringEncoding::English).is_err());
+        assert!(super::encode_char('À', StringEncoding::Russian).is_err());
+        assert!(super::encode_char('😀', StringEncoding::Normal).is_err());
+    }
+
+    #[test]
+    fn encode_string_should_pad_and_limit_the_record() {
+        assert_eq!(super::encode_string("Hi", 4, StringEncoding::Normal), Ok(vec!(b'I' as u16, b'j' as u16, 0, 0)));
+        assert!(super::encode_string("Hey", 3, StringEncoding::Normal).is_err());
+        assert_eq!(super::decode_string(&[b'I' as u16, b'j' as u16, b'!' as u16], StringEncoding::Normal), "Hi");
+    }
+
+    #[test]
+    fn for_file_name_should_detect_the_layout() {
+        assert_eq!(EdtLayout::for_file_name("IMPTEXT.EDT").record_chars(), 400);
+        assert_eq!(EdtLayout::for_file_name("help.edt").record_chars(), 640);
+        assert_eq!(EdtLayout::for_file_name("credits.edt").record_chars(), 80);
+        assert_eq!(EdtLayout::for_file_name("ItemDesc.edt").field_sizes, vec!(80, 80, 240));
+        assert_eq!(EdtLayout::for_file_name("009.edt"), EdtLayout::dialogue());
+    }
+
+    #[test]
+    fn decode_edt_should_read_imptext() {
+        let records = super::read_edt_file(&imp_text_path(), ResourceVersion::ENGLISH).unwrap();
+
+        assert_eq!(records.len(), 239);
+        assert_eq!(records[0], vec!("On Target Killer Profiles!"));
+    }
+
+    #[test]
+    fn decode_edt_then_encode_edt_should_round_trip() {
+        let mut data = vec!();
+        File::open(imp_text_path()).unwrap().read_to_end(&mut data).unwrap();
+        let layout = EdtLayout::for_path(&imp_text_path());
+
+        for version in ResourceVersion::all() {
+            let records = super::decode_edt(&data, &layout, *version).unwrap();
+            assert_eq!(super::encode_edt(&records, &layout, *version).unwrap(), data, "{}", version);
+        }
+    }
+
+    #[test]
+    fn decode_edt_should_fail_with_partial_records() {
+        assert!(super::decode_edt(&[0; 10], &EdtLayout::dialogue(), ResourceVersion::ENGLISH).is_err());
+        assert!(super::encode_edt(&[vec!()], &EdtLayout::dialogue(), ResourceVersion::ENGLISH).is_err());
+    }
+
+    #[test]
+    fn compile_json_to_edt_then_export_edt_to_json_should_keep_the_quotes() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        let edt_path = temp_dir.path().join("009.edt");
+        let json_path = temp_dir.path().join("009.edt.json");
+        let mut original = String::new();
+        File::open(json_dialog_path()).unwrap().read_to_string(&mut original).unwrap();
+
+        assert_eq!(super::compile_json_to_edt(&json_dialog_path(), &edt_path, ResourceVersion::RUSSIAN), Ok(200));
+        assert_eq!(edt_path.metadata().unwrap().len(), 200 * 240 * 2);
+        assert_eq!(super::export_edt_to_json(&edt_path, &json_path, ResourceVersion::RUSSIAN), Ok(200));
+
+        let mut exported = String::new();
+        File::open(&json_path).unwrap().read_to_string(&mut exported).unwrap();
+        assert_eq!(exported, original);
+    }
+
+    #[test]
+    fn dialogue_to_json_should_keep_empty_quotes() {
+        let records = vec!(vec!(String::from("First")), vec!(String::new()), vec!(String::from("Third")));
+        let json = super::dialogue_to_json(&records).unwrap();
+
+        assert_eq!(json, "[\n  \"First\",\n  \"\",\n  \"Third\"\n]\n");
+        assert_eq!(super::dialogue_from_json(&json), Ok(records));
+        assert!(super::dialogue_to_json(&[vec!(String::new(), String::new())]).is_err());
+    }
+}
@@ -0,0 +1,193 @@
+//! Extraction of all SLF libraries of the vanilla data directory to a directory tree.
+//!
+//! Known formats are converted on the way: images to PNG and EDT files to JSON, everything else, e.g. WAV, is written
+//! as it is. Files that fail to convert are written unchanged too, with the error in the manifest.
+//!
+//! `manifest.json` in the output directory lists the library and offset every file was extracted from.
+
+use std::fs;
+use std::fs::File;
+use std::path::{Component, Path};
+
+use serde_json;
+
+use data;
+use edt;
+use image;
+use slf;
+use ResourceVersion;
+
+pub static MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// Where a file of the game data came from and what it was extracted to.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct ManifestEntry {
+    /// Path inside the game data, e.g. `maps/a9.dat`.
+    pub path: String,
+    /// File name of the library.
+    pub library: String,
+    pub offset: u32,
+    pub length: u32,
+    /// The written files, relative to the output directory.
+    pub files: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Manifest {
+    /// The resource version used to decode the texts.
+    #[serde(rename = "resversion")]
+    pub resource_version: ResourceVersion,
+    pub libraries: Vec<String>,
+    pub files: Vec<ManifestEntry>,
+}
+
+impl Manifest {
+    /// The entries that could not be converted.
+    pub fn errors(&self) -> Vec<&ManifestEntry> {
+        self.files.iter().filter(|e| e.error.is_some()).collect()
+    }
+}
+
+fn extension(path: &str) -> String {
+    Path::new(path).extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default()
+}
+
+fn replace_extension(path: &str, extension: &str) -> String {
+    match path.rfind('.') {
+        Some(dot) if !path[dot..].contains('/') => format!("{}.{}", &path[..dot], extension),
+        _ => format!("{}.{}", path, extension),
+    }
+}
+
+fn images_to_png(path: &str, images: &[image::Image]) -> Result<Vec<(String, Vec<u8>)>, String> {
+    if images.len() == 1 {
+        return Ok(vec!((replace_extension(path, "png"), image::encode_png(&images[0])?)));
+    }
+    let stem = replace_extension(path, "");
+    let stem = stem.trim_end_matches('.');
+    images.iter().enumerate().map(|(i, image)| Ok((format!("{}_{}.png", stem, i), image::encode_png(image)?))).collect()
+}
+
+fn edt_to_json(path: &str, data: &[u8], version: ResourceVersion) -> Result<String, String> {
+    let records = edt::decode_edt(data, &edt::EdtLayout::for_path(Path::new(path)), version)?;
+    if records.iter().all(|r| r.len() == 1) {
+        return edt::dialogue_to_json(&records);
+    }
+
+    // Files with several fields per record become an array of records
+    let mut json = serde_json::to_string_pretty(&records).map_err(|e| format!("Error serializing records: {}", e))?;
+    json.push('\n');
+    Ok(json)
+}
+
+/// Converts a file of the game data into the files to extract, as paths relative to the output directory and contents.
+pub fn convert_file(path: &str, data: &[u8], version: ResourceVersion) -> Result<Vec<(String, Vec<u8>)>, String> {
+    match extension(path).as_str() {
+        "sti" => images_to_png(path, &image::decode_sti(data)?),
+        "pcx" => images_to_png(path, &[image::decode_pcx(data)?]),
+        "tga" => images_to_png(path, &[image::decode_tga(data)?]),
+        "edt" => Ok(vec!((format!("{}.json", path), edt_to_json(path, data, version)?.into_bytes()))),
+        _ => Ok(vec!((path.to_string(), data.to_vec()))),
+    }
+}
+
+/// Entry names are relative paths, anything else would write outside of the output directory.
+fn is_safe_path(path: &str) -> bool {
+    !path.is_empty() && Path::new(path).components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+fn extract_entry(library: &slf::SlfLibrary, file: &mut File, entry: &slf::SlfEntry, output_dir: &Path, version: ResourceVersion) -> Result<ManifestEntry, String> {
+    let path = library.game_path(entry);
+    let library_name = library.path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let mut manifest_entry = ManifestEntry { path: path.clone(), library: library_name, offset: entry.offset, length: entry.length, files: vec!(), error: None };
+    if !is_safe_path(&path) {
+        manifest_entry.error = Some(String::from("Invalid file name"));
+        return Ok(manifest_entry);
+    }
+
+    let data = slf::read_slf_entry(file, entry).map_err(|e| format!("Error reading {}: {}", library.path.display(), e))?;
+    let files = convert_file(&path, &data, version).unwrap_or_else(|e| {
+        manifest_entry.error = Some(e);
+        vec!((path.clone(), data))
+    });
+
+    for (name, contents) in files {
+        let output_path = output_dir.join(&name);
+        if let Some(parent) = output_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Error creating {}: {}", parent.display(), e))?;
+        }
+        fs::write(&output_path, contents).map_err(|e| format!("Error writing {}: {}", output_path.display(), e))?;
+        manifest_entry.files.push(name);
+    }
+    Ok(manifest_entry)
+}
+
+/// Extracts every library in the vanilla data directory to `output_dir` and writes the manifest there.
+pub fn extract_data_dir(vanilla_data_dir: &Path, output_dir: &Path, version: ResourceVersion) -> Result<Manifest, String> {
+    let data_dir = data::find_data_dir(vanilla_data_dir)?;
+    let mut manifest = Manifest { resource_version: version, libraries: vec!(), files: vec!() };
+
+    for library_path in data::list_slf_libraries(&data_dir)? {
+        let library = slf::read_slf_library(&library_path)?;
+        let mut file = File::open(&library_path).map_err(|e| format!("Error reading {}: {}", library_path.display(), e))?;
+
+        for entry in &library.entries {
+            manifest.files.push(extract_entry(&library, &mut file, entry, output_dir, version)?);
+        }
+        manifest.libraries.push(library_path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default());
+    }
+
+    let mut json = serde_json::to_string_pretty(&manifest).map_err(|e| format!("Error serializing manifest: {}", e))?;
+    json.push('\n');
+    let manifest_path = output_dir.join(MANIFEST_FILE_NAME);
+    fs::create_dir_all(output_dir).and_then(|_| fs::write(&manifest_path, json)).map_err(|e| format!("Error writing {}: {}", manifest_path.display(), e))?;
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate tempdir;
+
+    use std::fs;
+
+    use super::{convert_file, extract_data_dir};
+    use test_utils::slf_file;
+    use ResourceVersion;
+
+    #[test]
+    fn convert_file_should_convert_known_formats() {
+        let edt: Vec<u8> = "Hi".encode_utf16().map(|c| c + 1).chain(vec!(0; 238)).flat_map(|c| c.to_le_bytes().to_vec()).collect();
+        let files = convert_file("mercedt/009.edt", &edt, ResourceVersion::ENGLISH).unwrap();
+        assert_eq!(files, vec!((String::from("mercedt/009.edt.json"), b"[\n  \"Hi\"\n]\n".to_vec())));
+
+        let files = convert_file("sounds/a.wav", b"RIFF", ResourceVersion::ENGLISH).unwrap();
+        assert_eq!(files, vec!((String::from("sounds/a.wav"), b"RIFF".to_vec())));
+        assert_eq!(convert_file("interface/a.sti", b"RIFF", ResourceVersion::ENGLISH), Err(String::from("Unexpected end of STI data")));
+    }
+
+    #[test]
+    fn extract_data_dir_should_write_the_files_and_the_manifest() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        let data_dir = temp_dir.path().join("game/Data");
+        let output_dir = temp_dir.path().join("out");
+        let sti = fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/../assets/externalized/sti/interface/LOADSCREENTAB.STI")).unwrap();
+        fs::create_dir_all(&data_dir).unwrap();
+        fs::write(data_dir.join("Interface.slf"), slf_file("interface\\", &[("tab.sti", Some(&sti)), ("broken.sti", Some(b"STCI")), ("../evil.wav", Some(b"x"))])).unwrap();
+
+        let manifest = extract_data_dir(&temp_dir.path().join("game"), &output_dir, ResourceVersion::ENGLISH).unwrap();
+
+        assert_eq!(manifest.libraries, vec!("Interface.slf"));
+        assert_eq!(manifest.files[0].files, vec!("interface/tab_0.png", "interface/tab_1.png", "interface/tab_2.png", "interface/tab_3.png"));
+        assert_eq!((manifest.files[0].library.as_str(), manifest.files[0].offset), ("Interface.slf", 532));
+        assert_eq!(manifest.files[1].files, vec!("interface/broken.sti"));
+        assert_eq!(manifest.errors().len(), 2);
+        assert!(output_dir.join("interface/tab_3.png").is_file());
+        assert!(!temp_dir.path().join("evil.wav").exists());
+
+        let json: ::serde_json::Value = ::serde_json::from_str(&fs::read_to_string(output_dir.join("manifest.json")).unwrap()).unwrap();
+        assert_eq!(json["files"][0]["path"], "interface/tab.sti");
+        assert_eq!(json["files"][1]["error"], "Unexpected end of STI data");
+    }
+}
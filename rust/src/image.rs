@@ -0,0 +1,545 @@
+//! Decoding of the image formats of the game, `.sti`, `.pcx` and `.tga`, and their export to PNG.
+//!
+//! The decoders follow `STCI.cc`, `PCX.cc` and `ImpTGA.cc` and support the same variants the game loads. All of them
+//! produce `Image`s with 8 bit RGBA pixels, palette index 0 of indexed STIs is transparent like in the game.
+//!
+//! Some graphics contain text and exist once per language, see `GetMLGFilename` in `GameRes.cc`. `mlg_file_name`
+//! returns the file the game loads for a resource version.
+
+use std::fs::File;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+use png;
+
+use ResourceVersion;
+
+pub const STCI_HEADER_SIZE: usize = 64;
+pub const STCI_SUBIMAGE_SIZE: usize = 16;
+pub const STCI_PALETTE_SIZE: usize = 256 * 3;
+
+pub const STCI_ZLIB_COMPRESSED: u32 = 0x10;
+pub const STCI_INDEXED: u32 = 0x08;
+pub const STCI_RGB: u32 = 0x04;
+pub const STCI_ETRLE_COMPRESSED: u32 = 0x20;
+
+pub const PCX_HEADER_SIZE: usize = 128;
+pub const PCX_PALETTE_SIZE: usize = 256 * 3;
+
+/// An image with 8 bit RGBA pixels, row by row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width: u16,
+    pub height: u16,
+    /// Position of the image relative to the origin of its object, only used by STI subimages.
+    pub offset_x: i16,
+    pub offset_y: i16,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Creates a transparent image.
+    pub fn new(width: u16, height: u16) -> Image {
+        Image { width, height, offset_x: 0, offset_y: 0, pixels: vec!(0; width as usize * height as usize * 4) }
+    }
+
+    /// The RGBA value of the pixel at `x`, `y`.
+    pub fn pixel(&self, x: u16, y: u16) -> [u8; 4] {
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        [self.pixels[i], self.pixels[i + 1], self.pixels[i + 2], self.pixels[i + 3]]
+    }
+
+    fn set_pixel(&mut self, x: usize, y: usize, rgba: [u8; 4]) {
+        let i = (y * self.width as usize + x) * 4;
+        self.pixels[i..i + 4].copy_from_slice(&rgba);
+    }
+}
+
+/// An image with 8 bit palette indices, row by row, like the subimages of an ETRLE compressed STI.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedImage {
+    pub width: u16,
+    pub height: u16,
+    pub offset_x: i16,
+    pub offset_y: i16,
+    pub pixels: Vec<u8>,
+}
+
+impl IndexedImage {
+    /// Creates an image filled with index 0, which is transparent.
+    pub fn new(width: u16, height: u16) -> IndexedImage {
+        IndexedImage { width, height, offset_x: 0, offset_y: 0, pixels: vec!(0; width as usize * height as usize) }
+    }
+
+    /// The palette index of the pixel at `x`, `y`.
+    pub fn pixel(&self, x: u16, y: u16) -> u8 {
+        self.pixels[y as usize * self.width as usize + x as usize]
+    }
+
+    /// Converts the image to RGBA with index 0 transparent.
+    pub fn to_image(&self, palette: &[[u8; 4]]) -> Image {
+        let pixels = self.pixels.iter().flat_map(|&index| if index == 0 { [0; 4] } else { palette[index as usize] }).collect();
+        Image { width: self.width, height: self.height, offset_x: self.offset_x, offset_y: self.offset_y, pixels }
+    }
+}
+
+fn truncated(what: &str) -> String {
+    format!("Unexpected end of {} data", what)
+}
+
+/// Converts a 16 bit pixel in the 565 format of the game to RGBA.
+fn rgb565_to_rgba(value: u16) -> [u8; 4] {
+    let r = ((value >> 11) & 0x1F) as u8;
+    let g = ((value >> 5) & 0x3F) as u8;
+    let b = (value & 0x1F) as u8;
+    [(r << 3) | (r >> 2), (g << 2) | (g >> 4), (b << 3) | (b >> 2), 0xFF]
+}
+
+fn palette_to_rgba(palette: &[u8]) -> Vec<[u8; 4]> {
+    palette.chunks(3).map(|c| [c[0], c[1], c[2], 0xFF]).collect()
+}
+
+/// Decodes the ETRLE compressed scanlines of an indexed STI subimage.
+fn decode_etrle(data: &[u8], image: &mut IndexedImage) -> Result<(), String> {
+    let width = image.width as usize;
+    let mut bytes = data.iter();
+
+    for y in 0..image.height as usize {
+        let mut x = 0;
+        loop {
+            let control = *bytes.next().ok_or_else(|| truncated("ETRLE"))?;
+            if control == 0 {
+                break;
+            }
+            let count = (control & 0x7F) as usize;
+            if x + count > width {
+                return Err(format!("ETRLE run exceeds the width of {} pixels in line {}", width, y));
+            }
+            if control & 0x80 == 0 {
+                for _ in 0..count {
+                    image.pixels[y * width + x] = *bytes.next().ok_or_else(|| truncated("ETRLE"))?;
+                    x += 1;
+                }
+            } else {
+                x += count;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The fields of the STI header the decoders use.
+struct StiHeader {
+    stored_size: usize,
+    flags: u32,
+    height: u16,
+    width: u16,
+    number_of_colours: u32,
+    number_of_subimages: usize,
+    depth: u8,
+}
+
+fn read_sti_header(data: &[u8]) -> Result<StiHeader, String> {
+    if data.len() < STCI_HEADER_SIZE {
+        return Err(truncated("STI"));
+    }
+    if &data[0..4] != b"STCI" {
+        return Err(String::from("Not an STI file"));
+    }
+
+    let mut header = Cursor::new(&data[4..]);
+    let _original_size = header.read_u32::<LittleEndian>().unwrap();
+    let stored_size = header.read_u32::<LittleEndian>().unwrap() as usize;
+    let _transparent_value = header.read_u32::<LittleEndian>().unwrap();
+    let flags = header.read_u32::<LittleEndian>().unwrap();
+    let height = header.read_u16::<LittleEndian>().unwrap();
+    let width = header.read_u16::<LittleEndian>().unwrap();
+    let number_of_colours = header.read_u32::<LittleEndian>().unwrap();
+    let number_of_subimages = header.read_u16::<LittleEndian>().unwrap() as usize;
+
+    if flags & STCI_ZLIB_COMPRESSED != 0 {
+        return Err(String::from("Zlib compressed STIs are not supported"));
+    }
+    Ok(StiHeader { stored_size, flags, height, width, number_of_colours, number_of_subimages, depth: data[44] })
+}
+
+/// Reads the 256 colour palette of an indexed STI.
+fn read_sti_palette(data: &[u8], header: &StiHeader) -> Result<Vec<[u8; 4]>, String> {
+    if header.flags & STCI_INDEXED == 0 {
+        return Err(String::from("STI is neither indexed nor RGB"));
+    }
+    if header.number_of_colours != 256 {
+        return Err(format!("Indexed STIs with {} colours are not supported", header.number_of_colours));
+    }
+    Ok(palette_to_rgba(data.get(STCI_HEADER_SIZE..STCI_HEADER_SIZE + STCI_PALETTE_SIZE).ok_or_else(|| truncated("STI"))?))
+}
+
+/// Decodes the subimages of an ETRLE compressed STI, which follow the palette.
+fn decode_etrle_subimages(data: &[u8], header: &StiHeader) -> Result<Vec<IndexedImage>, String> {
+    let palette_end = STCI_HEADER_SIZE + STCI_PALETTE_SIZE;
+    let subimages_end = palette_end + header.number_of_subimages * STCI_SUBIMAGE_SIZE;
+    let subimages = data.get(palette_end..subimages_end).ok_or_else(|| truncated("STI"))?;
+    let pixel_data = data.get(subimages_end..subimages_end + header.stored_size).ok_or_else(|| truncated("STI"))?;
+
+    subimages.chunks(STCI_SUBIMAGE_SIZE).enumerate().map(|(i, subimage)| {
+        let mut subimage = Cursor::new(subimage);
+        let offset = subimage.read_u32::<LittleEndian>().unwrap() as usize;
+        let length = subimage.read_u32::<LittleEndian>().unwrap() as usize;
+        let offset_x = subimage.read_i16::<LittleEndian>().unwrap();
+        let offset_y = subimage.read_i16::<LittleEndian>().unwrap();
+        let height = subimage.read_u16::<LittleEndian>().unwrap();
+        let width = subimage.read_u16::<LittleEndian>().unwrap();
+
+        let compressed = pixel_data.get(offset..offset + length).ok_or_else(|| format!("Subimage {} is outside of the pixel data", i))?;
+        let mut image = IndexedImage { offset_x, offset_y, ..IndexedImage::new(width, height) };
+        decode_etrle(compressed, &mut image).map_err(|e| format!("Subimage {}: {}", i, e))?;
+        Ok(image)
+    }).collect()
+}
+
+/// Decodes an STI file. Indexed STIs can contain several subimages, RGB STIs always contain one.
+pub fn decode_sti(data: &[u8]) -> Result<Vec<Image>, String> {
+    let header = read_sti_header(data)?;
+    let (width, height) = (header.width, header.height);
+
+    if header.flags & STCI_RGB != 0 {
+        if header.depth != 16 {
+            return Err(format!("RGB STIs with {} bits per pixel are not supported", header.depth));
+        }
+        let pixel_data = &data[STCI_HEADER_SIZE..];
+        let mut image = Image::new(width, height);
+        if pixel_data.len() < image.pixels.len() / 2 {
+            return Err(truncated("STI"));
+        }
+        for (i, pixel) in pixel_data.chunks(2).take(width as usize * height as usize).enumerate() {
+            image.set_pixel(i % width as usize, i / width as usize, rgb565_to_rgba(u16::from(pixel[0]) | u16::from(pixel[1]) << 8));
+        }
+        return Ok(vec!(image));
+    }
+
+    let palette = read_sti_palette(data, &header)?;
+
+    if header.flags & STCI_ETRLE_COMPRESSED == 0 {
+        let palette_end = STCI_HEADER_SIZE + STCI_PALETTE_SIZE;
+        let pixel_data = data.get(palette_end..palette_end + width as usize * height as usize).ok_or_else(|| truncated("STI"))?;
+        let mut image = Image::new(width, height);
+        for (i, &index) in pixel_data.iter().enumerate() {
+            image.set_pixel(i % width as usize, i / width as usize, palette[index as usize]);
+        }
+        return Ok(vec!(image));
+    }
+
+    Ok(decode_etrle_subimages(data, &header)?.iter().map(|i| i.to_image(&palette)).collect())
+}
+
+/// Decodes an ETRLE compressed STI without converting the palette indices, e.g. the glyphs of a font.
+pub fn decode_sti_indexed(data: &[u8]) -> Result<(Vec<[u8; 4]>, Vec<IndexedImage>), String> {
+    let header = read_sti_header(data)?;
+    if header.flags & STCI_ETRLE_COMPRESSED == 0 {
+        return Err(String::from("Only ETRLE compressed STIs can be decoded to indexed subimages"));
+    }
+    let palette = read_sti_palette(data, &header)?;
+
+    Ok((palette, decode_etrle_subimages(data, &header)?))
+}
+
+/// Decodes a run length encoded PCX file with a 256 colour palette.
+pub fn decode_pcx(data: &[u8]) -> Result<Image, String> {
+    if data.len() < PCX_HEADER_SIZE + PCX_PALETTE_SIZE {
+        return Err(truncated("PCX"));
+    }
+    if data[0] != 10 || data[2] != 1 {
+        return Err(String::from("Not a run length encoded PCX file"));
+    }
+
+    let mut header = Cursor::new(&data[4..12]);
+    let left = header.read_u16::<LittleEndian>().unwrap();
+    let top = header.read_u16::<LittleEndian>().unwrap();
+    let right = header.read_u16::<LittleEndian>().unwrap();
+    let bottom = header.read_u16::<LittleEndian>().unwrap();
+    if right < left || bottom < top {
+        return Err(format!("Invalid PCX dimensions {},{} to {},{}", left, top, right, bottom));
+    }
+
+    let mut image = Image::new(right - left + 1, bottom - top + 1);
+    let palette = palette_to_rgba(&data[data.len() - PCX_PALETTE_SIZE..]);
+    let mut bytes = data[PCX_HEADER_SIZE..data.len() - PCX_PALETTE_SIZE].iter();
+    let pixel_count = image.width as usize * image.height as usize;
+    let mut i = 0;
+
+    while i < pixel_count {
+        let byte = *bytes.next().ok_or_else(|| truncated("PCX"))?;
+        let (count, index) = if byte >= 0xC0 {
+            ((byte & 0x3F) as usize, *bytes.next().ok_or_else(|| truncated("PCX"))?)
+        } else {
+            (1, byte)
+        };
+        for _ in 0..count.min(pixel_count - i) {
+            let width = image.width as usize;
+            image.set_pixel(i % width, i / width, palette[index as usize]);
+            i += 1;
+        }
+    }
+    Ok(image)
+}
+
+/// Decodes an uncompressed true colour TGA file with 16 or 24 bits per pixel.
+pub fn decode_tga(data: &[u8]) -> Result<Image, String> {
+    if data.len() < 18 {
+        return Err(truncated("TGA"));
+    }
+    let id_length = data[0] as usize;
+    let colour_map_type = data[1];
+    match data[2] {
+        2 => {},
+        image_type @ 1 | image_type @ 9 | image_type @ 10 => return Err(format!("TGA format {} is not supported", image_type)),
+        image_type => return Err(format!("Unknown TGA format {}", image_type)),
+    }
+
+    let mut header = Cursor::new(&data[3..18]);
+    header.set_position(2);
+    let colour_map_length = header.read_u16::<LittleEndian>().unwrap() as usize;
+    header.set_position(9);
+    let width = header.read_u16::<LittleEndian>().unwrap();
+    let height = header.read_u16::<LittleEndian>().unwrap();
+    let pixel_size = header.read_u8().unwrap();
+    if pixel_size != 16 && pixel_size != 24 {
+        return Err(format!("TGAs with {} bits per pixel are not supported", pixel_size));
+    }
+
+    let bytes_per_pixel = pixel_size as usize / 8;
+    let mut start = 18 + id_length;
+    if colour_map_type != 0 {
+        start += colour_map_length * bytes_per_pixel;
+    }
+    let row_size = width as usize * bytes_per_pixel;
+    let pixel_data = data.get(start..start + row_size * height as usize).ok_or_else(|| truncated("TGA"))?;
+
+    // The rows are stored from the bottom to the top
+    let mut image = Image::new(width, height);
+    for (row, line) in pixel_data.chunks(row_size.max(1)).enumerate() {
+        let y = height as usize - 1 - row;
+        for (x, pixel) in line.chunks(bytes_per_pixel).enumerate() {
+            let rgba = match bytes_per_pixel {
+                2 => rgb565_to_rgba(u16::from(pixel[0]) | u16::from(pixel[1]) << 8),
+                _ => [pixel[2], pixel[1], pixel[0], 0xFF],
+            };
+            image.set_pixel(x, y, rgba);
+        }
+    }
+    Ok(image)
+}
+
+/// Reads and decodes an STI, PCX or TGA file, depending on its extension.
+pub fn read_image_file(path: &Path) -> Result<Vec<Image>, String> {
+    let mut data = Vec::new();
+    File::open(path).and_then(|mut f| f.read_to_end(&mut data)).map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+
+    let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+    let images = match extension.as_str() {
+        "sti" => decode_sti(&data),
+        "pcx" => decode_pcx(&data).map(|i| vec!(i)),
+        "tga" => decode_tga(&data).map(|i| vec!(i)),
+        _ => Err(format!("Unknown image format '{}'", extension)),
+    };
+    images.map_err(|e| format!("Error parsing {}: {}", path.display(), e))
+}
+
+/// Encodes an image as PNG.
+pub fn encode_png(image: &Image) -> Result<Vec<u8>, String> {
+    let mut data = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut data, u32::from(image.width), u32::from(image.height));
+        encoder.set_color(png::ColorType::RGBA);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(|e| format!("Error encoding PNG: {}", e))?;
+        writer.write_image_data(&image.pixels).map_err(|e| format!("Error encoding PNG: {}", e))?;
+    }
+    Ok(data)
+}
+
+/// Writes an image to a PNG file.
+pub fn write_png(path: &Path, image: &Image) -> Result<(), String> {
+    let data = encode_png(image)?;
+    std::fs::write(path, data).map_err(|e| format!("Error writing {}: {}", path.display(), e))
+}
+
+/// Writes images to `path`, if there are several they get numbered like `name_0.png`. Returns the written files.
+pub fn write_pngs(path: &Path, images: &[Image]) -> Result<Vec<PathBuf>, String> {
+    if images.len() == 1 {
+        write_png(path, &images[0])?;
+        return Ok(vec!(path.to_path_buf()));
+    }
+
+    let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    images.iter().enumerate().map(|(i, image)| {
+        let numbered = path.with_file_name(format!("{}_{}.png", stem, i));
+        write_png(&numbered, image)?;
+        Ok(numbered)
+    }).collect()
+}
+
+/// A graphic with a version per language: its name, the default file, the German file and the stems used for
+/// the `STI(LNG, x)` and `PCX(LNG, x)` files of the Dutch, Italian and Polish and of the Russian version.
+type MultiLanguageGraphic = (&'static str, &'static str, &'static str, &'static str, &'static str);
+
+static MULTI_LANGUAGE_GRAPHICS: [MultiLanguageGraphic; 33] = [
+    ("aimsymbol", "laptop/aimsymbol.sti", "laptop/aimsymbol.sti", "aimsymbol", "aimsymbol"),
+    ("bobbyname", "laptop/bobbyname.sti", "laptop/bobbyname.sti", "bobbyname", "bobbyname"),
+    ("bobbyrayad21", "laptop/bobbyrayad_21.sti", "laptop/bobbyrayad_21.sti", "bobbyrayad_21", "bobbyrayad_21"),
+    ("bobbyraylink", "laptop/bobbyraylink.sti", "german/bobbyraylink_german.sti", "bobbyraylink", "bobbyraylink"),
+    ("closed", "laptop/closed.sti", "german/closed_german.sti", "closed", "closed"),
+    ("confirmorder", "laptop/confirmorder.sti", "german/confirmorder_german.sti", "confirmorder", "confirmorder"),
+    ("desktop", "laptop/desktop.pcx", "german/desktop_german.pcx", "desktop", "desktop"),
+    ("funeralad9", "laptop/funeralad_9.sti", "german/funeralad_12_german.sti", "funeralad_9", "funeralad_9"),
+    ("goldpiecebuttons", "interface/goldpiecebuttons.sti", "german/goldpiecebuttons_german.sti", "goldpiecebuttons", "goldpiecebuttons"),
+    ("history", "laptop/history.sti", "german/history_german.sti", "history", "history"),
+    ("impsymbol", "laptop/impsymbol.sti", "german/impsymbol_german.sti", "impsymbol", "impsymbol"),
+    ("insurancead10", "laptop/insurancead_10.sti", "german/insurancead_10_german.sti", "insurancead_10", "insurancead_10"),
+    ("insurancelink", "laptop/insurancelink.sti", "german/insurancelink_german.sti", "insurancelink", "insurancelink"),
+    ("insurancetitle", "laptop/largetitle.sti", "german/largetitle_german.sti", "largetitle", "largetitle"),
+    ("largefloristsymbol", "laptop/largesymbol.sti", "german/largesymbol_german.sti", "largesymbol", "largesymbol"),
+    ("loadsaveheader", "interface/loadscreenaddons.sti", "german/loadscreenaddons_german.sti", "loadscreenaddons", "loadscreenaddons"),
+    ("mcgillicuttys", "laptop/mcgillicuttys.sti", "german/mcgillicuttys_german.sti", "mcgillicuttys", "mcgillicuttys"),
+    ("mortuary", "laptop/mortuary.sti", "german/mortuary_german.sti", "mortuary", "mortuary"),
+    ("mortuarylink", "laptop/mortuarylink.sti", "german/mortuarylink_german.sti", "mortuarylink", "mortuarylink"),
+    ("optionheader", "interface/optionscreenaddons.sti", "german/optionscreenaddons_german.sti", "optionscreenaddons", "optionscreenaddons"),
+    ("ordergrid", "laptop/ordergrid.sti", "laptop/ordergrid.sti", "ordergrid", "ordergrid"),
+    ("prebattlepanel", "interface/prebattlepanel.sti", "german/prebattlepanel_german.sti", "prebattlepanel", "prebattlepanel"),
+    ("smallfloristsymbol", "laptop/smallsymbol.sti", "german/smallsymbol_german.sti", "smallsymbol", "smallsymbol"),
+    ("smalltitle", "laptop/smalltitle.sti", "german/smalltitle_german.sti", "smalltitle", "smalltitle"),
+    ("splash", "interface/splash.sti", "german/splash_german.sti", "splash", "splash"),
+    ("statsbox", "laptop/statsbox.sti", "laptop/statsbox.sti", "statsbox", "statsbox"),
+    ("storeplaque", "laptop/bobbystoreplaque.sti", "german/storeplaque_german.sti", "storeplaque", "bobbystoreplaque"),
+    ("titletext", "loadscreens/titletext.sti", "german/titletext_german.sti", "titletext", "titletext"),
+    ("toalumni", "laptop/toalumni.sti", "german/toalumni_german.sti", "toalumni", "toalumni"),
+    ("tomugshots", "laptop/tomugshots.sti", "german/tomugshots_german.sti", "tomugshots", "tomugshots"),
+    ("tostats", "laptop/tostats.sti", "german/tostats_german.sti", "tostats", "tostats"),
+    ("warning", "laptop/warning.sti", "german/warning_german.sti", "warning", "warning"),
+    ("yourad13", "laptop/yourad_13.sti", "german/yourad_13_german.sti", "yourad_13", "yourad_13"),
+];
+
+/// The names of all graphics with a version per language, e.g. `desktop`.
+pub fn mlg_names() -> Vec<&'static str> {
+    MULTI_LANGUAGE_GRAPHICS.iter().map(|g| g.0).collect()
+}
+
+/// The file of the graphic `name` that the game loads for `version`, e.g. `dutch/desktop_dutch.pcx`.
+pub fn mlg_file_name(name: &str, version: ResourceVersion) -> Option<String> {
+    let &(_, default, german, stem, russian_stem) = MULTI_LANGUAGE_GRAPHICS.iter().find(|g| g.0 == name)?;
+    let extension = if default.ends_with(".pcx") { "pcx" } else { "sti" };
+    let localized = |language: &str, stem: &str| format!("{}/{}_{}.{}", language, stem, language, extension);
+
+    Some(match version {
+        ResourceVersion::ENGLISH | ResourceVersion::FRENCH | ResourceVersion::RUSSIAN_GOLD => String::from(default),
+        ResourceVersion::GERMAN => String::from(german),
+        ResourceVersion::DUTCH => localized("dutch", stem),
+        ResourceVersion::ITALIAN => localized("italian", stem),
+        ResourceVersion::POLISH => localized("polish", stem),
+        ResourceVersion::RUSSIAN => localized("russian", russian_stem),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{decode_pcx, decode_sti, decode_tga, encode_png, mlg_file_name, mlg_names, read_image_file, Image};
+    use ResourceVersion;
+
+    fn pcx_file(width: u16, height: u16, pixel_data: &[u8]) -> Vec<u8> {
+        let mut data = vec!(0u8; 128);
+        data[0] = 10;
+        data[2] = 1;
+        data[3] = 8;
+        data[8..10].copy_from_slice(&(width - 1).to_le_bytes());
+        data[10..12].copy_from_slice(&(height - 1).to_le_bytes());
+        data.extend_from_slice(pixel_data);
+        data.push(12);
+        let mut palette = vec!(0u8; 768);
+        palette[3..6].copy_from_slice(&[255, 0, 0]);
+        palette[6..9].copy_from_slice(&[0, 0, 255]);
+        data.extend(palette);
+        data
+    }
+
+    #[test]
+    fn decode_sti_should_decode_etrle_subimages() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../assets/externalized/sti/interface/LOADSCREENTAB.STI");
+        let images = read_image_file(&path).unwrap();
+
+        assert_eq!(images.len(), 4);
+        assert!(images.iter().all(|i| i.width == 64 && i.height == 30 && i.pixels.len() == 64 * 30 * 4));
+        assert!(images[0].pixels.chunks(4).any(|p| p[3] == 0xFF));
+    }
+
+    #[test]
+    fn decode_sti_should_decode_rgb_images() {
+        let mut data = vec!(0u8; 64);
+        data[0..4].copy_from_slice(b"STCI");
+        data[16] = 0x04;
+        data[20] = 1;
+        data[22] = 2;
+        data[44] = 16;
+        data.extend_from_slice(&[0x00, 0xF8, 0xE0, 0x07]);
+
+        let images = decode_sti(&data).unwrap();
+        assert_eq!(images[0].pixel(0, 0), [0xFF, 0, 0, 0xFF]);
+        assert_eq!(images[0].pixel(1, 0), [0, 0xFF, 0, 0xFF]);
+    }
+
+    #[test]
+    fn decode_sti_should_fail_with_other_files() {
+        assert_eq!(decode_sti(&[0; 64]), Err(String::from("Not an STI file")));
+        assert_eq!(decode_sti(b"STCI"), Err(String::from("Unexpected end of STI data")));
+    }
+
+    #[test]
+    fn decode_pcx_should_decode_runs() {
+        let image = decode_pcx(&pcx_file(3, 2, &[0xC4, 1, 2, 0xC1, 0])).unwrap();
+
+        assert_eq!((image.width, image.height), (3, 2));
+        assert_eq!(image.pixel(2, 0), [255, 0, 0, 255]);
+        assert_eq!(image.pixel(0, 1), [255, 0, 0, 255]);
+        assert_eq!(image.pixel(1, 1), [0, 0, 255, 255]);
+        assert_eq!(image.pixel(2, 1), [0, 0, 0, 255]);
+        assert_eq!(decode_pcx(&pcx_file(3, 2, &[0xC4, 1])), Err(String::from("Unexpected end of PCX data")));
+    }
+
+    #[test]
+    fn decode_tga_should_flip_the_rows() {
+        let mut data = vec!(0u8; 18);
+        data[2] = 2;
+        data[12] = 1;
+        data[14] = 2;
+        data[16] = 24;
+        data.extend_from_slice(&[0, 0, 255, 255, 0, 0]);
+
+        let image = decode_tga(&data).unwrap();
+        assert_eq!(image.pixel(0, 0), [0, 0, 255, 255]);
+        assert_eq!(image.pixel(0, 1), [255, 0, 0, 255]);
+        data[2] = 10;
+        assert_eq!(decode_tga(&data), Err(String::from("TGA format 10 is not supported")));
+    }
+
+    #[test]
+    fn encode_png_should_write_a_png_file() {
+        let image = Image::new(3, 2);
+        let png = encode_png(&image).unwrap();
+
+        assert_eq!(&png[0..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[16..24], &[0, 0, 0, 3, 0, 0, 0, 2]);
+    }
+
+    #[test]
+    fn mlg_file_name_should_follow_the_language() {
+        assert_eq!(mlg_names().len(), 33);
+        assert_eq!(mlg_file_name("desktop", ResourceVersion::ENGLISH), Some(String::from("laptop/desktop.pcx")));
+        assert_eq!(mlg_file_name("desktop", ResourceVersion::DUTCH), Some(String::from("dutch/desktop_dutch.pcx")));
+        assert_eq!(mlg_file_name("funeralad9", ResourceVersion::GERMAN), Some(String::from("german/funeralad_12_german.sti")));
+        assert_eq!(mlg_file_name("storeplaque", ResourceVersion::POLISH), Some(String::from("polish/storeplaque_polish.sti")));
+        assert_eq!(mlg_file_name("storeplaque", ResourceVersion::RUSSIAN), Some(String::from("russian/bobbystoreplaque_russian.sti")));
+        assert_eq!(mlg_file_name("unknown", ResourceVersion::ENGLISH), None);
+    }
+}
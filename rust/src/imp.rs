@@ -0,0 +1,176 @@
+//! The starting kit of IMP mercs in `externalized/imp.json`, read by `DefaultIMPPolicy.cc`.
+//!
+//! `GiveItemsToPC` puts the `inventory` and one of the kits, `if_good_shooter` for a marksmanship of 80 or more and
+//! `if_normal_shooter` otherwise, into the pockets of the new merc, one item per pocket: the small pockets first if the
+//! item fits into one, then the big pockets. Items without a free pocket are left out silently, and an unknown item name
+//! stops the game, so `ImpPolicy::validate` checks both before anybody creates a merc.
+
+use std::fs;
+use std::path::Path;
+
+use serde_json;
+
+use items::{Item, ItemDatabase, ItemKind};
+use policy;
+
+pub const SMALL_POCKETS: usize = 8;
+pub const BIG_POCKETS: usize = 4;
+
+/// Names of the kits, only one of them is given to a merc.
+pub static KITS: [&str; 2] = ["if_good_shooter", "if_normal_shooter"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImpPolicy {
+    /// Items every IMP merc gets.
+    pub inventory: Vec<String>,
+    pub if_good_shooter: Vec<String>,
+    pub if_normal_shooter: Vec<String>,
+}
+
+/// The free pockets while the items are given out.
+#[derive(Debug, Clone, Copy)]
+struct Pockets {
+    small: usize,
+    big: usize,
+}
+
+impl Pockets {
+    /// Takes a pocket like `FirstFreeBigEnoughPocket`, false if there is none left.
+    fn take(&mut self, item: &Item) -> bool {
+        if item.per_pocket != 0 && self.small > 0 {
+            self.small -= 1;
+        } else if self.big > 0 {
+            self.big -= 1;
+        } else {
+            return false;
+        }
+        true
+    }
+}
+
+impl ImpPolicy {
+    pub fn from_json(json: &str) -> Result<ImpPolicy, String> {
+        serde_json::from_str(&policy::strip_json_comments(json)).map_err(|e| e.to_string())
+    }
+
+    /// The items of a key of `imp.json`, e.g. `if_good_shooter`.
+    pub fn items(&self, key: &str) -> Option<&[String]> {
+        match key {
+            "inventory" => Some(&self.inventory),
+            "if_good_shooter" => Some(&self.if_good_shooter),
+            "if_normal_shooter" => Some(&self.if_normal_shooter),
+            _ => None,
+        }
+    }
+
+    /// Returns the problems, each starting with the key and the position of the item, e.g. `if_good_shooter[1]`.
+    pub fn validate(&self, items: &ItemDatabase) -> Vec<String> {
+        let mut problems = Vec::new();
+        let mut resolve = |key: &str| -> Vec<(String, &Item)> {
+            let mut resolved = Vec::new();
+            for (i, name) in self.items(key).unwrap().iter().enumerate() {
+                match items.get(name) {
+                    Some(item) => resolved.push((format!("{}[{}]", key, i), item)),
+                    None => problems.push(format!("{}[{}]: unknown item '{}'", key, i, name)),
+                }
+            }
+            resolved
+        };
+        let inventory = resolve("inventory");
+        let kits: Vec<(&str, Vec<(String, &Item)>)> = KITS.iter().map(|&kit| (kit, resolve(kit))).collect();
+
+        let mut pockets = Pockets { small: SMALL_POCKETS, big: BIG_POCKETS };
+        for &(ref position, item) in &inventory {
+            if !pockets.take(item) {
+                problems.push(format!("{}: no free pocket left for '{}', the merc does not get it", position, item.name));
+            }
+        }
+
+        for &(kit, ref kit_items) in &kits {
+            let calibres: Vec<&String> = inventory.iter().chain(kit_items).filter_map(|&(_, item)| match item.kind {
+                ItemKind::Weapon(Some(ref calibre)) => Some(calibre),
+                _ => None,
+            }).collect();
+            for &(ref position, item) in inventory.iter().chain(kit_items) {
+                if let ItemKind::Magazine(ref calibre) = item.kind {
+                    if !calibres.contains(&calibre) {
+                        problems.push(format!("{}: magazine '{}' with calibre {} fits no weapon of the {} kit", position, item.name, calibre, kit));
+                    }
+                }
+            }
+
+            let mut kit_pockets = pockets;
+            for &(ref position, item) in kit_items {
+                if !kit_pockets.take(item) {
+                    problems.push(format!("{}: no free pocket left for '{}', the merc does not get it", position, item.name));
+                }
+            }
+        }
+        problems
+    }
+}
+
+pub fn read_imp_policy(path: &Path) -> Result<ImpPolicy, String> {
+    let json = fs::read_to_string(path).map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+
+    ImpPolicy::from_json(&json).map_err(|e| format!("Error parsing {}: {}", path.display(), e))
+}
+
+/// Reads and validates an `imp.json`, the problems start with the path of the file.
+pub fn validate_imp_file(path: &Path, items: &ItemDatabase) -> Result<Vec<String>, String> {
+    let problems = read_imp_policy(path)?.validate(items);
+
+    Ok(problems.into_iter().map(|p| format!("{}: {}", path.display(), p)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{read_imp_policy, ImpPolicy};
+    use items::read_items;
+
+    fn assets_dir() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../assets")
+    }
+
+    #[test]
+    fn validate_should_accept_the_shipped_imp_json() {
+        let items = read_items(&::EngineOptions::default(), &assets_dir()).unwrap();
+        let imp_policy = read_imp_policy(&assets_dir().join("externalized/imp.json")).unwrap();
+
+        assert_eq!(imp_policy.if_good_shooter, vec!("MP5K", "CLIP9_30", "CLIP9_30"));
+        assert_eq!(imp_policy.validate(&items), Vec::<String>::new());
+    }
+
+    #[test]
+    fn validate_should_report_unknown_items_and_wrong_magazines() {
+        let items = read_items(&::EngineOptions::default(), &assets_dir()).unwrap();
+        let imp_policy = ImpPolicy::from_json(r#"{
+            // comments are allowed like in the game
+            "inventory": ["CANTEEN", "FLAK_JACKETT"],
+            "if_good_shooter": ["MP5K", "CLIP9_30", "CLIP545_30_AP"],
+            "if_normal_shooter": ["BERETTA_93R", "CLIP9_15"]
+        }"#).unwrap();
+
+        assert_eq!(imp_policy.validate(&items), vec!(
+            "inventory[1]: unknown item 'FLAK_JACKETT'",
+            "if_good_shooter[2]: magazine 'CLIP545_30_AP' with calibre AMMO545 fits no weapon of the if_good_shooter kit",
+        ));
+    }
+
+    #[test]
+    fn validate_should_report_items_without_a_pocket() {
+        let items = read_items(&::EngineOptions::default(), &assets_dir()).unwrap();
+        let imp_policy = ImpPolicy {
+            inventory: vec!("FLAK_JACKET"; 4).into_iter().map(String::from).collect(),
+            if_good_shooter: vec!("CANTEEN"; 9).into_iter().map(String::from).collect(),
+            if_normal_shooter: vec!(String::from("FLAK_JACKET")),
+        };
+
+        assert_eq!(imp_policy.validate(&items), vec!(
+            "if_good_shooter[8]: no free pocket left for 'CANTEEN', the merc does not get it",
+            "if_normal_shooter[0]: no free pocket left for 'FLAK_JACKET', the merc does not get it",
+        ));
+    }
+}
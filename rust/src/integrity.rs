@@ -0,0 +1,146 @@
+//! Checks of the SLF libraries in the vanilla data directory.
+//!
+//! `check_data_dir` reports which of the libraries that every release ships are missing. There are no checksums of
+//! the original releases to compare with, so the contents of the libraries are not verified and the release is not
+//! detected. `hash_data_dir` lists the sizes and MD5 checksums of the libraries, e.g. for bug reports.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use md5;
+
+use data;
+
+/// Lower case names of the libraries of every release. Localized releases have more, like `german.slf`.
+pub static REQUIRED_LIBRARIES: [&str; 21] = [
+    "ambient.slf", "anims.slf", "battlesnds.slf", "bigitems.slf", "binarydata.slf", "cursors.slf", "faces.slf",
+    "fonts.slf", "interface.slf", "intro.slf", "laptop.slf", "loadscreens.slf", "maps.slf", "mercedt.slf",
+    "music.slf", "npc_speech.slf", "npcdata.slf", "radarmaps.slf", "sounds.slf", "speech.slf", "tilesets.slf",
+];
+
+/// Size and checksum of a library found in the data directory.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct LibraryChecksum {
+    pub name: String,
+    pub size: u64,
+    pub md5: String,
+}
+
+#[derive(Debug, PartialEq, Default)]
+pub struct DataDirReport {
+    /// Required libraries that were found.
+    pub present: Vec<String>,
+    pub missing: Vec<String>,
+    /// Libraries that are not required, like the ones of localized releases, the editor or patches.
+    pub other: Vec<String>,
+}
+
+impl DataDirReport {
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+/// Computes size and MD5 checksum of a file without loading it into memory at once.
+pub fn hash_library(path: &Path) -> Result<LibraryChecksum, String> {
+    let mut f = File::open(path).map_err(|e| format!("Error opening {}: {}", path.display(), e))?;
+    let mut context = md5::Context::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut size = 0u64;
+
+    loop {
+        let read = f.read(&mut buffer).map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+        if read == 0 {
+            break;
+        }
+        context.consume(&buffer[..read]);
+        size += read as u64;
+    }
+
+    Ok(LibraryChecksum {
+        name: library_name(path),
+        size,
+        md5: format!("{:x}", context.compute()),
+    })
+}
+
+fn library_name(path: &Path) -> String {
+    path.file_name().map(|n| n.to_string_lossy().to_lowercase()).unwrap_or_default()
+}
+
+/// Hashes every SLF library in the `Data` folder of the vanilla data directory.
+pub fn hash_data_dir(vanilla_data_dir: &Path) -> Result<Vec<LibraryChecksum>, String> {
+    let data_dir = data::find_data_dir(vanilla_data_dir)?;
+    data::list_slf_libraries(&data_dir)?.iter().map(|p| hash_library(p)).collect()
+}
+
+/// Sorts the lower case library names into required, missing and other libraries.
+pub fn check_libraries(names: &[String]) -> DataDirReport {
+    let mut report = DataDirReport::default();
+    for &library in REQUIRED_LIBRARIES.iter() {
+        if names.iter().any(|n| n == library) {
+            report.present.push(String::from(library));
+        } else {
+            report.missing.push(String::from(library));
+        }
+    }
+    report.other = names.iter().filter(|n| !REQUIRED_LIBRARIES.contains(&n.as_str())).cloned().collect();
+    report
+}
+
+/// Checks that the `Data` folder of the vanilla data directory has the required libraries.
+pub fn check_data_dir(vanilla_data_dir: &Path) -> Result<DataDirReport, String> {
+    let data_dir = data::find_data_dir(vanilla_data_dir)?;
+    let names: Vec<String> = data::list_slf_libraries(&data_dir)?.iter().map(|p| library_name(p)).collect();
+    Ok(check_libraries(&names))
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate tempdir;
+
+    use std::fs;
+    use std::fs::File;
+    use std::io::Write;
+
+    use super::{LibraryChecksum, REQUIRED_LIBRARIES};
+
+    #[test]
+    fn hash_library_should_compute_md5_and_size() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        let path = temp_dir.path().join("Maps.SLF");
+        File::create(&path).unwrap().write_all(b"The quick brown fox jumps over the lazy dog").unwrap();
+
+        let expected = LibraryChecksum { name: String::from("maps.slf"), size: 43, md5: String::from("9e107d9d372bb6826bd81d3542a419d6") };
+        assert_eq!(super::hash_library(&path), Ok(expected));
+    }
+
+    #[test]
+    fn check_libraries_should_report_missing_and_other_libraries() {
+        let mut names: Vec<String> = REQUIRED_LIBRARIES.iter().filter(|&&n| n != "music.slf").map(|&n| String::from(n)).collect();
+        names.push(String::from("german.slf"));
+        names.push(String::from("editor.slf"));
+
+        let report = super::check_libraries(&names);
+        assert_eq!(report.missing, vec!("music.slf"));
+        assert_eq!(report.other, vec!("german.slf", "editor.slf"));
+        assert_eq!(report.present.len(), REQUIRED_LIBRARIES.len() - 1);
+        assert!(!report.is_ok());
+    }
+
+    #[test]
+    fn check_data_dir_should_ignore_the_case_of_the_names() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        fs::create_dir(temp_dir.path().join("Data")).unwrap();
+        File::create(temp_dir.path().join("Data/Maps.slf")).unwrap();
+        File::create(temp_dir.path().join("Data/GERMAN.SLF")).unwrap();
+
+        let report = super::check_data_dir(temp_dir.path()).unwrap();
+
+        assert_eq!(report.present, vec!("maps.slf"));
+        assert!(report.missing.contains(&String::from("tilesets.slf")));
+        assert_eq!(report.other, vec!("german.slf"));
+        assert!(super::check_data_dir(&temp_dir.path().join("missing")).is_err());
+    }
+}
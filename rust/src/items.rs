@@ -0,0 +1,337 @@
+//! The items the game knows by their internal name, as `DefaultContentManager` builds them: the weapons of
+//! `weapons.json`, the magazines of `magazines.json` and the remaining items hardcoded in `Items.cc`.
+
+use std::fs;
+use std::path::Path;
+
+use serde_json;
+
+use data;
+use policy;
+use EngineOptions;
+
+/// The items created by `createAllHardcodedItemModels`: index, internal name and `ubPerPocket`.
+static HARDCODED_ITEMS: [(u16, &str, u8); 220] = [
+    (131, "STUN_GRENADE", 4),
+    (132, "TEARGAS_GRENADE", 4),
+    (133, "MUSTARD_GRENADE", 4),
+    (134, "MINI_GRENADE", 6),
+    (135, "HAND_GRENADE", 4),
+    (136, "RDX", 2),
+    (137, "TNT", 1),
+    (138, "HMX", 1),
+    (139, "C1", 1),
+    (140, "MORTAR_SHELL", 2),
+    (141, "MINE", 1),
+    (142, "C4", 1),
+    (143, "TRIP_FLARE", 2),
+    (144, "TRIP_KLAXON", 2),
+    (145, "SHAPED_CHARGE", 4),
+    (146, "BREAK_LIGHT", 6),
+    (147, "GL_HE_GRENADE", 4),
+    (148, "GL_TEARGAS_GRENADE", 4),
+    (149, "GL_STUN_GRENADE", 4),
+    (150, "GL_SMOKE_GRENADE", 4),
+    (151, "SMOKE_GRENADE", 4),
+    (152, "TANK_SHELL", 8),
+    (153, "STRUCTURE_IGNITE", 2),
+    (154, "CREATURE_COCKTAIL", 4),
+    (155, "STRUCTURE_EXPLOSION", 2),
+    (156, "GREAT_BIG_EXPLOSION", 2),
+    (157, "BIG_TEAR_GAS", 4),
+    (158, "SMALL_CREATURE_GAS", 0),
+    (159, "LARGE_CREATURE_GAS", 0),
+    (160, "VERY_SMALL_CREATURE_GAS", 0),
+    (161, "FLAK_JACKET", 0),
+    (162, "FLAK_JACKET_18", 0),
+    (163, "FLAK_JACKET_Y", 0),
+    (164, "KEVLAR_VEST", 0),
+    (165, "KEVLAR_VEST_18", 0),
+    (166, "KEVLAR_VEST_Y", 0),
+    (167, "SPECTRA_VEST", 0),
+    (168, "SPECTRA_VEST_18", 0),
+    (169, "SPECTRA_VEST_Y", 0),
+    (170, "KEVLAR_LEGGINGS", 0),
+    (171, "KEVLAR_LEGGINGS_18", 0),
+    (172, "KEVLAR_LEGGINGS_Y", 0),
+    (173, "SPECTRA_LEGGINGS", 0),
+    (174, "SPECTRA_LEGGINGS_18", 0),
+    (175, "SPECTRA_LEGGINGS_Y", 0),
+    (176, "STEEL_HELMET", 0),
+    (177, "KEVLAR_HELMET", 0),
+    (178, "KEVLAR_HELMET_18", 0),
+    (179, "KEVLAR_HELMET_Y", 0),
+    (180, "SPECTRA_HELMET", 0),
+    (181, "SPECTRA_HELMET_18", 0),
+    (182, "SPECTRA_HELMET_Y", 0),
+    (183, "CERAMIC_PLATES", 2),
+    (184, "CREATURE_INFANT_HIDE", 0),
+    (185, "CREATURE_YOUNG_MALE_HIDE", 0),
+    (186, "CREATURE_OLD_MALE_HIDE", 0),
+    (187, "CREATURE_QUEEN_HIDE", 0),
+    (188, "LEATHER_JACKET", 0),
+    (189, "LEATHER_JACKET_W_KEVLAR", 0),
+    (190, "LEATHER_JACKET_W_KEVLAR_18", 0),
+    (191, "LEATHER_JACKET_W_KEVLAR_Y", 0),
+    (192, "CREATURE_YOUNG_FEMALE_HIDE", 0),
+    (193, "CREATURE_OLD_FEMALE_HIDE", 0),
+    (194, "TSHIRT", 1),
+    (195, "TSHIRT_DEIDRANNA", 1),
+    (196, "KEVLAR2_VEST", 0),
+    (197, "KEVLAR2_VEST_18", 0),
+    (198, "KEVLAR2_VEST_Y", 0),
+    (199, "NOTHING", 0),
+    (200, "NOTHING", 0),
+    (201, "FIRSTAIDKIT", 4),
+    (202, "MEDICKIT", 0),
+    (203, "TOOLKIT", 0),
+    (204, "LOCKSMITHKIT", 1),
+    (205, "CAMOUFLAGEKIT", 4),
+    (206, "BOOBYTRAPKIT", 0),
+    (207, "SILENCER", 4),
+    (208, "SNIPERSCOPE", 4),
+    (209, "BIPOD", 2),
+    (210, "EXTENDEDEAR", 1),
+    (211, "NIGHTGOGGLES", 1),
+    (212, "SUNGOGGLES", 4),
+    (213, "GASMASK", 1),
+    (214, "CANTEEN", 4),
+    (215, "METALDETECTOR", 1),
+    (216, "COMPOUND18", 4),
+    (217, "JAR_QUEEN_CREATURE_BLOOD", 4),
+    (218, "JAR_ELIXIR", 4),
+    (219, "MONEY", 1),
+    (220, "JAR", 2),
+    (221, "JAR_CREATURE_BLOOD", 2),
+    (222, "ADRENALINE_BOOSTER", 8),
+    (223, "DETONATOR", 4),
+    (224, "REMDETONATOR", 4),
+    (225, "VIDEOTAPE", 8),
+    (226, "DEED", 8),
+    (227, "LETTER", 1),
+    (228, "TERRORIST_INFO", 1),
+    (229, "CHALICE", 1),
+    (230, "BLOODCAT_CLAWS", 4),
+    (231, "BLOODCAT_TEETH", 4),
+    (232, "BLOODCAT_PELT", 0),
+    (233, "SWITCH", 99),
+    (234, "ACTION_ITEM", 99),
+    (235, "REGEN_BOOSTER", 6),
+    (236, "SYRINGE_3", 99),
+    (237, "SYRINGE_4", 99),
+    (238, "SYRINGE_5", 99),
+    (239, "JAR_HUMAN_BLOOD", 2),
+    (240, "OWNERSHIP", 0),
+    (241, "LASERSCOPE", 4),
+    (242, "REMOTEBOMBTRIGGER", 4),
+    (243, "WIRECUTTERS", 2),
+    (244, "DUCKBILL", 4),
+    (245, "ALCOHOL", 1),
+    (246, "UVGOGGLES", 1),
+    (247, "DISCARDED_LAW", 0),
+    (248, "HEAD_1", 0),
+    (249, "HEAD_2", 0),
+    (250, "HEAD_3", 0),
+    (251, "HEAD_4", 0),
+    (252, "HEAD_5", 0),
+    (253, "HEAD_6", 0),
+    (254, "HEAD_7", 0),
+    (255, "WINE", 1),
+    (256, "BEER", 4),
+    (257, "PORNOS", 2),
+    (258, "VIDEO_CAMERA", 0),
+    (259, "ROBOT_REMOTE_CONTROL", 1),
+    (260, "CREATURE_PART_CLAWS", 0),
+    (261, "CREATURE_PART_FLESH", 0),
+    (262, "CREATURE_PART_ORGAN", 0),
+    (263, "REMOTETRIGGER", 4),
+    (264, "GOLDWATCH", 8),
+    (265, "GOLFCLUBS", 0),
+    (266, "WALKMAN", 1),
+    (267, "PORTABLETV", 0),
+    (268, "MONEY_FOR_PLAYERS_ACCOUNT", 0),
+    (269, "CIGARS", 1),
+    (270, "NOTHING", 0),
+    (271, "KEY_1", 8),
+    (272, "KEY_2", 8),
+    (273, "KEY_3", 8),
+    (274, "KEY_4", 8),
+    (275, "KEY_5", 8),
+    (276, "KEY_6", 8),
+    (277, "KEY_7", 8),
+    (278, "KEY_8", 8),
+    (279, "KEY_9", 0),
+    (280, "KEY_10", 0),
+    (281, "KEY_11", 0),
+    (282, "KEY_12", 0),
+    (283, "KEY_13", 0),
+    (284, "KEY_14", 0),
+    (285, "KEY_15", 0),
+    (286, "KEY_16", 0),
+    (287, "KEY_17", 0),
+    (288, "KEY_18", 0),
+    (289, "KEY_19", 0),
+    (290, "KEY_20", 0),
+    (291, "KEY_21", 0),
+    (292, "KEY_22", 0),
+    (293, "KEY_23", 0),
+    (294, "KEY_24", 0),
+    (295, "KEY_25", 0),
+    (296, "KEY_26", 0),
+    (297, "KEY_27", 0),
+    (298, "KEY_28", 0),
+    (299, "KEY_29", 0),
+    (300, "KEY_30", 0),
+    (301, "KEY_31", 0),
+    (302, "KEY_32", 0),
+    (303, "SILVER_PLATTER", 0),
+    (304, "DUCT_TAPE", 6),
+    (305, "ALUMINUM_ROD", 1),
+    (306, "SPRING", 8),
+    (307, "SPRING_AND_BOLT_UPGRADE", 1),
+    (308, "STEEL_ROD", 1),
+    (309, "QUICK_GLUE", 6),
+    (310, "GUN_BARREL_EXTENDER", 1),
+    (311, "STRING", 8),
+    (312, "TIN_CAN", 1),
+    (313, "STRING_TIED_TO_TIN_CAN", 4),
+    (314, "MARBLES", 6),
+    (315, "LAME_BOY", 1),
+    (316, "COPPER_WIRE", 8),
+    (317, "DISPLAY_UNIT", 1),
+    (318, "FUMBLE_PAK", 1),
+    (319, "XRAY_BULB", 2),
+    (320, "CHEWING_GUM", 8),
+    (321, "FLASH_DEVICE", 1),
+    (322, "BATTERIES", 6),
+    (323, "ELASTIC", 8),
+    (324, "XRAY_DEVICE", 1),
+    (325, "SILVER", 1),
+    (326, "GOLD", 1),
+    (327, "GAS_CAN", 0),
+    (328, "UNUSED_26", 0),
+    (329, "UNUSED_27", 0),
+    (330, "UNUSED_28", 0),
+    (331, "UNUSED_29", 0),
+    (332, "UNUSED_30", 0),
+    (333, "UNUSED_31", 0),
+    (334, "UNUSED_32", 0),
+    (335, "UNUSED_33", 0),
+    (336, "UNUSED_34", 0),
+    (337, "UNUSED_35", 0),
+    (338, "UNUSED_36", 0),
+    (339, "UNUSED_37", 0),
+    (340, "UNUSED_38", 0),
+    (341, "UNUSED_39", 0),
+    (342, "UNUSED_40", 0),
+    (343, "UNUSED_41", 0),
+    (344, "UNUSED_42", 0),
+    (345, "UNUSED_43", 0),
+    (346, "UNUSED_44", 0),
+    (347, "UNUSED_45", 0),
+    (348, "UNUSED_46", 0),
+    (349, "UNUSED_47", 0),
+    (350, "UNUSED_48", 0),
+];
+
+/// What an item is, as far as the checks of the externalized data need to know.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ItemKind {
+    /// A weapon with the calibre of its ammunition, knives and launchers have none.
+    Weapon(Option<String>),
+    /// A magazine with its calibre.
+    Magazine(String),
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Item {
+    pub index: u16,
+    pub name: String,
+    /// How many of the item fit into a small pocket, 0 if it only fits into a big one.
+    pub per_pocket: u8,
+    pub kind: ItemKind,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WeaponOrMagazineJson {
+    item_index: u16,
+    internal_name: String,
+    #[serde(rename = "ubPerPocket")]
+    per_pocket: u8,
+    calibre: Option<String>,
+}
+
+fn parse_items(json: &str) -> Result<Vec<WeaponOrMagazineJson>, String> {
+    serde_json::from_str(&policy::strip_json_comments(json)).map_err(|e| e.to_string())
+}
+
+/// All items by index.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemDatabase {
+    pub items: Vec<Item>,
+}
+
+impl ItemDatabase {
+    /// Builds the items from the contents of `weapons.json` and `magazines.json`.
+    pub fn from_json(weapons_json: &str, magazines_json: &str) -> Result<ItemDatabase, String> {
+        let weapons = parse_items(weapons_json).map_err(|e| format!("Error parsing weapons.json: {}", e))?;
+        let magazines = parse_items(magazines_json).map_err(|e| format!("Error parsing magazines.json: {}", e))?;
+        let mut items: Vec<Item> = HARDCODED_ITEMS.iter()
+            .map(|&(index, name, per_pocket)| Item { index, name: name.to_string(), per_pocket, kind: ItemKind::Other })
+            .collect();
+
+        items.extend(weapons.into_iter().map(|w| Item { index: w.item_index, name: w.internal_name, per_pocket: w.per_pocket, kind: ItemKind::Weapon(w.calibre) }));
+        for m in magazines {
+            let calibre = match m.calibre {
+                Some(calibre) => calibre,
+                None => return Err(format!("Error parsing magazines.json: magazine '{}' has no calibre", m.internal_name)),
+            };
+            items.push(Item { index: m.item_index, name: m.internal_name, per_pocket: m.per_pocket, kind: ItemKind::Magazine(calibre) });
+        }
+        items.sort_by_key(|i| i.index);
+        Ok(ItemDatabase { items })
+    }
+
+    /// The item with the internal name, e.g. `CLIP9_30`.
+    pub fn get(&self, name: &str) -> Option<&Item> {
+        self.items.iter().find(|i| i.name == name)
+    }
+}
+
+/// Reads the items the game would use, with the weapons and magazines of the first mod if it overrides them.
+pub fn read_items(engine_options: &EngineOptions, extra_data_dir: &Path) -> Result<ItemDatabase, String> {
+    let read = |name: &str| {
+        let path = data::externalized_file(engine_options, extra_data_dir, name);
+        fs::read_to_string(&path).map_err(|e| format!("Error reading {}: {}", path.display(), e))
+    };
+
+    ItemDatabase::from_json(&read("weapons.json")?, &read("magazines.json")?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{read_items, ItemDatabase, ItemKind};
+
+    #[test]
+    fn read_items_should_combine_the_json_and_the_hardcoded_items() {
+        let items = read_items(&::EngineOptions::default(), &PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../assets")).unwrap();
+
+        assert_eq!(items.items.len(), 351);
+        assert_eq!(items.get("GLOCK_17").map(|i| (i.index, &i.kind)), Some((1, &ItemKind::Weapon(Some(String::from("AMMO9"))))));
+        assert_eq!(items.get("CLIP9_30").map(|i| (i.index, i.per_pocket, &i.kind)), Some((72, 4, &ItemKind::Magazine(String::from("AMMO9")))));
+        assert_eq!(items.get("FLAK_JACKET").map(|i| (i.index, i.per_pocket, &i.kind)), Some((161, 0, &ItemKind::Other)));
+        assert_eq!(items.get("COMBAT_KNIFE").map(|i| &i.kind), Some(&ItemKind::Weapon(None)));
+        assert_eq!(items.get("UNKNOWN"), None);
+    }
+
+    #[test]
+    fn from_json_should_fail_with_a_magazine_without_calibre() {
+        let magazines = r#"[{ "itemIndex": 71, "internalName": "CLIP9_15", "ubPerPocket": 8 }]"#;
+
+        assert_eq!(ItemDatabase::from_json("[]", magazines), Err(String::from("Error parsing magazines.json: magazine 'CLIP9_15' has no calibre")));
+    }
+}
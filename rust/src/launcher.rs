@@ -0,0 +1,376 @@
+//! Functionality shared by the game launchers.
+//!
+//! Besides finding the game executable this contains the launcher model: the choices a launcher offers, the validation
+//! of what the user entered and the command that starts the game. Frontends only render it.
+
+use std::convert::TryFrom;
+use std::env;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use data;
+use parse_resolution;
+use ResourceVersion;
+
+/// Name of the game executable without extension, as built by CMake.
+pub static DEFAULT_EXECUTABLE_NAME: &str = "ja2";
+
+fn is_windows_executable(path: &Path) -> bool {
+    cfg!(windows) || path.extension().map(|e| e.to_string_lossy().to_lowercase() == "exe").unwrap_or(false)
+}
+
+fn executable_file_name(launcher_path: &Path, executable_name: &str) -> String {
+    if is_windows_executable(launcher_path) && Path::new(executable_name).extension().is_none() {
+        format!("{}.exe", executable_name)
+    } else {
+        String::from(executable_name)
+    }
+}
+
+/// Directories that may contain the game executable, in the order they are searched.
+fn candidate_dirs(launcher_path: &Path, appdir: Option<&Path>, path_env: Option<&OsString>) -> Vec<PathBuf> {
+    let mut dirs = vec!();
+    let parent = launcher_path.parent().filter(|p| *p != Path::new(""));
+
+    match parent {
+        Some(p) => dirs.push(PathBuf::from(p)),
+        // started through PATH, e.g. from /usr/bin
+        None => match path_env {
+            Some(paths) => dirs.extend(env::split_paths(paths)),
+            None => dirs.push(PathBuf::from(".")),
+        },
+    }
+
+    // the launcher might be a symlink, e.g. /usr/local/bin/ja2-launcher -> /opt/ja2/ja2-launcher
+    if let Ok(resolved) = launcher_path.canonicalize() {
+        if let Some(p) = resolved.parent() {
+            dirs.push(PathBuf::from(p));
+        }
+    }
+
+    // inside an AppImage both binaries are in $APPDIR/usr/bin
+    if let Some(appdir) = appdir {
+        dirs.push(appdir.join("usr").join("bin"));
+    }
+
+    dirs
+}
+
+/// Finds the game executable `executable_name` that belongs to the launcher at `launcher_path`.
+///
+/// `appdir` and `path_env` are the values of the `APPDIR` and `PATH` environment variables.
+pub fn find_executable(launcher_path: &Path, executable_name: &str, appdir: Option<&Path>, path_env: Option<&OsString>) -> Result<PathBuf, String> {
+    if executable_name.is_empty() {
+        return Err(String::from("The name of the game executable must not be empty"));
+    }
+    let file_name = executable_file_name(launcher_path, executable_name);
+
+    candidate_dirs(launcher_path, appdir, path_env).into_iter()
+        .map(|d| d.join(&file_name))
+        .find(|p| p.is_file())
+        .ok_or_else(|| format!("Could not find the game executable '{}' for launcher {}", file_name, launcher_path.display()))
+}
+
+/// Like `find_executable`, but takes `APPDIR` and `PATH` from the environment.
+pub fn find_ja2_executable(launcher_path: &Path, executable_name: &str) -> Result<PathBuf, String> {
+    let appdir = env::var_os("APPDIR").map(PathBuf::from);
+    let path_env = env::var_os("PATH");
+
+    find_executable(launcher_path, executable_name, appdir.as_deref(), path_env.as_ref())
+}
+
+/// The smallest resolution the game can run in.
+pub static MINIMUM_RESOLUTION: (u16, u16) = (640, 480);
+
+/// Resolutions offered by the launchers, the first one is the default.
+pub static RECOMMENDED_RESOLUTIONS: [(u16, u16); 6] = [
+    (640, 480),
+    (800, 600),
+    (1024, 768),
+    (1280, 720),
+    (1600, 900),
+    (1920, 1080),
+];
+
+/// The game versions a launcher offers.
+pub fn available_versions() -> &'static [ResourceVersion] {
+    ResourceVersion::all()
+}
+
+/// Formats a resolution the way it is stored in `ja2.json`, e.g. `1024x768`.
+pub fn format_resolution((x, y): (u16, u16)) -> String {
+    format!("{}x{}", x, y)
+}
+
+pub fn is_recommended_resolution(resolution: (u16, u16)) -> bool {
+    RECOMMENDED_RESOLUTIONS.contains(&resolution)
+}
+
+pub fn validate_resolution((x, y): (u16, u16)) -> Result<(), String> {
+    let (min_x, min_y) = MINIMUM_RESOLUTION;
+
+    if x < min_x || y < min_y {
+        Err(format!("Invalid resolution {}x{}. JA2 Stracciatella needs a resolution of at least {}x{}.", x, y, min_x, min_y))
+    } else {
+        Ok(())
+    }
+}
+
+/// Checks a resolution entered as numbers, which may be negative or too large for a resolution.
+pub fn validate_resolution_values(x: i32, y: i32) -> Result<(u16, u16), String> {
+    match (u16::try_from(x), u16::try_from(y)) {
+        (Ok(x), Ok(y)) => validate_resolution((x, y)).map(|_| (x, y)),
+        _ => Err(format!("Invalid resolution {}x{}.", x, y)),
+    }
+}
+
+/// Parses a resolution like `800x600` and checks that the game can run in it.
+pub fn validate_resolution_string(resolution: &str) -> Result<(u16, u16), String> {
+    let resolution = parse_resolution(resolution.trim())?;
+
+    validate_resolution(resolution)?;
+    Ok(resolution)
+}
+
+pub fn validate_resource_version(version: &str) -> Result<ResourceVersion, String> {
+    ResourceVersion::from_str(version.trim())
+}
+
+/// Checks that `vanilla_data_dir` is an installation of the original game, i.e. it has a `Data` folder.
+pub fn validate_vanilla_data_dir(vanilla_data_dir: &str) -> Result<PathBuf, String> {
+    if vanilla_data_dir.trim().is_empty() {
+        return Err(String::from("Please choose the directory of the original Jagged Alliance 2 installation."));
+    }
+    let path = PathBuf::from(vanilla_data_dir);
+    if !path.is_dir() {
+        return Err(format!("The directory {} does not exist.", path.display()));
+    }
+
+    data::find_data_dir(&path)
+        .map(|_| path.clone())
+        .map_err(|_| format!("{} does not look like a Jagged Alliance 2 installation, it has no Data folder.", path.display()))
+}
+
+/// The command line that starts the game from a launcher.
+#[derive(Debug, PartialEq, Clone)]
+pub struct LaunchCommand {
+    executable: PathBuf,
+    editor: bool,
+}
+
+impl LaunchCommand {
+    pub fn new(executable: &Path) -> LaunchCommand {
+        LaunchCommand { executable: PathBuf::from(executable), editor: false }
+    }
+
+    /// Starts the map editor instead of the game.
+    pub fn editor(mut self, editor: bool) -> LaunchCommand {
+        self.editor = editor;
+        self
+    }
+
+    pub fn executable(&self) -> &Path {
+        &self.executable
+    }
+
+    /// Arguments passed to the game. Everything else is read from `ja2.json`, which the launcher writes before.
+    pub fn args(&self) -> Vec<String> {
+        let mut args = vec!();
+        if self.editor {
+            args.push(String::from("-editor"));
+        }
+        args
+    }
+
+    /// The command as a single string suitable for `system()`, with the executable quoted.
+    pub fn to_command_line(&self) -> String {
+        let mut command_line = format!("\"{}\"", self.executable.display());
+        for arg in self.args() {
+            command_line.push(' ');
+            command_line.push_str(&arg);
+        }
+        command_line
+    }
+
+    pub fn to_process_command(&self) -> ::std::process::Command {
+        let mut command = ::std::process::Command::new(&self.executable);
+        command.args(self.args());
+        command
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate tempdir;
+
+    use std::env;
+    use std::fs;
+    use std::fs::File;
+    use std::path::Path;
+
+    fn touch(path: &Path) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        File::create(path).unwrap();
+    }
+
+    #[test]
+    fn find_executable_should_find_the_game_next_to_the_launcher() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        touch(&temp_dir.path().join("ja2"));
+        touch(&temp_dir.path().join("ja2-launcher"));
+
+        assert_eq!(super::find_executable(&temp_dir.path().join("ja2-launcher"), "ja2", None, None), Ok(temp_dir.path().join("ja2")));
+    }
+
+    #[test]
+    fn find_executable_should_ignore_the_name_of_the_launcher() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        touch(&temp_dir.path().join("ja2"));
+        touch(&temp_dir.path().join("l"));
+        touch(&temp_dir.path().join("ja2-stracciatella-launcher-ö"));
+
+        assert_eq!(super::find_executable(&temp_dir.path().join("l"), "ja2", None, None), Ok(temp_dir.path().join("ja2")));
+        assert_eq!(super::find_executable(&temp_dir.path().join("ja2-stracciatella-launcher-ö"), "ja2", None, None), Ok(temp_dir.path().join("ja2")));
+    }
+
+    #[test]
+    fn find_executable_should_append_exe_for_windows_launchers() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        touch(&temp_dir.path().join("ja2.exe"));
+        touch(&temp_dir.path().join("JA2-LAUNCHER.EXE"));
+
+        assert_eq!(super::find_executable(&temp_dir.path().join("JA2-LAUNCHER.EXE"), "ja2", None, None), Ok(temp_dir.path().join("ja2.exe")));
+        assert_eq!(super::find_executable(&temp_dir.path().join("JA2-LAUNCHER.EXE"), "ja2.exe", None, None), Ok(temp_dir.path().join("ja2.exe")));
+    }
+
+    #[test]
+    fn find_executable_should_use_a_configurable_name() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        touch(&temp_dir.path().join("ja2-stracciatella"));
+
+        assert_eq!(super::find_executable(&temp_dir.path().join("ja2-launcher"), "ja2-stracciatella", None, None), Ok(temp_dir.path().join("ja2-stracciatella")));
+        assert!(super::find_executable(&temp_dir.path().join("ja2-launcher"), "", None, None).is_err());
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn find_executable_should_follow_symlinks() {
+        use std::os::unix::fs::symlink;
+
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        let install_dir = temp_dir.path().join("opt/ja2");
+        let bin_dir = temp_dir.path().join("usr/local/bin");
+        touch(&install_dir.join("ja2"));
+        touch(&install_dir.join("ja2-launcher"));
+        fs::create_dir_all(&bin_dir).unwrap();
+        symlink(install_dir.join("ja2-launcher"), bin_dir.join("ja2-launcher")).unwrap();
+
+        let found = super::find_executable(&bin_dir.join("ja2-launcher"), "ja2", None, None).unwrap();
+
+        assert_eq!(found, install_dir.canonicalize().unwrap().join("ja2"));
+    }
+
+    #[test]
+    fn find_executable_should_search_the_path_for_bare_launcher_names() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        let usr_bin = temp_dir.path().join("usr/bin");
+        touch(&usr_bin.join("ja2"));
+        let path_env = env::join_paths(vec!(temp_dir.path().join("bin"), usr_bin.clone())).unwrap();
+
+        assert_eq!(super::find_executable(Path::new("ja2-launcher"), "ja2", None, Some(&path_env)), Ok(usr_bin.join("ja2")));
+    }
+
+    #[test]
+    fn find_executable_should_look_into_the_appimage() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        let appdir = temp_dir.path().join("squashfs-root");
+        touch(&appdir.join("usr/bin/ja2"));
+
+        let found = super::find_executable(&temp_dir.path().join("JA2-Stracciatella.AppImage"), "ja2", Some(&appdir), None);
+
+        assert_eq!(found, Ok(appdir.join("usr/bin/ja2")));
+    }
+
+    #[test]
+    fn find_executable_should_fail_if_the_game_does_not_exist() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+
+        assert_eq!(
+            super::find_executable(&temp_dir.path().join("ja2-launcher"), "ja2", None, None),
+            Err(format!("Could not find the game executable 'ja2' for launcher {}", temp_dir.path().join("ja2-launcher").display()))
+        );
+        assert!(super::find_executable(Path::new("x"), "ja2", None, None).is_err());
+    }
+
+    #[test]
+    fn available_versions_should_offer_every_resource_version() {
+        assert_eq!(super::available_versions().len(), 8);
+        assert_eq!(super::available_versions()[0], ::ResourceVersion::DUTCH);
+    }
+
+    #[test]
+    fn recommended_resolutions_should_be_valid() {
+        for resolution in super::RECOMMENDED_RESOLUTIONS.iter() {
+            assert_eq!(super::validate_resolution(*resolution), Ok(()));
+            assert_eq!(super::validate_resolution_string(&super::format_resolution(*resolution)), Ok(*resolution));
+        }
+        assert!(super::is_recommended_resolution((1280, 720)));
+        assert!(!super::is_recommended_resolution((1280, 721)));
+    }
+
+    #[test]
+    fn validate_resolution_string_should_reject_bad_input() {
+        assert_eq!(super::validate_resolution_string(" 1024x768 "), Ok((1024, 768)));
+        assert_eq!(super::validate_resolution_string("1024"), Err(String::from("Incorrect resolution format, should be WIDTHxHEIGHT.")));
+        assert_eq!(
+            super::validate_resolution_string("320x200"),
+            Err(String::from("Invalid resolution 320x200. JA2 Stracciatella needs a resolution of at least 640x480."))
+        );
+    }
+
+    #[test]
+    fn validate_resolution_values_should_reject_values_out_of_range() {
+        assert_eq!(super::validate_resolution_values(1024, 768), Ok((1024, 768)));
+        assert_eq!(super::validate_resolution_values(-1, 768), Err(String::from("Invalid resolution -1x768.")));
+        assert_eq!(super::validate_resolution_values(70000, 70000), Err(String::from("Invalid resolution 70000x70000.")));
+        assert_eq!(
+            super::validate_resolution_values(4464, 480 - 65536),
+            Err(String::from("Invalid resolution 4464x-65056."))
+        );
+        assert_eq!(
+            super::validate_resolution_values(320, 200),
+            Err(String::from("Invalid resolution 320x200. JA2 Stracciatella needs a resolution of at least 640x480."))
+        );
+    }
+
+    #[test]
+    fn validate_resource_version_should_accept_known_versions_only() {
+        assert_eq!(super::validate_resource_version("RUSSIAN_GOLD"), Ok(::ResourceVersion::RUSSIAN_GOLD));
+        assert_eq!(super::validate_resource_version("KLINGON"), Err(String::from("Resource version KLINGON is unknown")));
+    }
+
+    #[test]
+    fn validate_vanilla_data_dir_should_require_a_data_folder() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        let dir = temp_dir.path().to_string_lossy().into_owned();
+
+        assert!(super::validate_vanilla_data_dir("").is_err());
+        assert!(super::validate_vanilla_data_dir(&format!("{}/missing", dir)).is_err());
+        assert!(super::validate_vanilla_data_dir(&dir).is_err());
+
+        fs::create_dir(temp_dir.path().join("DATA")).unwrap();
+        assert_eq!(super::validate_vanilla_data_dir(&dir), Ok(temp_dir.path().to_path_buf()));
+    }
+
+    #[test]
+    fn launch_command_should_quote_the_executable_and_add_the_editor_switch() {
+        let command = super::LaunchCommand::new(Path::new("/opt/ja2 stracciatella/ja2"));
+        assert_eq!(command.to_command_line(), "\"/opt/ja2 stracciatella/ja2\"");
+        assert!(command.args().is_empty());
+
+        let command = command.editor(true);
+        assert_eq!(command.args(), vec!("-editor"));
+        assert_eq!(command.to_command_line(), "\"/opt/ja2 stracciatella/ja2\" -editor");
+    }
+}
@@ -0,0 +1,276 @@
+//! Completeness check of the translated string resources in `externalized/strings`.
+//!
+//! Every resource like `new-strings` has one file per language, see `loadStringRes` in `DefaultContentManager.cc`.
+//! The translations are compared with the English file: they need the same entries and the same printf placeholders
+//! in the same order, or the game prints garbage or crashes.
+
+use std::fs;
+use std::fs::File;
+use std::path::Path;
+
+use serde_json;
+use serde_json::Value;
+
+use ResourceVersion;
+
+/// The file name suffix of the string resources of a resource version.
+pub fn language_suffix(version: ResourceVersion) -> &'static str {
+    match version {
+        ResourceVersion::DUTCH => "dut",
+        ResourceVersion::ENGLISH => "eng",
+        ResourceVersion::FRENCH => "fr",
+        ResourceVersion::GERMAN => "ger",
+        ResourceVersion::ITALIAN => "it",
+        ResourceVersion::POLISH => "pl",
+        ResourceVersion::RUSSIAN | ResourceVersion::RUSSIAN_GOLD => "rus",
+    }
+}
+
+/// All language suffixes, the Russian versions share theirs.
+pub fn language_suffixes() -> Vec<&'static str> {
+    let mut suffixes: Vec<&'static str> = ResourceVersion::all().iter().map(|v| language_suffix(*v)).collect();
+    suffixes.dedup();
+    suffixes
+}
+
+/// The problems of one language file compared with the English one. Entries are named by their path, e.g. `[3]`.
+#[derive(Debug, PartialEq, Default)]
+pub struct LanguageReport {
+    pub file_name: String,
+    /// Set if the file is missing or no valid JSON, the other fields are empty then.
+    pub error: Option<String>,
+    pub missing: Vec<String>,
+    pub extra: Vec<String>,
+    pub placeholder_mismatches: Vec<String>,
+}
+
+impl LanguageReport {
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none() && self.missing.is_empty() && self.extra.is_empty() && self.placeholder_mismatches.is_empty()
+    }
+}
+
+/// Returns the printf conversions of `text` in order, without flags, width and precision, e.g. `["ls", "d"]`.
+pub fn printf_placeholders(text: &str) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut placeholders = vec!();
+    let mut i = 0;
+
+    while i < chars.len() {
+        if chars[i] != '%' {
+            i += 1;
+            continue;
+        }
+        if chars.get(i + 1) == Some(&'%') {
+            i += 2;
+            continue;
+        }
+
+        let mut j = i + 1;
+        let mut position = String::new();
+        let digits_end = (j..chars.len()).find(|k| !chars[*k].is_ascii_digit()).unwrap_or(chars.len());
+        if digits_end > j && chars.get(digits_end) == Some(&'$') {
+            position = chars[j..=digits_end].iter().collect();
+            j = digits_end + 1;
+        }
+        while j < chars.len() && "-+ #0'*.0123456789".contains(chars[j]) {
+            j += 1;
+        }
+        let length_start = j;
+        while j < chars.len() && "hlLqjzt".contains(chars[j]) {
+            j += 1;
+        }
+        match chars.get(j) {
+            Some(c) if "diouxXeEfFgGaAcCsSpn".contains(*c) => {
+                let conversion: String = chars[length_start..=j].iter().collect();
+                placeholders.push(format!("{}{}", position, conversion));
+                i = j + 1;
+            },
+            // a lone percent sign
+            _ => i += 1,
+        }
+    }
+
+    placeholders
+}
+
+fn describe(value: &Value) -> &'static str {
+    match *value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+fn compare_values(path: &str, reference: &Value, translation: &Value, report: &mut LanguageReport) {
+    match (reference, translation) {
+        (Value::Array(reference), Value::Array(translation)) => {
+            for (index, value) in reference.iter().enumerate() {
+                let entry = format!("{}[{}]", path, index);
+                match translation.get(index) {
+                    Some(translated) => compare_values(&entry, value, translated, report),
+                    None => report.missing.push(entry),
+                }
+            }
+            for index in reference.len()..translation.len() {
+                report.extra.push(format!("{}[{}]", path, index));
+            }
+        },
+        (Value::Object(reference), Value::Object(translation)) => {
+            for (key, value) in reference {
+                let entry = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                match translation.get(key) {
+                    Some(translated) => compare_values(&entry, value, translated, report),
+                    None => report.missing.push(entry),
+                }
+            }
+            for key in translation.keys().filter(|k| !reference.contains_key(*k)) {
+                report.extra.push(if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) });
+            }
+        },
+        (Value::String(reference), Value::String(translation)) => {
+            let expected = printf_placeholders(reference);
+            let actual = printf_placeholders(translation);
+            if expected != actual {
+                report.placeholder_mismatches.push(format!("{}: expected [{}], found [{}]", path, expected.join(", "), actual.join(", ")));
+            }
+        },
+        (reference, translation) if describe(reference) != describe(translation) => {
+            report.placeholder_mismatches.push(format!("{}: expected {}, found {}", path, describe(reference), describe(translation)));
+        },
+        _ => {},
+    }
+}
+
+/// Compares a translation with the English reference.
+pub fn compare_strings(file_name: &str, reference: &Value, translation: &Value) -> LanguageReport {
+    let mut report = LanguageReport { file_name: String::from(file_name), ..Default::default() };
+
+    compare_values("", reference, translation, &mut report);
+    report
+}
+
+/// Reads a string file, e.g. `new-strings-ger.json`.
+pub fn read_json(path: &Path) -> Result<Value, String> {
+    let file = File::open(path).map_err(|e| format!("Error opening {}: {}", path.display(), e))?;
+    serde_json::from_reader(file).map_err(|e| format!("Error parsing {}: {}", path.display(), e))
+}
+
+/// Lists the string resources in `dir` by name, e.g. `new-strings`. Every resource has an English file.
+pub fn list_string_resources(dir: &Path) -> Result<Vec<String>, String> {
+    let english_suffix = format!("-{}.json", language_suffix(ResourceVersion::ENGLISH));
+    let entries = fs::read_dir(dir).map_err(|e| format!("Error reading {}: {}", dir.display(), e))?;
+    let mut resources: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.file_name().into_string().ok())
+        .filter(|n| n.ends_with(&english_suffix))
+        .map(|n| String::from(&n[..n.len() - english_suffix.len()]))
+        .collect();
+
+    resources.sort();
+    Ok(resources)
+}
+
+/// Checks every translation of the string resource `name` in `dir`.
+pub fn check_string_resource(dir: &Path, name: &str) -> Result<Vec<LanguageReport>, String> {
+    let english = language_suffix(ResourceVersion::ENGLISH);
+    let reference = read_json(&dir.join(format!("{}-{}.json", name, english)))?;
+
+    Ok(language_suffixes().into_iter().filter(|s| *s != english).map(|suffix| {
+        let file_name = format!("{}-{}.json", name, suffix);
+        match read_json(&dir.join(&file_name)) {
+            Ok(translation) => compare_strings(&file_name, &reference, &translation),
+            Err(e) => LanguageReport { file_name, error: Some(e), ..Default::default() },
+        }
+    }).collect())
+}
+
+/// Checks all string resources in `dir`, usually `externalized/strings`.
+pub fn check_strings_dir(dir: &Path) -> Result<Vec<LanguageReport>, String> {
+    let mut reports = vec!();
+
+    for name in list_string_resources(dir)? {
+        reports.extend(check_string_resource(dir, &name)?);
+    }
+    Ok(reports)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate tempdir;
+
+    use std::fs::File;
+    use std::io::Write;
+    use std::path::PathBuf;
+
+    fn strings_dir() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../assets/externalized/strings")
+    }
+
+    fn json(text: &str) -> ::serde_json::Value {
+        ::serde_json::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn printf_placeholders_should_ignore_flags_and_escaped_percent_signs() {
+        assert_eq!(super::printf_placeholders("%ls equips %ls"), vec!("ls", "ls"));
+        assert_eq!(super::printf_placeholders("%-5.2f%% of %+03d, 100 %"), vec!("f", "d"));
+        assert_eq!(super::printf_placeholders("%2$s %1$s"), vec!("2$s", "1$s"));
+        assert!(super::printf_placeholders("no placeholders").is_empty());
+    }
+
+    #[test]
+    fn language_suffixes_should_match_the_shipped_files() {
+        assert_eq!(super::language_suffixes(), vec!("dut", "eng", "fr", "ger", "it", "pl", "rus"));
+    }
+
+    #[test]
+    fn compare_strings_should_report_missing_extra_and_mismatched_entries() {
+        let reference = json(r#"["%ls equips %ls", "%d%%", "last"]"#);
+
+        let report = super::compare_strings("x-ger.json", &reference, &json(r#"["%ls rüstet %ls aus", "%s%%"]"#));
+        assert_eq!(report.missing, vec!("[2]"));
+        assert_eq!(report.placeholder_mismatches, vec!("[1]: expected [d], found [s]"));
+        assert!(!report.is_ok());
+
+        let report = super::compare_strings("x-ger.json", &reference, &json(r#"["%ls %ls", "%d%%", "letzte", "extra"]"#));
+        assert_eq!(report.extra, vec!("[3]"));
+        assert!(report.missing.is_empty());
+    }
+
+    #[test]
+    fn compare_strings_should_compare_nested_objects() {
+        let reference = json(r#"{"a": {"b": "%d"}, "c": ["x"]}"#);
+        let report = super::compare_strings("x-fr.json", &reference, &json(r#"{"a": {"b": "%d", "z": ""}, "c": "x"}"#));
+
+        assert_eq!(report.extra, vec!("a.z"));
+        assert_eq!(report.placeholder_mismatches, vec!("c: expected an array, found a string"));
+    }
+
+    #[test]
+    fn check_string_resource_should_report_missing_files() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        File::create(temp_dir.path().join("test-eng.json")).unwrap().write_all(b"[\"%d\"]").unwrap();
+        File::create(temp_dir.path().join("test-dut.json")).unwrap().write_all(b"[\"%d\"]").unwrap();
+
+        let reports = super::check_string_resource(temp_dir.path(), "test").unwrap();
+
+        assert_eq!(reports.len(), 6);
+        assert!(reports[0].is_ok());
+        assert!(reports[1].error.as_ref().unwrap().starts_with("Error opening"));
+    }
+
+    #[test]
+    fn shipped_strings_should_be_complete() {
+        assert_eq!(super::list_string_resources(&strings_dir()).unwrap(), vec!("ammo-calibre", "ammo-calibre-bobbyray", "new-strings"));
+
+        let problems: Vec<String> = super::check_strings_dir(&strings_dir()).unwrap().iter()
+            .filter(|r| !r.is_ok())
+            .map(|r| format!("{:?}", r))
+            .collect();
+        assert!(problems.is_empty(), "{}", problems.join("\n"));
+    }
+}
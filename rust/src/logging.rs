@@ -0,0 +1,555 @@
+//! The logging backend of the `SLOG*` macros in `slog/slog.h`.
+//!
+//! `slog.c` only formats the printf style messages and passes them on. Here they are filtered by their level and
+//! topic and written to the console and to the log file in `stracciatella_home`. The log file of the previous start
+//! and files that grow beyond `max_file_size` are moved to `ja2.log.1`, `ja2.log.2` and so on.
+//!
+//! The `log` object of `ja2.json` and the `--log` switches set the levels, e.g. `--log warning --log sgp=debug`. With
+//! `"json": true` the log file has one JSON object per message, which is easier to search in bug reports.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::fs::File;
+use std::io::Write;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use libc;
+use serde_json;
+
+/// The levels of `SLOGLevel`, more severe levels are greater. `Off` is only used in the configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warning,
+    Error,
+    Off,
+}
+
+pub static LOG_LEVEL_NAMES: [&str; 5] = ["debug", "info", "warning", "error", "off"];
+
+impl FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "debug" => Ok(LogLevel::Debug),
+            "info" => Ok(LogLevel::Info),
+            "warning" => Ok(LogLevel::Warning),
+            "error" => Ok(LogLevel::Error),
+            "off" => Ok(LogLevel::Off),
+            _ => Err(format!("Unknown log level '{}', possible levels: {}", s, LOG_LEVEL_NAMES.join(", "))),
+        }
+    }
+}
+
+impl LogLevel {
+    /// Converts a `SLOGLevel`, unknown values are errors.
+    pub fn from_slog(level: u32) -> LogLevel {
+        match level {
+            0 => LogLevel::Debug,
+            1 => LogLevel::Info,
+            2 => LogLevel::Warning,
+            _ => LogLevel::Error,
+        }
+    }
+}
+
+/// The console destinations of `SLOGConsole`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogConsole {
+    Stdout,
+    Stderr,
+    None,
+}
+
+impl LogConsole {
+    /// Converts a `SLOGConsole`, unknown values log to no console.
+    pub fn from_slog(console: u32) -> LogConsole {
+        match console {
+            0 => LogConsole::Stdout,
+            1 => LogConsole::Stderr,
+            _ => LogConsole::None,
+        }
+    }
+}
+
+pub const NUMBER_OF_TOPICS: usize = 63;
+
+/// The topics of `SLOGTopics` in order: the name used in the configuration and the tag written to the log.
+pub static TOPICS: [(&str, &str); NUMBER_OF_TOPICS] = [
+    ("gameloop", "Game Loop"),
+    ("smap", "Strategic Map"),
+    ("ai", "AI"),
+    ("scheduler", "Scheduler"),
+    ("pathai", "Path AI"),
+    ("animations", "Animations"),
+    ("gap", "Gap"),
+    ("weapons", "Weapons"),
+    ("overhead", "Overhead"),
+    ("keys", "Keys"),
+    ("points", "Points"),
+    ("morale", "Morale"),
+    ("merchire", "Merc Hire"),
+    ("opplist", "Opp List"),
+    ("lighting", "Lighting"),
+    ("renderworld", "Render World"),
+    ("tiles", "Tiles"),
+    ("explosion", "Explosion"),
+    ("physics", "Physics"),
+    ("ambient", "Ambient"),
+    ("saveload", "Save/Load"),
+    ("musicctl", "Music Control"),
+    ("smk", "Smacker"),
+    ("eventpump", "Event Pump"),
+    ("quests", "Quests"),
+    ("editor", "Editor"),
+    ("resources", "Resources"),
+    ("ja2screens", "JA2 Screens"),
+    ("init", "Init"),
+    ("font", "Font"),
+    ("sgp", "SGP"),
+    ("stci", "STCI"),
+    ("container", "Container"),
+    ("video", "Video"),
+    ("vsurface", "VSurface"),
+    ("sound", "Sound"),
+    ("memory", "Memory Manager"),
+    ("gamescreen", "Game Screen"),
+    ("bobbyray", "Bobby Ray"),
+    ("sai", "Strategic AI"),
+    ("airraid", "Air Raid"),
+    ("bullets", "Bullets"),
+    ("handleitems", "Handle Items"),
+    ("interface", "Interface"),
+    ("los", "Line of Sight"),
+    ("tactsave", "Tactical Save"),
+    ("teamturn", "Team Turns"),
+    ("worlddef", "World Def"),
+    ("himage", "Himage"),
+    ("libdb", "Library DB"),
+    ("fileman", "File Man"),
+    ("modpack", "Mod Pack"),
+    ("defaultcm", "Default CM"),
+    ("soldier", "Soldier"),
+    ("asserts", "Asserts"),
+    ("queencmd", "Queen Command"),
+    ("npc", "NPC"),
+    ("campaign", "Campaign"),
+    ("loyalty", "Loyalty"),
+    ("autoresolve", "Auto Resolve"),
+    ("corpses", "Corpses"),
+    ("fixme", "Fixme"),
+    ("launcher", "Launcher"),
+];
+
+/// The index of a topic by its name in the configuration, e.g. `sgp`.
+pub fn topic_index(name: &str) -> Option<usize> {
+    TOPICS.iter().position(|&(n, _)| n == name)
+}
+
+/// The `log` object of `ja2.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogOptions {
+    /// The level of the topics without their own, unset keeps the one of the game: warnings, everything with `-debug`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<LogLevel>,
+    /// Levels by topic, e.g. `{"sgp": "debug", "ai": "off"}`.
+    pub topics: BTreeMap<String, LogLevel>,
+    /// The log file is rotated when it would grow beyond this size in bytes.
+    pub max_file_size: u64,
+    /// How many rotated log files are kept.
+    pub max_files: u32,
+    /// Writes one JSON object per message to the log file instead of text.
+    pub json: bool,
+}
+
+impl Default for LogOptions {
+    fn default() -> LogOptions {
+        LogOptions { level: None, topics: BTreeMap::new(), max_file_size: 1 << 20, max_files: 3, json: false }
+    }
+}
+
+impl LogOptions {
+    /// Applies a `--log` switch, either `LEVEL` for all topics or `TOPIC=LEVEL`.
+    pub fn apply_switch(&mut self, switch: &str) -> Result<(), String> {
+        match switch.find('=') {
+            Some(i) => {
+                let (topic, level) = (&switch[..i], &switch[i + 1..]);
+                if topic_index(topic).is_none() {
+                    return Err(format!("Unknown log topic '{}'", topic));
+                }
+                self.topics.insert(String::from(topic), level.parse()?);
+            },
+            None => self.level = Some(switch.parse()?),
+        }
+        Ok(())
+    }
+
+    /// Topics of `ja2.json` the game does not know.
+    pub fn validate(&self) -> Vec<String> {
+        self.topics.keys()
+            .filter(|t| topic_index(t).is_none())
+            .map(|t| format!("Unknown log topic '{}' in ja2.json", t))
+            .collect()
+    }
+}
+
+/// The path of the `n`th rotated log file, e.g. `ja2.log.1`.
+pub fn rotated_path(path: &Path, n: u32) -> PathBuf {
+    let file_name = path.file_name().map(|f| f.to_string_lossy().into_owned()).unwrap_or_default();
+    path.with_file_name(format!("{}.{}", file_name, n))
+}
+
+/// Moves the log file to `.1`, `.1` to `.2` and so on, dropping the oldest. Missing files are skipped.
+pub fn rotate_log_files(path: &Path, max_files: u32) {
+    if max_files == 0 {
+        fs::remove_file(path).ok();
+        return;
+    }
+    fs::remove_file(rotated_path(path, max_files)).ok();
+    for n in (1..max_files).rev() {
+        fs::rename(rotated_path(path, n), rotated_path(path, n + 1)).ok();
+    }
+    fs::rename(path, rotated_path(path, 1)).ok();
+}
+
+/// The local time as year, month, day, hour, minute and second.
+fn local_time(time: SystemTime) -> (i32, i32, i32, i32, i32, i32) {
+    let seconds = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0) as libc::time_t;
+    let mut tm: libc::tm = unsafe { mem::zeroed() };
+    #[cfg(not(windows))]
+    unsafe { libc::localtime_r(&seconds, &mut tm); }
+    #[cfg(windows)]
+    unsafe { libc::localtime_s(&mut tm, &seconds); }
+    (tm.tm_year + 1900, tm.tm_mon + 1, tm.tm_mday, tm.tm_hour, tm.tm_min, tm.tm_sec)
+}
+
+/// A message of the JSON log file.
+#[derive(Debug, Serialize)]
+struct LogRecord<'a> {
+    time: String,
+    level: LogLevel,
+    topic: &'a str,
+    message: &'a str,
+}
+
+/// Formats a message like slog did, `message` ends with a newline.
+pub fn format_text_line(time: SystemTime, level: LogLevel, topic: usize, message: &str) -> String {
+    let (year, month, day, hour, minute, second) = local_time(time);
+    let level_name = format!("{:?}", level).to_uppercase();
+    format!("{:4}/{:02}/{:02} {:02}:{:02}:{:02} {:<7} [{:<16}] {}", year, month, day, hour, minute, second, level_name, TOPICS[topic].1, message)
+}
+
+/// Formats a message as one line of JSON.
+pub fn format_json_line(time: SystemTime, level: LogLevel, topic: usize, message: &str) -> String {
+    let (year, month, day, hour, minute, second) = local_time(time);
+    let record = LogRecord {
+        time: format!("{:4}-{:02}-{:02}T{:02}:{:02}:{:02}", year, month, day, hour, minute, second),
+        level,
+        topic: TOPICS[topic].0,
+        message: message.trim_end_matches('\n'),
+    };
+    format!("{}\n", serde_json::to_string(&record).unwrap())
+}
+
+#[derive(Debug)]
+struct LogFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+}
+
+/// The state behind the `SLOG_*` functions.
+#[derive(Debug)]
+pub struct Logger {
+    initialized: bool,
+    console: LogConsole,
+    console_level: LogLevel,
+    file_level: LogLevel,
+    topic_levels: [Option<LogLevel>; NUMBER_OF_TOPICS],
+    disabled_topics: u64,
+    file: Option<LogFile>,
+    max_file_size: u64,
+    max_files: u32,
+    json: bool,
+}
+
+impl Default for Logger {
+    fn default() -> Logger {
+        Logger::new()
+    }
+}
+
+impl Logger {
+    pub const fn new() -> Logger {
+        Logger {
+            initialized: false,
+            console: LogConsole::None,
+            console_level: LogLevel::Info,
+            file_level: LogLevel::Debug,
+            topic_levels: [None; NUMBER_OF_TOPICS],
+            disabled_topics: 0,
+            file: None,
+            max_file_size: 1 << 20,
+            max_files: 3,
+            json: false,
+        }
+    }
+
+    /// `SLOG_Init`, only the first call has an effect. Messages are dropped until it is called.
+    pub fn init(&mut self, console: LogConsole, log_file: Option<&Path>) -> Result<(), String> {
+        if self.initialized {
+            return Ok(());
+        }
+        self.initialized = true;
+        self.console = console;
+        match log_file {
+            Some(path) => self.open_file(path),
+            None => Ok(()),
+        }
+    }
+
+    /// `SLOG_Deinit`, closes the log file.
+    pub fn close_file(&mut self) {
+        self.file = None;
+    }
+
+    /// `SLOG_SetLevel`, the levels of the topics without their own.
+    pub fn set_levels(&mut self, console: LogLevel, file: LogLevel) {
+        self.console_level = console;
+        self.file_level = file;
+    }
+
+    /// `SLOG_EnableTopic` and `SLOG_DisableTopic`.
+    pub fn set_topic_enabled(&mut self, topic: usize, enabled: bool) {
+        if topic < NUMBER_OF_TOPICS {
+            if enabled { self.disabled_topics &= !(1 << topic) } else { self.disabled_topics |= 1 << topic }
+        }
+    }
+
+    /// Applies the `log` object of `ja2.json` and moves the log file to `path`, rotating the one of the last start.
+    pub fn configure(&mut self, options: &LogOptions, path: &Path) -> Result<(), String> {
+        if let Some(level) = options.level {
+            self.set_levels(level, level);
+        }
+        self.topic_levels = [None; NUMBER_OF_TOPICS];
+        for (topic, level) in &options.topics {
+            if let Some(index) = topic_index(topic) {
+                self.topic_levels[index] = Some(*level);
+            }
+        }
+        self.max_file_size = options.max_file_size;
+        self.max_files = options.max_files;
+        self.json = options.json;
+        self.open_file(path)
+    }
+
+    fn open_file(&mut self, path: &Path) -> Result<(), String> {
+        self.file = None;
+        if fs::metadata(path).map(|m| m.len() > 0).unwrap_or(false) {
+            rotate_log_files(path, self.max_files);
+        }
+        let file = File::create(path).map_err(|e| format!("Error creating {}: {}", path.display(), e))?;
+        self.file = Some(LogFile { path: path.to_path_buf(), file, size: 0 });
+        Ok(())
+    }
+
+    /// Whether a message would be written by `log`, so that the caller can skip formatting it.
+    pub fn is_enabled(&self, level: LogLevel, topic: usize) -> bool {
+        let (to_console, to_file) = self.destinations(level, topic);
+        to_console || to_file
+    }
+
+    /// Whether a message goes to the console and to the file.
+    fn destinations(&self, level: LogLevel, topic: usize) -> (bool, bool) {
+        if !self.initialized || topic >= NUMBER_OF_TOPICS || self.disabled_topics & (1 << topic) != 0 {
+            return (false, false);
+        }
+        let (console_level, file_level) = match self.topic_levels[topic] {
+            Some(level) => (level, level),
+            None => (self.console_level, self.file_level),
+        };
+        (self.console != LogConsole::None && level >= console_level, self.file.is_some() && level >= file_level)
+    }
+
+    /// `SLOG_LogMessage`, returns whether the message was written anywhere.
+    pub fn log(&mut self, time: SystemTime, level: LogLevel, topic: usize, message: &str) -> bool {
+        let (to_console, to_file) = self.destinations(level, topic);
+        self.write(time, level, topic, message, to_console, to_file)
+    }
+
+    /// Writes a message whatever the levels and the disabled topics are, to the log file if there is one, otherwise to
+    /// the console. For the few messages that are needed in every bug report, like the random seed.
+    pub fn log_unfiltered(&mut self, time: SystemTime, level: LogLevel, topic: usize, message: &str) -> bool {
+        if !self.initialized || topic >= NUMBER_OF_TOPICS {
+            return false;
+        }
+        let to_file = self.file.is_some();
+        let to_console = !to_file && self.console != LogConsole::None;
+        self.write(time, level, topic, message, to_console, to_file)
+    }
+
+    fn write(&mut self, time: SystemTime, level: LogLevel, topic: usize, message: &str, to_console: bool, to_file: bool) -> bool {
+        if to_console {
+            let line = format_text_line(time, level, topic, message);
+            match self.console {
+                LogConsole::Stdout => { std::io::stdout().write_all(line.as_bytes()).ok(); },
+                _ => { std::io::stderr().write_all(line.as_bytes()).ok(); },
+            }
+        }
+        if to_file {
+            let line = if self.json { format_json_line(time, level, topic, message) } else { format_text_line(time, level, topic, message) };
+            self.write_to_file(&line);
+        }
+        to_console || to_file
+    }
+
+    fn write_to_file(&mut self, line: &str) {
+        let (max_file_size, max_files) = (self.max_file_size, self.max_files);
+        let rotated = match self.file {
+            Some(ref log_file) if log_file.size > 0 && log_file.size + line.len() as u64 > max_file_size => {
+                rotate_log_files(&log_file.path, max_files);
+                Some(log_file.path.clone())
+            },
+            _ => None,
+        };
+        if let Some(path) = rotated {
+            self.file = File::create(&path).ok().map(|file| LogFile { path, file, size: 0 });
+        }
+        if let Some(ref mut log_file) = self.file {
+            if log_file.file.write_all(line.as_bytes()).and_then(|_| log_file.file.flush()).is_ok() {
+                log_file.size += line.len() as u64;
+            }
+        }
+    }
+}
+
+/// The logger of the game, used through the FFI functions of `slog.c`.
+pub static LOGGER: Mutex<Logger> = Mutex::new(Logger::new());
+
+#[cfg(test)]
+mod tests {
+    extern crate tempdir;
+
+    use std::fs;
+    use std::time::SystemTime;
+
+    use super::{rotated_path, topic_index, LogConsole, LogLevel, LogOptions, Logger, TOPICS};
+
+    fn read_lines(path: &::std::path::Path) -> Vec<String> {
+        fs::read_to_string(path).unwrap().lines().map(|l| l[20..].to_string()).collect()
+    }
+
+    #[test]
+    fn topics_should_match_slog_h() {
+        assert_eq!(topic_index("gameloop"), Some(0));
+        assert_eq!(topic_index("sgp"), Some(30));
+        assert_eq!(topic_index("launcher"), Some(62));
+        assert_eq!(TOPICS[54], ("asserts", "Asserts"));
+    }
+
+    #[test]
+    fn apply_switch_should_set_the_levels() {
+        let mut options = LogOptions::default();
+
+        options.apply_switch("info").unwrap();
+        options.apply_switch("sgp=debug").unwrap();
+        options.apply_switch("ai=off").unwrap();
+
+        assert_eq!(options.level, Some(LogLevel::Info));
+        assert_eq!(options.topics.get("sgp"), Some(&LogLevel::Debug));
+        assert_eq!(options.apply_switch("nothing=debug"), Err(String::from("Unknown log topic 'nothing'")));
+        assert_eq!(options.apply_switch("verbose"), Err(String::from("Unknown log level 'verbose', possible levels: debug, info, warning, error, off")));
+    }
+
+    #[test]
+    fn log_should_filter_by_the_levels_of_the_topics() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        let path = temp_dir.path().join("ja2.log");
+        let mut logger = Logger::new();
+        let mut options: LogOptions = ::serde_json::from_str(r#"{"topics": {"sgp": "debug", "ai": "off"}}"#).unwrap();
+        options.level = Some(LogLevel::Warning);
+
+        assert!(!logger.log(SystemTime::now(), LogLevel::Error, 30, "Dropped before init\n"));
+        logger.init(LogConsole::None, None).unwrap();
+        assert!(!logger.is_enabled(LogLevel::Error, 30));
+        logger.configure(&options, &path).unwrap();
+        assert!(logger.is_enabled(LogLevel::Debug, 30));
+        assert!(!logger.is_enabled(LogLevel::Error, 2));
+        assert!(!logger.is_enabled(LogLevel::Info, 35));
+        assert!(!logger.is_enabled(LogLevel::Error, 200));
+        assert!(logger.log(SystemTime::now(), LogLevel::Debug, 30, "Resolution 640x480\n"));
+        assert!(!logger.log(SystemTime::now(), LogLevel::Error, 2, "Not logged\n"));
+        assert!(!logger.log(SystemTime::now(), LogLevel::Info, 35, "Not logged\n"));
+        assert!(logger.log(SystemTime::now(), LogLevel::Warning, 35, "Sound file missing\n"));
+        logger.set_topic_enabled(35, false);
+        assert!(!logger.log(SystemTime::now(), LogLevel::Error, 35, "Not logged\n"));
+
+        assert_eq!(read_lines(&path), vec!(
+            "DEBUG   [SGP             ] Resolution 640x480",
+            "WARNING [Sound           ] Sound file missing",
+        ));
+    }
+
+    #[test]
+    fn log_unfiltered_should_ignore_the_levels() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        let path = temp_dir.path().join("ja2.log");
+        let mut logger = Logger::new();
+        let mut options = LogOptions::default();
+        options.level = Some(LogLevel::Off);
+
+        assert!(!logger.log_unfiltered(SystemTime::now(), LogLevel::Info, 30, "Dropped before init\n"));
+        logger.init(LogConsole::None, None).unwrap();
+        logger.configure(&options, &path).unwrap();
+        logger.set_topic_enabled(30, false);
+        assert!(!logger.log(SystemTime::now(), LogLevel::Error, 30, "Not logged\n"));
+        assert!(logger.log_unfiltered(SystemTime::now(), LogLevel::Info, 30, "Random seed: 1234\n"));
+
+        assert_eq!(read_lines(&path), vec!("INFO    [SGP             ] Random seed: 1234"));
+    }
+
+    #[test]
+    fn log_should_write_json_lines() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        let path = temp_dir.path().join("ja2.log");
+        let mut logger = Logger::new();
+        let options = LogOptions { json: true, ..Default::default() };
+
+        logger.init(LogConsole::None, None).unwrap();
+        logger.configure(&options, &path).unwrap();
+        logger.log(SystemTime::now(), LogLevel::Error, 51, "Mod \"x\" not found\n");
+
+        let record: ::serde_json::Value = ::serde_json::from_str(fs::read_to_string(&path).unwrap().trim_end()).unwrap();
+        assert_eq!(record["level"], "error");
+        assert_eq!(record["topic"], "modpack");
+        assert_eq!(record["message"], "Mod \"x\" not found");
+    }
+
+    #[test]
+    fn log_should_rotate_the_files() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        let path = temp_dir.path().join("ja2.log");
+        fs::write(&path, "last start\n").unwrap();
+        let mut logger = Logger::new();
+        let options = LogOptions { max_file_size: 120, max_files: 2, ..Default::default() };
+
+        logger.init(LogConsole::None, None).unwrap();
+        logger.configure(&options, &path).unwrap();
+        for i in 0..5 {
+            logger.log(SystemTime::now(), LogLevel::Error, 0, &format!("Message {}\n", i));
+        }
+
+        assert_eq!(read_lines(&path), vec!("ERROR   [Game Loop       ] Message 4"));
+        assert_eq!(read_lines(&rotated_path(&path, 1)), vec!("ERROR   [Game Loop       ] Message 2", "ERROR   [Game Loop       ] Message 3"));
+        assert_eq!(read_lines(&rotated_path(&path, 2)).len(), 2);
+        assert!(!rotated_path(&path, 3).exists());
+    }
+}
@@ -0,0 +1,1165 @@
+//! Reading of the sector maps in `maps`, e.g. `A9.dat` or `A10_b1.dat`.
+//!
+//! The layout follows `LoadWorld` in `WorldDef.cc` and the functions it calls for the optional parts, which are
+//! present if the matching `MAP_*_SAVED` flag is set in the header. Tiles are kept as tile type and sub index like
+//! they are stored, the tileset decides which graphics they refer to.
+//!
+//! `diff_maps` compares two maps semantically, e.g. a map replaced by a mod with the vanilla one.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{Cursor, Read};
+use std::path::Path;
+
+use byteorder::{LittleEndian, ReadBytesExt};
+use serde::Serialize;
+use serde_json;
+use serde_json::Value;
+
+use ResourceVersion;
+
+pub const WORLD_COLS: usize = 160;
+pub const WORLD_ROWS: usize = 160;
+pub const WORLD_MAX: usize = WORLD_COLS * WORLD_ROWS;
+
+pub const MAP_FULLSOLDIER_SAVED: u32 = 0x0000_0001;
+pub const MAP_WORLDLIGHTS_SAVED: u32 = 0x0000_0004;
+pub const MAP_WORLDITEMS_SAVED: u32 = 0x0000_0008;
+pub const MAP_EXITGRIDS_SAVED: u32 = 0x0000_0010;
+pub const MAP_DOORTABLE_SAVED: u32 = 0x0000_0020;
+pub const MAP_EDGEPOINTS_SAVED: u32 = 0x0000_0040;
+pub const MAP_AMBIENTLIGHTLEVEL_SAVED: u32 = 0x0000_0080;
+pub const MAP_NPCSCHEDULES_SAVED: u32 = 0x0000_0100;
+
+static MAP_FLAGS: [(u32, &str); 8] = [
+    (MAP_FULLSOLDIER_SAVED, "FULLSOLDIER"),
+    (MAP_WORLDLIGHTS_SAVED, "WORLDLIGHTS"),
+    (MAP_WORLDITEMS_SAVED, "WORLDITEMS"),
+    (MAP_EXITGRIDS_SAVED, "EXITGRIDS"),
+    (MAP_DOORTABLE_SAVED, "DOORTABLE"),
+    (MAP_EDGEPOINTS_SAVED, "EDGEPOINTS"),
+    (MAP_AMBIENTLIGHTLEVEL_SAVED, "AMBIENTLIGHTLEVEL"),
+    (MAP_NPCSCHEDULES_SAVED, "NPCSCHEDULES"),
+];
+
+/// Object layer entries of this type and above are not loaded.
+pub const FIRSTPOINTERS: u8 = 131;
+
+// The `TileTypeDefines` enum in `TileDat.h`.
+static TILE_TYPES: [&str; 151] = [
+    "FIRSTTEXTURE", "SECONDTEXTURE", "THIRDTEXTURE", "FOURTHTEXTURE", "FIFTHTEXTURE", "SIXTHTEXTURE", "SEVENTHTEXTURE",
+    "REGWATERTEXTURE", "DEEPWATERTEXTURE", "FIRSTCLIFFHANG", "FIRSTCLIFF", "FIRSTCLIFFSHADOW", "FIRSTOSTRUCT",
+    "SECONDOSTRUCT", "THIRDOSTRUCT", "FOURTHOSTRUCT", "FIFTHOSTRUCT", "SIXTHOSTRUCT", "SEVENTHOSTRUCT", "EIGHTOSTRUCT",
+    "FIRSTFULLSTRUCT", "SECONDFULLSTRUCT", "THIRDFULLSTRUCT", "FOURTHFULLSTRUCT", "FIRSTSHADOW", "SECONDSHADOW",
+    "THIRDSHADOW", "FOURTHSHADOW", "FIFTHSHADOW", "SIXTHSHADOW", "SEVENTHSHADOW", "EIGHTSHADOW", "FIRSTFULLSHADOW",
+    "SECONDFULLSHADOW", "THIRDFULLSHADOW", "FOURTHFULLSHADOW", "FIRSTWALL", "SECONDWALL", "THIRDWALL", "FOURTHWALL",
+    "FIRSTDOOR", "SECONDDOOR", "THIRDDOOR", "FOURTHDOOR", "FIRSTDOORSHADOW", "SECONDDOORSHADOW", "THIRDDOORSHADOW",
+    "FOURTHDOORSHADOW", "SLANTROOFCEILING", "ANOTHERDEBRIS", "ROADPIECES", "FOURTHWINDOW", "FIRSTDECORATIONS",
+    "SECONDDECORATIONS", "THIRDDECORATIONS", "FOURTHDECORATIONS", "FIRSTWALLDECAL", "SECONDWALLDECAL", "THIRDWALLDECAL",
+    "FOURTHWALLDECAL", "FIRSTFLOOR", "SECONDFLOOR", "THIRDFLOOR", "FOURTHFLOOR", "FIRSTROOF", "SECONDROOF", "THIRDROOF",
+    "FOURTHROOF", "FIRSTSLANTROOF", "SECONDSLANTROOF", "FIRSTONROOF", "SECONDONROOF", "MOCKFLOOR", "FIRSTISTRUCT",
+    "SECONDISTRUCT", "THRIDISTRUCT", "FOURTHISTRUCT", "FIRSTCISTRUCT", "FIRSTROAD", "DEBRISROCKS", "DEBRISWOOD",
+    "DEBRISWEEDS", "DEBRISGRASS", "DEBRISSAND", "DEBRISMISC", "ANIOSTRUCT", "FENCESTRUCT", "FENCESHADOW",
+    "FIRSTVEHICLE", "SECONDVEHICLE", "FIRSTVEHICLESHADOW", "SECONDVEHICLESHADOW", "DEBRIS2MISC", "FIRSTDEBRISSTRUCT",
+    "SECONDDEBRISSTRUCT", "FIRSTDEBRISSTRUCTSHADOW", "SECONDDEBRISSTRUCTSHADOW", "NINTHOSTRUCT", "TENTHOSTRUCT",
+    "NINTHOSTRUCTSHADOW", "TENTHOSTRUCTSHADOW", "FIRSTEXPLDEBRIS", "SECONDEXPLDEBRIS", "FIRSTLARGEEXPDEBRIS",
+    "SECONDLARGEEXPDEBRIS", "FIRSTLARGEEXPDEBRISSHADOW", "SECONDLARGEEXPDEBRISSHADOW", "FIFTHISTRUCT", "SIXTHISTRUCT",
+    "SEVENTHISTRUCT", "EIGHTISTRUCT", "FIRSTHIGHROOF", "SECONDHIGHROOF", "FIFTHWALLDECAL", "SIXTHWALLDECAL",
+    "SEVENTHWALLDECAL", "EIGTHWALLDECAL", "HUMANBLOOD", "CREATUREBLOOD", "FIRSTSWITCHES", "REVEALEDSLANTROOFS",
+    "FIRSTREVEALEDHIGHROOFS", "SECONDREVEALEDHIGHROOFS", "GUNS", "P1ITEMS", "P2ITEMS", "WINDOWSHATTER", "P3ITEMS",
+    "BODYEXPLOSION", "EXITTEXTURE", "FOOTPRINTS", "FIRSTPOINTERS", "SECONDPOINTERS", "THIRDPOINTERS", "GOODRUN",
+    "GOODWALK", "GOODSWAT", "GOODPRONE", "CONFIRMMOVE", "VEHICLEMOVE", "ACTIONTWO", "XMARKER", "GOODRING",
+    "ROTATINGKEY", "SELRING", "SPECIALTILES", "BULLETTILE", "FIRSTMISS", "SECONDMISS", "THIRDMISS", "WIREFRAMES",
+];
+
+// The `TileSetID` enum in `World_Tileset_Enums.h`.
+static TILESETS: [&str; 50] = [
+    "GENERIC_1", "CAVES_1", "DESERT_1", "LUSH_1", "TROPICAL_1", "MOUNTAIN_1", "COASTAL_1", "SWAMP_1", "FARM_1",
+    "OMERTA_1", "GENERIC_2", "FARM_2", "PRISON", "HOSPITAL", "TEMP_", "BURNT_TREES", "LAWLESS_1", "AIRSTRIP",
+    "LAWLESS_2", "DEAD_AIRSTRIP", "TEMP_1", "TEMP_2", "TEMP_3", "TEMP_4", "TEMP_5", "TEMP_6", "TEMP_7", "TEMP_8",
+    "TEMP_9", "TEMP_10", "TEMP_11", "TEMP_12", "TEMP_13", "TEMP_14", "TEMP_15", "TEMP_16", "TEMP_17", "TEMP_18",
+    "TEMP_19", "TEMP_20", "TEMP_21", "TEMP_22", "TEMP_23", "TEMP_24", "TEMP_25", "TEMP_26", "TEMP_27", "TEMP_28",
+    "TEMP_29", "TEMP_30",
+];
+
+static TEAMS: [&str; 5] = ["OUR_TEAM", "ENEMY_TEAM", "CREATURE_TEAM", "MILITIA_TEAM", "CIV_TEAM"];
+
+const NUM_INV_SLOTS: usize = 19;
+const MAX_ATTACHMENTS: usize = 4;
+const MAXPATROLGRIDS: usize = 10;
+const MAX_SCHEDULE_ACTIONS: usize = 4;
+const PALETTE_LENGTH: usize = 30;
+const NAME_LENGTH: usize = 10;
+const MAP_INFORMATION_SIZE: usize = 100;
+/// The Russian version has unknown data in front of the room information.
+const RUSSIAN_EXTRA_SIZE: usize = 148;
+
+/// Returns the name of a tile type, e.g. `FIRSTTEXTURE`.
+pub fn tile_type_name(tile_type: u8) -> Option<&'static str> {
+    TILE_TYPES.get(tile_type as usize).cloned()
+}
+
+/// Returns the name of a tileset, e.g. `GENERIC_1`.
+pub fn tileset_name(tileset: i32) -> Option<&'static str> {
+    if tileset < 0 {
+        return None;
+    }
+    TILESETS.get(tileset as usize).cloned()
+}
+
+/// Returns the names of the `MAP_*_SAVED` flags that are set, unknown bits are left out.
+pub fn map_flag_names(flags: u32) -> Vec<&'static str> {
+    MAP_FLAGS.iter().filter(|(bit, _)| flags & bit != 0).map(|(_, name)| *name).collect()
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct MapHeader {
+    pub major_version: f32,
+    /// Only stored since major version 4.
+    pub minor_version: u8,
+    pub flags: u32,
+    pub tileset: i32,
+    pub soldier_size: i32,
+}
+
+/// A tile of a layer, see `GetTileIndexFromTypeSubIndex`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+pub struct TileRef {
+    pub tile_type: u8,
+    pub sub_index: u16,
+}
+
+/// The layers of one grid, from bottom to top like they are stored.
+#[derive(Debug, PartialEq, Clone, Default, Serialize)]
+pub struct MapTile {
+    pub height: u8,
+    /// The world flags stored in the high nibble of the land count.
+    pub flags: u8,
+    pub room: u8,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub land: Vec<TileRef>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub objects: Vec<TileRef>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub structs: Vec<TileRef>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub shadows: Vec<TileRef>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub roofs: Vec<TileRef>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub on_roof: Vec<TileRef>,
+}
+
+/// An `OBJECTTYPE`. The meaning of `data` depends on the item class, e.g. the status of every object in the stack.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct ItemObject {
+    pub item: u16,
+    pub number: u8,
+    pub data: Vec<u8>,
+    pub attachments: Vec<u16>,
+    pub attachment_status: Vec<i8>,
+    pub flags: i8,
+    pub mission: u8,
+    pub trap: i8,
+    pub imprint_id: u8,
+    pub weight: u8,
+    pub used: u8,
+}
+
+/// A `WORLDITEM`.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct WorldItem {
+    pub exists: bool,
+    pub grid_no: i16,
+    pub level: u8,
+    pub object: ItemObject,
+    pub flags: u16,
+    pub render_z_height_above_level: i8,
+    pub visible: i8,
+    /// The chance in percent that the item is not placed.
+    pub non_exist_chance: u8,
+}
+
+/// Only stored for underground maps.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct AmbientLight {
+    pub basement: bool,
+    pub caves: bool,
+    pub level: u8,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct LightColor {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct LightSprite {
+    pub x: i16,
+    pub y: i16,
+    pub flags: u32,
+    pub template: String,
+}
+
+/// A `MAPCREATE_STRUCT`, a grid number of -1 means the entry point is not used.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct MapInformation {
+    pub north_grid_no: i16,
+    pub east_grid_no: i16,
+    pub south_grid_no: i16,
+    pub west_grid_no: i16,
+    pub num_individuals: u8,
+    pub map_version: u8,
+    pub restricted_scroll_id: u8,
+    pub editor_smoothing_type: u8,
+    pub center_grid_no: i16,
+    pub isolated_grid_no: i16,
+}
+
+/// A `BASIC_SOLDIERCREATE_STRUCT`.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct BasicPlacement {
+    pub detailed_placement: bool,
+    pub starting_grid_no: u16,
+    pub team: i8,
+    pub relative_attribute_level: i8,
+    pub relative_equipment_level: i8,
+    pub direction: i8,
+    pub orders: i8,
+    pub attitude: i8,
+    pub body_type: i8,
+    pub patrol_grid: Vec<i16>,
+    pub patrol_count: i8,
+    pub on_roof: bool,
+    pub soldier_class: u8,
+    pub civilian_group: u8,
+    pub priority_existence: bool,
+    pub has_keys: bool,
+}
+
+/// A `SOLDIERCREATE_STRUCT` in the vanilla format with UTF-16 names.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct DetailedPlacement {
+    pub is_static: bool,
+    pub profile: u8,
+    pub copy_profile_items_over: bool,
+    pub sector_x: i16,
+    pub sector_y: i16,
+    pub direction: i8,
+    pub insertion_grid_no: i16,
+    pub team: i8,
+    pub body_type: i8,
+    pub attitude: i8,
+    pub orders: i8,
+    pub life_max: i8,
+    pub life: i8,
+    pub agility: i8,
+    pub dexterity: i8,
+    pub exp_level: i8,
+    pub marksmanship: i8,
+    pub medical: i8,
+    pub mechanical: i8,
+    pub explosive: i8,
+    pub leadership: i8,
+    pub strength: i8,
+    pub wisdom: i8,
+    pub morale: i8,
+    pub ai_morale: i8,
+    pub inventory: Vec<ItemObject>,
+    pub head_palette: String,
+    pub pants_palette: String,
+    pub vest_palette: String,
+    pub skin_palette: String,
+    pub patrol_grid: Vec<i16>,
+    pub patrol_count: i8,
+    pub visible: bool,
+    pub name: String,
+    pub soldier_class: u8,
+    pub on_roof: bool,
+    pub sector_z: i8,
+    pub civilian_group: u8,
+    pub schedule_id: u8,
+    pub use_given_vehicle: bool,
+    pub given_vehicle_id: i8,
+    pub has_keys: bool,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct SoldierPlacement {
+    pub basic: BasicPlacement,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detailed: Option<DetailedPlacement>,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct ExitGrid {
+    /// Where the exit grid is on this map.
+    pub map_index: u16,
+    /// Where the mercs are placed in the destination sector.
+    pub grid_no: u16,
+    pub sector_x: u8,
+    pub sector_y: u8,
+    pub sector_z: u8,
+}
+
+/// A `DOOR`, the perceived values are not loaded by the game.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Door {
+    pub grid_no: i16,
+    pub locked: bool,
+    pub trap_level: u8,
+    pub trap_id: u8,
+    pub lock_id: u8,
+    pub lock_damage: i8,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Edgepoints {
+    pub middle_index: u16,
+    pub grid_nos: Vec<i16>,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Schedule {
+    pub time: Vec<u16>,
+    pub data1: Vec<u16>,
+    pub data2: Vec<u16>,
+    pub action: Vec<u8>,
+    pub flags: u16,
+}
+
+/// A whole map, the optional parts are empty if they are not saved.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct MapFile {
+    pub header: MapHeader,
+    /// `WORLD_MAX` tiles row by row.
+    pub tiles: Vec<MapTile>,
+    pub items: Vec<WorldItem>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ambient_light: Option<AmbientLight>,
+    pub light_colors: Vec<LightColor>,
+    pub lights: Vec<LightSprite>,
+    pub information: MapInformation,
+    pub placements: Vec<SoldierPlacement>,
+    pub exit_grids: Vec<ExitGrid>,
+    pub doors: Vec<Door>,
+    /// North, east, south and west edgepoints, followed by the second layer since map version 17.
+    pub edgepoints: Vec<Edgepoints>,
+    pub schedules: Vec<Schedule>,
+}
+
+struct MapReader<'a> {
+    cursor: Cursor<&'a [u8]>,
+}
+
+macro_rules! read_value {
+    ($name:ident, $t:ty, $read:ident) => {
+        fn $name(&mut self) -> Result<$t, String> {
+            let position = self.cursor.position();
+            self.cursor.$read::<LittleEndian>().map_err(|_| format!("Unexpected end of map data at offset {}", position))
+        }
+    };
+}
+
+impl<'a> MapReader<'a> {
+    fn bytes(&mut self, len: usize) -> Result<Vec<u8>, String> {
+        let position = self.cursor.position();
+        let mut data = vec!(0u8; len);
+        self.cursor.read_exact(&mut data).map_err(|_| format!("Unexpected end of map data at offset {}", position))?;
+        Ok(data)
+    }
+
+    fn skip(&mut self, len: usize) -> Result<(), String> {
+        self.bytes(len).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn i8(&mut self) -> Result<i8, String> {
+        Ok(self.u8()? as i8)
+    }
+
+    fn bool(&mut self) -> Result<bool, String> {
+        Ok(self.u8()? != 0)
+    }
+
+    read_value!(u16, u16, read_u16);
+    read_value!(i16, i16, read_i16);
+    read_value!(u32, u32, read_u32);
+    read_value!(i32, i32, read_i32);
+    read_value!(f32, f32, read_f32);
+
+    fn u16s(&mut self, len: usize) -> Result<Vec<u16>, String> {
+        (0..len).map(|_| self.u16()).collect()
+    }
+
+    fn i16s(&mut self, len: usize) -> Result<Vec<i16>, String> {
+        (0..len).map(|_| self.i16()).collect()
+    }
+
+    fn i8s(&mut self, len: usize) -> Result<Vec<i8>, String> {
+        (0..len).map(|_| self.i8()).collect()
+    }
+
+    fn c_string(&mut self, len: usize) -> Result<String, String> {
+        let data = self.bytes(len)?;
+        Ok(String::from_utf8_lossy(data.split(|b| *b == 0).next().unwrap_or(&[])).into_owned())
+    }
+
+    fn utf16(&mut self, len: usize) -> Result<String, String> {
+        let codes: Vec<u16> = self.u16s(len)?.into_iter().take_while(|c| *c != 0).collect();
+        Ok(String::from_utf16_lossy(&codes))
+    }
+
+    fn tile_refs(&mut self, count: u8, wide_sub_index: bool) -> Result<Vec<TileRef>, String> {
+        (0..count).map(|_| {
+            let tile_type = self.u8()?;
+            let sub_index = if wide_sub_index { self.u16()? } else { u16::from(self.u8()?) };
+            Ok(TileRef { tile_type, sub_index })
+        }).collect()
+    }
+
+    fn object(&mut self) -> Result<ItemObject, String> {
+        let item = self.u16()?;
+        let number = self.u8()?;
+        self.skip(1)?;
+        let object = ItemObject {
+            item,
+            number,
+            data: self.bytes(12)?,
+            attachments: self.u16s(MAX_ATTACHMENTS)?,
+            attachment_status: self.i8s(MAX_ATTACHMENTS)?,
+            flags: self.i8()?,
+            mission: self.u8()?,
+            trap: self.i8()?,
+            imprint_id: self.u8()?,
+            weight: self.u8()?,
+            used: self.u8()?,
+        };
+        self.skip(2)?;
+        Ok(object)
+    }
+
+    fn world_item(&mut self) -> Result<WorldItem, String> {
+        let exists = self.bool()?;
+        self.skip(1)?;
+        let grid_no = self.i16()?;
+        let level = self.u8()?;
+        self.skip(3)?;
+        let item = WorldItem {
+            exists,
+            grid_no,
+            level,
+            object: self.object()?,
+            flags: self.u16()?,
+            render_z_height_above_level: self.i8()?,
+            visible: self.i8()?,
+            non_exist_chance: self.u8()?,
+        };
+        self.skip(3)?;
+        Ok(item)
+    }
+
+    fn light(&mut self) -> Result<LightSprite, String> {
+        let x = self.i16()?;
+        let y = self.i16()?;
+        self.skip(12)?;
+        let flags = self.u32()?;
+        self.skip(4)?;
+        let len = self.u8()? as usize;
+        Ok(LightSprite { x, y, flags, template: self.c_string(len)? })
+    }
+
+    fn information(&mut self) -> Result<MapInformation, String> {
+        let information = MapInformation {
+            north_grid_no: self.i16()?,
+            east_grid_no: self.i16()?,
+            south_grid_no: self.i16()?,
+            west_grid_no: self.i16()?,
+            num_individuals: self.u8()?,
+            map_version: self.u8()?,
+            restricted_scroll_id: self.u8()?,
+            editor_smoothing_type: self.u8()?,
+            center_grid_no: self.i16()?,
+            isolated_grid_no: self.i16()?,
+        };
+        self.skip(MAP_INFORMATION_SIZE - 16)?;
+        Ok(information)
+    }
+
+    fn basic_placement(&mut self) -> Result<BasicPlacement, String> {
+        let detailed_placement = self.bool()?;
+        self.skip(1)?;
+        let starting_grid_no = self.u16()?;
+        let team = self.i8()?;
+        let relative_attribute_level = self.i8()?;
+        let relative_equipment_level = self.i8()?;
+        let direction = self.i8()?;
+        let orders = self.i8()?;
+        let attitude = self.i8()?;
+        let body_type = self.i8()?;
+        self.skip(1)?;
+        let placement = BasicPlacement {
+            detailed_placement,
+            starting_grid_no,
+            team,
+            relative_attribute_level,
+            relative_equipment_level,
+            direction,
+            orders,
+            attitude,
+            body_type,
+            patrol_grid: self.i16s(MAXPATROLGRIDS)?,
+            patrol_count: self.i8()?,
+            on_roof: self.bool()?,
+            soldier_class: self.u8()?,
+            civilian_group: self.u8()?,
+            priority_existence: self.bool()?,
+            has_keys: self.bool()?,
+        };
+        self.skip(14)?;
+        Ok(placement)
+    }
+
+    fn detailed_placement(&mut self) -> Result<DetailedPlacement, String> {
+        let is_static = self.bool()?;
+        let profile = self.u8()?;
+        self.skip(2)?;
+        let copy_profile_items_over = self.bool()?;
+        self.skip(1)?;
+        let sector_x = self.i16()?;
+        let sector_y = self.i16()?;
+        let direction = self.i8()?;
+        self.skip(1)?;
+        let insertion_grid_no = self.i16()?;
+        let team = self.i8()?;
+        let body_type = self.i8()?;
+        let attitude = self.i8()?;
+        let orders = self.i8()?;
+        let life_max = self.i8()?;
+        let life = self.i8()?;
+        let agility = self.i8()?;
+        let dexterity = self.i8()?;
+        let exp_level = self.i8()?;
+        let marksmanship = self.i8()?;
+        let medical = self.i8()?;
+        let mechanical = self.i8()?;
+        let explosive = self.i8()?;
+        let leadership = self.i8()?;
+        let strength = self.i8()?;
+        let wisdom = self.i8()?;
+        let morale = self.i8()?;
+        let ai_morale = self.i8()?;
+        let inventory = (0..NUM_INV_SLOTS).map(|_| self.object()).collect::<Result<Vec<_>, _>>()?;
+        let head_palette = self.c_string(PALETTE_LENGTH)?;
+        let pants_palette = self.c_string(PALETTE_LENGTH)?;
+        let vest_palette = self.c_string(PALETTE_LENGTH)?;
+        let skin_palette = self.c_string(PALETTE_LENGTH)?;
+        self.skip(30)?;
+        let patrol_grid = self.i16s(MAXPATROLGRIDS)?;
+        let patrol_count = self.i8()?;
+        let visible = self.bool()?;
+        let name = self.utf16(NAME_LENGTH)?;
+        let soldier_class = self.u8()?;
+        let on_roof = self.bool()?;
+        let sector_z = self.i8()?;
+        self.skip(6)?;
+        let civilian_group = self.u8()?;
+        self.skip(1)?;
+        let placement = DetailedPlacement {
+            is_static, profile, copy_profile_items_over, sector_x, sector_y, direction, insertion_grid_no, team,
+            body_type, attitude, orders, life_max, life, agility, dexterity, exp_level, marksmanship, medical,
+            mechanical, explosive, leadership, strength, wisdom, morale, ai_morale, inventory, head_palette,
+            pants_palette, vest_palette, skin_palette, patrol_grid, patrol_count, visible, name, soldier_class,
+            on_roof, sector_z, civilian_group,
+            schedule_id: self.u8()?,
+            use_given_vehicle: self.bool()?,
+            given_vehicle_id: self.i8()?,
+            has_keys: self.bool()?,
+        };
+        self.skip(117)?;
+        Ok(placement)
+    }
+
+    fn edgepoints(&mut self) -> Result<Edgepoints, String> {
+        let count = self.u16()? as usize;
+        let middle_index = self.u16()?;
+        Ok(Edgepoints { middle_index, grid_nos: self.i16s(count)? })
+    }
+
+    fn schedule(&mut self) -> Result<Schedule, String> {
+        self.skip(4)?;
+        let schedule = Schedule {
+            time: self.u16s(MAX_SCHEDULE_ACTIONS)?,
+            data1: self.u16s(MAX_SCHEDULE_ACTIONS)?,
+            data2: self.u16s(MAX_SCHEDULE_ACTIONS)?,
+            action: self.bytes(MAX_SCHEDULE_ACTIONS)?,
+            flags: {
+                self.skip(2)?;
+                self.u16()?
+            },
+        };
+        Ok(schedule)
+    }
+}
+
+/// Parses a map like `LoadWorld` does. The Russian version stores additional data that is skipped.
+pub fn parse_map(data: &[u8], version: ResourceVersion) -> Result<MapFile, String> {
+    let mut r = MapReader { cursor: Cursor::new(data) };
+
+    let major_version = r.f32()?;
+    let minor_version = if major_version >= 4.0 { r.u8()? } else { 0 };
+    let header = MapHeader { major_version, minor_version, flags: r.u32()?, tileset: r.i32()?, soldier_size: r.i32()? };
+
+    let mut tiles = vec!(MapTile::default(); WORLD_MAX);
+    for tile in tiles.iter_mut() {
+        tile.height = r.u8()?;
+        r.skip(1)?;
+    }
+
+    let mut counts = Vec::with_capacity(WORLD_MAX);
+    for tile in tiles.iter_mut() {
+        let combined = r.bytes(4)?;
+        tile.flags = combined[0] >> 4;
+        counts.push([combined[0] & 0x0F, combined[1] & 0x0F, combined[1] >> 4, combined[2] & 0x0F, combined[2] >> 4, combined[3] & 0x0F]);
+    }
+
+    for (tile, count) in tiles.iter_mut().zip(&counts) {
+        tile.land = r.tile_refs(count[0], false)?;
+    }
+    // Since minor version 15 the object sub indices need 16 bits for the road pieces
+    for (tile, count) in tiles.iter_mut().zip(&counts) {
+        tile.objects = r.tile_refs(count[1], minor_version >= 15)?;
+    }
+    for (tile, count) in tiles.iter_mut().zip(&counts) {
+        tile.structs = r.tile_refs(count[2], false)?;
+    }
+    for (tile, count) in tiles.iter_mut().zip(&counts) {
+        tile.shadows = r.tile_refs(count[3], false)?;
+    }
+    for (tile, count) in tiles.iter_mut().zip(&counts) {
+        tile.roofs = r.tile_refs(count[4], false)?;
+    }
+    for (tile, count) in tiles.iter_mut().zip(&counts) {
+        tile.on_roof = r.tile_refs(count[5], false)?;
+    }
+
+    if version == ResourceVersion::RUSSIAN {
+        r.skip(RUSSIAN_EXTRA_SIZE)?;
+    }
+    for tile in tiles.iter_mut() {
+        tile.room = r.u8()?;
+    }
+
+    let flags = header.flags;
+    let mut items = vec!();
+    if flags & MAP_WORLDITEMS_SAVED != 0 {
+        let count = r.u32()?;
+        for _ in 0..count {
+            items.push(r.world_item()?);
+        }
+    }
+
+    let ambient_light = if flags & MAP_AMBIENTLIGHTLEVEL_SAVED != 0 {
+        Some(AmbientLight { basement: r.bool()?, caves: r.bool()?, level: r.u8()? })
+    } else {
+        None
+    };
+
+    let mut light_colors = vec!();
+    let mut lights = vec!();
+    if flags & MAP_WORLDLIGHTS_SAVED != 0 {
+        let count = r.u8()?;
+        for _ in 0..count {
+            let color = LightColor { red: r.u8()?, green: r.u8()?, blue: r.u8()? };
+            r.skip(1)?;
+            light_colors.push(color);
+        }
+        let count = r.u16()?;
+        for _ in 0..count {
+            lights.push(r.light()?);
+        }
+    }
+
+    let information = r.information()?;
+
+    let mut placements = vec!();
+    if flags & MAP_FULLSOLDIER_SAVED != 0 {
+        for _ in 0..information.num_individuals {
+            let basic = r.basic_placement()?;
+            let detailed = if basic.detailed_placement { Some(r.detailed_placement()?) } else { None };
+            placements.push(SoldierPlacement { basic, detailed });
+        }
+    }
+
+    let mut exit_grids = vec!();
+    if flags & MAP_EXITGRIDS_SAVED != 0 {
+        let count = r.u16()?;
+        for _ in 0..count {
+            exit_grids.push(ExitGrid { map_index: r.u16()?, grid_no: r.u16()?, sector_x: r.u8()?, sector_y: r.u8()?, sector_z: r.u8()? });
+        }
+    }
+
+    let mut doors = vec!();
+    if flags & MAP_DOORTABLE_SAVED != 0 {
+        let count = r.u8()?;
+        for _ in 0..count {
+            let grid_no = r.i16()?;
+            let locked = r.bool()?;
+            let trap_level = r.u8()?;
+            let trap_id = r.u8()?;
+            let lock_id = r.u8()?;
+            r.skip(2)?;
+            let lock_damage = r.i8()?;
+            r.skip(5)?;
+            doors.push(Door { grid_no, locked, trap_level, trap_id, lock_id, lock_damage });
+        }
+    }
+
+    let mut edgepoints = vec!();
+    if flags & MAP_EDGEPOINTS_SAVED != 0 {
+        let layers = if information.map_version >= 17 { 2 } else { 1 };
+        for _ in 0..layers * 4 {
+            edgepoints.push(r.edgepoints()?);
+        }
+    }
+
+    let mut schedules = vec!();
+    if flags & MAP_NPCSCHEDULES_SAVED != 0 {
+        let count = r.u8()?;
+        for _ in 0..count {
+            schedules.push(r.schedule()?);
+        }
+    }
+
+    Ok(MapFile { header, tiles, items, ambient_light, light_colors, lights, information, placements, exit_grids, doors, edgepoints, schedules })
+}
+
+pub fn read_map_file(path: &Path, version: ResourceVersion) -> Result<MapFile, String> {
+    let mut data = vec!();
+    File::open(path)
+        .and_then(|mut f| f.read_to_end(&mut data))
+        .map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+
+    parse_map(&data, version).map_err(|e| format!("Error parsing {}: {}", path.display(), e))
+}
+
+/// Pretty printed JSON of the map, ending with a newline.
+pub fn map_to_json(map: &MapFile) -> Result<String, String> {
+    let mut json = serde_json::to_string_pretty(map).map_err(|e| format!("Error serializing map: {}", e))?;
+
+    json.push('\n');
+    Ok(json)
+}
+
+/// The overview of a map that `map info` prints.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct MapSummary {
+    pub version: String,
+    pub tileset: String,
+    pub flags: Vec<&'static str>,
+    /// Number of tiles per layer: land, objects, structs, shadows, roofs and on roof.
+    pub layer_tiles: [usize; 6],
+    /// Number of tiles per tile type name over all layers.
+    pub tile_types: BTreeMap<String, usize>,
+    pub rooms: usize,
+    pub items: usize,
+    pub lights: usize,
+    /// Number of placements per team name.
+    pub placements: BTreeMap<String, usize>,
+    pub detailed_placements: usize,
+    pub exit_grids: usize,
+    pub doors: usize,
+    pub schedules: usize,
+    /// Entry points for north, east, south, west, center and isolated, `None` if unused.
+    pub entry_points: Vec<(&'static str, Option<i16>)>,
+}
+
+impl MapSummary {
+    pub fn new(map: &MapFile) -> MapSummary {
+        let layers = |tile: &MapTile| [tile.land.len(), tile.objects.len(), tile.structs.len(), tile.shadows.len(), tile.roofs.len(), tile.on_roof.len()];
+        let mut layer_tiles = [0; 6];
+        let mut tile_types = BTreeMap::new();
+        let mut rooms: Vec<u8> = vec!();
+
+        for tile in &map.tiles {
+            for (sum, count) in layer_tiles.iter_mut().zip(layers(tile).iter()) {
+                *sum += count;
+            }
+            for tile_ref in tile.land.iter().chain(&tile.objects).chain(&tile.structs).chain(&tile.shadows).chain(&tile.roofs).chain(&tile.on_roof) {
+                let name = tile_type_name(tile_ref.tile_type).map(String::from).unwrap_or_else(|| tile_ref.tile_type.to_string());
+                *tile_types.entry(name).or_insert(0) += 1;
+            }
+            if tile.room != 0 && !rooms.contains(&tile.room) {
+                rooms.push(tile.room);
+            }
+        }
+
+        let mut placements = BTreeMap::new();
+        for placement in &map.placements {
+            let team = TEAMS.get(placement.basic.team as usize).map(|t| String::from(*t)).unwrap_or_else(|| placement.basic.team.to_string());
+            *placements.entry(team).or_insert(0) += 1;
+        }
+
+        let info = &map.information;
+        let entry_point = |grid_no: i16| if grid_no < 0 { None } else { Some(grid_no) };
+
+        MapSummary {
+            version: format!("{:.2}.{}", map.header.major_version, map.header.minor_version),
+            tileset: tileset_name(map.header.tileset).map(String::from).unwrap_or_else(|| map.header.tileset.to_string()),
+            flags: map_flag_names(map.header.flags),
+            layer_tiles,
+            tile_types,
+            rooms: rooms.len(),
+            items: map.items.len(),
+            lights: map.lights.len(),
+            placements,
+            detailed_placements: map.placements.iter().filter(|p| p.detailed.is_some()).count(),
+            exit_grids: map.exit_grids.len(),
+            doors: map.doors.len(),
+            schedules: map.schedules.len(),
+            entry_points: vec!(
+                ("north", entry_point(info.north_grid_no)),
+                ("east", entry_point(info.east_grid_no)),
+                ("south", entry_point(info.south_grid_no)),
+                ("west", entry_point(info.west_grid_no)),
+                ("center", entry_point(info.center_grid_no)),
+                ("isolated", entry_point(info.isolated_grid_no)),
+            ),
+        }
+    }
+}
+
+impl fmt::Display for MapSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let counts = |map: &BTreeMap<String, usize>| map.iter().map(|(k, v)| format!("{} {}", k, v)).collect::<Vec<_>>().join(", ");
+        let entry_points: Vec<String> = self.entry_points.iter()
+            .map(|(name, grid_no)| format!("{} {}", name, grid_no.map(|g| g.to_string()).unwrap_or_else(|| String::from("-"))))
+            .collect();
+
+        writeln!(f, "Version:      {}", self.version)?;
+        writeln!(f, "Tileset:      {}", self.tileset)?;
+        writeln!(f, "Saved parts:  {}", self.flags.join(", "))?;
+        writeln!(f, "Layers:       land {}, objects {}, structs {}, shadows {}, roofs {}, on roof {}",
+                 self.layer_tiles[0], self.layer_tiles[1], self.layer_tiles[2], self.layer_tiles[3], self.layer_tiles[4], self.layer_tiles[5])?;
+        writeln!(f, "Tile types:   {}", self.tile_types.len())?;
+        writeln!(f, "Rooms:        {}", self.rooms)?;
+        writeln!(f, "Items:        {}", self.items)?;
+        writeln!(f, "Lights:       {}", self.lights)?;
+        writeln!(f, "Placements:   {} ({} detailed)", counts(&self.placements), self.detailed_placements)?;
+        writeln!(f, "Exit grids:   {}", self.exit_grids)?;
+        writeln!(f, "Doors:        {}", self.doors)?;
+        writeln!(f, "Schedules:    {}", self.schedules)?;
+        writeln!(f, "Entry points: {}", entry_points.join(", "))
+    }
+}
+
+/// One difference between two maps. `before` is missing for additions, `after` for removals.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct MapChange {
+    /// The part of the map, e.g. `tiles` or `placements`.
+    pub section: &'static str,
+    /// What changed in the section, e.g. `grid 1234 structs`.
+    pub subject: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<Value>,
+}
+
+#[derive(Debug, PartialEq, Clone, Default, Serialize)]
+pub struct MapDiff {
+    pub changes: Vec<MapChange>,
+}
+
+impl MapDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    fn push(&mut self, section: &'static str, subject: String, before: Option<Value>, after: Option<Value>) {
+        self.changes.push(MapChange { section, subject, before, after });
+    }
+
+    /// Compares the fields of two values that serialize to JSON objects.
+    fn diff_fields<T: Serialize>(&mut self, section: &'static str, before: &T, after: &T) {
+        let (before, after) = (to_value(before), to_value(after));
+        let empty = serde_json::Map::new();
+        let before_fields = before.as_object().unwrap_or(&empty);
+        let after_fields = after.as_object().unwrap_or(&empty);
+
+        for (field, value) in before_fields {
+            let other = after_fields.get(field).cloned().unwrap_or(Value::Null);
+            if *value != other {
+                self.push(section, field.clone(), Some(value.clone()), Some(other));
+            }
+        }
+    }
+
+    /// Compares entries by their position in the list.
+    fn diff_indexed<T: Serialize + PartialEq>(&mut self, section: &'static str, before: &[T], after: &[T], subject: &dyn Fn(usize) -> String) {
+        for i in 0..before.len().max(after.len()) {
+            let (x, y) = (before.get(i), after.get(i));
+            if x != y {
+                self.push(section, subject(i), x.map(to_value), y.map(to_value));
+            }
+        }
+    }
+
+    /// Compares entries that are identified by a key, e.g. exit grids by their position.
+    fn diff_keyed<T: Serialize, K: Ord + fmt::Display>(&mut self, section: &'static str, before: &[T], after: &[T], key: &dyn Fn(&T) -> K) {
+        let before: BTreeMap<K, Value> = before.iter().map(|e| (key(e), to_value(e))).collect();
+        let mut after: BTreeMap<K, Value> = after.iter().map(|e| (key(e), to_value(e))).collect();
+
+        for (k, value) in before {
+            match after.remove(&k) {
+                Some(ref other) if *other == value => {},
+                other => self.push(section, k.to_string(), Some(value), other),
+            }
+        }
+        for (k, value) in after {
+            self.push(section, k.to_string(), None, Some(value));
+        }
+    }
+
+    /// Compares entries without identity, e.g. placements. Equal entries match regardless of their order.
+    fn diff_unordered<T: Serialize>(&mut self, section: &'static str, before: &[T], after: &[T], subject: &dyn Fn(&T) -> String) {
+        let mut unmatched: Vec<Option<Value>> = after.iter().map(|e| Some(to_value(e))).collect();
+
+        for entry in before {
+            let value = to_value(entry);
+            match unmatched.iter().position(|v| v.as_ref() == Some(&value)) {
+                Some(i) => unmatched[i] = None,
+                None => self.push(section, subject(entry), Some(value), None),
+            }
+        }
+        for (entry, value) in after.iter().zip(unmatched) {
+            if value.is_some() {
+                self.push(section, subject(entry), None, value);
+            }
+        }
+    }
+}
+
+impl fmt::Display for MapDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for change in &self.changes {
+            match (&change.before, &change.after) {
+                (Some(before), Some(after)) => writeln!(f, "~ {} {}: {} -> {}", change.section, change.subject, before, after)?,
+                (None, Some(after)) => writeln!(f, "+ {} {}: {}", change.section, change.subject, after)?,
+                (Some(before), None) => writeln!(f, "- {} {}: {}", change.section, change.subject, before)?,
+                (None, None) => {},
+            }
+        }
+        Ok(())
+    }
+}
+
+fn to_value<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+fn tile_refs_value(tiles: &[TileRef]) -> Value {
+    tiles.iter().map(|t| match tile_type_name(t.tile_type) {
+        Some(name) => format!("{}:{}", name, t.sub_index),
+        None => format!("{}:{}", t.tile_type, t.sub_index),
+    }).collect()
+}
+
+const EDGEPOINT_NAMES: [&str; 8] = ["north", "east", "south", "west", "2nd north", "2nd east", "2nd south", "2nd west"];
+
+/// Lists what changed from `before` to `after`. Tiles are compared per grid and layer.
+pub fn diff_maps(before: &MapFile, after: &MapFile) -> MapDiff {
+    let mut diff = MapDiff::default();
+
+    diff.diff_fields("header", &before.header, &after.header);
+    diff.diff_fields("information", &before.information, &after.information);
+    if before.ambient_light != after.ambient_light {
+        diff.push("ambient_light", String::from("level"), Some(to_value(&before.ambient_light)), Some(to_value(&after.ambient_light)));
+    }
+
+    for (grid_no, (a, b)) in before.tiles.iter().zip(&after.tiles).enumerate() {
+        for (property, x, y) in [("height", a.height, b.height), ("flags", a.flags, b.flags), ("room", a.room, b.room)].iter() {
+            if x != y {
+                diff.push("tiles", format!("grid {} {}", grid_no, property), Some(Value::from(*x)), Some(Value::from(*y)));
+            }
+        }
+        let layers = [
+            ("land", &a.land, &b.land),
+            ("objects", &a.objects, &b.objects),
+            ("structs", &a.structs, &b.structs),
+            ("shadows", &a.shadows, &b.shadows),
+            ("roofs", &a.roofs, &b.roofs),
+            ("on_roof", &a.on_roof, &b.on_roof),
+        ];
+        for (layer, x, y) in layers.iter() {
+            if x != y {
+                diff.push("tiles", format!("grid {} {}", grid_no, layer), Some(tile_refs_value(x)), Some(tile_refs_value(y)));
+            }
+        }
+    }
+
+    diff.diff_unordered("placements", &before.placements, &after.placements, &|p| format!("grid {}", p.basic.starting_grid_no));
+    diff.diff_unordered("items", &before.items, &after.items, &|i| format!("item {} at grid {}", i.object.item, i.grid_no));
+    diff.diff_indexed("light_colors", &before.light_colors, &after.light_colors, &|i| format!("color {}", i));
+    diff.diff_unordered("lights", &before.lights, &after.lights, &|l| format!("{} at {},{}", l.template, l.x, l.y));
+    diff.diff_keyed("exit_grids", &before.exit_grids, &after.exit_grids, &|e| e.map_index);
+    diff.diff_keyed("doors", &before.doors, &after.doors, &|d| d.grid_no);
+    diff.diff_unordered("schedules", &before.schedules, &after.schedules, &|s| format!("with gridnos {:?}", s.data1));
+
+    diff.diff_indexed("edgepoints", &before.edgepoints, &after.edgepoints, &|i| {
+        EDGEPOINT_NAMES.get(i).map(|n| String::from(*n)).unwrap_or_else(|| i.to_string())
+    });
+
+    diff
+}
+
+/// Pretty printed JSON of a diff, ending with a newline.
+pub fn map_diff_to_json(diff: &MapDiff) -> Result<String, String> {
+    let mut json = serde_json::to_string_pretty(diff).map_err(|e| format!("Error serializing map diff: {}", e))?;
+
+    json.push('\n');
+    Ok(json)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use ResourceVersion;
+
+    fn a9() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../assets/mods/from-russia-with-love/data/maps/A9.dat")
+    }
+
+    fn a10_b1() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../assets/mods/generous-rebels/data/Maps/A10_b1.dat")
+    }
+
+    #[test]
+    fn read_map_file_should_read_a_surface_map() {
+        let map = super::read_map_file(&a9(), ResourceVersion::ENGLISH).unwrap();
+
+        assert_eq!(map.header.major_version, 5.0);
+        assert_eq!(map.header.minor_version, 25);
+        assert_eq!(map.header.tileset, 9);
+        assert_eq!(map.tiles.len(), super::WORLD_MAX);
+        assert_eq!(map.items.len(), 9);
+        assert_eq!(map.ambient_light, None);
+        assert_eq!(map.light_colors.len(), 1);
+        assert_eq!(map.information.north_grid_no, 4870);
+        assert_eq!(map.information.isolated_grid_no, -1);
+        assert_eq!(map.placements.len(), 36);
+        assert_eq!(map.doors.len(), 1);
+        assert_eq!(map.edgepoints.len(), 8);
+        assert_eq!(map.schedules.len(), 4);
+        assert!(map.tiles.iter().all(|t| !t.land.is_empty()));
+    }
+
+    #[test]
+    fn read_map_file_should_read_a_basement() {
+        let map = super::read_map_file(&a10_b1(), ResourceVersion::ENGLISH).unwrap();
+
+        assert_eq!(map.ambient_light, Some(super::AmbientLight { basement: true, caves: false, level: 8 }));
+        assert_eq!(map.lights.len(), 12);
+        assert!(map.lights.iter().all(|l| !l.template.is_empty() && !l.template.contains('\0')));
+        assert_eq!(map.exit_grids.len(), 2);
+        assert_eq!(map.exit_grids[0].sector_x, 10);
+        assert_eq!(map.exit_grids[0].sector_y, 1);
+        assert_eq!(map.placements.len(), 20);
+        assert_eq!(map.information.map_version, 25);
+    }
+
+    #[test]
+    fn parse_map_should_fail_on_truncated_data() {
+        let data = ::std::fs::read(a9()).unwrap();
+
+        assert_eq!(super::parse_map(&data[..1000], ResourceVersion::ENGLISH).unwrap_err(), "Unexpected end of map data at offset 1000");
+        assert!(super::parse_map(&data, ResourceVersion::RUSSIAN).is_err());
+    }
+
+    #[test]
+    fn map_summary_should_count_the_contents() {
+        let map = super::read_map_file(&a9(), ResourceVersion::ENGLISH).unwrap();
+        let summary = super::MapSummary::new(&map);
+
+        assert_eq!(summary.version, "5.00.25");
+        assert_eq!(summary.tileset, "OMERTA_1");
+        assert_eq!(summary.flags, vec!("FULLSOLDIER", "WORLDLIGHTS", "WORLDITEMS", "EXITGRIDS", "DOORTABLE", "EDGEPOINTS", "NPCSCHEDULES"));
+        assert_eq!(summary.layer_tiles[0], map.tiles.iter().map(|t| t.land.len()).sum::<usize>());
+        assert_eq!(summary.placements.values().sum::<usize>(), 36);
+        assert!(summary.to_string().contains("Entry points: north 4870, east 8123, south 18677, west 19895, center 15281, isolated -\n"));
+    }
+
+    #[test]
+    fn map_to_json_should_leave_out_empty_layers() {
+        let map = super::read_map_file(&a10_b1(), ResourceVersion::ENGLISH).unwrap();
+        let json = super::map_to_json(&map).unwrap();
+        let value: ::serde_json::Value = ::serde_json::from_str(&json).unwrap();
+
+        assert_eq!(value["tiles"].as_array().unwrap().len(), super::WORLD_MAX);
+        assert!(value["tiles"][0].get("on_roof").is_none());
+        assert_eq!(value["ambient_light"]["level"], 8);
+    }
+
+    #[test]
+    fn diff_maps_should_find_nothing_for_the_same_map() {
+        let map = super::read_map_file(&a9(), ResourceVersion::ENGLISH).unwrap();
+        let diff = super::diff_maps(&map, &map.clone());
+
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "");
+    }
+
+    #[test]
+    fn diff_maps_should_list_semantic_changes() {
+        let before = super::read_map_file(&a10_b1(), ResourceVersion::ENGLISH).unwrap();
+        let mut after = before.clone();
+        after.tiles[1234].structs = vec!(super::TileRef { tile_type: 0, sub_index: 7 });
+        after.tiles[1234].room = 3;
+        let removed = after.placements.remove(5);
+        after.placements.reverse();
+        after.items.push(after.items[0].clone());
+        after.exit_grids[1].sector_z = 2;
+        after.lights[0].x += 1;
+        after.header.tileset = 1;
+
+        let diff = super::diff_maps(&before, &after);
+        let subjects: Vec<String> = diff.changes.iter().map(|c| format!("{} {}", c.section, c.subject)).collect();
+        assert_eq!(subjects, vec!(
+            "header tileset",
+            "tiles grid 1234 room",
+            "tiles grid 1234 structs",
+            format!("placements grid {}", removed.basic.starting_grid_no).as_str(),
+            format!("items item {} at grid {}", before.items[0].object.item, before.items[0].grid_no).as_str(),
+            format!("lights {} at {},{}", before.lights[0].template, before.lights[0].x, before.lights[0].y).as_str(),
+            format!("lights {} at {},{}", after.lights[0].template, after.lights[0].x, after.lights[0].y).as_str(),
+            format!("exit_grids {}", before.exit_grids[1].map_index).as_str(),
+        ));
+
+        let text = diff.to_string();
+        assert!(text.starts_with("~ header tileset: 20 -> 1\n~ tiles grid 1234 room: "), "{}", text);
+        assert!(text.contains("\n- placements grid "), "{}", text);
+        assert!(text.contains("\n+ items item "), "{}", text);
+        assert!(text.contains("-> [\"FIRSTTEXTURE:7\"]\n"), "{}", text);
+    }
+
+    #[test]
+    fn map_diff_to_json_should_leave_out_missing_sides() {
+        let before = super::read_map_file(&a10_b1(), ResourceVersion::ENGLISH).unwrap();
+        let mut after = before.clone();
+        after.doors.push(super::Door { grid_no: 100, locked: true, trap_level: 0, trap_id: 0, lock_id: 4, lock_damage: 0 });
+
+        let json = super::map_diff_to_json(&super::diff_maps(&before, &after)).unwrap();
+        let value: ::serde_json::Value = ::serde_json::from_str(&json).unwrap();
+        assert_eq!(value["changes"][0]["section"], "doors");
+        assert_eq!(value["changes"][0]["subject"], "100");
+        assert!(value["changes"][0].get("before").is_none());
+        assert_eq!(value["changes"][0]["after"]["lock_id"], 4);
+    }
+}
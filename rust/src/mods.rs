@@ -0,0 +1,99 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Lists the names of all mods installed in `mods_dir`, sorted alphabetically.
+///
+/// Every subdirectory of the mods folder is a mod, its name is the name that is passed to `--mod`.
+pub fn list_available_mods(mods_dir: &Path) -> Result<Vec<String>, String> {
+    let entries = fs::read_dir(mods_dir).map_err(|e| format!("Error reading mods directory {}: {}", mods_dir.display(), e))?;
+    let mut mods: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_dir())
+        .filter_map(|e| e.file_name().into_string().ok())
+        .collect();
+
+    mods.sort();
+    Ok(mods)
+}
+
+fn collect_files(dir: &Path, prefix: &str, files: &mut Vec<String>) -> Result<(), String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("Error reading {}: {}", dir.display(), e))?;
+
+    for entry in entries.filter_map(|e| e.ok()) {
+        let name = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+        if entry.path().is_dir() {
+            collect_files(&entry.path(), &format!("{}/", name), files)?;
+        } else {
+            files.push(name);
+        }
+    }
+    Ok(())
+}
+
+/// Lists all files of a mod relative to its folder, using `/` as separator.
+pub fn list_mod_files(mod_dir: &Path) -> Result<Vec<String>, String> {
+    let mut files = vec!();
+
+    collect_files(mod_dir, "", &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+/// Finds the text file describing a mod. Mods name it differently, so `readme.txt` is preferred over any other text file that is not a license.
+pub fn find_mod_readme(mod_dir: &Path) -> Option<PathBuf> {
+    let mut candidates: Vec<PathBuf> = fs::read_dir(mod_dir).ok()?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_file())
+        .filter(|p| p.extension().map(|e| e.to_string_lossy().to_lowercase() == "txt").unwrap_or(false))
+        .filter(|p| !p.file_name().unwrap().to_string_lossy().to_lowercase().starts_with("license"))
+        .collect();
+
+    candidates.sort_by_key(|p| p.file_name().unwrap().to_string_lossy().to_lowercase() != "readme.txt");
+    candidates.into_iter().next()
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate tempdir;
+
+    use std::fs;
+    use std::fs::File;
+    use std::path::Path;
+
+    #[test]
+    fn list_available_mods_should_return_sorted_directories_only() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        fs::create_dir(temp_dir.path().join("zzz")).unwrap();
+        fs::create_dir(temp_dir.path().join("from-russia-with-love")).unwrap();
+        File::create(temp_dir.path().join("readme.txt")).unwrap();
+
+        assert_eq!(super::list_available_mods(temp_dir.path()).unwrap(), vec!("from-russia-with-love", "zzz"));
+    }
+
+    #[test]
+    fn list_mod_files_should_list_nested_files() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        fs::create_dir_all(temp_dir.path().join("data/maps")).unwrap();
+        File::create(temp_dir.path().join("data/maps/A9.dat")).unwrap();
+        File::create(temp_dir.path().join("readme.txt")).unwrap();
+
+        assert_eq!(super::list_mod_files(temp_dir.path()).unwrap(), vec!("data/maps/A9.dat", "readme.txt"));
+    }
+
+    #[test]
+    fn find_mod_readme_should_prefer_readme_and_skip_licenses() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        File::create(temp_dir.path().join("license.txt")).unwrap();
+        File::create(temp_dir.path().join("Generous Rebels Mod.txt")).unwrap();
+        assert_eq!(super::find_mod_readme(temp_dir.path()), Some(temp_dir.path().join("Generous Rebels Mod.txt")));
+
+        File::create(temp_dir.path().join("readme.txt")).unwrap();
+        assert_eq!(super::find_mod_readme(temp_dir.path()), Some(temp_dir.path().join("readme.txt")));
+    }
+
+    #[test]
+    fn list_available_mods_should_fail_with_missing_directory() {
+        assert!(super::list_available_mods(Path::new("/non/existing/mods")).is_err());
+    }
+}
@@ -0,0 +1,156 @@
+//! The music of the game modes in `externalized/music.json`, read by `DefaultContentManager::loadMusic`.
+//!
+//! The game picks a random file of the list of a mode and plays it with the decoders of `audio`, which detect WAV, Ogg
+//! Vorbis and FLAC by the signature of the file. A file that is missing or can not be decoded just plays silence, so
+//! `check_music` resolves every file like the game does and checks its header.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde_json;
+
+use audio;
+use data::GameResources;
+use policy;
+
+/// The keys of `music.json`, in the order of `MusicMode`.
+pub static MUSIC_MODES: [&str; 10] = [
+    "main_menu",
+    "laptop",
+    "tactical",
+    "tactical_enemypresent",
+    "tactical_battle",
+    "tactical_creature",
+    "tactical_creature_enemypresent",
+    "tactical_creature_battle",
+    "tactical_victory",
+    "tactical_defeat",
+];
+
+/// The file lists of `music.json` by mode, including keys the game does not know.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MusicManifest {
+    #[serde(flatten)]
+    pub modes: BTreeMap<String, Vec<String>>,
+}
+
+impl MusicManifest {
+    pub fn from_json(json: &str) -> Result<MusicManifest, String> {
+        serde_json::from_str(&policy::strip_json_comments(json)).map_err(|e| e.to_string())
+    }
+
+    /// Problems with the keys and lists that need no game data: unknown and missing modes and empty lists.
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for (mode, files) in &self.modes {
+            if !MUSIC_MODES.contains(&mode.as_str()) {
+                problems.push(format!("{}: unknown mode, possible modes: {}", mode, MUSIC_MODES.join(", ")));
+            } else if files.is_empty() {
+                problems.push(format!("{}: the list is empty", mode));
+            }
+        }
+        for mode in MUSIC_MODES.iter().filter(|m| !self.modes.contains_key(**m)) {
+            problems.push(format!("{}: the mode is missing", mode));
+        }
+        problems
+    }
+}
+
+/// Validates a manifest and resolves its files, the problems start with the mode and the position in its list.
+pub fn check_music(manifest: &MusicManifest, resources: &GameResources) -> Vec<String> {
+    let mut problems = manifest.validate();
+    for (mode, files) in manifest.modes.iter().filter(|&(m, _)| MUSIC_MODES.contains(&m.as_str())) {
+        for (i, file) in files.iter().enumerate() {
+            let problem = match resources.find(file) {
+                None => Some(String::from("not found")),
+                Some(location) => resources.read(&location).and_then(|data| audio::check_audio_header(&data)).err(),
+            };
+            if let Some(problem) = problem {
+                problems.push(format!("{}[{}]: {}: {}", mode, i, file, problem));
+            }
+        }
+    }
+    problems
+}
+
+pub fn read_music_manifest(path: &Path) -> Result<MusicManifest, String> {
+    let json = fs::read_to_string(path).map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+
+    MusicManifest::from_json(&json).map_err(|e| format!("Error parsing {}: {}", path.display(), e))
+}
+
+/// Reads and checks a `music.json`, the problems start with the path of the file.
+pub fn check_music_file(path: &Path, resources: &GameResources) -> Result<Vec<String>, String> {
+    let problems = check_music(&read_music_manifest(path)?, resources);
+
+    Ok(problems.into_iter().map(|p| format!("{}: {}", path.display(), p)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate tempdir;
+
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use super::{check_music, read_music_manifest, MusicManifest, MUSIC_MODES};
+    use data::GameResources;
+    use test_utils::slf_file;
+
+    /// A WAV file with the format tag and no samples.
+    fn wav_file(format: u16) -> Vec<u8> {
+        let mut data = b"RIFF\x24\0\0\0WAVEfmt \x10\0\0\0".to_vec();
+        data.extend_from_slice(&format.to_le_bytes());
+        data.extend_from_slice(&[1, 0, 0x22, 0x56, 0, 0, 0x44, 0xAC, 0, 0, 2, 0, 16, 0]);
+        data.extend_from_slice(b"data\0\0\0\0");
+        data
+    }
+
+    #[test]
+    fn validate_should_accept_the_shipped_music_json() {
+        let assets_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../assets");
+
+        for path in &["externalized/music.json", "mods/o-fortuna/data/music.json"] {
+            let manifest = read_music_manifest(&assets_dir.join(path)).unwrap();
+            assert_eq!(manifest.modes.keys().len(), MUSIC_MODES.len());
+            assert_eq!(manifest.validate(), Vec::<String>::new());
+        }
+    }
+
+    #[test]
+    fn check_music_should_resolve_the_files() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        let mut engine_options = ::EngineOptions::default();
+        engine_options.vanilla_data_dir = temp_dir.path().join("game");
+        fs::create_dir_all(temp_dir.path().join("game/Data")).unwrap();
+        fs::write(temp_dir.path().join("game/Data/Music.slf"), slf_file("music\\", &[
+            ("menumix1.wav", Some(&wav_file(1))),
+            ("tensor a.wav", Some(b"RIFF")),
+            ("battle a.mp3", Some(b"ID3")),
+            ("creepy.ogg", Some(b"OggS")),
+        ])).unwrap();
+        let resources = GameResources::new(&engine_options, Path::new("/nonexistent")).unwrap();
+        let manifest = MusicManifest::from_json(r#"{
+            "main_menu": ["music/MENUMIX1.wav"],
+            "laptop": [],
+            "tactical_enemypresent": ["music/tensor a.wav", "music/tensor b.wav"],
+            "tactical_battle": ["music/battle a.mp3"],
+            "tactical_creature": ["music/creepy.ogg"],
+            "tactical_nothing": ["music/nothing a.wav"]
+        }"#).unwrap();
+
+        let problems = check_music(&manifest, &resources);
+
+        assert_eq!(problems[0..3].to_vec(), vec!(
+            "laptop: the list is empty",
+            "tactical_nothing: unknown mode, possible modes: main_menu, laptop, tactical, tactical_enemypresent, tactical_battle, tactical_creature, tactical_creature_enemypresent, tactical_creature_battle, tactical_victory, tactical_defeat",
+            "tactical: the mode is missing",
+        ));
+        assert_eq!(problems[7..].to_vec(), vec!(
+            "tactical_battle[0]: music/battle a.mp3: Not a WAV, Ogg Vorbis or FLAC file",
+            "tactical_enemypresent[0]: music/tensor a.wav: Not a WAV, Ogg Vorbis or FLAC file",
+            "tactical_enemypresent[1]: music/tensor b.wav: not found",
+        ));
+    }
+}
@@ -0,0 +1,807 @@
+//! Reading and writing of the NPC quote records in `npcdata`, i.e. `%03d.npc`, `miners.npc` and the civ quote files like
+//! `A9.npc`.
+//!
+//! The layout follows `ExtractNPCQuoteInfoArrayFromFile` in `NPC.cc`. A file holds 50 records of 32 bytes. The Russian
+//! version stores an identifier in front of every record, the other versions have 4 unused bytes at the end instead.
+//! The JSON dump uses the names of the C++ constants for approaches, actions and flags so quests can be modded without
+//! looking up the structs.
+
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use byteorder::{ByteOrder, LittleEndian};
+use serde_json;
+
+use ResourceVersion;
+
+/// Number of records in a `.npc` file.
+pub const NUM_NPC_QUOTE_RECORDS: usize = 50;
+/// Size of one record.
+pub const NPC_QUOTE_RECORD_SIZE: usize = 32;
+
+pub const NO_FACT: u16 = 65535;
+pub const NO_QUEST: u8 = 255;
+pub const NO_QUOTE: u8 = 255;
+pub const IRRELEVANT: u8 = 255;
+pub const MUST_BE_NEW_DAY: u8 = 254;
+pub const NO_MOVE: u16 = 65535;
+/// Quests above this number require the quest `quest - QUEST_NOT_STARTED_NUM` to not be started.
+pub const QUEST_NOT_STARTED_NUM: u8 = 100;
+/// Quests above this number require the quest `quest - QUEST_DONE_NUM` to be done.
+pub const QUEST_DONE_NUM: u8 = 200;
+
+static QUOTE_FLAGS: [(u16, &str); 3] = [
+    (0x0001, "SAID"),
+    (0x0002, "ERASE_ONCE_SAID"),
+    (0x0004, "SAY_ONCE_PER_CONVO"),
+];
+
+// Values of `usGiftItem` that control the interface instead of handing over an item.
+static GIFT_ITEM_SPECIALS: [(u16, &str); 4] = [
+    (65000, "TURN_UI_OFF"),
+    (65001, "TURN_UI_ON"),
+    (65002, "SPECIAL_TURN_UI_OFF"),
+    (65003, "SPECIAL_TURN_UI_ON"),
+];
+
+// The `Approach` enum in `NPC.h`.
+static APPROACHES: [&str; 31] = [
+    "APPROACH_NONE",
+    "APPROACH_FRIENDLY",
+    "APPROACH_DIRECT",
+    "APPROACH_THREATEN",
+    "APPROACH_RECRUIT",
+    "APPROACH_REPEAT",
+    "APPROACH_GIVINGITEM",
+    "NPC_INITIATING_CONV",
+    "NPC_INITIAL_QUOTE",
+    "NPC_WHOAREYOU",
+    "TRIGGER_NPC",
+    "APPROACH_GIVEFIRSTAID",
+    "APPROACH_SPECIAL_INITIAL_QUOTE",
+    "APPROACH_ENEMY_NPC_QUOTE",
+    "APPROACH_DECLARATION_OF_HOSTILITY",
+    "APPROACH_EPC_IN_WRONG_SECTOR",
+    "APPROACH_EPC_WHO_IS_RECRUITED",
+    "APPROACH_INITIAL_QUOTE",
+    "APPROACH_CLOSING_SHOP",
+    "APPROACH_SECTOR_NOT_SAFE",
+    "APPROACH_DONE_SLAPPED",
+    "APPROACH_DONE_PUNCH_0",
+    "APPROACH_DONE_PUNCH_1",
+    "APPROACH_DONE_PUNCH_2",
+    "APPROACH_DONE_OPEN_STRUCTURE",
+    "APPROACH_DONE_GET_ITEM",
+    "APPROACH_DONE_GIVING_ITEM",
+    "APPROACH_DONE_TRAVERSAL",
+    "APPROACH_BUYSELL",
+    "APPROACH_ONE_OF_FOUR_STANDARD",
+    "APPROACH_FRIENDLY_DIRECT_OR_RECRUIT",
+];
+
+const TURN_TO_FACE_PROFILE_PREFIX: &str = "NPC_ACTION_TURN_TO_FACE_PROFILE_ID_";
+const FIRST_TURN_TO_FACE_PROFILE: u16 = 501;
+const LAST_TURN_TO_FACE_PROFILE: u16 = 650;
+
+// The NPC action enum in `Interface_Dialogue.h`, without the range of `NPC_ACTION_TURN_TO_FACE_PROFILE_ID_0`.
+static NPC_ACTIONS: &[(u16, &str)] = &[
+    (0, "NPC_ACTION_NONE"),
+    (1, "NPC_ACTION_DONT_ACCEPT_ITEM"),
+    (2, "NPC_ACTION_FACE_CLOSEST_PLAYER"),
+    (3, "NPC_ACTION_OPEN_CLOSEST_DOOR"),
+    (4, "NPC_ACTION_RECRUIT"),
+    (5, "NPC_ACTION_THREATENINGLY_RAISE_GUN"),
+    (6, "NPC_ACTION_LOWER_GUN"),
+    (7, "NPC_ACTION_READY_GUN"),
+    (8, "NPC_ACTION_START_RUNNING"),
+    (9, "NPC_ACTION_STOP_RUNNING"),
+    (10, "NPC_ACTION_BOOST_TOWN_LOYALTY"),
+    (11, "NPC_ACTION_PENALIZE_TOWN_LOYALTY"),
+    (12, "NPC_ACTION_STOP_PLAYER_GIVING_FIRST_AID"),
+    (13, "NPC_ACTION_FACE_NORTH"),
+    (14, "NPC_ACTION_FACE_NORTH_EAST"),
+    (15, "NPC_ACTION_FACE_EAST"),
+    (16, "NPC_ACTION_FACE_SOUTH_EAST"),
+    (17, "NPC_ACTION_FACE_SOUTH"),
+    (18, "NPC_ACTION_FACE_SOUTH_WEST"),
+    (19, "NPC_ACTION_FACE_WEST"),
+    (20, "NPC_ACTION_FACE_NORTH_WEST"),
+    (21, "NPC_ACTION_TRIGGER_FRIEND_WITH_HOSTILE_QUOTE"),
+    (22, "NPC_ACTION_BECOME_ENEMY"),
+    (23, "NPC_ACTION_RECRUIT_WITH_SALARY"),
+    (24, "NPC_ACTION_CLOSE_DIALOGUE_PANEL"),
+    (25, "NPC_ACTION_ENTER_COMBAT"),
+    (26, "NPC_ACTION_TERRORIST_REVEALS_SELF"),
+    (27, "NPC_ACTION_OPEN_CLOSEST_CABINET"),
+    (28, "NPC_ACTION_SLAP"),
+    (29, "NPC_ACTION_TRIGGER_QUEEN_BY_CITIES_CONTROLLED"),
+    (30, "NPC_ACTION_SEND_SOLDIERS_TO_DRASSEN"),
+    (31, "NPC_ACTION_SEND_SOLDIERS_TO_BATTLE_LOCATION"),
+    (32, "NPC_ACTION_TRIGGER_QUEEN_BY_SAM_SITES_CONTROLLED"),
+    (33, "NPC_ACTION_PUNCH_PC_SLOT_0"),
+    (34, "NPC_ACTION_PUNCH_PC_SLOT_1"),
+    (35, "NPC_ACTION_PUNCH_PC_SLOT_2"),
+    (36, "NPC_ACTION_FRUSTRATED_SLAP"),
+    (37, "NPC_ACTION_PUNCH_FIRST_LIVING_PC"),
+    (38, "NPC_ACTION_SHOOT_ELLIOT"),
+    (39, "NPC_ACTION_PLAYER_SAYS_NICE_LATER"),
+    (40, "NPC_ACTION_GET_ITEMS_FROM_CLOSEST_CABINET"),
+    (41, "NPC_ACTION_INITIATE_SHOPKEEPER_INTERFACE"),
+    (42, "NPC_ACTION_GET_OUT_OF_WHEELCHAIR"),
+    (43, "NPC_ACTION_GET_OUT_OF_WHEELCHAIR_AND_BECOME_HOSTILE"),
+    (44, "NPC_ACTION_PLAYER_SAYS_NASTY_LATER"),
+    (50, "NPC_ACTION_GRANT_EXPERIENCE_1"),
+    (51, "NPC_ACTION_GRANT_EXPERIENCE_2"),
+    (52, "NPC_ACTION_GRANT_EXPERIENCE_3"),
+    (53, "NPC_ACTION_GRANT_EXPERIENCE_4"),
+    (54, "NPC_ACTION_GRANT_EXPERIENCE_5"),
+    (100, "NPC_ACTION_GOTO_HIDEOUT"),
+    (101, "NPC_ACTION_FATIMA_GIVE_LETTER"),
+    (102, "NPC_ACTION_LEAVE_HIDEOUT"),
+    (103, "NPC_ACTION_TRAVERSE_MAP_EAST"),
+    (104, "NPC_ACTION_TRAVERSE_MAP_SOUTH"),
+    (105, "NPC_ACTION_TRAVERSE_MAP_WEST"),
+    (106, "NPC_ACTION_TRAVERSE_MAP_NORTH"),
+    (107, "NPC_ACTION_REPORT_SHIPMENT_SIZE"),
+    (108, "NPC_ACTION_RETURN_STOLEN_SHIPMENT_ITEMS"),
+    (109, "NPC_ACTION_SET_PABLO_BRIBE_DELAY"),
+    (110, "NPC_ACTION_ASK_ABOUT_ESCORTING_EPC"),
+    (111, "NPC_ACTION_DRINK_DRINK_DRINK"),
+    (112, "NPC_ACTION_TRIGGER_END_OF_FOOD_QUEST"),
+    (113, "NPC_ACTION_SEND_PACOS_INTO_HIDING"),
+    (114, "NPC_ACTION_HAVE_PACOS_FOLLOW"),
+    (115, "NPC_ACTION_SET_DELAYED_PACKAGE_TIMER"),
+    (116, "NPC_ACTION_SET_RANDOM_PACKAGE_DAMAGE_TIMER"),
+    (117, "NPC_ACTION_FREE_KIDS"),
+    (118, "NPC_ACTION_CHOOSE_DOCTOR"),
+    (119, "NPC_ACTION_REPORT_BALANCE"),
+    (120, "NPC_ACTION_ASK_ABOUT_PAYING_RPC"),
+    (121, "NPC_ACTION_DELAYED_MAKE_BRENDA_LEAVE"),
+    (122, "NPC_ACTION_SEX"),
+    (123, "NPC_ACTION_KYLE_GETS_MONEY"),
+    (124, "NPC_ACTION_LAYLA_GIVEN_WRONG_AMOUNT_OF_CASH"),
+    (125, "NPC_ACTION_SET_GIRLS_AVAILABLE"),
+    (126, "NPC_ACTION_SET_DELAY_TILL_GIRLS_AVAILABLE"),
+    (127, "NPC_ACTION_SET_WAITED_FOR_GIRL_FALSE"),
+    (128, "NPC_ACTION_TRIGGER_LAYLA_13_14_OR_15"),
+    (129, "NPC_ACTION_OPEN_CARLAS_DOOR"),
+    (130, "NPC_ACTION_OPEN_CINDYS_DOOR"),
+    (131, "NPC_ACTION_OPEN_BAMBIS_DOOR"),
+    (132, "NPC_ACTION_OPEN_MARIAS_DOOR"),
+    (133, "NPC_ACTION_POSSIBLY_ADVERTISE_CINDY"),
+    (134, "NPC_ACTION_POSSIBLY_ADVERTISE_BAMBI"),
+    (135, "NPC_ACTION_DARREN_REQUESTOR"),
+    (136, "NPC_ACTION_ADD_JOEY_TO_WORLD"),
+    (137, "NPC_ACTION_MARK_KINGPIN_QUOTE_0_USED"),
+    (138, "NPC_ACTION_START_BOXING_MATCH"),
+    (139, "NPC_ACTION_ENABLE_CAMBRIA_DOCTOR_BONUS"),
+    (140, "NPC_ACTION_MARTHA_DIES"),
+    (141, "NPC_ACTION_DARREN_GIVEN_CASH"),
+    (142, "NPC_ACTION_ANGEL_GIVEN_CASH"),
+    (143, "NPC_ACTION_TRIGGER_ANGEL_17_OR_18"),
+    (144, "NPC_ACTION_BUY_LEATHER_KEVLAR_VEST"),
+    (145, "NPC_ACTION_TRIGGER_MARIA"),
+    (146, "NPC_ACTION_TRIGGER_ANGEL_16_OR_19"),
+    (147, "NPC_ACTION_ANGEL_LEAVES_DEED"),
+    (148, "NPC_ACTION_TRIGGER_ANGEL_21_OR_22"),
+    (149, "NPC_ACTION_UN_RECRUIT_EPC"),
+    (150, "NPC_ACTION_TELEPORT_NPC"),
+    (151, "NPC_ACTION_REMOVE_DOREEN"),
+    (152, "NPC_ACTION_RESET_SHIPMENT_ARRIVAL_STUFF"),
+    (154, "NPC_ACTION_DECIDE_ACTIVE_TERRORISTS"),
+    (155, "NPC_ACTION_TRIGGER_FATHER_18_20_OR_15"),
+    (156, "NPC_ACTION_CHECK_LAST_TERRORIST_HEAD"),
+    (157, "NPC_ACTION_CARMEN_LEAVES_FOR_C13"),
+    (158, "NPC_ACTION_CARMEN_LEAVES_FOR_GOOD"),
+    (159, "NPC_ACTION_CARMEN_LEAVES_ON_NEXT_SECTOR_LOAD"),
+    (160, "NPC_ACTION_TRIGGER_VINCE_BY_LOYALTY"),
+    (161, "NPC_ACTION_MEDICAL_REQUESTOR"),
+    (162, "NPC_ACTION_MEDICAL_REQUESTOR_2"),
+    (163, "NPC_ACTION_CHECK_DOCTORING_MONEY_GIVEN"),
+    (164, "NPC_ACTION_START_DOCTORING"),
+    (165, "NPC_ACTION_VINCE_UNRECRUITABLE"),
+    (166, "NPC_ACTION_END_COMBAT"),
+    (167, "NPC_ACTION_BECOME_FRIENDLY_END_COMBAT"),
+    (168, "NPC_ACTION_SET_EPC_TO_NPC"),
+    (169, "NPC_ACTION_BUY_VEHICLE_REQUESTOR"),
+    (170, "NPC_ACTION_END_MEANWHILE"),
+    (171, "NPC_ACTION_START_BLOODCAT_QUEST"),
+    (172, "NPC_ACTION_START_MINE"),
+    (173, "NPC_ACTION_STOP_MINE"),
+    (174, "NPC_ACTION_RESET_MINE_CAPTURED"),
+    (175, "NPC_ACTION_SET_OSWALD_RECORD_13_USED"),
+    (176, "NPC_ACTION_SET_CALVIN_RECORD_13_USED"),
+    (177, "NPC_ACTION_SET_CARL_RECORD_13_USED"),
+    (178, "NPC_ACTION_SET_FRED_RECORD_13_USED"),
+    (179, "NPC_ACTION_SET_MATT_RECORD_13_USED"),
+    (180, "NPC_ACTION_TRIGGER_MATT"),
+    (181, "NPC_ACTION_REDUCE_CONRAD_SALARY_CONDITIONS"),
+    (182, "NPC_ACTION_REMOVE_CONRAD"),
+    (183, "NPC_ACTION_KROTT_REQUESTOR"),
+    (184, "NPC_ACTION_KROTT_ALIVE_LOYALTY_BOOST"),
+    (185, "NPC_ACTION_TRIGGER_YANNI"),
+    (186, "NPC_ACTION_TRIGGER_MARY_OR_JOHN_RECORD_9"),
+    (187, "NPC_ACTION_TRIGGER_MARY_OR_JOHN_RECORD_10"),
+    (188, "NPC_ACTION_ADD_JOHNS_GUN_SHIPMENT"),
+    (190, "NPC_ACTION_TRIGGER_KROTT_11_OR_12"),
+    (191, "NPC_ACTION_MADLAB_GIVEN_GUN"),
+    (192, "NPC_ACTION_MADLAB_GIVEN_CAMERA"),
+    (193, "NPC_ACTION_MADLAB_ATTACHES_GOOD_CAMERA"),
+    (194, "NPC_ACTION_READY_ROBOT"),
+    (195, "NPC_ACTION_WALTER_GIVEN_MONEY_INITIALLY"),
+    (196, "NPC_ACTION_WALTER_GIVEN_MONEY"),
+    (197, "NPC_ACTION_MAKE_NPC_FIRST_BARTENDER"),
+    (198, "NPC_ACTION_MAKE_NPC_SECOND_BARTENDER"),
+    (199, "NPC_ACTION_MAKE_NPC_THIRD_BARTENDER"),
+    (200, "NPC_ACTION_MAKE_NPC_FOURTH_BARTENDER"),
+    (201, "NPC_ACTION_GERARD_GIVEN_CASH"),
+    (202, "NPC_ACTION_FILL_UP_CAR"),
+    (203, "NPC_ACTION_JOE_GIVEN_CASH"),
+    (204, "NPC_ACTION_TRIGGER_ELLIOT_9_OR_10"),
+    (205, "NPC_ACTION_HANDLE_END_OF_FIGHT"),
+    (206, "NPC_ACTION_DARREN_PAYS_PLAYER"),
+    (207, "NPC_ACTION_FIGHT_AGAIN_REQUESTOR"),
+    (208, "NPC_ACTION_TRIGGER_SPIKE_OR_DARREN"),
+    (210, "NPC_ACTION_CHANGE_MANNY_POSITION"),
+    (211, "NPC_ACTION_TIMER_FOR_VEHICLE"),
+    (212, "NPC_ACTION_ASK_ABOUT_PAYING_RPC_WITH_DAILY_SALARY"),
+    (213, "NPC_ACTION_TRIGGER_MICKY_BY_SCI_FI"),
+    (215, "NPC_ACTION_TRIGGER_ELLIOT_BY_BATTLE_RESULT"),
+    (216, "NPC_ACTION_TRIGGER_ELLIOT_BY_SAM_DISABLED"),
+    (217, "NPC_ACTION_LAYLAS_NEXT_LINE_AFTER_CARLA"),
+    (218, "NPC_ACTION_LAYLAS_NEXT_LINE_AFTER_CINDY"),
+    (219, "NPC_ACTION_LAYLAS_NEXT_LINE_AFTER_BAMBI"),
+    (220, "NPC_ACTION_LAYLAS_NEXT_LINE_AFTER_MARIA"),
+    (221, "NPC_ACTION_PROMPT_PLAYER_TO_LIE"),
+    (222, "NPC_ACTION_REMOVE_JOE_QUEEN"),
+    (223, "NPC_ACTION_REMOVE_ELLIOT_END_MEANWHILE"),
+    (224, "NPC_ACTION_NO_SCI_FI_END_MEANWHILE"),
+    (226, "NPC_ACTION_TRIGGER_MARRY_DARYL_PROMPT"),
+    (227, "NPC_ACTION_HAVE_MARRIED_NPC_LEAVE_TEAM"),
+    (228, "NPC_ACTION_KINGPIN_GIVEN_MONEY"),
+    (229, "NPC_ACTION_KINGPIN_TRIGGER_25_OR_14"),
+    (230, "NPC_ACTION_SEND_ENRICO_MIGUEL_EMAIL"),
+    (231, "NPC_ACTION_END_DEMO"),
+    (232, "NPC_ACTION_INVOKE_CONVERSATION_MODE"),
+    (234, "NPC_ACTION_START_TIMER_ON_KEITH_GOING_OUT_OF_BUSINESS"),
+    (235, "NPC_ACTION_KEITH_GOING_BACK_IN_BUSINESS"),
+    (236, "NPC_ACTION_MAKE_RAT_DISAPPEAR"),
+    (237, "NPC_ACTION_DOCTOR_ESCORT_PATIENTS"),
+    (238, "NPC_ACTION_ELLIOT_DECIDE_WHICH_QUOTE_FOR_PLAYER_ATTACK"),
+    (239, "NPC_ACTION_QUEEN_DECIDE_WHICH_QUOTE_FOR_PLAYER_ATTACK"),
+    (240, "NPC_ACTION_CANCEL_WAYPOINTS"),
+    (242, "NPC_ACTION_SHOW_TIXA"),
+    (243, "NPC_ACTION_SHOW_ORTA"),
+    (244, "NPC_ACTION_DRINK_WINE"),
+    (245, "NPC_ACTION_DRINK_BOOZE"),
+    (246, "NPC_ACTION_TRIGGER_ANGEL_22_OR_24"),
+    (247, "NPC_ACTION_SET_FACT_105_FALSE"),
+    (248, "NPC_ACTION_MAKE_BRENDA_STATIONARY"),
+    (249, "NPC_ACTION_TRIGGER_DARREN_OR_KINGPIN_IMPRESSED"),
+    (250, "NPC_ACTION_TRIGGER_KINGPIN_IMPRESSED"),
+    (251, "NPC_ACTION_ADD_RAT"),
+    (253, "NPC_ACTION_ENDGAME_STATE_1"),
+    (254, "NPC_ACTION_ENDGAME_STATE_2"),
+    (255, "NPC_ACTION_MAKE_MIGUEL_STATIONARY"),
+    (256, "NPC_ACTION_MAKE_ESTONI_A_FUEL_SITE"),
+    (257, "NPC_ACTION_24_HOURS_SINCE_JOEY_RESCUED"),
+    (258, "NPC_ACTION_24_HOURS_SINCE_DOCTORS_TALKED_TO"),
+    (259, "NPC_ACTION_SEND_SOLDIERS_TO_OMERTA"),
+    (260, "NPC_ACTION_ADD_MORE_ELITES"),
+    (261, "NPC_ACTION_GIVE_KNOWLEDGE_OF_ALL_MERCS"),
+    (262, "NPC_ACTION_REMOVE_MERC_FOR_MARRIAGE"),
+    (263, "NPC_ACTION_TRIGGER_JOE_32_OR_33"),
+    (264, "NPC_ACTION_REMOVE_NPC"),
+    (265, "NPC_ACTION_HISTORY_GOT_ROCKET_RIFLES"),
+    (266, "NPC_ACTION_HISTORY_DEIDRANNA_DEAD_BODIES"),
+    (267, "NPC_ACTION_HISTORY_BOXING_MATCHES"),
+    (268, "NPC_ACTION_HISTORY_SOMETHING_IN_MINES"),
+    (269, "NPC_ACTION_HISTORY_DEVIN"),
+    (270, "NPC_ACTION_HISTORY_MIKE"),
+    (271, "NPC_ACTION_HISTORY_TONY"),
+    (272, "NPC_ACTION_HISTORY_KROTT"),
+    (273, "NPC_ACTION_HISTORY_KYLE"),
+    (274, "NPC_ACTION_HISTORY_MADLAB"),
+    (275, "NPC_ACTION_HISTORY_GABBY"),
+    (276, "NPC_ACTION_HISTORY_KEITH_OUT_OF_BUSINESS"),
+    (277, "NPC_ACTION_HISTORY_HOWARD_CYANIDE"),
+    (278, "NPC_ACTION_HISTORY_KEITH"),
+    (279, "NPC_ACTION_HISTORY_HOWARD"),
+    (280, "NPC_ACTION_HISTORY_PERKO"),
+    (281, "NPC_ACTION_HISTORY_SAM"),
+    (282, "NPC_ACTION_HISTORY_FRANZ"),
+    (283, "NPC_ACTION_HISTORY_ARNOLD"),
+    (284, "NPC_ACTION_HISTORY_FREDO"),
+    (285, "NPC_ACTION_HISTORY_RICHGUY_BALIME"),
+    (286, "NPC_ACTION_HISTORY_JAKE"),
+    (287, "NPC_ACTION_HISTORY_BUM_KEYCARD"),
+    (288, "NPC_ACTION_HISTORY_WALTER"),
+    (289, "NPC_ACTION_HISTORY_DAVE"),
+    (290, "NPC_ACTION_HISTORY_PABLO"),
+    (291, "NPC_ACTION_HISTORY_KINGPIN_MONEY"),
+    (292, "NPC_ACTION_SEND_TROOPS_TO_SAM"),
+    (293, "NPC_ACTION_PUT_PACOS_IN_BASEMENT"),
+    (294, "NPC_ACTION_HISTORY_ASSASSIN"),
+    (295, "NPC_ACTION_TRIGGER_HANS_BY_ROOM"),
+    (296, "NPC_ACTION_TRIGGER_MADLAB_31"),
+    (297, "NPC_ACTION_TRIGGER_MADLAB_32"),
+    (298, "NPC_ACTION_TRIGGER_BREWSTER_BY_WARDEN_PROXIMITY"),
+    (500, "NPC_ACTION_TURN_TO_FACE_NEAREST_MERC"),
+];
+
+/// Returns the name of an NPC action, e.g. `NPC_ACTION_RECRUIT`.
+pub fn npc_action_name(action: u16) -> Option<String> {
+    if (FIRST_TURN_TO_FACE_PROFILE..LAST_TURN_TO_FACE_PROFILE).contains(&action) {
+        return Some(format!("{}{}", TURN_TO_FACE_PROFILE_PREFIX, action - FIRST_TURN_TO_FACE_PROFILE));
+    }
+    NPC_ACTIONS.iter().find(|(value, _)| *value == action).map(|(_, name)| String::from(*name))
+}
+
+/// Returns the NPC action of a name, the inverse of `npc_action_name`.
+pub fn npc_action_from_name(name: &str) -> Option<u16> {
+    if let Some(profile) = name.strip_prefix(TURN_TO_FACE_PROFILE_PREFIX) {
+        return profile.parse::<u16>().ok()
+            .map(|profile| profile.saturating_add(FIRST_TURN_TO_FACE_PROFILE))
+            .filter(|action| *action < LAST_TURN_TO_FACE_PROFILE);
+    }
+    NPC_ACTIONS.iter().find(|(_, n)| *n == name).map(|(value, _)| *value)
+}
+
+/// One quote record, see `NPCQuoteInfo` in `NPC.h`.
+///
+/// The JSON form names the special values: approaches, actions and flags by their constants, unused facts, quests,
+/// quotes and moves by `null`. Unknown values are kept as numbers.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct NpcQuoteRecord {
+    /// Only stored in the Russian version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identifier: Option<u32>,
+    #[serde(with = "quote_flags")]
+    pub flags: u16,
+    /// The item to give for `APPROACH_GIVINGITEM`, the gridno to be near to otherwise.
+    pub required_item_or_gridno: i16,
+    #[serde(with = "optional_u16")]
+    pub fact_must_be_true: u16,
+    #[serde(with = "optional_u16")]
+    pub fact_must_be_false: u16,
+    #[serde(with = "optional_u8")]
+    pub quest: u8,
+    /// `MUST_BE_NEW_DAY` requires the NPC to not have talked today.
+    pub first_day: u8,
+    pub last_day: u8,
+    #[serde(with = "approach")]
+    pub approach_required: u8,
+    #[serde(with = "optional_u8")]
+    pub opinion_required: u8,
+    #[serde(with = "optional_u8")]
+    pub quote_num: u8,
+    pub num_quotes: u8,
+    #[serde(with = "optional_u8")]
+    pub start_quest: u8,
+    #[serde(with = "optional_u8")]
+    pub end_quest: u8,
+    pub trigger_npc: u8,
+    pub trigger_npc_record: u8,
+    #[serde(with = "optional_u16")]
+    pub set_fact_true: u16,
+    #[serde(with = "gift_item")]
+    pub gift_item: u16,
+    #[serde(with = "optional_u16")]
+    pub go_to_gridno: u16,
+    #[serde(with = "npc_action")]
+    pub action: u16,
+    /// Negative action data in the file, the action is done before the NPC moves.
+    #[serde(default)]
+    pub action_before_move: bool,
+}
+
+impl Default for NpcQuoteRecord {
+    fn default() -> NpcQuoteRecord {
+        NpcQuoteRecord {
+            identifier: None,
+            flags: 0,
+            required_item_or_gridno: 0,
+            fact_must_be_true: NO_FACT,
+            fact_must_be_false: NO_FACT,
+            quest: NO_QUEST,
+            first_day: 0,
+            last_day: 0,
+            approach_required: 0,
+            opinion_required: IRRELEVANT,
+            quote_num: NO_QUOTE,
+            num_quotes: 0,
+            start_quest: NO_QUEST,
+            end_quest: NO_QUEST,
+            trigger_npc: 0,
+            trigger_npc_record: 0,
+            set_fact_true: NO_FACT,
+            gift_item: 0,
+            go_to_gridno: NO_MOVE,
+            action: 0,
+            action_before_move: false,
+        }
+    }
+}
+
+fn stores_identifier(version: ResourceVersion) -> bool {
+    version == ResourceVersion::RUSSIAN
+}
+
+/// Parses one 32 byte record.
+pub fn parse_npc_quote_record(data: &[u8], version: ResourceVersion) -> Result<NpcQuoteRecord, String> {
+    if data.len() != NPC_QUOTE_RECORD_SIZE {
+        return Err(format!("NPC quote record has {} bytes, expected {}", data.len(), NPC_QUOTE_RECORD_SIZE));
+    }
+
+    let (identifier, d) = if stores_identifier(version) {
+        (Some(LittleEndian::read_u32(data)), &data[4..])
+    } else {
+        (None, data)
+    };
+    let action_data = LittleEndian::read_i16(&d[26..]);
+
+    Ok(NpcQuoteRecord {
+        identifier,
+        flags: LittleEndian::read_u16(&d[0..]),
+        required_item_or_gridno: LittleEndian::read_i16(&d[2..]),
+        fact_must_be_true: LittleEndian::read_u16(&d[4..]),
+        fact_must_be_false: LittleEndian::read_u16(&d[6..]),
+        quest: d[8],
+        first_day: d[9],
+        last_day: d[10],
+        approach_required: d[11],
+        opinion_required: d[12],
+        quote_num: d[13],
+        num_quotes: d[14],
+        start_quest: d[15],
+        end_quest: d[16],
+        trigger_npc: d[17],
+        trigger_npc_record: d[18],
+        // d[19] is padding
+        set_fact_true: LittleEndian::read_u16(&d[20..]),
+        gift_item: LittleEndian::read_u16(&d[22..]),
+        go_to_gridno: LittleEndian::read_u16(&d[24..]),
+        action: action_data.unsigned_abs(),
+        action_before_move: action_data < 0,
+    })
+}
+
+/// Serializes one record, the inverse of `parse_npc_quote_record`. Padding is zeroed.
+pub fn serialize_npc_quote_record(record: &NpcQuoteRecord, version: ResourceVersion) -> Result<Vec<u8>, String> {
+    if record.action > i16::MAX as u16 {
+        return Err(format!("NPC action {} is out of range", record.action));
+    }
+
+    let mut data = vec!(0u8; NPC_QUOTE_RECORD_SIZE);
+    let offset = if stores_identifier(version) {
+        LittleEndian::write_u32(&mut data, record.identifier.unwrap_or(0));
+        4
+    } else {
+        0
+    };
+    let action_data = if record.action_before_move { -(record.action as i16) } else { record.action as i16 };
+
+    {
+        let d = &mut data[offset..];
+        LittleEndian::write_u16(&mut d[0..], record.flags);
+        LittleEndian::write_i16(&mut d[2..], record.required_item_or_gridno);
+        LittleEndian::write_u16(&mut d[4..], record.fact_must_be_true);
+        LittleEndian::write_u16(&mut d[6..], record.fact_must_be_false);
+        d[8] = record.quest;
+        d[9] = record.first_day;
+        d[10] = record.last_day;
+        d[11] = record.approach_required;
+        d[12] = record.opinion_required;
+        d[13] = record.quote_num;
+        d[14] = record.num_quotes;
+        d[15] = record.start_quest;
+        d[16] = record.end_quest;
+        d[17] = record.trigger_npc;
+        d[18] = record.trigger_npc_record;
+        LittleEndian::write_u16(&mut d[20..], record.set_fact_true);
+        LittleEndian::write_u16(&mut d[22..], record.gift_item);
+        LittleEndian::write_u16(&mut d[24..], record.go_to_gridno);
+        LittleEndian::write_i16(&mut d[26..], action_data);
+    }
+    Ok(data)
+}
+
+/// Parses a whole `.npc` file.
+pub fn parse_npc_file(data: &[u8], version: ResourceVersion) -> Result<Vec<NpcQuoteRecord>, String> {
+    let expected = NUM_NPC_QUOTE_RECORDS * NPC_QUOTE_RECORD_SIZE;
+    if data.len() != expected {
+        return Err(format!("NPC file has {} bytes, expected {}", data.len(), expected));
+    }
+
+    data.chunks(NPC_QUOTE_RECORD_SIZE).map(|r| parse_npc_quote_record(r, version)).collect()
+}
+
+/// Serializes a whole `.npc` file, it needs exactly `NUM_NPC_QUOTE_RECORDS` records.
+pub fn serialize_npc_file(records: &[NpcQuoteRecord], version: ResourceVersion) -> Result<Vec<u8>, String> {
+    if records.len() != NUM_NPC_QUOTE_RECORDS {
+        return Err(format!("NPC file needs {} records, got {}", NUM_NPC_QUOTE_RECORDS, records.len()));
+    }
+
+    let mut data = Vec::with_capacity(NUM_NPC_QUOTE_RECORDS * NPC_QUOTE_RECORD_SIZE);
+    for (i, record) in records.iter().enumerate() {
+        data.extend(serialize_npc_quote_record(record, version).map_err(|e| format!("Record {}: {}", i, e))?);
+    }
+    Ok(data)
+}
+
+pub fn read_npc_file(path: &Path, version: ResourceVersion) -> Result<Vec<NpcQuoteRecord>, String> {
+    let mut data = vec!();
+    File::open(path)
+        .and_then(|mut f| f.read_to_end(&mut data))
+        .map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+
+    parse_npc_file(&data, version).map_err(|e| format!("Error parsing {}: {}", path.display(), e))
+}
+
+pub fn write_npc_file(path: &Path, records: &[NpcQuoteRecord], version: ResourceVersion) -> Result<(), String> {
+    let data = serialize_npc_file(records, version)?;
+
+    File::create(path)
+        .and_then(|mut f| f.write_all(&data))
+        .map_err(|e| format!("Error writing {}: {}", path.display(), e))
+}
+
+/// Pretty printed JSON of the records, ending with a newline.
+pub fn npc_records_to_json(records: &[NpcQuoteRecord]) -> Result<String, String> {
+    let mut json = serde_json::to_string_pretty(records).map_err(|e| format!("Error serializing NPC records: {}", e))?;
+
+    json.push('\n');
+    Ok(json)
+}
+
+pub fn npc_records_from_json(json: &str) -> Result<Vec<NpcQuoteRecord>, String> {
+    serde_json::from_str(json).map_err(|e| format!("Error parsing NPC records: {}", e))
+}
+
+// Serde helpers for the named values, unknown values are written as numbers.
+
+fn name_or_number(name: Option<String>, value: u64) -> serde_json::Value {
+    match name {
+        Some(name) => serde_json::Value::String(name),
+        None => serde_json::Value::from(value),
+    }
+}
+
+fn number_from_json<T: ::std::convert::TryFrom<u64>>(value: &serde_json::Value, from_name: &dyn Fn(&str) -> Option<u64>, what: &str) -> Result<T, String> {
+    let number = match *value {
+        serde_json::Value::String(ref name) => from_name(name).ok_or_else(|| format!("unknown {} {}", what, name))?,
+        serde_json::Value::Number(ref n) => n.as_u64().ok_or_else(|| format!("invalid {} {}", what, n))?,
+        ref other => return Err(format!("invalid {} {}", what, other)),
+    };
+    T::try_from(number).map_err(|_| format!("{} {} is out of range", what, number))
+}
+
+mod quote_flags {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use serde::de::Error;
+    use serde_json::Value;
+
+    use super::QUOTE_FLAGS;
+
+    pub fn serialize<S: Serializer>(flags: &u16, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut values: Vec<Value> = QUOTE_FLAGS.iter()
+            .filter(|(bit, _)| flags & bit != 0)
+            .map(|(_, name)| Value::from(*name))
+            .collect();
+        let known = QUOTE_FLAGS.iter().fold(0, |all, (bit, _)| all | bit);
+        if flags & !known != 0 {
+            values.push(Value::from(flags & !known));
+        }
+        serializer.collect_seq(values)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u16, D::Error> {
+        let values = Vec::<Value>::deserialize(deserializer)?;
+        let from_name = |name: &str| QUOTE_FLAGS.iter().find(|(_, n)| *n == name).map(|(bit, _)| u64::from(*bit));
+
+        values.iter().try_fold(0u16, |flags, value| {
+            super::number_from_json::<u16>(value, &from_name, "quote flag").map(|bit| flags | bit)
+        }).map_err(D::Error::custom)
+    }
+}
+
+mod approach {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use serde::de::Error;
+    use serde_json::Value;
+
+    use super::APPROACHES;
+
+    pub fn serialize<S: Serializer>(approach: &u8, serializer: S) -> Result<S::Ok, S::Error> {
+        let name = APPROACHES.get(*approach as usize).map(|n| String::from(*n));
+        super::name_or_number(name, u64::from(*approach)).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u8, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        let from_name = |name: &str| APPROACHES.iter().position(|n| *n == name).map(|i| i as u64);
+
+        super::number_from_json(&value, &from_name, "approach").map_err(D::Error::custom)
+    }
+}
+
+mod npc_action {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use serde::de::Error;
+    use serde_json::Value;
+
+    pub fn serialize<S: Serializer>(action: &u16, serializer: S) -> Result<S::Ok, S::Error> {
+        super::name_or_number(super::npc_action_name(*action), u64::from(*action)).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u16, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        let from_name = |name: &str| super::npc_action_from_name(name).map(u64::from);
+
+        super::number_from_json(&value, &from_name, "NPC action").map_err(D::Error::custom)
+    }
+}
+
+mod gift_item {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use serde::de::Error;
+    use serde_json::Value;
+
+    use super::GIFT_ITEM_SPECIALS;
+
+    pub fn serialize<S: Serializer>(item: &u16, serializer: S) -> Result<S::Ok, S::Error> {
+        let name = GIFT_ITEM_SPECIALS.iter().find(|(value, _)| value == item).map(|(_, name)| String::from(*name));
+        super::name_or_number(name, u64::from(*item)).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u16, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        let from_name = |name: &str| GIFT_ITEM_SPECIALS.iter().find(|(_, n)| *n == name).map(|(value, _)| u64::from(*value));
+
+        super::number_from_json(&value, &from_name, "gift item").map_err(D::Error::custom)
+    }
+}
+
+// `NO_QUEST`, `NO_QUOTE` and `IRRELEVANT` as null.
+mod optional_u8 {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &u8, serializer: S) -> Result<S::Ok, S::Error> {
+        Some(*value).filter(|v| *v != u8::MAX).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u8, D::Error> {
+        Ok(Option::<u8>::deserialize(deserializer)?.unwrap_or(u8::MAX))
+    }
+}
+
+// `NO_FACT` and `NO_MOVE` as null.
+mod optional_u16 {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &u16, serializer: S) -> Result<S::Ok, S::Error> {
+        Some(*value).filter(|v| *v != u16::MAX).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u16, D::Error> {
+        Ok(Option::<u16>::deserialize(deserializer)?.unwrap_or(u16::MAX))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NpcQuoteRecord, NO_FACT, NO_MOVE, NUM_NPC_QUOTE_RECORDS, NPC_QUOTE_RECORD_SIZE};
+    use ResourceVersion;
+
+    fn record() -> NpcQuoteRecord {
+        NpcQuoteRecord {
+            flags: 0x0002 | 0x0100,
+            required_item_or_gridno: 1000,
+            fact_must_be_true: 42,
+            quest: 201,
+            first_day: super::MUST_BE_NEW_DAY,
+            approach_required: 6,
+            opinion_required: 50,
+            quote_num: 12,
+            num_quotes: 3,
+            end_quest: 7,
+            trigger_npc: 57,
+            trigger_npc_record: 9,
+            gift_item: 65001,
+            go_to_gridno: 12345,
+            action: 4,
+            action_before_move: true,
+            ..Default::default()
+        }
+    }
+
+    fn records() -> Vec<NpcQuoteRecord> {
+        let mut records = vec!(NpcQuoteRecord::default(); NUM_NPC_QUOTE_RECORDS);
+        records[3] = record();
+        records[49].action = 501 + 57;
+        records[49].approach_required = 99;
+        records
+    }
+
+    #[test]
+    fn serialize_npc_quote_record_should_follow_the_file_layout() {
+        let data = super::serialize_npc_quote_record(&record(), ResourceVersion::ENGLISH).unwrap();
+
+        assert_eq!(data.len(), NPC_QUOTE_RECORD_SIZE);
+        assert_eq!(&data[0..8], &[0x02, 0x01, 0xE8, 0x03, 42, 0, 0xFF, 0xFF]);
+        assert_eq!(&data[8..20], &[201, 254, 0, 6, 50, 12, 3, 255, 7, 57, 9, 0]);
+        assert_eq!(&data[20..32], &[0xFF, 0xFF, 0xE9, 0xFD, 0x39, 0x30, 0xFC, 0xFF, 0, 0, 0, 0]);
+        assert_eq!(super::parse_npc_quote_record(&data, ResourceVersion::ENGLISH).unwrap(), record());
+    }
+
+    #[test]
+    fn russian_records_should_start_with_the_identifier() {
+        let r = NpcQuoteRecord { identifier: Some(0x0403_0201), ..record() };
+        let data = super::serialize_npc_quote_record(&r, ResourceVersion::RUSSIAN).unwrap();
+
+        assert_eq!(&data[0..6], &[1, 2, 3, 4, 0x02, 0x01]);
+        assert_eq!(&data[28..32], &[0x39, 0x30, 0xFC, 0xFF]);
+        assert_eq!(super::parse_npc_quote_record(&data, ResourceVersion::RUSSIAN).unwrap(), r);
+        // Russian Gold uses the common layout
+        assert_eq!(super::parse_npc_quote_record(&data, ResourceVersion::RUSSIAN_GOLD).unwrap().flags, 0x0201);
+    }
+
+    #[test]
+    fn npc_files_should_round_trip_through_json() {
+        let data = super::serialize_npc_file(&records(), ResourceVersion::GERMAN).unwrap();
+        let parsed = super::parse_npc_file(&data, ResourceVersion::GERMAN).unwrap();
+        let json = super::npc_records_to_json(&parsed).unwrap();
+        let from_json = super::npc_records_from_json(&json).unwrap();
+
+        assert_eq!(parsed, records());
+        assert_eq!(super::serialize_npc_file(&from_json, ResourceVersion::GERMAN).unwrap(), data);
+    }
+
+    #[test]
+    fn npc_records_to_json_should_name_special_values() {
+        let json = super::npc_records_to_json(&records()).unwrap();
+        let value: ::serde_json::Value = ::serde_json::from_str(&json).unwrap();
+
+        assert_eq!(value[0]["fact_must_be_true"], ::serde_json::Value::Null);
+        assert_eq!(value[0]["action"], "NPC_ACTION_NONE");
+        assert_eq!(value[3]["flags"][0], "ERASE_ONCE_SAID");
+        assert_eq!(value[3]["flags"][1], 256);
+        assert_eq!(value[3]["approach_required"], "APPROACH_GIVINGITEM");
+        assert_eq!(value[3]["gift_item"], "TURN_UI_ON");
+        assert_eq!(value[3]["action"], "NPC_ACTION_RECRUIT");
+        assert_eq!(value[49]["action"], "NPC_ACTION_TURN_TO_FACE_PROFILE_ID_57");
+        assert_eq!(value[49]["approach_required"], 99);
+        assert!(value[0].get("identifier").is_none());
+    }
+
+    #[test]
+    fn npc_records_from_json_should_reject_unknown_names() {
+        let json = super::npc_records_to_json(&records()).unwrap();
+
+        let err = super::npc_records_from_json(&json.replace("NPC_ACTION_RECRUIT", "NPC_ACTION_FLY")).unwrap_err();
+        assert!(err.contains("unknown NPC action NPC_ACTION_FLY"), "{}", err);
+        let err = super::npc_records_from_json(&json.replace("\"APPROACH_GIVINGITEM\"", "300")).unwrap_err();
+        assert!(err.contains("approach 300 is out of range"), "{}", err);
+    }
+
+    #[test]
+    fn npc_action_names_should_cover_the_enum() {
+        assert_eq!(super::npc_action_name(4).unwrap(), "NPC_ACTION_RECRUIT");
+        assert_eq!(super::npc_action_name(298).unwrap(), "NPC_ACTION_TRIGGER_BREWSTER_BY_WARDEN_PROXIMITY");
+        assert_eq!(super::npc_action_name(500).unwrap(), "NPC_ACTION_TURN_TO_FACE_NEAREST_MERC");
+        assert_eq!(super::npc_action_name(501).unwrap(), "NPC_ACTION_TURN_TO_FACE_PROFILE_ID_0");
+        assert_eq!(super::npc_action_name(650), None);
+        assert_eq!(super::npc_action_from_name("NPC_ACTION_TURN_TO_FACE_PROFILE_ID_148"), Some(649));
+        assert_eq!(super::npc_action_from_name("NPC_ACTION_TURN_TO_FACE_PROFILE_ID_149"), None);
+        for (value, name) in super::NPC_ACTIONS.iter() {
+            assert_eq!(super::npc_action_from_name(name), Some(*value));
+        }
+    }
+
+    #[test]
+    fn parse_npc_file_should_check_the_size() {
+        assert_eq!(super::parse_npc_file(&[0; 10], ResourceVersion::ENGLISH).unwrap_err(), "NPC file has 10 bytes, expected 1600");
+        assert_eq!(NO_FACT, NO_MOVE);
+    }
+}
@@ -0,0 +1,480 @@
+//! The game policy in `externalized/game.json`, read by `DefaultGamePolicy.cc` into `GamePolicy`.
+//!
+//! The game reads the values without any checks, so a bad value only shows up as odd gameplay. `GamePolicy::validate`
+//! checks the ranges the comments in `game.json` and `GamePolicy.h` describe, and the presets allow to compare a
+//! customized file with the vanilla game or the defaults shipped with Stracciatella.
+//!
+//! A `PolicySnapshot` records the effective policy and the active mods in a sidecar next to a saved game, e.g.
+//! `SavedGames/SaveGame01.sav.policy.json`, so loading a save made under different rules can be warned about.
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use md5;
+use serde_json;
+use serde_json::Value;
+
+use data;
+use EngineOptions;
+
+/// `game.json` as shipped with Stracciatella.
+static STRACCIATELLA_GAME_JSON: &str = include_str!("../../assets/externalized/game.json");
+
+/// Names of the built-in presets.
+pub static PRESETS: [&str; 2] = ["vanilla", "stracciatella default"];
+
+/// The highest experience level of a soldier.
+pub const MAX_EXP_LEVEL: i8 = 10;
+
+/// Removes the `//` and `/* */` comments that the game allows in its JSON files, like rapidjson's `kParseCommentsFlag`.
+pub fn strip_json_comments(json: &str) -> String {
+    let mut stripped = String::with_capacity(json.len());
+    let mut chars = json.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            stripped.push(c);
+            match c {
+                '\\' => stripped.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {},
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                stripped.push(c);
+            },
+            ('/', Some('/')) => {
+                // Keep the line break so error positions stay the same
+                while chars.peek().is_some_and(|&c| c != '\n') {
+                    chars.next();
+                }
+            },
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        stripped.push(c);
+                    }
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            },
+            _ => stripped.push(c),
+        }
+    }
+    stripped
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AiPolicy {
+    /// Decide where to shoot depending on the chance to hit if a random choice is made.
+    pub better_aiming_choice: bool,
+    /// Lay down when already facing the focus.
+    pub go_prone_more_often: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImpPolicy {
+    /// Maximum of an attribute, 0 to 100.
+    pub max_attribute_points: i8,
+    /// Minimum of an attribute, 0 to `max_attribute_points`. Below 15 a character can start dying.
+    pub min_attribute_points: i8,
+    /// Points given back for an attribute set to zero instead of the minimum.
+    pub zero_attribute_points_bonus: i32,
+    /// Initial pool of points to distribute.
+    pub bonus_attribute_points: i32,
+}
+
+/// The settings of `game.json`, with the types the game reads them into.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GamePolicy {
+    pub extra_hotkeys: bool,
+    pub draw_item_shadow: bool,
+    /// Milliseconds per game cycle, has to be positive.
+    pub ms_per_game_cycle: i32,
+    pub starting_cash_easy: i32,
+    pub starting_cash_medium: i32,
+    pub starting_cash_hard: i32,
+    pub drop_everything: bool,
+    pub all_drops_visible: bool,
+    pub multiple_interrupts: bool,
+    /// Minimal status of enemy weapons in percent.
+    pub enemy_weapon_minimal_status: i8,
+    pub ai: AiPolicy,
+    /// Chance to hit in percent above which the AI always aims for the head.
+    pub threshold_cth_head: i8,
+    /// Chance to hit in percent above which the AI aims for the legs instead of the torso.
+    pub threshold_cth_legs: i8,
+    pub enemy_elite_minimum_level: i8,
+    pub enemy_elite_maximum_level: i8,
+    pub gui_extras: bool,
+    pub extra_attachments: bool,
+    pub middle_mouse_look: bool,
+    pub can_enter_turnbased: bool,
+    pub pablo_wont_steal: bool,
+    pub chance_to_hit_minimum: i8,
+    pub chance_to_hit_maximum: i8,
+    pub tactical_head_damage_multiplier: f64,
+    pub tactical_legs_damage_multiplier: f64,
+    pub imp: ImpPolicy,
+}
+
+fn check_percentage(problems: &mut Vec<String>, key: &str, value: i8) {
+    if !(0..=100).contains(&value) {
+        problems.push(format!("{} is {}, it has to be from 0 to 100", key, value));
+    }
+}
+
+impl GamePolicy {
+    /// Parses `game.json`, comments are allowed like in the game.
+    pub fn from_json(json: &str) -> Result<GamePolicy, String> {
+        serde_json::from_str(&strip_json_comments(json)).map_err(|e| format!("Error parsing game policy: {}", e))
+    }
+
+    /// The built-in preset with the given name, see `PRESETS`.
+    pub fn preset(name: &str) -> Option<GamePolicy> {
+        match name {
+            "vanilla" => Some(GamePolicy::vanilla()),
+            "stracciatella default" => Some(GamePolicy::from_json(STRACCIATELLA_GAME_JSON).expect("the built-in game.json is valid")),
+            _ => None,
+        }
+    }
+
+    /// The behaviour of the original game, following the vanilla values in the comments of `game.json`.
+    pub fn vanilla() -> GamePolicy {
+        GamePolicy {
+            extra_hotkeys: false,
+            draw_item_shadow: true,
+            ms_per_game_cycle: 25,
+            starting_cash_easy: 45000,
+            starting_cash_medium: 35000,
+            starting_cash_hard: 30000,
+            drop_everything: false,
+            all_drops_visible: false,
+            multiple_interrupts: false,
+            enemy_weapon_minimal_status: 0,
+            ai: AiPolicy { better_aiming_choice: false, go_prone_more_often: false },
+            threshold_cth_head: 0,
+            threshold_cth_legs: 0,
+            enemy_elite_minimum_level: 6,
+            enemy_elite_maximum_level: 10,
+            gui_extras: false,
+            extra_attachments: false,
+            middle_mouse_look: false,
+            can_enter_turnbased: false,
+            pablo_wont_steal: false,
+            chance_to_hit_minimum: 1,
+            chance_to_hit_maximum: 99,
+            tactical_head_damage_multiplier: 1.5,
+            tactical_legs_damage_multiplier: 0.5,
+            imp: ImpPolicy { max_attribute_points: 85, min_attribute_points: 35, zero_attribute_points_bonus: 15, bonus_attribute_points: 40 },
+        }
+    }
+
+    /// Returns a list of human readable problems, values that fit the types but that the engine cannot handle.
+    /// Unusual but legal settings, like IMP characters with little health, are not reported.
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = vec!();
+
+        if self.ms_per_game_cycle <= 0 {
+            problems.push(format!("ms_per_game_cycle is {}, it has to be positive", self.ms_per_game_cycle));
+        }
+        for &(key, cash) in &[("starting_cash_easy", self.starting_cash_easy), ("starting_cash_medium", self.starting_cash_medium), ("starting_cash_hard", self.starting_cash_hard)] {
+            if cash < 0 {
+                problems.push(format!("{} is {}, it cannot be negative", key, cash));
+            }
+        }
+        check_percentage(&mut problems, "enemy_weapon_minimal_status", self.enemy_weapon_minimal_status);
+        check_percentage(&mut problems, "threshold_cth_head", self.threshold_cth_head);
+        check_percentage(&mut problems, "threshold_cth_legs", self.threshold_cth_legs);
+
+        for &(key, level) in &[("enemy_elite_minimum_level", self.enemy_elite_minimum_level), ("enemy_elite_maximum_level", self.enemy_elite_maximum_level)] {
+            if !(1..=MAX_EXP_LEVEL).contains(&level) {
+                problems.push(format!("{} is {}, it has to be from 1 to {}", key, level, MAX_EXP_LEVEL));
+            }
+        }
+        if self.enemy_elite_minimum_level > self.enemy_elite_maximum_level {
+            problems.push(format!("enemy_elite_minimum_level {} is above enemy_elite_maximum_level {}", self.enemy_elite_minimum_level, self.enemy_elite_maximum_level));
+        }
+
+        check_percentage(&mut problems, "chance_to_hit_minimum", self.chance_to_hit_minimum);
+        check_percentage(&mut problems, "chance_to_hit_maximum", self.chance_to_hit_maximum);
+        if self.chance_to_hit_minimum > self.chance_to_hit_maximum {
+            problems.push(format!("chance_to_hit_minimum {} is above chance_to_hit_maximum {}", self.chance_to_hit_minimum, self.chance_to_hit_maximum));
+        }
+        for &(key, multiplier) in &[("tactical_head_damage_multiplier", self.tactical_head_damage_multiplier), ("tactical_legs_damage_multiplier", self.tactical_legs_damage_multiplier)] {
+            if !multiplier.is_finite() || multiplier < 0.0 {
+                problems.push(format!("{} is {}, it cannot be negative", key, multiplier));
+            }
+        }
+
+        check_percentage(&mut problems, "imp.max_attribute_points", self.imp.max_attribute_points);
+        check_percentage(&mut problems, "imp.min_attribute_points", self.imp.min_attribute_points);
+        if self.imp.min_attribute_points > self.imp.max_attribute_points {
+            problems.push(format!("imp.min_attribute_points {} is above imp.max_attribute_points {}", self.imp.min_attribute_points, self.imp.max_attribute_points));
+        }
+        for &(key, points) in &[("imp.zero_attribute_points_bonus", self.imp.zero_attribute_points_bonus), ("imp.bonus_attribute_points", self.imp.bonus_attribute_points)] {
+            if points < 0 {
+                problems.push(format!("{} is {}, it cannot be negative", key, points));
+            }
+        }
+
+        problems
+    }
+}
+
+/// Reads and parses a `game.json` file.
+pub fn read_game_policy(path: &Path) -> Result<GamePolicy, String> {
+    let json = fs::read_to_string(path).map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+
+    GamePolicy::from_json(&json).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// A setting that differs between two policies, nested keys are joined with a dot like `imp.max_attribute_points`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyChange {
+    pub key: String,
+    pub before: Value,
+    pub after: Value,
+}
+
+impl fmt::Display for PolicyChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {} -> {}", self.key, self.before, self.after)
+    }
+}
+
+fn flatten(prefix: &str, value: &Value, flat: &mut Vec<(String, Value)>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                let key = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                flatten(&key, value, flat);
+            }
+        },
+        _ => flat.push((prefix.to_string(), value.clone())),
+    }
+}
+
+/// Lists the settings that differ from `before` to `after`, sorted by key.
+pub fn diff_policies(before: &GamePolicy, after: &GamePolicy) -> Vec<PolicyChange> {
+    let mut before_values = vec!();
+    let mut after_values = vec!();
+    flatten("", &serde_json::to_value(before).unwrap(), &mut before_values);
+    flatten("", &serde_json::to_value(after).unwrap(), &mut after_values);
+
+    before_values.into_iter().zip(after_values)
+        .filter(|(b, a)| b.1 != a.1)
+        .map(|((key, before), (_, after))| PolicyChange { key, before, after })
+        .collect()
+}
+
+/// The `game.json` the game reads: the one of the first mod if it has one, otherwise the one in `externalized`.
+pub fn effective_game_json_path(engine_options: &EngineOptions, extra_data_dir: &Path) -> PathBuf {
+    data::externalized_file(engine_options, extra_data_dir, "game.json")
+}
+
+/// The rules a game was played with: the effective policy and the active mods.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicySnapshot {
+    /// MD5 of the canonical JSON of the mods and the policy, equal hashes mean equal rules.
+    pub hash: String,
+    pub mods: Vec<String>,
+    pub policy: GamePolicy,
+}
+
+impl PolicySnapshot {
+    pub fn new(mods: Vec<String>, policy: GamePolicy) -> PolicySnapshot {
+        let canonical = serde_json::to_string(&(&mods, &policy)).unwrap();
+        let hash = format!("{:x}", md5::compute(canonical.as_bytes()));
+        PolicySnapshot { hash, mods, policy }
+    }
+
+    /// The rules the game would use now.
+    pub fn current(engine_options: &EngineOptions, extra_data_dir: &Path) -> Result<PolicySnapshot, String> {
+        let policy = read_game_policy(&effective_game_json_path(engine_options, extra_data_dir))?;
+        Ok(PolicySnapshot::new(engine_options.mods.clone(), policy))
+    }
+}
+
+/// The differences between the rules of a saved game and the current ones.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PolicyMismatch {
+    /// The mods of the saved game and the current ones, if they differ.
+    pub mods: Option<(Vec<String>, Vec<String>)>,
+    pub changes: Vec<PolicyChange>,
+}
+
+impl PolicyMismatch {
+    pub fn is_empty(&self) -> bool {
+        self.mods.is_none() && self.changes.is_empty()
+    }
+}
+
+impl fmt::Display for PolicyMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names = |mods: &[String]| if mods.is_empty() { String::from("none") } else { mods.join(", ") };
+        if let Some((ref saved, ref current)) = self.mods {
+            writeln!(f, "mods: {} -> {}", names(saved), names(current))?;
+        }
+        for change in &self.changes {
+            writeln!(f, "{}", change)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares the snapshot of a saved game with the current rules.
+pub fn compare_snapshots(saved: &PolicySnapshot, current: &PolicySnapshot) -> PolicyMismatch {
+    if saved.hash == current.hash {
+        return PolicyMismatch::default();
+    }
+    PolicyMismatch {
+        mods: if saved.mods != current.mods { Some((saved.mods.clone(), current.mods.clone())) } else { None },
+        changes: diff_policies(&saved.policy, &current.policy),
+    }
+}
+
+/// The sidecar of a saved game, e.g. `SaveGame01.sav.policy.json`.
+pub fn snapshot_path(saved_game: &Path) -> PathBuf {
+    let mut name = saved_game.file_name().map(|n| n.to_os_string()).unwrap_or_default();
+    name.push(".policy.json");
+    saved_game.with_file_name(name)
+}
+
+pub fn write_policy_snapshot(saved_game: &Path, snapshot: &PolicySnapshot) -> Result<(), String> {
+    let path = snapshot_path(saved_game);
+    let mut json = serde_json::to_string_pretty(snapshot).map_err(|e| format!("Error serializing policy snapshot: {}", e))?;
+    json.push('\n');
+
+    fs::write(&path, json).map_err(|e| format!("Error writing {}: {}", path.display(), e))
+}
+
+/// Reads the sidecar of a saved game, `None` if the game was saved without one.
+pub fn read_policy_snapshot(saved_game: &Path) -> Result<Option<PolicySnapshot>, String> {
+    let path = snapshot_path(saved_game);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let json = fs::read_to_string(&path).map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+
+    serde_json::from_str(&json).map(Some).map_err(|e| format!("Error parsing {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate tempdir;
+
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use super::{compare_snapshots, diff_policies, effective_game_json_path, read_policy_snapshot, snapshot_path, strip_json_comments, write_policy_snapshot, GamePolicy, PolicySnapshot, PRESETS};
+
+    #[test]
+    fn strip_json_comments_should_keep_strings() {
+        let json = "{\n  // comment \"x\"\n  \"a\": \"http://b\", /* block\n */ \"c\": \"\\\"//\"\n}";
+
+        assert_eq!(strip_json_comments(json), "{\n  \n  \"a\": \"http://b\", \n \"c\": \"\\\"//\"\n}");
+    }
+
+    #[test]
+    fn presets_should_be_valid() {
+        for name in PRESETS.iter() {
+            assert_eq!(GamePolicy::preset(name).unwrap().validate(), Vec::<String>::new(), "{}", name);
+        }
+        assert_eq!(GamePolicy::preset("easy"), None);
+    }
+
+    #[test]
+    fn validate_should_check_the_ranges() {
+        let mut policy = GamePolicy::vanilla();
+        policy.ms_per_game_cycle = 0;
+        policy.chance_to_hit_minimum = 100;
+        policy.chance_to_hit_maximum = 101;
+        policy.enemy_elite_minimum_level = 8;
+        policy.enemy_elite_maximum_level = 7;
+        policy.imp.min_attribute_points = 10;
+
+        assert_eq!(policy.validate(), vec!(
+            "ms_per_game_cycle is 0, it has to be positive",
+            "enemy_elite_minimum_level 8 is above enemy_elite_maximum_level 7",
+            "chance_to_hit_maximum is 101, it has to be from 0 to 100",
+        ));
+
+        policy.imp.min_attribute_points = 60;
+        policy.imp.max_attribute_points = 50;
+        assert!(policy.validate().contains(&String::from("imp.min_attribute_points 60 is above imp.max_attribute_points 50")));
+    }
+
+    #[test]
+    fn from_json_should_fail_with_values_the_game_cannot_store() {
+        let json = super::STRACCIATELLA_GAME_JSON.replace("\"chance_to_hit_maximum\": 99", "\"chance_to_hit_maximum\": 200");
+
+        assert!(GamePolicy::from_json(&json).unwrap_err().contains("invalid value: integer `200`, expected i8"));
+    }
+
+    #[test]
+    fn diff_policies_should_list_the_changed_settings() {
+        let changes = diff_policies(&GamePolicy::vanilla(), &GamePolicy::preset("stracciatella default").unwrap());
+        let lines: Vec<String> = changes.iter().map(|c| c.to_string()).collect();
+
+        assert_eq!(lines, vec!(
+            "extra_hotkeys: false -> true",
+            "gui_extras: false -> true",
+            "middle_mouse_look: false -> true",
+            "threshold_cth_head: 0 -> 67",
+            "threshold_cth_legs: 0 -> 67",
+        ));
+    }
+
+    #[test]
+    fn policy_snapshot_should_hash_the_mods_and_the_policy() {
+        let vanilla = PolicySnapshot::new(vec!(), GamePolicy::vanilla());
+        let modded = PolicySnapshot::new(vec!(String::from("o-fortuna")), GamePolicy::vanilla());
+        let mut policy = GamePolicy::vanilla();
+        policy.starting_cash_easy = 100000;
+        let rich = PolicySnapshot::new(vec!(), policy);
+
+        assert_eq!(vanilla.hash.len(), 32);
+        assert_eq!(vanilla, PolicySnapshot::new(vec!(), GamePolicy::vanilla()));
+        assert_ne!(vanilla.hash, modded.hash);
+        assert!(compare_snapshots(&vanilla, &vanilla.clone()).is_empty());
+        assert_eq!(compare_snapshots(&vanilla, &modded).to_string(), "mods: none -> o-fortuna\n");
+        assert_eq!(compare_snapshots(&vanilla, &rich).to_string(), "starting_cash_easy: 45000 -> 100000\n");
+    }
+
+    #[test]
+    fn policy_snapshot_should_be_stored_next_to_the_saved_game() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        let saved_game = temp_dir.path().join("SaveGame01.sav");
+        let snapshot = PolicySnapshot::new(vec!(String::from("o-fortuna")), GamePolicy::vanilla());
+
+        assert_eq!(read_policy_snapshot(&saved_game), Ok(None));
+        write_policy_snapshot(&saved_game, &snapshot).unwrap();
+        assert_eq!(snapshot_path(&saved_game), temp_dir.path().join("SaveGame01.sav.policy.json"));
+        assert_eq!(read_policy_snapshot(&saved_game), Ok(Some(snapshot)));
+    }
+
+    #[test]
+    fn effective_game_json_path_should_prefer_the_first_mod() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        fs::create_dir_all(temp_dir.path().join("mods/a/data")).unwrap();
+        fs::write(temp_dir.path().join("mods/a/data/Game.json"), "{}").unwrap();
+        let mut engine_options = ::EngineOptions::default();
+
+        assert_eq!(effective_game_json_path(&engine_options, temp_dir.path()), temp_dir.path().join("externalized/game.json"));
+        engine_options.mods = vec!(String::from("b"), String::from("a"));
+        assert_eq!(effective_game_json_path(&engine_options, temp_dir.path()), temp_dir.path().join("externalized/game.json"));
+        engine_options.mods = vec!(String::from("a"));
+        assert_eq!(effective_game_json_path(&engine_options, temp_dir.path()), temp_dir.path().join("mods/a/data/Game.json"));
+        assert_eq!(effective_game_json_path(&engine_options, Path::new("/nonexistent")), PathBuf::from("/nonexistent/externalized/game.json"));
+    }
+}
@@ -0,0 +1,922 @@
+//! Reading and writing of the mercenary profile table `BinaryData/prof.dat`.
+//!
+//! The layout follows `ExtractMercProfile` and `InjectMercProfile` in `LoadSaveMercProfile.cc`. Every record is
+//! encrypted separately like `JA2EncryptedFileRead` does. Padding bytes are not kept, the writer zeroes them and
+//! recomputes the checksum like the game, so files round-trip field by field but not necessarily byte by byte.
+
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use serde_json;
+
+use ResourceVersion;
+
+/// Size of one vanilla profile record.
+pub const MERC_PROFILE_SIZE: usize = 716;
+/// Number of profiles in `prof.dat`.
+pub const NUM_PROFILES: usize = 170;
+
+const NAME_LENGTH: usize = 30;
+const NICKNAME_LENGTH: usize = 10;
+const PALETTE_LENGTH: usize = 30;
+const INVENTORY_SLOTS: usize = 19;
+
+// The C++ source has 057 in here, which is octal.
+static ROTATION_ARRAY: [u8; 46] = [
+    132, 235, 125, 99, 15, 220, 140, 89, 205, 132, 254, 144, 217, 78, 156, 58, 215, 76, 163, 187, 55, 49, 65, 48,
+    156, 140, 201, 68, 184, 13, 45, 69, 102, 185, 122, 225, 23, 250, 160, 220, 114, 240, 64, 175, 47, 233,
+];
+
+/// Undoes `JA2EncryptedFileWrite` for one record.
+pub fn decrypt(data: &[u8]) -> Vec<u8> {
+    let mut last_byte = 0u8;
+
+    data.iter().enumerate().map(|(i, b)| {
+        let decrypted = b.wrapping_sub(last_byte.wrapping_add(ROTATION_ARRAY[i % ROTATION_ARRAY.len()]));
+        last_byte = *b;
+        decrypted
+    }).collect()
+}
+
+/// Encrypts one record like `JA2EncryptedFileWrite`.
+pub fn encrypt(data: &[u8]) -> Vec<u8> {
+    let mut last_byte = 0u8;
+
+    data.iter().enumerate().map(|(i, b)| {
+        last_byte = b.wrapping_add(last_byte).wrapping_add(ROTATION_ARRAY[i % ROTATION_ARRAY.len()]);
+        last_byte
+    }).collect()
+}
+
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+pub struct Stats {
+    pub life: i8,
+    pub life_max: i8,
+    pub agility: i8,
+    pub dexterity: i8,
+    pub strength: i8,
+    pub leadership: i8,
+    pub wisdom: i8,
+    pub marksmanship: i8,
+    pub mechanical: i8,
+    pub explosive: i8,
+    pub medical: i8,
+    pub exp_level: i8,
+}
+
+/// Experience gained or stat points changed, per stat.
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+pub struct StatChanges<T> {
+    pub exp_level: T,
+    pub life: T,
+    pub agility: T,
+    pub dexterity: T,
+    pub strength: T,
+    pub leadership: T,
+    pub wisdom: T,
+    pub marksmanship: T,
+    pub mechanical: T,
+    pub explosive: T,
+    pub medical: T,
+}
+
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+pub struct InventorySlot {
+    pub item: u16,
+    pub status: u8,
+    pub number: u8,
+}
+
+/// A record of `prof.dat`, `MERCPROFILESTRUCT` in C++. Fields are named after it without the type prefixes.
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+pub struct MercProfile {
+    pub name: String,
+    pub nickname: String,
+    pub face_index: u8,
+    pub eyes_x: u16,
+    pub eyes_y: u16,
+    pub mouth_x: u16,
+    pub mouth_y: u16,
+    pub blink_frequency: u32,
+    pub expression_frequency: u32,
+    pub pants: String,
+    pub vest: String,
+    pub skin: String,
+    pub hair: String,
+    pub sex: i8,
+    pub body_type: u8,
+    pub body_type_sub_flags: u32,
+
+    pub stats: Stats,
+    pub stat_gains: StatChanges<i16>,
+    pub stat_deltas: StatChanges<i8>,
+    pub stat_change_chances: Vec<u16>,
+    pub stat_change_successes: Vec<u16>,
+    pub evolution: i8,
+
+    pub personality_trait: i8,
+    pub skill_trait: i8,
+    pub skill_trait2: i8,
+    pub attitude: i8,
+    pub sexist: u8,
+    pub racist: i8,
+    pub race: i8,
+    pub nationality: i8,
+    pub hated_nationality: i8,
+    pub hated_nationality_care_level: i8,
+    pub appearance: i8,
+    pub appearance_care_level: i8,
+    pub refinement: i8,
+    pub refinement_care_level: i8,
+    pub reputation_tolerance: i8,
+    pub death_rate: i8,
+    pub need_for_sleep: u8,
+
+    pub salary: i16,
+    pub weekly_salary: u32,
+    pub bi_weekly_salary: u32,
+    pub medical_deposit: i8,
+    pub medical_deposit_amount: u16,
+    pub optional_gear_cost: u16,
+    pub merc_merc_contract_length: i32,
+    pub total_cost_to_date: u32,
+    pub balance: i32,
+    pub money: u32,
+
+    pub inventory: Vec<InventorySlot>,
+    pub inv_undroppable: u8,
+    pub main_gun_attractiveness: i8,
+    pub armour_attractiveness: i8,
+
+    pub buddy: Vec<i8>,
+    pub hated: Vec<i8>,
+    pub hated_time: Vec<i8>,
+    pub hated_count: Vec<i8>,
+    pub learn_to_like: i8,
+    pub learn_to_like_time: i8,
+    pub learn_to_like_count: i8,
+    pub learn_to_hate: i8,
+    pub learn_to_hate_time: i8,
+    pub learn_to_hate_count: i8,
+    pub merc_opinion: Vec<i8>,
+    pub time_till_next_hated_complaint: u8,
+
+    pub sector_x: u16,
+    pub sector_y: u16,
+    pub sector_z: i8,
+    pub town: i8,
+    pub town_attachment: i8,
+    pub day_becomes_available: u32,
+    pub use_profile_insertion_info: bool,
+    pub grid_no: i16,
+    pub pre_combat_grid_no: i16,
+    pub strategic_insertion_code: u8,
+    pub strategic_insertion_data: u16,
+    pub room_range_start: Vec<u8>,
+    pub room_range_end: Vec<u8>,
+    pub civilian_group: u8,
+
+    pub quote_record: u8,
+    pub quote_action_id: u8,
+    pub last_quote_said: u8,
+    pub last_quote_said_was_special: u8,
+    pub precedent_quote_said: u32,
+    pub last_date_spoken_to: u8,
+    pub approached: i8,
+    pub approach_factor: Vec<u16>,
+    pub approach_val: Vec<u8>,
+    pub approach_mod: Vec<Vec<u8>>,
+    pub friendly_or_direct_default_response_used_recently: i8,
+    pub recruit_default_response_used_recently: i8,
+    pub threaten_default_response_used_recently: i8,
+    pub npc_data: i8,
+    pub npc_data2: i8,
+
+    pub merc_status: i8,
+    pub misc_flags: u8,
+    pub misc_flags2: u8,
+    pub misc_flags3: u8,
+    pub kills: u16,
+    pub assists: u16,
+    pub shots_fired: u16,
+    pub shots_hit: u16,
+    pub battles_fought: u16,
+    pub times_wounded: u16,
+    pub total_days_served: u16,
+    pub days_of_morale_hangover: u8,
+    pub num_times_drug_use_in_lifetime: u8,
+    pub suspicious_death: u8,
+}
+
+impl MercProfile {
+    /// The checksum the game stores with a profile, `SoldierProfileChecksum` in C++.
+    pub fn checksum(&self) -> u32 {
+        let s = &self.stats;
+        let mut sum = 1u32;
+        let term = |stat: i8| (1 + i32::from(stat)) as u32;
+
+        sum = sum.wrapping_add(term(s.life));
+        sum = sum.wrapping_mul(term(s.life_max));
+        sum = sum.wrapping_add(term(s.agility));
+        sum = sum.wrapping_mul(term(s.dexterity));
+        sum = sum.wrapping_add(term(s.strength));
+        sum = sum.wrapping_mul(term(s.marksmanship));
+        sum = sum.wrapping_add(term(s.medical));
+        sum = sum.wrapping_mul(term(s.mechanical));
+        sum = sum.wrapping_add(term(s.explosive));
+        sum = sum.wrapping_mul(term(s.exp_level));
+        for slot in &self.inventory {
+            sum = sum.wrapping_add(u32::from(slot.item));
+        }
+        for slot in &self.inventory {
+            sum = sum.wrapping_add(u32::from(slot.number));
+        }
+        sum
+    }
+}
+
+/// Fixes the names of the Russian versions, see `RussianEncodingCorrector`.
+fn fix_name_char(code: u16, version: ResourceVersion) -> u16 {
+    match version {
+        ResourceVersion::RUSSIAN | ResourceVersion::RUSSIAN_GOLD if (0xC0..=0xFF).contains(&code) => code + 0x350,
+        _ => code,
+    }
+}
+
+fn unfix_name_char(code: u16, version: ResourceVersion) -> u16 {
+    match version {
+        ResourceVersion::RUSSIAN | ResourceVersion::RUSSIAN_GOLD if (0x410..=0x44F).contains(&code) => code - 0x350,
+        _ => code,
+    }
+}
+
+struct RecordReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> RecordReader<'a> {
+    fn bytes(&mut self, len: usize) -> &'a [u8] {
+        let bytes = &self.data[self.position..self.position + len];
+        self.position += len;
+        bytes
+    }
+
+    fn skip(&mut self, len: usize) {
+        self.position += len;
+    }
+
+    fn u8(&mut self) -> u8 {
+        self.bytes(1)[0]
+    }
+
+    fn i8(&mut self) -> i8 {
+        self.u8() as i8
+    }
+
+    fn u16(&mut self) -> u16 {
+        let b = self.bytes(2);
+        u16::from(b[0]) | (u16::from(b[1]) << 8)
+    }
+
+    fn i16(&mut self) -> i16 {
+        self.u16() as i16
+    }
+
+    fn u32(&mut self) -> u32 {
+        u32::from(self.u16()) | (u32::from(self.u16()) << 16)
+    }
+
+    fn i32(&mut self) -> i32 {
+        self.u32() as i32
+    }
+
+    fn u8s(&mut self, len: usize) -> Vec<u8> {
+        self.bytes(len).to_vec()
+    }
+
+    fn i8s(&mut self, len: usize) -> Vec<i8> {
+        self.bytes(len).iter().map(|b| *b as i8).collect()
+    }
+
+    fn u16s(&mut self, len: usize) -> Vec<u16> {
+        (0..len).map(|_| self.u16()).collect()
+    }
+
+    fn utf16(&mut self, len: usize, version: ResourceVersion) -> String {
+        let codes: Vec<u16> = self.u16s(len).into_iter().take_while(|c| *c != 0).map(|c| fix_name_char(c, version)).collect();
+        String::from_utf16_lossy(&codes)
+    }
+
+    fn c_string(&mut self, len: usize) -> String {
+        String::from_utf8_lossy(self.bytes(len).split(|b| *b == 0).next().unwrap_or(&[])).into_owned()
+    }
+}
+
+struct RecordWriter {
+    data: Vec<u8>,
+}
+
+impl RecordWriter {
+    fn skip(&mut self, len: usize) {
+        self.data.extend(::std::iter::repeat(0).take(len));
+    }
+
+    fn u8(&mut self, value: u8) {
+        self.data.push(value);
+    }
+
+    fn i8(&mut self, value: i8) {
+        self.u8(value as u8);
+    }
+
+    fn u16(&mut self, value: u16) {
+        self.data.push(value as u8);
+        self.data.push((value >> 8) as u8);
+    }
+
+    fn i16(&mut self, value: i16) {
+        self.u16(value as u16);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.u16(value as u16);
+        self.u16((value >> 16) as u16);
+    }
+
+    fn i32(&mut self, value: i32) {
+        self.u32(value as u32);
+    }
+
+    fn check_len(field: &str, actual: usize, expected: usize) -> Result<(), String> {
+        if actual != expected {
+            return Err(format!("'{}' must have {} entries, found {}", field, expected, actual));
+        }
+        Ok(())
+    }
+
+    fn u8s(&mut self, field: &str, values: &[u8], len: usize) -> Result<(), String> {
+        RecordWriter::check_len(field, values.len(), len)?;
+        self.data.extend_from_slice(values);
+        Ok(())
+    }
+
+    fn i8s(&mut self, field: &str, values: &[i8], len: usize) -> Result<(), String> {
+        RecordWriter::check_len(field, values.len(), len)?;
+        self.data.extend(values.iter().map(|v| *v as u8));
+        Ok(())
+    }
+
+    fn u16s(&mut self, field: &str, values: &[u16], len: usize) -> Result<(), String> {
+        RecordWriter::check_len(field, values.len(), len)?;
+        for v in values {
+            self.u16(*v);
+        }
+        Ok(())
+    }
+
+    fn utf16(&mut self, field: &str, value: &str, len: usize, version: ResourceVersion) -> Result<(), String> {
+        let codes: Vec<u16> = value.encode_utf16().map(|c| unfix_name_char(c, version)).collect();
+        if codes.len() >= len {
+            return Err(format!("'{}' is longer than {} characters: {}", field, len - 1, value));
+        }
+        self.u16s(field, &codes, codes.len())?;
+        self.skip((len - codes.len()) * 2);
+        Ok(())
+    }
+
+    fn c_string(&mut self, field: &str, value: &str, len: usize) -> Result<(), String> {
+        if value.len() >= len {
+            return Err(format!("'{}' is longer than {} bytes: {}", field, len - 1, value));
+        }
+        self.data.extend_from_slice(value.as_bytes());
+        self.skip(len - value.len());
+        Ok(())
+    }
+}
+
+/// Parses one decrypted record.
+pub fn parse_profile(data: &[u8], version: ResourceVersion) -> Result<MercProfile, String> {
+    if data.len() != MERC_PROFILE_SIZE {
+        return Err(format!("A profile has {} bytes, found {}", MERC_PROFILE_SIZE, data.len()));
+    }
+    let mut r = RecordReader { data, position: 0 };
+    let mut p = MercProfile {
+        name: r.utf16(NAME_LENGTH, version),
+        nickname: r.utf16(NICKNAME_LENGTH, version),
+        ..Default::default()
+    };
+
+    r.skip(28);
+    p.face_index = r.u8();
+    p.pants = r.c_string(PALETTE_LENGTH);
+    p.vest = r.c_string(PALETTE_LENGTH);
+    p.skin = r.c_string(PALETTE_LENGTH);
+    p.hair = r.c_string(PALETTE_LENGTH);
+    p.sex = r.i8();
+    p.armour_attractiveness = r.i8();
+    p.misc_flags2 = r.u8();
+    p.evolution = r.i8();
+    p.misc_flags = r.u8();
+    p.sexist = r.u8();
+    p.learn_to_hate = r.i8();
+    r.skip(2);
+    p.quote_record = r.u8();
+    p.death_rate = r.i8();
+    r.skip(2);
+    p.stat_gains.exp_level = r.i16();
+    p.stat_gains.life = r.i16();
+    p.stat_gains.agility = r.i16();
+    p.stat_gains.dexterity = r.i16();
+    p.stat_gains.wisdom = r.i16();
+    p.stat_gains.marksmanship = r.i16();
+    p.stat_gains.medical = r.i16();
+    p.stat_gains.mechanical = r.i16();
+    p.stat_gains.explosive = r.i16();
+    p.body_type = r.u8();
+    p.stats.medical = r.i8();
+    p.eyes_x = r.u16();
+    p.eyes_y = r.u16();
+    p.mouth_x = r.u16();
+    p.mouth_y = r.u16();
+    r.skip(10);
+    p.blink_frequency = r.u32();
+    p.expression_frequency = r.u32();
+    p.sector_x = r.u16();
+    p.sector_y = r.u16();
+    p.day_becomes_available = r.u32();
+    p.stats.strength = r.i8();
+    p.stats.life_max = r.i8();
+    p.stat_deltas.exp_level = r.i8();
+    p.stat_deltas.life = r.i8();
+    p.stat_deltas.agility = r.i8();
+    p.stat_deltas.dexterity = r.i8();
+    p.stat_deltas.wisdom = r.i8();
+    p.stat_deltas.marksmanship = r.i8();
+    p.stat_deltas.medical = r.i8();
+    p.stat_deltas.mechanical = r.i8();
+    p.stat_deltas.explosive = r.i8();
+    p.stat_deltas.strength = r.i8();
+    p.stat_deltas.leadership = r.i8();
+    r.skip(1);
+    p.kills = r.u16();
+    p.assists = r.u16();
+    p.shots_fired = r.u16();
+    p.shots_hit = r.u16();
+    p.battles_fought = r.u16();
+    p.times_wounded = r.u16();
+    p.total_days_served = r.u16();
+    p.stat_gains.leadership = r.i16();
+    p.stat_gains.strength = r.i16();
+    p.body_type_sub_flags = r.u32();
+    p.salary = r.i16();
+    p.stats.life = r.i8();
+    p.stats.dexterity = r.i8();
+    p.personality_trait = r.i8();
+    p.skill_trait = r.i8();
+    p.reputation_tolerance = r.i8();
+    p.stats.explosive = r.i8();
+    p.skill_trait2 = r.i8();
+    p.stats.leadership = r.i8();
+    p.buddy = r.i8s(5);
+    p.hated = r.i8s(5);
+    p.stats.exp_level = r.i8();
+    p.stats.marksmanship = r.i8();
+    r.skip(1);
+    p.stats.wisdom = r.i8();
+    r.skip(2);
+    let inv_status = r.u8s(INVENTORY_SLOTS);
+    let inv_number = r.u8s(INVENTORY_SLOTS);
+    p.approach_factor = r.u16s(4);
+    p.main_gun_attractiveness = r.i8();
+    p.stats.agility = r.i8();
+    p.use_profile_insertion_info = r.u8() != 0;
+    r.skip(1);
+    p.grid_no = r.i16();
+    p.quote_action_id = r.u8();
+    p.stats.mechanical = r.i8();
+    p.inv_undroppable = r.u8();
+    p.room_range_start = r.u8s(2);
+    r.skip(1);
+    let inv_items = r.u16s(INVENTORY_SLOTS);
+    r.skip(20);
+    p.stat_change_chances = r.u16s(12);
+    p.stat_change_successes = r.u16s(12);
+    p.strategic_insertion_code = r.u8();
+    p.room_range_end = r.u8s(2);
+    r.skip(4);
+    p.last_quote_said = r.u8();
+    p.race = r.i8();
+    p.nationality = r.i8();
+    p.appearance = r.i8();
+    p.appearance_care_level = r.i8();
+    p.refinement = r.i8();
+    p.refinement_care_level = r.i8();
+    p.hated_nationality = r.i8();
+    p.hated_nationality_care_level = r.i8();
+    p.racist = r.i8();
+    r.skip(1);
+    p.weekly_salary = r.u32();
+    p.bi_weekly_salary = r.u32();
+    p.medical_deposit = r.i8();
+    p.attitude = r.i8();
+    r.skip(2);
+    p.medical_deposit_amount = r.u16();
+    p.learn_to_like = r.i8();
+    p.approach_val = r.u8s(4);
+    p.approach_mod = (0..3).map(|_| r.u8s(4)).collect();
+    p.town = r.i8();
+    p.town_attachment = r.i8();
+    r.skip(1);
+    p.optional_gear_cost = r.u16();
+    p.merc_opinion = r.i8s(75);
+    p.approached = r.i8();
+    p.merc_status = r.i8();
+    p.hated_time = r.i8s(5);
+    p.learn_to_like_time = r.i8();
+    p.learn_to_hate_time = r.i8();
+    p.hated_count = r.i8s(5);
+    p.learn_to_like_count = r.i8();
+    p.learn_to_hate_count = r.i8();
+    p.last_date_spoken_to = r.u8();
+    p.last_quote_said_was_special = r.u8();
+    p.sector_z = r.i8();
+    p.strategic_insertion_data = r.u16();
+    p.friendly_or_direct_default_response_used_recently = r.i8();
+    p.recruit_default_response_used_recently = r.i8();
+    p.threaten_default_response_used_recently = r.i8();
+    p.npc_data = r.i8();
+    p.balance = r.i32();
+    r.skip(2);
+    p.civilian_group = r.u8();
+    p.need_for_sleep = r.u8();
+    p.money = r.u32();
+    p.npc_data2 = r.i8();
+    p.misc_flags3 = r.u8();
+    p.days_of_morale_hangover = r.u8();
+    p.num_times_drug_use_in_lifetime = r.u8();
+    p.precedent_quote_said = r.u32();
+    // the checksum is not checked, like in the game
+    r.u32();
+    p.pre_combat_grid_no = r.i16();
+    p.time_till_next_hated_complaint = r.u8();
+    p.suspicious_death = r.u8();
+    p.merc_merc_contract_length = r.i32();
+    p.total_cost_to_date = r.u32();
+    r.skip(4);
+    debug_assert_eq!(r.position, MERC_PROFILE_SIZE);
+
+    p.inventory = (0..INVENTORY_SLOTS)
+        .map(|i| InventorySlot { item: inv_items[i], status: inv_status[i], number: inv_number[i] })
+        .collect();
+    Ok(p)
+}
+
+/// Serializes a profile into an unencrypted record.
+pub fn serialize_profile(p: &MercProfile, version: ResourceVersion) -> Result<Vec<u8>, String> {
+    let mut w = RecordWriter { data: Vec::with_capacity(MERC_PROFILE_SIZE) };
+
+    RecordWriter::check_len("inventory", p.inventory.len(), INVENTORY_SLOTS)?;
+    RecordWriter::check_len("approach_mod", p.approach_mod.len(), 3)?;
+
+    w.utf16("name", &p.name, NAME_LENGTH, version)?;
+    w.utf16("nickname", &p.nickname, NICKNAME_LENGTH, version)?;
+    w.skip(28);
+    w.u8(p.face_index);
+    w.c_string("pants", &p.pants, PALETTE_LENGTH)?;
+    w.c_string("vest", &p.vest, PALETTE_LENGTH)?;
+    w.c_string("skin", &p.skin, PALETTE_LENGTH)?;
+    w.c_string("hair", &p.hair, PALETTE_LENGTH)?;
+    w.i8(p.sex);
+    w.i8(p.armour_attractiveness);
+    w.u8(p.misc_flags2);
+    w.i8(p.evolution);
+    w.u8(p.misc_flags);
+    w.u8(p.sexist);
+    w.i8(p.learn_to_hate);
+    w.skip(2);
+    w.u8(p.quote_record);
+    w.i8(p.death_rate);
+    w.skip(2);
+    w.i16(p.stat_gains.exp_level);
+    w.i16(p.stat_gains.life);
+    w.i16(p.stat_gains.agility);
+    w.i16(p.stat_gains.dexterity);
+    w.i16(p.stat_gains.wisdom);
+    w.i16(p.stat_gains.marksmanship);
+    w.i16(p.stat_gains.medical);
+    w.i16(p.stat_gains.mechanical);
+    w.i16(p.stat_gains.explosive);
+    w.u8(p.body_type);
+    w.i8(p.stats.medical);
+    w.u16(p.eyes_x);
+    w.u16(p.eyes_y);
+    w.u16(p.mouth_x);
+    w.u16(p.mouth_y);
+    w.skip(10);
+    w.u32(p.blink_frequency);
+    w.u32(p.expression_frequency);
+    w.u16(p.sector_x);
+    w.u16(p.sector_y);
+    w.u32(p.day_becomes_available);
+    w.i8(p.stats.strength);
+    w.i8(p.stats.life_max);
+    w.i8(p.stat_deltas.exp_level);
+    w.i8(p.stat_deltas.life);
+    w.i8(p.stat_deltas.agility);
+    w.i8(p.stat_deltas.dexterity);
+    w.i8(p.stat_deltas.wisdom);
+    w.i8(p.stat_deltas.marksmanship);
+    w.i8(p.stat_deltas.medical);
+    w.i8(p.stat_deltas.mechanical);
+    w.i8(p.stat_deltas.explosive);
+    w.i8(p.stat_deltas.strength);
+    w.i8(p.stat_deltas.leadership);
+    w.skip(1);
+    w.u16(p.kills);
+    w.u16(p.assists);
+    w.u16(p.shots_fired);
+    w.u16(p.shots_hit);
+    w.u16(p.battles_fought);
+    w.u16(p.times_wounded);
+    w.u16(p.total_days_served);
+    w.i16(p.stat_gains.leadership);
+    w.i16(p.stat_gains.strength);
+    w.u32(p.body_type_sub_flags);
+    w.i16(p.salary);
+    w.i8(p.stats.life);
+    w.i8(p.stats.dexterity);
+    w.i8(p.personality_trait);
+    w.i8(p.skill_trait);
+    w.i8(p.reputation_tolerance);
+    w.i8(p.stats.explosive);
+    w.i8(p.skill_trait2);
+    w.i8(p.stats.leadership);
+    w.i8s("buddy", &p.buddy, 5)?;
+    w.i8s("hated", &p.hated, 5)?;
+    w.i8(p.stats.exp_level);
+    w.i8(p.stats.marksmanship);
+    w.skip(1);
+    w.i8(p.stats.wisdom);
+    w.skip(2);
+    for slot in &p.inventory {
+        w.u8(slot.status);
+    }
+    for slot in &p.inventory {
+        w.u8(slot.number);
+    }
+    w.u16s("approach_factor", &p.approach_factor, 4)?;
+    w.i8(p.main_gun_attractiveness);
+    w.i8(p.stats.agility);
+    w.u8(p.use_profile_insertion_info as u8);
+    w.skip(1);
+    w.i16(p.grid_no);
+    w.u8(p.quote_action_id);
+    w.i8(p.stats.mechanical);
+    w.u8(p.inv_undroppable);
+    w.u8s("room_range_start", &p.room_range_start, 2)?;
+    w.skip(1);
+    for slot in &p.inventory {
+        w.u16(slot.item);
+    }
+    w.skip(20);
+    w.u16s("stat_change_chances", &p.stat_change_chances, 12)?;
+    w.u16s("stat_change_successes", &p.stat_change_successes, 12)?;
+    w.u8(p.strategic_insertion_code);
+    w.u8s("room_range_end", &p.room_range_end, 2)?;
+    w.skip(4);
+    w.u8(p.last_quote_said);
+    w.i8(p.race);
+    w.i8(p.nationality);
+    w.i8(p.appearance);
+    w.i8(p.appearance_care_level);
+    w.i8(p.refinement);
+    w.i8(p.refinement_care_level);
+    w.i8(p.hated_nationality);
+    w.i8(p.hated_nationality_care_level);
+    w.i8(p.racist);
+    w.skip(1);
+    w.u32(p.weekly_salary);
+    w.u32(p.bi_weekly_salary);
+    w.i8(p.medical_deposit);
+    w.i8(p.attitude);
+    w.skip(2);
+    w.u16(p.medical_deposit_amount);
+    w.i8(p.learn_to_like);
+    w.u8s("approach_val", &p.approach_val, 4)?;
+    for row in &p.approach_mod {
+        w.u8s("approach_mod", row, 4)?;
+    }
+    w.i8(p.town);
+    w.i8(p.town_attachment);
+    w.skip(1);
+    w.u16(p.optional_gear_cost);
+    w.i8s("merc_opinion", &p.merc_opinion, 75)?;
+    w.i8(p.approached);
+    w.i8(p.merc_status);
+    w.i8s("hated_time", &p.hated_time, 5)?;
+    w.i8(p.learn_to_like_time);
+    w.i8(p.learn_to_hate_time);
+    w.i8s("hated_count", &p.hated_count, 5)?;
+    w.i8(p.learn_to_like_count);
+    w.i8(p.learn_to_hate_count);
+    w.u8(p.last_date_spoken_to);
+    w.u8(p.last_quote_said_was_special);
+    w.i8(p.sector_z);
+    w.u16(p.strategic_insertion_data);
+    w.i8(p.friendly_or_direct_default_response_used_recently);
+    w.i8(p.recruit_default_response_used_recently);
+    w.i8(p.threaten_default_response_used_recently);
+    w.i8(p.npc_data);
+    w.i32(p.balance);
+    w.skip(2);
+    w.u8(p.civilian_group);
+    w.u8(p.need_for_sleep);
+    w.u32(p.money);
+    w.i8(p.npc_data2);
+    w.u8(p.misc_flags3);
+    w.u8(p.days_of_morale_hangover);
+    w.u8(p.num_times_drug_use_in_lifetime);
+    w.u32(p.precedent_quote_said);
+    w.u32(p.checksum());
+    w.i16(p.pre_combat_grid_no);
+    w.u8(p.time_till_next_hated_complaint);
+    w.u8(p.suspicious_death);
+    w.i32(p.merc_merc_contract_length);
+    w.u32(p.total_cost_to_date);
+    w.skip(4);
+    debug_assert_eq!(w.data.len(), MERC_PROFILE_SIZE);
+
+    Ok(w.data)
+}
+
+/// Parses the encrypted records of `prof.dat`.
+pub fn parse_profiles(data: &[u8], version: ResourceVersion) -> Result<Vec<MercProfile>, String> {
+    if data.len() % MERC_PROFILE_SIZE != 0 {
+        return Err(format!("Size {} is not a multiple of the profile size {}", data.len(), MERC_PROFILE_SIZE));
+    }
+
+    data.chunks(MERC_PROFILE_SIZE).enumerate()
+        .map(|(i, record)| parse_profile(&decrypt(record), version).map_err(|e| format!("Profile {}: {}", i, e)))
+        .collect()
+}
+
+/// Serializes profiles into the encrypted format of `prof.dat`.
+pub fn serialize_profiles(profiles: &[MercProfile], version: ResourceVersion) -> Result<Vec<u8>, String> {
+    let mut data = Vec::with_capacity(profiles.len() * MERC_PROFILE_SIZE);
+
+    for (i, profile) in profiles.iter().enumerate() {
+        let record = serialize_profile(profile, version).map_err(|e| format!("Profile {} ({}): {}", i, profile.nickname, e))?;
+        data.extend(encrypt(&record));
+    }
+    Ok(data)
+}
+
+pub fn read_profiles_file(path: &Path, version: ResourceVersion) -> Result<Vec<MercProfile>, String> {
+    let mut data = vec!();
+    File::open(path)
+        .and_then(|mut f| f.read_to_end(&mut data))
+        .map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+
+    parse_profiles(&data, version).map_err(|e| format!("Error parsing {}: {}", path.display(), e))
+}
+
+pub fn write_profiles_file(path: &Path, profiles: &[MercProfile], version: ResourceVersion) -> Result<(), String> {
+    let data = serialize_profiles(profiles, version)?;
+
+    File::create(path)
+        .and_then(|mut f| f.write_all(&data))
+        .map_err(|e| format!("Error writing {}: {}", path.display(), e))
+}
+
+/// Exports the profiles as a JSON array, the index in the array is the profile ID.
+pub fn profiles_to_json(profiles: &[MercProfile]) -> Result<String, String> {
+    let mut json = serde_json::to_string_pretty(profiles).map_err(|e| format!("Error serializing profiles: {}", e))?;
+
+    json.push('\n');
+    Ok(json)
+}
+
+pub fn profiles_from_json(json: &str) -> Result<Vec<MercProfile>, String> {
+    serde_json::from_str(json).map_err(|e| format!("Error parsing profiles: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InventorySlot, MercProfile, MERC_PROFILE_SIZE};
+    use ResourceVersion;
+
+    fn profile() -> MercProfile {
+        let mut p = MercProfile {
+            name: String::from("Ivan Dolvich"),
+            nickname: String::from("Ivan"),
+            pants: String::from("GREENPANTS"),
+            vest: String::from("JEANVEST"),
+            skin: String::from("PINKSKIN"),
+            hair: String::from("BROWNHEAD"),
+            salary: 1250,
+            weekly_salary: 7500,
+            bi_weekly_salary: 14000,
+            stat_change_chances: vec!(0; 12),
+            stat_change_successes: (1..13).collect(),
+            inventory: vec!(InventorySlot::default(); 19),
+            buddy: vec!(-1, 2, 3, 4, 5),
+            hated: vec!(1, 2, 3, 4, -1),
+            hated_time: vec!(0; 5),
+            hated_count: vec!(0; 5),
+            merc_opinion: (0..75).map(|i| i - 37).collect(),
+            room_range_start: vec!(1, 2),
+            room_range_end: vec!(3, 4),
+            approach_factor: vec!(100, 200, 300, 400),
+            approach_val: vec!(5, 6, 7, 8),
+            approach_mod: vec!(vec!(1, 2, 3, 4), vec!(5, 6, 7, 8), vec!(9, 10, 11, 12)),
+            balance: -500,
+            total_cost_to_date: 123_456,
+            use_profile_insertion_info: true,
+            ..Default::default()
+        };
+        p.stats.life = 89;
+        p.stats.life_max = 91;
+        p.stats.marksmanship = 93;
+        p.stats.exp_level = 5;
+        p.stat_gains.strength = -3;
+        p.stat_deltas.leadership = 2;
+        p.inventory[0] = InventorySlot { item: 1, status: 100, number: 1 };
+        p.inventory[18] = InventorySlot { item: 351, status: 97, number: 2 };
+        p
+    }
+
+    #[test]
+    fn encrypt_should_be_the_inverse_of_decrypt() {
+        let data: Vec<u8> = (0..MERC_PROFILE_SIZE).map(|i| (i * 7) as u8).collect();
+
+        assert_ne!(super::encrypt(&data), data);
+        assert_eq!(super::decrypt(&super::encrypt(&data)), data);
+        assert_eq!(super::encrypt(&[0, 0]), vec!(132, 111));
+    }
+
+    #[test]
+    fn serialize_profile_should_produce_a_record_that_parses_back() {
+        let record = super::serialize_profile(&profile(), ResourceVersion::ENGLISH).unwrap();
+
+        assert_eq!(record.len(), MERC_PROFILE_SIZE);
+        assert_eq!(&record[0..4], &[b'I', 0, b'v', 0]);
+        assert_eq!(super::parse_profile(&record, ResourceVersion::ENGLISH), Ok(profile()));
+    }
+
+    #[test]
+    fn serialize_profiles_should_round_trip_through_the_encrypted_format_and_json() {
+        let profiles = vec!(profile(), MercProfile { nickname: String::from("Shadow"), ..profile() });
+        let data = super::serialize_profiles(&profiles, ResourceVersion::GERMAN).unwrap();
+
+        assert_eq!(data.len(), 2 * MERC_PROFILE_SIZE);
+        let parsed = super::parse_profiles(&data, ResourceVersion::GERMAN).unwrap();
+        assert_eq!(parsed, profiles);
+
+        let json = super::profiles_to_json(&parsed).unwrap();
+        assert!(json.contains("\"nickname\": \"Shadow\""));
+        assert_eq!(super::profiles_from_json(&json), Ok(profiles));
+    }
+
+    #[test]
+    fn parse_profile_should_fix_russian_names() {
+        let mut p = profile();
+        p.nickname = String::from("Иван");
+        let record = super::serialize_profile(&p, ResourceVersion::RUSSIAN).unwrap();
+
+        assert_eq!(&record[60..62], &[0xC8, 0]);
+        assert_eq!(super::parse_profile(&record, ResourceVersion::RUSSIAN).unwrap().nickname, "Иван");
+    }
+
+    #[test]
+    fn serialize_profile_should_validate_lengths() {
+        let mut p = profile();
+        p.nickname = String::from("Much too long");
+        assert_eq!(super::serialize_profile(&p, ResourceVersion::ENGLISH), Err(String::from("'nickname' is longer than 9 characters: Much too long")));
+
+        let mut p = profile();
+        p.buddy.pop();
+        assert_eq!(super::serialize_profile(&p, ResourceVersion::ENGLISH), Err(String::from("'buddy' must have 5 entries, found 4")));
+    }
+
+    #[test]
+    fn checksum_should_match_soldier_profile_checksum() {
+        let p = profile();
+        let mut expected = 1u32;
+        expected += 1 + 89;
+        expected *= 1 + 91;
+        expected += 1;
+        expected *= 1;
+        expected += 1;
+        expected *= 1 + 93;
+        expected += 1;
+        expected *= 1;
+        expected += 1;
+        expected *= 1 + 5;
+        expected += 1 + 351 + 1 + 2;
+
+        assert_eq!(p.checksum(), expected);
+        assert!(super::parse_profiles(&[0; 10], ResourceVersion::ENGLISH).is_err());
+    }
+}
@@ -0,0 +1,264 @@
+//! The random number generator behind `Random` and `PreRandom` in `sgp/Random.cc`.
+//!
+//! It is a PCG32 generator (XSH RR with a 64-bit state), so the same seed produces the same numbers on every platform.
+//! The seed is written to the log at startup, whatever the log levels are, and can be given with `--seed` to replay a
+//! game, e.g. a combat or an auto-resolve battle.
+//!
+//! The state of the generator and the table of pregenerated `PreRandom` numbers are stored in the save games, so
+//! loading a game repeats the numbers that followed when it was saved and reloading does not change the outcome of the
+//! next shot.
+
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The number of pregenerated numbers, changing it invalidates the save games.
+pub const MAX_PREGENERATED_NUMS: usize = 256;
+
+const MULTIPLIER: u64 = 6_364_136_223_846_793_005;
+/// The stream of `pcg32_random` of the reference implementation, its increment is `2 * DEFAULT_STREAM + 1`.
+const DEFAULT_STREAM: u64 = 721_347_520_444_481_703;
+
+const fn step(state: u64, increment: u64) -> u64 {
+    state.wrapping_mul(MULTIPLIER).wrapping_add(increment)
+}
+
+const fn output(state: u64) -> u32 {
+    ((((state >> 18) ^ state) >> 27) as u32).rotate_right((state >> 59) as u32)
+}
+
+/// A PCG32 generator, its whole state is the `state` and the odd `increment` that selects the stream.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pcg32 {
+    state: u64,
+    increment: u64,
+}
+
+impl Pcg32 {
+    /// Seeds the generator like `pcg32_srandom` of the reference implementation.
+    pub const fn new(seed: u64) -> Pcg32 {
+        Pcg32::with_stream(seed, DEFAULT_STREAM)
+    }
+
+    /// Seeds the generator like `pcg32_srandom_r`, different streams give different numbers for the same seed.
+    pub const fn with_stream(seed: u64, stream: u64) -> Pcg32 {
+        let increment = (stream << 1) | 1;
+        Pcg32 { state: step(step(0, increment).wrapping_add(seed), increment), increment }
+    }
+
+    /// Restores a generator from `state()` and `increment()`.
+    pub fn from_parts(state: u64, increment: u64) -> Pcg32 {
+        Pcg32 { state, increment: increment | 1 }
+    }
+
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    pub fn increment(&self) -> u64 {
+        self.increment
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let old_state = self.state;
+        self.state = step(old_state, self.increment);
+        output(old_state)
+    }
+
+    /// A number in `0..range` without modulo bias, 0 if `range` is 0.
+    pub fn below(&mut self, range: u32) -> u32 {
+        if range == 0 {
+            return 0;
+        }
+        let threshold = range.wrapping_neg() % range;
+        loop {
+            let x = self.next_u32();
+            if x >= threshold {
+                return x % range;
+            }
+        }
+    }
+}
+
+/// The generator of `Random` and the table of `PreRandom`.
+#[derive(Debug, Clone)]
+pub struct RandomNumbers {
+    seed: u64,
+    generator: Pcg32,
+    pre_random_index: u32,
+    pre_random_nums: [u32; MAX_PREGENERATED_NUMS],
+}
+
+impl Default for RandomNumbers {
+    fn default() -> RandomNumbers {
+        RandomNumbers::new(0)
+    }
+}
+
+impl RandomNumbers {
+    /// Const for the static, so the table is generated with `step` and `output` instead of `Pcg32::next_u32`.
+    pub const fn new(seed: u64) -> RandomNumbers {
+        let generator = Pcg32::new(seed);
+        let mut state = generator.state;
+        let mut pre_random_nums = [0; MAX_PREGENERATED_NUMS];
+        let mut i = 0;
+        while i < MAX_PREGENERATED_NUMS {
+            pre_random_nums[i] = output(state);
+            state = step(state, generator.increment);
+            i += 1;
+        }
+        RandomNumbers { seed, generator: Pcg32 { state, increment: generator.increment }, pre_random_index: 0, pre_random_nums }
+    }
+
+    /// The seed of `new`.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// `Random`, a number in `0..range`.
+    pub fn random(&mut self, range: u32) -> u32 {
+        self.generator.below(range)
+    }
+
+    /// `PreRandom`, the next pregenerated number modulo `range`. Like the original the table is reused in a circle.
+    pub fn pre_random(&mut self, range: u32) -> u32 {
+        if range == 0 {
+            return 0;
+        }
+        let x = self.pre_random_nums[self.pre_random_index as usize] % range;
+        self.pre_random_index = (self.pre_random_index + 1) % MAX_PREGENERATED_NUMS as u32;
+        x
+    }
+
+    /// The state of the generator of `Random`, it is saved with the increment, the table with `pre_random_nums`.
+    pub fn generator_state(&self) -> u64 {
+        self.generator.state()
+    }
+
+    /// Sets the state, keeping the increment.
+    pub fn set_generator_state(&mut self, state: u64) {
+        self.generator = Pcg32::from_parts(state, self.generator.increment());
+    }
+
+    pub fn generator_increment(&self) -> u64 {
+        self.generator.increment()
+    }
+
+    /// Sets the increment, keeping the state.
+    pub fn set_generator_increment(&mut self, increment: u64) {
+        self.generator = Pcg32::from_parts(self.generator.state(), increment);
+    }
+
+    pub fn pre_random_index(&self) -> u32 {
+        self.pre_random_index
+    }
+
+    /// Sets the position in the table, out of range values start over at 0.
+    pub fn set_pre_random_index(&mut self, index: u32) {
+        self.pre_random_index = if (index as usize) < MAX_PREGENERATED_NUMS { index } else { 0 };
+    }
+
+    pub fn pre_random_nums(&self) -> &[u32; MAX_PREGENERATED_NUMS] {
+        &self.pre_random_nums
+    }
+
+    pub fn set_pre_random_nums(&mut self, nums: &[u32; MAX_PREGENERATED_NUMS]) {
+        self.pre_random_nums = *nums;
+    }
+}
+
+/// A seed from the current time for games started without `--seed`.
+pub fn seed_from_time() -> u64 {
+    let duration = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    duration.as_secs().wrapping_mul(1_000_000_000).wrapping_add(u64::from(duration.subsec_nanos()))
+}
+
+/// The random numbers of the game, used through the FFI functions of `Random.cc`.
+pub static RANDOM_NUMBERS: Mutex<RandomNumbers> = Mutex::new(RandomNumbers::new(0));
+
+#[cfg(test)]
+mod tests {
+    use super::{Pcg32, RandomNumbers, MAX_PREGENERATED_NUMS};
+
+    #[test]
+    fn pcg32_should_match_the_reference_implementation() {
+        // pcg32_srandom_r(&rng, 42, 54) of pcg-c-basic
+        let mut rng = Pcg32::with_stream(42, 54);
+
+        assert_eq!((0..6).map(|_| rng.next_u32()).collect::<Vec<u32>>(), vec!(0xa15c02b7, 0x7b47f409, 0xba1d3330, 0x83d2f293, 0xbfa4784b, 0xcbed606e));
+    }
+
+    #[test]
+    fn pcg32_should_continue_from_its_parts() {
+        let mut rng = Pcg32::with_stream(42, 54);
+        rng.next_u32();
+        let mut restored = Pcg32::from_parts(rng.state(), rng.increment());
+
+        assert_eq!(restored, rng);
+        assert_eq!((0..4).map(|_| restored.next_u32()).collect::<Vec<u32>>(), (0..4).map(|_| rng.next_u32()).collect::<Vec<u32>>());
+        assert_eq!(Pcg32::from_parts(1, 2).increment(), 3);
+        assert_ne!(Pcg32::with_stream(42, 1).next_u32(), Pcg32::new(42).next_u32());
+    }
+
+    #[test]
+    fn pcg32_should_be_stable() {
+        let mut rng = Pcg32::new(42);
+
+        assert_eq!((0..4).map(|_| rng.next_u32()).collect::<Vec<u32>>(), vec!(3270867926, 1795671209, 1924641435, 1143034755));
+    }
+
+    #[test]
+    fn below_should_stay_in_range() {
+        let mut rng = Pcg32::new(7);
+
+        assert_eq!(rng.below(0), 0);
+        assert_eq!(rng.below(1), 0);
+        let mut counts = [0; 6];
+        for _ in 0..6000 {
+            counts[rng.below(6) as usize] += 1;
+        }
+        assert!(counts.iter().all(|&c| c > 850 && c < 1150), "{:?}", counts);
+        assert!((0..1000).all(|_| rng.below(u32::MAX) < u32::MAX));
+    }
+
+    #[test]
+    fn random_numbers_should_be_reproducible_from_the_seed_and_the_state() {
+        let mut a = RandomNumbers::new(1234);
+        let mut b = RandomNumbers::new(1234);
+
+        let first: Vec<u32> = (0..20).map(|_| a.random(100)).collect();
+        assert_eq!(first, (0..20).map(|_| b.random(100)).collect::<Vec<u32>>());
+        assert_ne!(first, (0..20).map(|_| RandomNumbers::new(1235).random(100)).collect::<Vec<u32>>());
+
+        let state = a.generator_state();
+        let next: Vec<u32> = (0..20).map(|_| a.random(1000)).collect();
+        a.set_generator_state(state);
+        assert_eq!(next, (0..20).map(|_| a.random(1000)).collect::<Vec<u32>>());
+
+        // A loaded game
+        let mut loaded = RandomNumbers::new(1);
+        loaded.set_generator_increment(a.generator_increment());
+        loaded.set_generator_state(a.generator_state());
+        assert_eq!((0..20).map(|_| loaded.random(1000)).collect::<Vec<u32>>(), (0..20).map(|_| a.random(1000)).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn pre_random_should_cycle_through_the_table() {
+        let mut numbers = RandomNumbers::new(99);
+        let table = *numbers.pre_random_nums();
+        let mut rng = Pcg32::new(99);
+        assert!(table.iter().all(|&n| n == rng.next_u32()));
+        assert_eq!(numbers.generator_state(), rng.state());
+
+        assert_eq!(numbers.pre_random(0), 0);
+        assert_eq!(numbers.pre_random(100), table[0] % 100);
+        numbers.set_pre_random_index(MAX_PREGENERATED_NUMS as u32 - 1);
+        assert_eq!(numbers.pre_random(1000), table[MAX_PREGENERATED_NUMS - 1] % 1000);
+        assert_eq!(numbers.pre_random_index(), 0);
+        numbers.set_pre_random_index(5000);
+        assert_eq!(numbers.pre_random_index(), 0);
+
+        let mut other = RandomNumbers::new(1);
+        other.set_pre_random_nums(&table);
+        assert_eq!(other.pre_random(100), table[0] % 100);
+    }
+}
@@ -0,0 +1,201 @@
+//! Reading of the headers of saved games, as done by `ExtractSavedGameHeaderFromFile` in `SaveLoadGame.cc`.
+
+use std::fs;
+use std::fs::File;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+
+use EngineOptions;
+
+const GAME_VERSION_LENGTH: usize = 16;
+const SIZE_OF_SAVE_GAME_DESC: usize = 128;
+/// Enough bytes to cover every field of the header we are interested in, in both formats.
+const HEADER_PREFIX_SIZE: usize = 4 + GAME_VERSION_LENGTH + SIZE_OF_SAVE_GAME_DESC * 4 + 4 + 16;
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct SavedGameHeader {
+    pub saved_game_version: u32,
+    pub game_version: String,
+    pub description: String,
+    pub day: u32,
+    pub hour: u8,
+    pub minute: u8,
+    pub sector_x: i16,
+    pub sector_y: i16,
+    pub sector_z: i8,
+    pub number_of_mercs: u8,
+    pub balance: i32,
+    /// Whether the description is stored with 4 bytes per character (Stracciatella on Linux and macOS).
+    pub utf32_description: bool,
+}
+
+impl SavedGameHeader {
+    /// Basic plausibility check, the same as `isValidSavedGameHeader`.
+    pub fn is_valid(&self) -> bool {
+        if self.sector_x == 0 && self.sector_y == 0 && self.sector_z == -1 {
+            // sector N/A at the game start
+            self.day != 0 && self.balance >= 0
+        } else {
+            self.day != 0
+                && self.sector_x > 0 && self.sector_x <= 16
+                && self.sector_y > 0 && self.sector_y <= 16
+                && self.sector_z >= 0 && self.sector_z <= 3
+                && self.balance >= 0
+        }
+    }
+
+    /// Short sector name like `A9` or `D15-1`.
+    pub fn sector_name(&self) -> String {
+        if self.sector_x <= 0 || self.sector_y <= 0 {
+            return String::from("N/A");
+        }
+        let row = (b'A' + (self.sector_y - 1) as u8) as char;
+        match self.sector_z {
+            0 => format!("{}{}", row, self.sector_x),
+            z => format!("{}{}-{}", row, self.sector_x, z),
+        }
+    }
+}
+
+fn read_description(cursor: &mut Cursor<&[u8]>, utf32: bool) -> Result<String, String> {
+    let mut chars = String::new();
+    let mut ended = false;
+
+    for _ in 0..SIZE_OF_SAVE_GAME_DESC {
+        let code = if utf32 {
+            cursor.read_u32::<LittleEndian>()
+        } else {
+            cursor.read_u16::<LittleEndian>().map(u32::from)
+        }.map_err(|e| e.to_string())?;
+
+        if code == 0 {
+            ended = true;
+        }
+        if !ended {
+            chars.push(::std::char::from_u32(code).unwrap_or('?'));
+        }
+    }
+
+    Ok(chars)
+}
+
+fn parse_header(data: &[u8], utf32: bool) -> Result<SavedGameHeader, String> {
+    let mut cursor = Cursor::new(data);
+    let saved_game_version = cursor.read_u32::<LittleEndian>().map_err(|e| e.to_string())?;
+
+    let mut game_version = [0u8; GAME_VERSION_LENGTH];
+    cursor.read_exact(&mut game_version).map_err(|e| e.to_string())?;
+    let game_version = String::from_utf8_lossy(game_version.split(|b| *b == 0).next().unwrap_or(&[])).into_owned();
+
+    let description = read_description(&mut cursor, utf32)?;
+    cursor.set_position(cursor.position() + 4);
+
+    macro_rules! read { ($e:expr) => { $e.map_err(|e| e.to_string())? } }
+    Ok(SavedGameHeader {
+        saved_game_version,
+        game_version,
+        description,
+        day: read!(cursor.read_u32::<LittleEndian>()),
+        hour: read!(cursor.read_u8()),
+        minute: read!(cursor.read_u8()),
+        sector_x: read!(cursor.read_i16::<LittleEndian>()),
+        sector_y: read!(cursor.read_i16::<LittleEndian>()),
+        sector_z: read!(cursor.read_i8()),
+        number_of_mercs: read!(cursor.read_u8()),
+        balance: read!(cursor.read_i32::<LittleEndian>()),
+        utf32_description: utf32,
+    })
+}
+
+/// Reads the header of a saved game, trying the Stracciatella Linux format first and the vanilla format second.
+pub fn read_saved_game_header(path: &Path) -> Result<SavedGameHeader, String> {
+    let mut data = Vec::with_capacity(HEADER_PREFIX_SIZE);
+    File::open(path)
+        .and_then(|f| f.take(HEADER_PREFIX_SIZE as u64).read_to_end(&mut data))
+        .map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+
+    if let Ok(header) = parse_header(&data, true) {
+        if header.is_valid() {
+            return Ok(header);
+        }
+    }
+
+    parse_header(&data, false).map_err(|e| format!("Error parsing saved game header of {}: {}", path.display(), e))
+}
+
+/// The folder the game stores its saved games in, which depends on the first active mod.
+pub fn saved_games_dir(engine_options: &EngineOptions) -> PathBuf {
+    match engine_options.mods.first() {
+        Some(m) => engine_options.stracciatella_home.join(format!("SavedGames-{}", m)),
+        None => engine_options.stracciatella_home.join("SavedGames"),
+    }
+}
+
+/// Lists all `.sav` files in `dir`, sorted by file name. A missing folder means there are no saves yet.
+pub fn list_saved_games(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    if !dir.exists() {
+        return Ok(vec!());
+    }
+
+    let entries = fs::read_dir(dir).map_err(|e| format!("Error reading {}: {}", dir.display(), e))?;
+    let mut saves: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_file() && p.extension().map(|e| e.to_string_lossy().to_lowercase() == "sav").unwrap_or(false))
+        .collect();
+
+    saves.sort();
+    Ok(saves)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    fn unittest_save(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../assets/unittests/saves").join(name)
+    }
+
+    #[test]
+    fn read_saved_game_header_should_read_linux_saves() {
+        let header = super::read_saved_game_header(&unittest_save("strac-linux/SaveGame01.sav")).unwrap();
+
+        assert!(header.utf32_description);
+        assert_eq!(header.saved_game_version, 0x63);
+        assert_eq!(header.game_version, "Build 04.12.02");
+        assert_eq!(header.description, "1");
+        assert_eq!((header.day, header.hour, header.minute), (1, 1, 0));
+        assert_eq!(header.sector_name(), "A9");
+        assert_eq!(header.number_of_mercs, 1);
+        assert_eq!(header.balance, 42000);
+    }
+
+    #[test]
+    fn read_saved_game_header_should_read_windows_saves() {
+        let header = super::read_saved_game_header(&unittest_save("strac-win/SaveGame09.sav")).unwrap();
+
+        assert!(!header.utf32_description);
+        assert_eq!(header.description, "9");
+        assert_eq!((header.day, header.hour, header.minute), (2, 9, 51));
+        assert_eq!(header.sector_name(), "D15");
+        assert_eq!(header.number_of_mercs, 6);
+        assert_eq!(header.balance, 13030);
+    }
+
+    #[test]
+    fn list_saved_games_should_return_nothing_for_missing_folder() {
+        assert_eq!(super::list_saved_games(&unittest_save("non-existing")), Ok(vec!()));
+    }
+
+    #[test]
+    fn saved_games_dir_should_depend_on_the_first_mod() {
+        let mut engine_options = ::EngineOptions::default();
+        engine_options.stracciatella_home = PathBuf::from("/home/.ja2");
+        assert_eq!(super::saved_games_dir(&engine_options), PathBuf::from("/home/.ja2/SavedGames"));
+
+        engine_options.mods = vec!(String::from("o-fortuna"));
+        assert_eq!(super::saved_games_dir(&engine_options), PathBuf::from("/home/.ja2/SavedGames-o-fortuna"));
+    }
+}
@@ -0,0 +1,305 @@
+//! Reading and writing of `Ja2.set`, the in-game options like subtitles, speech and the volumes.
+//!
+//! The layout follows `LoadGameSettings` and `SaveGameSettings` in `GameSettings.cc`. The game looks for the file
+//! next to `ja2.json` and silently falls back to the defaults of `InitGameSettings` if it is missing, has the wrong size
+//! or was written by an older version.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::ser::SerializeMap;
+use serde::Serializer;
+
+use EngineOptions;
+
+pub const GAME_SETTINGS_FILE_NAME: &str = "Ja2.set";
+pub const GAME_SETTINGS_SIZE: usize = 76;
+/// Files with an older version are reset by the game.
+pub const GAME_SETTING_CURRENT_VERSION: u32 = 522;
+/// `g_version_number`, written into the file but never checked.
+pub const VERSION_NUMBER: &str = "Build 04.12.02";
+pub const VERSION_NUMBER_SIZE: usize = 16;
+
+pub const HIGHVOLUME: u8 = 127;
+pub const MIDVOLUME: u8 = 65;
+/// `NUM_SAVE_GAMES * NUM_SAVE_GAMES_TABS`
+pub const NUM_SAVE_GAME_SLOTS: i8 = 22;
+
+pub const NUM_ALL_GAME_OPTIONS: usize = 23;
+
+/// Names of the entries of `fOptions`, `TOPTION_*` in `GameSettings.h`. The last three are not in the options screen.
+pub static GAME_OPTIONS: [&str; NUM_ALL_GAME_OPTIONS] = [
+    "speech",
+    "mute_confirmations",
+    "subtitles",
+    "key_advance_speech",
+    "animate_smoke",
+    "blood_n_gore",
+    "dont_move_mouse",
+    "old_selection_method",
+    "always_show_movement_path",
+    "show_misses",
+    "rtconfirm",
+    "sleepwake_notification",
+    "use_metric_system",
+    "merc_always_light_up",
+    "smart_cursor",
+    "snap_cursor_to_door",
+    "glow_items",
+    "toggle_tree_tops",
+    "toggle_wireframe",
+    "3d_cursor",
+    "merc_casts_light",
+    "hide_bullets",
+    "tracking_mode",
+];
+
+/// The keys accepted by `GameSettings::get` and `GameSettings::set` besides the names of the options.
+pub static SETTING_KEYS: [&str; 7] = [
+    "last_saved_game_slot",
+    "music_volume",
+    "sound_volume",
+    "speech_volume",
+    "hide_help_in_all_screens",
+    "size_of_display_cover",
+    "size_of_los",
+];
+
+/// The contents of `Ja2.set`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GameSettings {
+    /// -1 if no game was saved yet.
+    pub last_saved_game_slot: i8,
+    pub music_volume: u8,
+    pub sound_volume: u8,
+    pub speech_volume: u8,
+    #[serde(serialize_with = "serialize_options")]
+    pub options: [bool; NUM_ALL_GAME_OPTIONS],
+    pub version_number: String,
+    pub settings_version: u32,
+    pub meanwhile_scenes_seen_flags: u32,
+    pub hide_help_in_all_screens: bool,
+    pub size_of_display_cover: u8,
+    pub size_of_los: u8,
+}
+
+fn serialize_options<S: Serializer>(options: &[bool; NUM_ALL_GAME_OPTIONS], serializer: S) -> Result<S::Ok, S::Error> {
+    let mut map = serializer.serialize_map(Some(options.len()))?;
+    for (name, value) in GAME_OPTIONS.iter().zip(options.iter()) {
+        map.serialize_entry(name, value)?;
+    }
+    map.end()
+}
+
+impl Default for GameSettings {
+    /// The settings of a new installation, `InitGameSettings` in C++.
+    fn default() -> GameSettings {
+        let mut options = [false; NUM_ALL_GAME_OPTIONS];
+        for name in &["speech", "subtitles", "animate_smoke", "blood_n_gore", "sleepwake_notification", "snap_cursor_to_door", "glow_items",
+                      "toggle_tree_tops", "toggle_wireframe", "merc_casts_light", "tracking_mode"] {
+            options[option_index(name).unwrap()] = true;
+        }
+
+        GameSettings {
+            last_saved_game_slot: -1,
+            music_volume: 63,
+            sound_volume: 63,
+            speech_volume: 63,
+            options,
+            version_number: String::from(VERSION_NUMBER),
+            settings_version: GAME_SETTING_CURRENT_VERSION,
+            meanwhile_scenes_seen_flags: 0,
+            hide_help_in_all_screens: false,
+            size_of_display_cover: 4,
+            size_of_los: 4,
+        }
+    }
+}
+
+/// The index of an option in `fOptions`.
+pub fn option_index(name: &str) -> Option<usize> {
+    GAME_OPTIONS.iter().position(|o| *o == name)
+}
+
+/// All keys of `GameSettings::get` and `GameSettings::set`, the options first.
+pub fn all_setting_keys() -> Vec<&'static str> {
+    GAME_OPTIONS.iter().chain(SETTING_KEYS.iter()).cloned().collect()
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value.to_lowercase().as_str() {
+        "true" | "on" | "yes" | "1" => Ok(true),
+        "false" | "off" | "no" | "0" => Ok(false),
+        _ => Err(format!("'{}' is not on or off", value)),
+    }
+}
+
+fn parse_number<T: ::std::str::FromStr + PartialOrd + ::std::fmt::Display>(value: &str, min: T, max: T) -> Result<T, String> {
+    match value.parse::<T>() {
+        Ok(n) if n >= min && n <= max => Ok(n),
+        _ => Err(format!("'{}' is not a number from {} to {}", value, min, max)),
+    }
+}
+
+impl GameSettings {
+    /// The game resets settings written by an older version.
+    pub fn is_outdated(&self) -> bool {
+        self.settings_version < GAME_SETTING_CURRENT_VERSION
+    }
+
+    /// The value of an option or setting as text, `None` for unknown keys.
+    pub fn get(&self, key: &str) -> Option<String> {
+        if let Some(index) = option_index(key) {
+            return Some(self.options[index].to_string());
+        }
+        Some(match key {
+            "last_saved_game_slot" => self.last_saved_game_slot.to_string(),
+            "music_volume" => self.music_volume.to_string(),
+            "sound_volume" => self.sound_volume.to_string(),
+            "speech_volume" => self.speech_volume.to_string(),
+            "hide_help_in_all_screens" => self.hide_help_in_all_screens.to_string(),
+            "size_of_display_cover" => self.size_of_display_cover.to_string(),
+            "size_of_los" => self.size_of_los.to_string(),
+            _ => return None,
+        })
+    }
+
+    /// Changes an option or setting to a value given as text, e.g. `on` or `100`. Values the game would reset are
+    /// rejected, so is turning off both subtitles and speech.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let invalid = |e: String| format!("Invalid value for '{}': {}", key, e);
+
+        if let Some(index) = option_index(key) {
+            let mut options = self.options;
+            options[index] = parse_bool(value).map_err(invalid)?;
+            if !options[option_index("subtitles").unwrap()] && !options[option_index("speech").unwrap()] {
+                return Err(String::from("At least one of subtitles and speech has to be on"));
+            }
+            self.options = options;
+            return Ok(());
+        }
+
+        match key {
+            "last_saved_game_slot" => self.last_saved_game_slot = parse_number(value, -1, NUM_SAVE_GAME_SLOTS - 1).map_err(invalid)?,
+            "music_volume" => self.music_volume = parse_number(value, 0, HIGHVOLUME).map_err(invalid)?,
+            "sound_volume" => self.sound_volume = parse_number(value, 0, HIGHVOLUME).map_err(invalid)?,
+            "speech_volume" => self.speech_volume = parse_number(value, 0, HIGHVOLUME).map_err(invalid)?,
+            "hide_help_in_all_screens" => self.hide_help_in_all_screens = parse_bool(value).map_err(invalid)?,
+            "size_of_display_cover" => self.size_of_display_cover = parse_number(value, 0, u8::MAX).map_err(invalid)?,
+            "size_of_los" => self.size_of_los = parse_number(value, 0, u8::MAX).map_err(invalid)?,
+            _ => return Err(format!("Unknown setting '{}', possible settings: {}", key, all_setting_keys().join(", "))),
+        }
+        Ok(())
+    }
+}
+
+/// Parses the contents of `Ja2.set`.
+pub fn parse_game_settings(data: &[u8]) -> Result<GameSettings, String> {
+    if data.len() != GAME_SETTINGS_SIZE {
+        return Err(format!("Size {} is not the size of a settings file {}", data.len(), GAME_SETTINGS_SIZE));
+    }
+
+    let mut options = [false; NUM_ALL_GAME_OPTIONS];
+    for (option, &value) in options.iter_mut().zip(data[4..27].iter()) {
+        *option = value != 0;
+    }
+    let version_number = &data[27..27 + VERSION_NUMBER_SIZE];
+    let version_end = version_number.iter().position(|&b| b == 0).unwrap_or(VERSION_NUMBER_SIZE);
+    let u32_at = |offset: usize| u32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]]);
+
+    Ok(GameSettings {
+        last_saved_game_slot: data[0] as i8,
+        music_volume: data[1],
+        sound_volume: data[2],
+        speech_volume: data[3],
+        options,
+        version_number: String::from_utf8_lossy(&version_number[..version_end]).into_owned(),
+        settings_version: u32_at(44),
+        meanwhile_scenes_seen_flags: u32_at(48),
+        hide_help_in_all_screens: data[52] != 0,
+        size_of_display_cover: data[54],
+        size_of_los: data[55],
+    })
+}
+
+/// Serializes settings in the layout of `SaveGameSettings`.
+pub fn serialize_game_settings(settings: &GameSettings) -> Vec<u8> {
+    let mut data = vec!(0u8; GAME_SETTINGS_SIZE);
+    data[0] = settings.last_saved_game_slot as u8;
+    data[1] = settings.music_volume;
+    data[2] = settings.sound_volume;
+    data[3] = settings.speech_volume;
+    for (byte, &option) in data[4..27].iter_mut().zip(settings.options.iter()) {
+        *byte = option as u8;
+    }
+    let version_number = settings.version_number.as_bytes();
+    let length = version_number.len().min(VERSION_NUMBER_SIZE - 1);
+    data[27..27 + length].copy_from_slice(&version_number[..length]);
+    data[44..48].copy_from_slice(&settings.settings_version.to_le_bytes());
+    data[48..52].copy_from_slice(&settings.meanwhile_scenes_seen_flags.to_le_bytes());
+    data[52] = settings.hide_help_in_all_screens as u8;
+    data[54] = settings.size_of_display_cover;
+    data[55] = settings.size_of_los;
+    data
+}
+
+/// Where the game keeps `Ja2.set`, next to `ja2.json`.
+pub fn game_settings_path(engine_options: &EngineOptions) -> PathBuf {
+    engine_options.stracciatella_home.join(GAME_SETTINGS_FILE_NAME)
+}
+
+pub fn read_game_settings(path: &Path) -> Result<GameSettings, String> {
+    let data = fs::read(path).map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+
+    parse_game_settings(&data).map_err(|e| format!("Error parsing {}: {}", path.display(), e))
+}
+
+pub fn write_game_settings(path: &Path, settings: &GameSettings) -> Result<(), String> {
+    fs::write(path, serialize_game_settings(settings)).map_err(|e| format!("Error writing {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{all_setting_keys, parse_game_settings, serialize_game_settings, GameSettings, GAME_OPTIONS};
+
+    #[test]
+    fn serialize_game_settings_should_use_the_layout_of_the_game() {
+        let data = serialize_game_settings(&GameSettings::default());
+
+        assert_eq!(data.len(), 76);
+        assert_eq!(data[0..6], [0xFF, 63, 63, 63, 1, 0]);
+        assert_eq!(&data[27..41], b"Build 04.12.02");
+        assert_eq!(data[44..48], [0x0A, 0x02, 0, 0]);
+        assert_eq!(data[54..56], [4, 4]);
+        assert_eq!(parse_game_settings(&data), Ok(GameSettings::default()));
+    }
+
+    #[test]
+    fn parse_game_settings_should_fail_with_the_wrong_size() {
+        assert_eq!(parse_game_settings(&[0; 75]), Err(String::from("Size 75 is not the size of a settings file 76")));
+    }
+
+    #[test]
+    fn set_should_parse_and_check_the_values() {
+        let mut settings = GameSettings::default();
+
+        assert_eq!(settings.set("speech", "off"), Ok(()));
+        assert_eq!(settings.get("speech"), Some(String::from("false")));
+        assert_eq!(settings.set("subtitles", "false"), Err(String::from("At least one of subtitles and speech has to be on")));
+        assert_eq!(settings.get("subtitles"), Some(String::from("true")));
+        assert_eq!(settings.set("music_volume", "100"), Ok(()));
+        assert_eq!(settings.music_volume, 100);
+        assert_eq!(settings.set("music_volume", "128"), Err(String::from("Invalid value for 'music_volume': '128' is not a number from 0 to 127")));
+        assert!(settings.set("volume", "1").unwrap_err().starts_with("Unknown setting 'volume', possible settings: speech, "));
+    }
+
+    #[test]
+    fn game_settings_should_serialize_options_by_name() {
+        let json = ::serde_json::to_value(GameSettings::default()).unwrap();
+
+        assert_eq!(json["options"].as_object().unwrap().len(), GAME_OPTIONS.len());
+        assert_eq!(json["options"]["3d_cursor"], false);
+        assert_eq!(json["music_volume"], 63);
+        assert_eq!(all_setting_keys().len(), 30);
+    }
+}
@@ -0,0 +1,147 @@
+//! Reading of the SLF libraries in the vanilla data directory, e.g. `maps.slf`.
+//!
+//! The layout follows `InitializeLibrary` in `LibraryDataBase.cc`: a header with the number of entries and the path of
+//! the library inside the game data, the file contents and a directory of fixed-size entries at the end of the file.
+
+use std::fs::File;
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+
+pub const FILENAME_SIZE: usize = 256;
+/// Size of `LIBHEADER` including the padding of the C struct.
+pub const LIBHEADER_SIZE: usize = 532;
+/// Size of `DIRENTRY` including the padding of the C struct.
+pub const DIRENTRY_SIZE: usize = 280;
+
+/// State of the entries that are part of the library, the others are deleted or replaced.
+pub const FILE_OK: u8 = 0x00;
+
+/// A file inside an SLF library.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlfEntry {
+    /// Name relative to the library path, with `/` as separator.
+    pub name: String,
+    pub offset: u32,
+    pub length: u32,
+}
+
+/// The directory of an SLF library.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlfLibrary {
+    pub path: PathBuf,
+    pub library_name: String,
+    /// Path of the contained files inside the game data, e.g. `maps/`. Empty for files in the top level.
+    pub library_path: String,
+    /// Entries in the state `FILE_OK`, in the order of the directory.
+    pub entries: Vec<SlfEntry>,
+}
+
+impl SlfLibrary {
+    /// The path of an entry inside the game data, e.g. `maps/a9.dat`.
+    pub fn game_path(&self, entry: &SlfEntry) -> String {
+        format!("{}{}", self.library_path, entry.name)
+    }
+}
+
+/// Reads a zero terminated string of the fixed-size fields, replacing the Windows path separators.
+fn read_name(data: &[u8]) -> String {
+    let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+    data[..end].iter().map(|&b| if b == b'\\' { '/' } else { b as char }).collect()
+}
+
+/// Reads the header and the directory of an SLF library.
+pub fn read_slf_library(path: &Path) -> Result<SlfLibrary, String> {
+    let read_error = |e: ::std::io::Error| format!("Error reading {}: {}", path.display(), e);
+    let mut file = File::open(path).map_err(read_error)?;
+    let mut header = vec!(0u8; LIBHEADER_SIZE);
+    file.read_exact(&mut header).map_err(read_error)?;
+
+    let library_name = read_name(&header[0..FILENAME_SIZE]);
+    let mut library_path = read_name(&header[FILENAME_SIZE..2 * FILENAME_SIZE]);
+    if !library_path.is_empty() && !library_path.ends_with('/') {
+        library_path.push('/');
+    }
+    let count = Cursor::new(&header[2 * FILENAME_SIZE..]).read_i32::<LittleEndian>().unwrap();
+    if count < 0 {
+        return Err(format!("Error parsing {}: invalid number of entries {}", path.display(), count));
+    }
+
+    let directory_size = count as u64 * DIRENTRY_SIZE as u64;
+    let file_size = file.metadata().map_err(read_error)?.len();
+    if directory_size + LIBHEADER_SIZE as u64 > file_size {
+        return Err(format!("Error parsing {}: {} entries do not fit into {} bytes", path.display(), count, file_size));
+    }
+    let mut directory = vec!(0u8; directory_size as usize);
+    file.seek(SeekFrom::Start(file_size - directory_size)).and_then(|_| file.read_exact(&mut directory)).map_err(read_error)?;
+
+    let mut entries = Vec::new();
+    for (index, raw) in directory.chunks(DIRENTRY_SIZE).enumerate() {
+        if raw[FILENAME_SIZE + 8] != FILE_OK {
+            continue;
+        }
+        let mut fields = Cursor::new(&raw[FILENAME_SIZE..]);
+        let offset = fields.read_u32::<LittleEndian>().unwrap();
+        let length = fields.read_u32::<LittleEndian>().unwrap();
+        if u64::from(offset) + u64::from(length) > file_size {
+            return Err(format!("Error parsing {}: entry {} is outside of the library", path.display(), index));
+        }
+        entries.push(SlfEntry { name: read_name(&raw[0..FILENAME_SIZE]), offset, length });
+    }
+
+    Ok(SlfLibrary { path: path.to_path_buf(), library_name, library_path, entries })
+}
+
+/// Reads the contents of an entry from an open library file.
+pub fn read_slf_entry(file: &mut File, entry: &SlfEntry) -> Result<Vec<u8>, String> {
+    let mut data = vec!(0u8; entry.length as usize);
+    file.seek(SeekFrom::Start(u64::from(entry.offset)))
+        .and_then(|_| file.read_exact(&mut data))
+        .map_err(|e| format!("Error reading {}: {}", entry.name, e))?;
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate tempdir;
+
+    use std::fs;
+    use std::fs::File;
+    use std::path::Path;
+
+    use super::{read_slf_entry, read_slf_library, SlfEntry};
+    use test_utils::slf_file;
+
+    fn write_slf(path: &Path, library_path: &str, files: &[(&str, Option<&[u8]>)]) {
+        fs::write(path, slf_file(library_path, files)).unwrap();
+    }
+
+    #[test]
+    fn read_slf_library_should_list_the_entries() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        let path = temp_dir.path().join("laptop.slf");
+        write_slf(&path, "laptop\\", &[("german\\desktop.pcx", Some(b"abc")), ("old.sti", None), ("b.sti", Some(b"de"))]);
+
+        let library = read_slf_library(&path).unwrap();
+        assert_eq!(library.library_name, "test.slf");
+        assert_eq!(library.library_path, "laptop/");
+        assert_eq!(library.entries, vec!(
+            SlfEntry { name: String::from("german/desktop.pcx"), offset: 532, length: 3 },
+            SlfEntry { name: String::from("b.sti"), offset: 535, length: 2 },
+        ));
+        assert_eq!(library.game_path(&library.entries[1]), "laptop/b.sti");
+        assert_eq!(read_slf_entry(&mut File::open(&path).unwrap(), &library.entries[1]), Ok(b"de".to_vec()));
+    }
+
+    #[test]
+    fn read_slf_library_should_fail_with_a_truncated_file() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        let path = temp_dir.path().join("maps.slf");
+        let mut data = slf_file("maps\\", &[("a9.dat", Some(b"abc"))]);
+        data.truncate(600);
+        fs::write(&path, data).unwrap();
+
+        assert!(read_slf_library(&path).unwrap_err().ends_with("1 entries do not fit into 600 bytes"));
+    }
+}
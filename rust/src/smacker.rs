@@ -0,0 +1,732 @@
+//! Decoding of the Smacker videos of the intro and the cinematics, replacing libsmacker in `Smack_Stub.cc`.
+//!
+//! The header of a Smacker file lists the size and the content of every frame and holds four Huffman trees for the
+//! video. Each frame chunk starts with an optional palette update and the audio of up to seven tracks, followed by the
+//! video data. The video is decoded in place in blocks of 4x4 palette indexes, so blocks that did not change need no
+//! data. Both versions of the format are supported, `SMK4` only adds two kinds of full blocks. The audio is raw PCM or
+//! Huffman coded DPCM, Bink audio is not supported.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use audio::AudioData;
+use image;
+use image::Image;
+
+pub const HEADER_SIZE: usize = 104;
+pub const AUDIO_TRACKS: usize = 7;
+pub const PALETTE_SIZE: usize = 256 * 3;
+
+/// Longest code of a Huffman tree, deeper trees are rejected like in FFmpeg.
+const MAX_CODE_LENGTH: usize = 32;
+
+const BLOCK_MONO: u16 = 0;
+const BLOCK_FULL: u16 = 1;
+const BLOCK_VOID: u16 = 2;
+
+/// Number of blocks of a run, indexed by bits 2 to 7 of a block type.
+static BLOCK_RUNS: [usize; 64] = [
+    1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31,
+    32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50, 51, 52, 53, 54, 55, 56, 57, 58, 59,
+    128, 256, 512, 1024, 2048,
+];
+
+/// Expands the 6 bit color components of the palette to 8 bits.
+static PALETTE_MAP: [u8; 64] = [
+    0x00, 0x04, 0x08, 0x0C, 0x10, 0x14, 0x18, 0x1C, 0x20, 0x24, 0x28, 0x2C, 0x30, 0x34, 0x38, 0x3C,
+    0x41, 0x45, 0x49, 0x4D, 0x51, 0x55, 0x59, 0x5D, 0x61, 0x65, 0x69, 0x6D, 0x71, 0x75, 0x79, 0x7D,
+    0x82, 0x86, 0x8A, 0x8E, 0x92, 0x96, 0x9A, 0x9E, 0xA2, 0xA6, 0xAA, 0xAE, 0xB2, 0xB6, 0xBA, 0xBE,
+    0xC3, 0xC7, 0xCB, 0xCF, 0xD3, 0xD7, 0xDB, 0xDF, 0xE3, 0xE7, 0xEB, 0xEF, 0xF3, 0xF7, 0xFB, 0xFF,
+];
+
+fn truncated() -> String {
+    String::from("Unexpected end of Smacker data")
+}
+
+/// How the rows of the frames are meant to be shown, the decoder leaves the scaling to the caller.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum YScale {
+    None,
+    /// Every row is shown twice.
+    Doubled,
+    /// Every row is followed by a black one.
+    Interlaced,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AudioCompression {
+    Pcm,
+    Dpcm,
+    Bink,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AudioTrack {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub bits_per_sample: u16,
+    pub compression: AudioCompression,
+}
+
+/// Reads bits starting with the least significant bit of each byte.
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> BitReader<'a> {
+        BitReader { data, position: 0 }
+    }
+
+    fn read_bit(&mut self) -> Result<bool, String> {
+        let byte = self.data.get(self.position / 8).ok_or_else(truncated)?;
+        let bit = byte & (1 << (self.position % 8)) != 0;
+        self.position += 1;
+        Ok(bit)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, String> {
+        let mut value = 0;
+        for i in 0..8 {
+            if self.read_bit()? {
+                value |= 1 << i;
+            }
+        }
+        Ok(value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    /// The indexes of the nodes for a 0 and a 1 bit.
+    Branch(usize, usize),
+    Leaf(u16),
+}
+
+/// A Huffman tree stored depth first, the root is the first node. An empty tree decodes to 0 without reading bits.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct HuffmanTree {
+    nodes: Vec<Node>,
+}
+
+impl HuffmanTree {
+    /// Reads a tree of 8 bit values, it starts with a set bit and ends with an unset one.
+    fn read(bits: &mut BitReader) -> Result<HuffmanTree, String> {
+        let mut tree = HuffmanTree::default();
+        if bits.read_bit()? {
+            tree.read_node(bits, 0, &mut |bits| bits.read_u8().map(u16::from))?;
+            if bits.read_bit()? {
+                return Err(String::from("Invalid end of a Huffman tree"));
+            }
+        }
+        Ok(tree)
+    }
+
+    /// Reads a node and its children, the values of the leaves with `read_leaf`. Returns the index of the node.
+    fn read_node<F>(&mut self, bits: &mut BitReader, depth: usize, read_leaf: &mut F) -> Result<usize, String>
+        where F: FnMut(&mut BitReader) -> Result<u16, String>
+    {
+        if depth > MAX_CODE_LENGTH {
+            return Err(String::from("Huffman tree is too deep"));
+        }
+        let index = self.nodes.len();
+        if bits.read_bit()? {
+            self.nodes.push(Node::Branch(0, 0));
+            let zero = self.read_node(bits, depth + 1, read_leaf)?;
+            let one = self.read_node(bits, depth + 1, read_leaf)?;
+            self.nodes[index] = Node::Branch(zero, one);
+        } else {
+            let value = read_leaf(bits)?;
+            self.nodes.push(Node::Leaf(value));
+        }
+        Ok(index)
+    }
+
+    fn decode(&self, bits: &mut BitReader) -> Result<u16, String> {
+        let mut index = 0;
+        loop {
+            match self.nodes.get(index) {
+                None => return Ok(0),
+                Some(&Node::Leaf(value)) => return Ok(value),
+                Some(&Node::Branch(zero, one)) => index = if bits.read_bit()? { one } else { zero },
+            }
+        }
+    }
+}
+
+/// A Huffman tree of 16 bit values. Leaves with one of the three escape values stand for the last three decoded
+/// values, which are reset for every frame.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct BigTree {
+    tree: HuffmanTree,
+    escapes: [u16; 3],
+    last: [u16; 3],
+}
+
+impl BigTree {
+    /// Reads a tree whose leaves are coded with two trees of 8 bit values, one for the low and one for the high byte.
+    fn read(bits: &mut BitReader) -> Result<BigTree, String> {
+        let mut big_tree = BigTree::default();
+        if !bits.read_bit()? {
+            return Ok(big_tree);
+        }
+        let low = HuffmanTree::read(bits)?;
+        let high = HuffmanTree::read(bits)?;
+        for escape in big_tree.escapes.iter_mut() {
+            *escape = u16::from(bits.read_u8()?) | u16::from(bits.read_u8()?) << 8;
+        }
+        big_tree.tree.read_node(bits, 0, &mut |bits| Ok(low.decode(bits)? | high.decode(bits)? << 8))?;
+        if bits.read_bit()? {
+            return Err(String::from("Invalid end of a Huffman tree"));
+        }
+        Ok(big_tree)
+    }
+
+    fn reset(&mut self) {
+        self.last = [0; 3];
+    }
+
+    fn decode(&mut self, bits: &mut BitReader) -> Result<u16, String> {
+        if self.tree.nodes.is_empty() {
+            return Ok(0);
+        }
+        let leaf = self.tree.decode(bits)?;
+        let value = match self.escapes.iter().position(|&e| e == leaf) {
+            Some(i) => self.last[i],
+            None => leaf,
+        };
+        if self.last[0] != value {
+            self.last = [value, self.last[0], self.last[1]];
+        }
+        Ok(value)
+    }
+}
+
+/// The trees of the video in the order of the file.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct VideoTrees {
+    /// The pattern of mono blocks.
+    mono_map: BigTree,
+    /// The two colors of mono blocks.
+    mono_colors: BigTree,
+    /// Two pixels of full blocks.
+    full: BigTree,
+    /// The type and the run length of blocks.
+    types: BigTree,
+}
+
+/// The chunk of a frame in the file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct FrameChunk {
+    offset: usize,
+    size: usize,
+    keyframe: bool,
+    /// Bit 0 for a palette update and bits 1 to 7 for the audio tracks.
+    contents: u8,
+}
+
+/// The parts of a frame chunk.
+struct FrameParts<'a> {
+    palette: Option<&'a [u8]>,
+    audio: [Option<&'a [u8]>; AUDIO_TRACKS],
+    video: &'a [u8],
+}
+
+/// A parsed Smacker file, `SmackerDecoder` decodes its frames.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmackerVideo {
+    /// 2 or 4.
+    pub version: u8,
+    pub width: u32,
+    pub height: u32,
+    /// Number of frames, without the ring frame that some files have for looping.
+    pub frame_count: usize,
+    pub frame_duration_us: u32,
+    pub y_scale: YScale,
+    pub audio_tracks: [Option<AudioTrack>; AUDIO_TRACKS],
+    trees: VideoTrees,
+    frames: Vec<FrameChunk>,
+    data: Vec<u8>,
+}
+
+impl SmackerVideo {
+    pub fn from_bytes(data: Vec<u8>) -> Result<SmackerVideo, String> {
+        if data.len() < HEADER_SIZE || &data[0..3] != b"SMK" {
+            return Err(String::from("Not a Smacker file"));
+        }
+        let version = match data[3] {
+            b'2' => 2,
+            b'4' => 4,
+            v => return Err(format!("Unsupported Smacker version '{}'", v as char)),
+        };
+        let width = LittleEndian::read_u32(&data[4..8]);
+        let height = LittleEndian::read_u32(&data[8..12]);
+        if width == 0 || height == 0 || width > 0xFFFF || height > 0xFFFF {
+            return Err(format!("Invalid video size {}x{}", width, height));
+        }
+        let frame_count = LittleEndian::read_u32(&data[12..16]) as usize;
+        // Positive rates are milliseconds per frame, negative ones are in units of 10 microseconds
+        let frame_duration_us = match LittleEndian::read_i32(&data[16..20]) {
+            rate if rate > 0 => (rate as u32).saturating_mul(1000),
+            rate if rate < 0 => rate.unsigned_abs().saturating_mul(10),
+            _ => 100_000,
+        };
+        let flags = LittleEndian::read_u32(&data[20..24]);
+        let y_scale = if flags & 0x04 != 0 {
+            YScale::Interlaced
+        } else if flags & 0x02 != 0 {
+            YScale::Doubled
+        } else {
+            YScale::None
+        };
+        let trees_size = LittleEndian::read_u32(&data[52..56]) as usize;
+        let mut audio_tracks = [None; AUDIO_TRACKS];
+        for (i, track) in audio_tracks.iter_mut().enumerate() {
+            let value = LittleEndian::read_u32(&data[72 + i * 4..76 + i * 4]);
+            if value & (1 << 30) == 0 {
+                continue;
+            }
+            let compression = if value & (3 << 26) != 0 {
+                AudioCompression::Bink
+            } else if value & (1 << 31) != 0 {
+                AudioCompression::Dpcm
+            } else {
+                AudioCompression::Pcm
+            };
+            *track = Some(AudioTrack {
+                sample_rate: value & 0x00FF_FFFF,
+                channels: if value & (1 << 28) != 0 { 2 } else { 1 },
+                bits_per_sample: if value & (1 << 29) != 0 { 16 } else { 8 },
+                compression,
+            });
+        }
+
+        let chunks = frame_count + (flags & 0x01) as usize;
+        let sizes_start = HEADER_SIZE;
+        let contents_start = sizes_start + chunks * 4;
+        let trees_start = contents_start + chunks;
+        if data.len() < trees_start + trees_size {
+            return Err(truncated());
+        }
+        let trees = {
+            let mut bits = BitReader::new(&data[trees_start..trees_start + trees_size]);
+            VideoTrees {
+                mono_map: BigTree::read(&mut bits)?,
+                mono_colors: BigTree::read(&mut bits)?,
+                full: BigTree::read(&mut bits)?,
+                types: BigTree::read(&mut bits)?,
+            }
+        };
+
+        let mut frames = Vec::with_capacity(frame_count);
+        let mut offset = trees_start + trees_size;
+        for i in 0..frame_count {
+            let value = LittleEndian::read_u32(&data[sizes_start + i * 4..sizes_start + i * 4 + 4]);
+            let size = (value & !0x03) as usize;
+            if data.len() < offset + size {
+                return Err(truncated());
+            }
+            frames.push(FrameChunk { offset, size, keyframe: value & 0x01 != 0, contents: data[contents_start + i] });
+            offset += size;
+        }
+
+        Ok(SmackerVideo { version, width, height, frame_count, frame_duration_us, y_scale, audio_tracks, trees, frames, data })
+    }
+
+    /// Whether the frame can be decoded without the ones before it.
+    pub fn is_keyframe(&self, frame: usize) -> bool {
+        self.frames.get(frame).map(|f| f.keyframe).unwrap_or(false)
+    }
+
+    fn chunk_data(&self, frame: usize) -> &[u8] {
+        let chunk = self.frames[frame];
+        &self.data[chunk.offset..chunk.offset + chunk.size]
+    }
+
+    /// Decodes all audio of a track.
+    pub fn decode_audio(&self, track: usize) -> Result<AudioData, String> {
+        let info = self.audio_tracks.get(track).cloned().unwrap_or(None)
+            .ok_or_else(|| format!("The video has no audio track {}", track))?;
+        let mut samples = Vec::new();
+        for frame in 0..self.frame_count {
+            if let Some(data) = split_chunk(self.chunk_data(frame), self.frames[frame].contents, frame)?.audio[track] {
+                decode_audio_chunk(&info, data, &mut samples).map_err(|e| format!("Error in audio track {} of frame {}: {}", track, frame, e))?;
+            }
+        }
+        Ok(AudioData { sample_rate: info.sample_rate, channels: info.channels, samples })
+    }
+}
+
+/// Splits the chunk of a frame into the palette update, the audio of each track and the video data.
+fn split_chunk<'a>(data: &'a [u8], contents: u8, frame: usize) -> Result<FrameParts<'a>, String> {
+    let mut position = 0;
+
+    let mut palette = None;
+    if contents & 0x01 != 0 {
+        // The first byte is the size of the palette record in units of 4 bytes
+        let size = *data.first().ok_or_else(truncated)? as usize * 4;
+        if size == 0 || size > data.len() {
+            return Err(format!("Invalid palette size {} in frame {}", size, frame));
+        }
+        palette = Some(&data[1..size]);
+        position = size;
+    }
+
+    let mut audio = [None; AUDIO_TRACKS];
+    for (track, part) in audio.iter_mut().enumerate() {
+        if contents & (0x02 << track) == 0 {
+            continue;
+        }
+        // The size includes the 4 bytes of the size itself
+        let size = data.get(position..position + 4).map(LittleEndian::read_u32).ok_or_else(truncated)? as usize;
+        if size < 4 || position + size > data.len() {
+            return Err(format!("Invalid size {} of audio track {} in frame {}", size, track, frame));
+        }
+        *part = Some(&data[position + 4..position + size]);
+        position += size;
+    }
+
+    Ok(FrameParts { palette, audio, video: &data[position..] })
+}
+
+/// Decodes the audio of a track in one frame chunk.
+fn decode_audio_chunk(track: &AudioTrack, data: &[u8], samples: &mut Vec<i16>) -> Result<(), String> {
+    let channels = track.channels as usize;
+    match track.compression {
+        AudioCompression::Pcm if track.bits_per_sample == 8 => samples.extend(data.iter().map(|&b| (i16::from(b) - 128) << 8)),
+        AudioCompression::Pcm => samples.extend(data.chunks(2).filter(|c| c.len() == 2).map(LittleEndian::read_i16)),
+        AudioCompression::Bink => return Err(String::from("Bink audio is not supported")),
+        AudioCompression::Dpcm => {
+            let size = data.get(0..4).map(LittleEndian::read_u32).ok_or_else(truncated)? as usize;
+            let mut bits = BitReader::new(&data[4..]);
+            if !bits.read_bit()? {
+                return Ok(());
+            }
+            let stereo = bits.read_bit()?;
+            let sixteen_bit = bits.read_bit()?;
+            if stereo != (channels == 2) || sixteen_bit != (track.bits_per_sample == 16) {
+                return Err(String::from("The format of the chunk differs from the one of the track"));
+            }
+
+            // One tree per channel for 8 bit samples, one for the low and one for the high byte of 16 bit samples
+            let bytes_per_sample = track.bits_per_sample as usize / 8;
+            let trees = (0..channels * bytes_per_sample).map(|_| HuffmanTree::read(&mut bits)).collect::<Result<Vec<_>, _>>()?;
+            let count = size / bytes_per_sample;
+
+            // The first samples are stored as they are, the last channel first and the high byte first
+            let mut predictors = [0i16; 2];
+            for predictor in predictors[..channels].iter_mut().rev() {
+                *predictor = if sixteen_bit {
+                    (u16::from(bits.read_u8()?) << 8 | u16::from(bits.read_u8()?)) as i16
+                } else {
+                    i16::from(bits.read_u8()?)
+                };
+            }
+            for &predictor in &predictors[..channels.min(count)] {
+                samples.push(if sixteen_bit { predictor } else { (predictor - 128) << 8 });
+            }
+            for i in channels..count {
+                let channel = i % channels;
+                let predictor = &mut predictors[channel];
+                if sixteen_bit {
+                    let delta = trees[channel * 2].decode(&mut bits)? | trees[channel * 2 + 1].decode(&mut bits)? << 8;
+                    *predictor = predictor.wrapping_add(delta as i16);
+                    samples.push(*predictor);
+                } else {
+                    let delta = trees[channel].decode(&mut bits)? as u8;
+                    *predictor = i16::from((*predictor as u8).wrapping_add(delta));
+                    samples.push((*predictor - 128) << 8);
+                }
+            }
+        },
+    }
+    Ok(())
+}
+
+/// Decodes a palette update, which copies runs of colors from the previous palette or sets new ones.
+fn decode_palette(previous: &[u8], data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut palette = vec!(0; PALETTE_SIZE);
+    let mut bytes = data.iter().cloned();
+    let mut next = || bytes.next().ok_or_else(truncated);
+    let mut color = 0;
+    while color < 256 {
+        let value = next()?;
+        let (count, source) = if value & 0x80 != 0 {
+            ((value & 0x7F) as usize + 1, color)
+        } else if value & 0x40 != 0 {
+            ((value & 0x3F) as usize + 1, next()? as usize)
+        } else {
+            let (green, blue) = (next()?, next()?);
+            palette[color * 3..color * 3 + 3].copy_from_slice(&[
+                PALETTE_MAP[(value & 0x3F) as usize],
+                PALETTE_MAP[(green & 0x3F) as usize],
+                PALETTE_MAP[(blue & 0x3F) as usize],
+            ]);
+            color += 1;
+            continue;
+        };
+        if color + count > 256 || source + count > 256 {
+            return Err(String::from("Invalid palette update"));
+        }
+        palette[color * 3..(color + count) * 3].copy_from_slice(&previous[source * 3..(source + count) * 3]);
+        color += count;
+    }
+    Ok(palette)
+}
+
+/// Decodes the video data of a frame into the pixels of the previous frame.
+fn decode_video(trees: &mut VideoTrees, version: u8, width: usize, height: usize, data: &[u8], pixels: &mut [u8]) -> Result<(), String> {
+    let mut bits = BitReader::new(data);
+    let (blocks_x, blocks_y) = ((width + 3) / 4, (height + 3) / 4);
+    let mut set_row = |x: usize, y: usize, values: [u8; 4]| {
+        if y < height {
+            for (i, &value) in values.iter().enumerate().filter(|&(i, _)| x + i < width) {
+                pixels[y * width + x + i] = value;
+            }
+        }
+    };
+    for tree in [&mut trees.mono_map, &mut trees.mono_colors, &mut trees.full, &mut trees.types].iter_mut() {
+        tree.reset();
+    }
+
+    let mut block = 0;
+    while block < blocks_x * blocks_y {
+        let block_type = trees.types.decode(&mut bits)?;
+        let run = BLOCK_RUNS[((block_type >> 2) & 0x3F) as usize];
+        // SMK4 full blocks can have half the vertical or the full resolution in both directions
+        let (doubled, half) = if block_type & 0x03 == BLOCK_FULL && version == 4 {
+            let doubled = bits.read_bit()?;
+            (doubled, !doubled && bits.read_bit()?)
+        } else {
+            (false, false)
+        };
+
+        for block in block..(block + run).min(blocks_x * blocks_y) {
+            let (x, y) = (block % blocks_x * 4, block / blocks_x * 4);
+            match block_type & 0x03 {
+                BLOCK_MONO => {
+                    let colors = trees.mono_colors.decode(&mut bits)?;
+                    let map = trees.mono_map.decode(&mut bits)?;
+                    let (set, unset) = ((colors >> 8) as u8, colors as u8);
+                    for row in 0..4 {
+                        let bit = |i: usize| if map & (1 << (row * 4 + i)) != 0 { set } else { unset };
+                        set_row(x, y + row, [bit(0), bit(1), bit(2), bit(3)]);
+                    }
+                },
+                BLOCK_FULL if doubled => {
+                    for row in 0..2 {
+                        let value = trees.full.decode(&mut bits)?;
+                        let (left, right) = (value as u8, (value >> 8) as u8);
+                        set_row(x, y + row * 2, [left, left, right, right]);
+                        set_row(x, y + row * 2 + 1, [left, left, right, right]);
+                    }
+                },
+                BLOCK_FULL if half => {
+                    for row in 0..2 {
+                        let right = trees.full.decode(&mut bits)?;
+                        let left = trees.full.decode(&mut bits)?;
+                        let values = [left as u8, (left >> 8) as u8, right as u8, (right >> 8) as u8];
+                        set_row(x, y + row * 2, values);
+                        set_row(x, y + row * 2 + 1, values);
+                    }
+                },
+                BLOCK_FULL => {
+                    for row in 0..4 {
+                        let right = trees.full.decode(&mut bits)?;
+                        let left = trees.full.decode(&mut bits)?;
+                        set_row(x, y + row, [left as u8, (left >> 8) as u8, right as u8, (right >> 8) as u8]);
+                    }
+                },
+                BLOCK_VOID => {},
+                // A solid block
+                _ => {
+                    let color = (block_type >> 8) as u8;
+                    for row in 0..4 {
+                        set_row(x, y + row, [color; 4]);
+                    }
+                },
+            }
+        }
+        block += run;
+    }
+    Ok(())
+}
+
+/// Decodes the frames of a video one after the other into a palette and palette indexes.
+#[derive(Debug, Clone)]
+pub struct SmackerDecoder {
+    video: SmackerVideo,
+    next_frame: usize,
+    palette: Vec<u8>,
+    pixels: Vec<u8>,
+}
+
+impl SmackerDecoder {
+    pub fn new(video: SmackerVideo) -> SmackerDecoder {
+        let pixels = vec!(0; video.width as usize * video.height as usize);
+        SmackerDecoder { video, next_frame: 0, palette: vec!(0; PALETTE_SIZE), pixels }
+    }
+
+    pub fn video(&self) -> &SmackerVideo {
+        &self.video
+    }
+
+    /// Decodes the next frame, returns false after the last one.
+    pub fn next_frame(&mut self) -> Result<bool, String> {
+        let frame = self.next_frame;
+        if frame >= self.video.frame_count {
+            return Ok(false);
+        }
+        let chunk = self.video.frames[frame];
+        let parts = split_chunk(&self.video.data[chunk.offset..chunk.offset + chunk.size], chunk.contents, frame)?;
+        if let Some(update) = parts.palette {
+            self.palette = decode_palette(&self.palette, update).map_err(|e| format!("Error in the palette of frame {}: {}", frame, e))?;
+        }
+        let (width, height) = (self.video.width as usize, self.video.height as usize);
+        decode_video(&mut self.video.trees, self.video.version, width, height, parts.video, &mut self.pixels)
+            .map_err(|e| format!("Error in the video of frame {}: {}", frame, e))?;
+        self.next_frame += 1;
+        Ok(true)
+    }
+
+    /// The number of decoded frames.
+    pub fn decoded_frames(&self) -> usize {
+        self.next_frame
+    }
+
+    /// The palette of the current frame, 256 RGB colors.
+    pub fn palette(&self) -> &[u8] {
+        &self.palette
+    }
+
+    /// The palette indexes of the current frame, row by row.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// The current frame with its palette applied.
+    pub fn to_image(&self) -> Image {
+        let mut image = Image::new(self.video.width as u16, self.video.height as u16);
+        for (rgba, &index) in image.pixels.chunks_mut(4).zip(&self.pixels) {
+            let color = &self.palette[index as usize * 3..index as usize * 3 + 3];
+            rgba.copy_from_slice(&[color[0], color[1], color[2], 0xFF]);
+        }
+        image
+    }
+}
+
+pub fn read_smacker_file(path: &Path) -> Result<SmackerVideo, String> {
+    let data = fs::read(path).map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+
+    SmackerVideo::from_bytes(data).map_err(|e| format!("Error parsing {}: {}", path.display(), e))
+}
+
+/// Writes every frame of a video to a PNG file numbered like `name_0.png`. Returns the written files.
+pub fn write_frame_pngs(video: SmackerVideo, path: &Path) -> Result<Vec<PathBuf>, String> {
+    let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let mut decoder = SmackerDecoder::new(video);
+    let mut written = Vec::new();
+    while decoder.next_frame()? {
+        let numbered = path.with_file_name(format!("{}_{}.png", stem, decoder.decoded_frames() - 1));
+        image::write_png(&numbered, &decoder.to_image())?;
+        written.push(numbered);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_audio_chunk, AudioCompression, AudioTrack, SmackerDecoder, SmackerVideo, YScale};
+    use test_utils::{test_video, BitWriter, TestTree};
+
+    #[test]
+    fn from_bytes_should_read_the_header() {
+        let video = SmackerVideo::from_bytes(test_video()).unwrap();
+
+        assert_eq!((video.version, video.width, video.height, video.frame_count), (4, 8, 8, 2));
+        assert_eq!(video.frame_duration_us, 66670);
+        assert_eq!(video.y_scale, YScale::Doubled);
+        assert_eq!(video.audio_tracks[0], Some(AudioTrack { sample_rate: 22050, channels: 1, bits_per_sample: 16, compression: AudioCompression::Dpcm }));
+        assert_eq!(video.audio_tracks[1], Some(AudioTrack { sample_rate: 11025, channels: 2, bits_per_sample: 8, compression: AudioCompression::Pcm }));
+        assert_eq!(video.audio_tracks[2], None);
+        assert!(video.is_keyframe(0));
+        assert!(!video.is_keyframe(1));
+    }
+
+    #[test]
+    fn from_bytes_should_reject_other_files() {
+        assert_eq!(SmackerVideo::from_bytes(b"RIFF".to_vec()), Err(String::from("Not a Smacker file")));
+        let mut data = test_video();
+        data[3] = b'3';
+        assert_eq!(SmackerVideo::from_bytes(data), Err(String::from("Unsupported Smacker version '3'")));
+        let mut data = test_video();
+        data.truncate(200);
+        assert_eq!(SmackerVideo::from_bytes(data), Err(String::from("Unexpected end of Smacker data")));
+    }
+
+    #[test]
+    fn next_frame_should_decode_the_blocks_and_the_palette() {
+        let mut decoder = SmackerDecoder::new(SmackerVideo::from_bytes(test_video()).unwrap());
+
+        assert_eq!(decoder.next_frame(), Ok(true));
+        assert_eq!(&decoder.palette()[0..9], &[0xFF, 0, 0, 0, 0xFF, 0, 0, 0, 0]);
+        let mono_row = |row: usize| -> Vec<u8> { (0..4).map(|i| if i == row { 1 } else { 2 }).collect() };
+        for row in 0..4 {
+            assert_eq!(&decoder.pixels()[row * 8..row * 8 + 8], &[7, 7, 7, 7, 5, 6, 3, 4]);
+            assert_eq!(&decoder.pixels()[(row + 4) * 8..(row + 4) * 8 + 4], mono_row(row).as_slice());
+            assert_eq!(&decoder.pixels()[(row + 4) * 8 + 4..(row + 5) * 8], mono_row(row).as_slice());
+        }
+        assert_eq!(decoder.to_image().pixel(4, 0), [0, 0, 0, 0xFF]);
+        assert_eq!(decoder.to_image().pixel(0, 4), [0, 0xFF, 0, 0xFF]);
+
+        assert_eq!(decoder.next_frame(), Ok(true));
+        assert_eq!(&decoder.palette()[0..9], &[0, 0, 0xFF, 0xFF, 0, 0, 0, 0, 0]);
+        for row in 0..4 {
+            assert_eq!(&decoder.pixels()[row * 8..row * 8 + 8], &[7, 7, 7, 7, 5, 6, 3, 4]);
+            assert_eq!(&decoder.pixels()[(row + 4) * 8..(row + 4) * 8 + 4], mono_row(row).as_slice());
+        }
+        assert_eq!(&decoder.pixels()[36..40], &[3, 3, 4, 4]);
+        assert_eq!(&decoder.pixels()[44..48], &[3, 3, 4, 4]);
+        assert_eq!(&decoder.pixels()[52..56], &[5, 5, 6, 6]);
+        assert_eq!(&decoder.pixels()[60..64], &[5, 5, 6, 6]);
+
+        assert_eq!(decoder.next_frame(), Ok(false));
+        assert_eq!(decoder.decoded_frames(), 2);
+    }
+
+    #[test]
+    fn decode_audio_should_join_the_chunks_of_a_track() {
+        let video = SmackerVideo::from_bytes(test_video()).unwrap();
+
+        let dpcm = video.decode_audio(0).unwrap();
+        assert_eq!((dpcm.sample_rate, dpcm.channels), (22050, 1));
+        assert_eq!(dpcm.samples, vec!(256, 272, 256, 272, -16));
+        let pcm = video.decode_audio(1).unwrap();
+        assert_eq!((pcm.sample_rate, pcm.channels), (11025, 2));
+        assert_eq!(pcm.samples, vec!(0, 127 << 8, -128 << 8, 1 << 8));
+        assert_eq!(video.decode_audio(2), Err(String::from("The video has no audio track 2")));
+    }
+
+    #[test]
+    fn decode_audio_chunk_should_decode_8_bit_stereo_dpcm() {
+        let track = AudioTrack { sample_rate: 22050, channels: 2, bits_per_sample: 8, compression: AudioCompression::Dpcm };
+        let mut bits = BitWriter::default();
+        bits.write(1, 1).write(1, 1).write(0, 1);
+        TestTree::new(&[0x01]).write(&mut bits);
+        TestTree::new(&[0xFF]).write(&mut bits);
+        // The right channel comes first
+        bits.write(0x80, 8).write(0x70, 8);
+        let mut data = vec!(6, 0, 0, 0);
+        data.extend(bits.bytes);
+
+        let mut samples = Vec::new();
+        decode_audio_chunk(&track, &data, &mut samples).unwrap();
+        assert_eq!(samples, vec!(-0x1000, 0, -0x0F00, -0x0100, -0x0E00, -0x0200));
+
+        let track = AudioTrack { bits_per_sample: 16, ..track };
+        assert_eq!(decode_audio_chunk(&track, &data, &mut samples), Err(String::from("The format of the chunk differs from the one of the track")));
+    }
+}
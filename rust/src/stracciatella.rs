@@ -1,7 +1,12 @@
 #![crate_type = "lib"]
 
+extern crate byteorder;
+extern crate claxon;
 extern crate getopts;
+extern crate lewton;
 extern crate libc;
+extern crate md5;
+extern crate png;
 extern crate serde;
 extern crate serde_json;
 #[macro_use]
@@ -13,6 +18,34 @@ extern crate user32;
 #[cfg(windows)]
 extern crate shell32;
 
+pub mod audio;
+pub mod commands;
+pub mod data;
+pub mod docs;
+pub mod edt;
+pub mod extract;
+pub mod image;
+pub mod imp;
+pub mod integrity;
+pub mod items;
+pub mod launcher;
+pub mod localization;
+pub mod logging;
+pub mod map;
+pub mod mods;
+pub mod music;
+pub mod npc;
+pub mod policy;
+pub mod profile;
+pub mod random;
+pub mod savegame;
+pub mod settings;
+pub mod slf;
+pub mod smacker;
+#[cfg(test)]
+mod test_utils;
+pub mod text;
+
 use std::slice;
 use std::str;
 use std::str::FromStr;
@@ -21,7 +54,7 @@ use std::fmt;
 use std::fmt::Display;
 use std::fs;
 use std::ffi::{CStr, CString};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::default::Default;
 use std::io::prelude::*;
 use std::fs::File;
@@ -34,6 +67,8 @@ use serde::Serialize;
 use getopts::Options;
 use libc::{size_t, c_char};
 
+use commands::Command;
+
 #[cfg(not(windows))]
 static DATA_DIR_OPTION_EXAMPLE: &'static str = "/opt/ja2";
 #[cfg(not(windows))]
@@ -64,6 +99,24 @@ pub enum ResourceVersion {
     RUSSIAN_GOLD,
 }
 
+static ALL_RESOURCE_VERSIONS: [ResourceVersion; 8] = [
+    ResourceVersion::DUTCH,
+    ResourceVersion::ENGLISH,
+    ResourceVersion::FRENCH,
+    ResourceVersion::GERMAN,
+    ResourceVersion::ITALIAN,
+    ResourceVersion::POLISH,
+    ResourceVersion::RUSSIAN,
+    ResourceVersion::RUSSIAN_GOLD,
+];
+
+impl ResourceVersion {
+    /// All known resource versions in declaration order.
+    pub fn all() -> &'static [ResourceVersion] {
+        &ALL_RESOURCE_VERSIONS
+    }
+}
+
 impl FromStr for ResourceVersion {
     type Err = String;
 
@@ -148,6 +201,11 @@ pub struct EngineOptions {
     start_in_debug_mode: bool,
     #[serde(rename = "nosound")]
     start_without_sound: bool,
+    log: logging::LogOptions,
+    #[serde(skip)]
+    random_seed: Option<u64>,
+    #[serde(skip)]
+    command: Command,
 }
 
 impl Default for EngineOptions {
@@ -165,73 +223,149 @@ impl Default for EngineOptions {
             start_in_window: true,
             start_in_debug_mode: false,
             start_without_sound: false,
+            log: logging::LogOptions::default(),
+            random_seed: None,
+            command: Command::RunGame,
         }
     }
 }
 
+/// How often an option may be given on the command line.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum CommandLineOptionKind {
+    Flag,
+    Single,
+    Multi,
+}
+
+/// What kind of value an option takes, used for documentation and shell completion.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum CommandLineOptionValues {
+    Nothing,
+    Directory,
+    Resolution,
+    ResourceVersion,
+    Mod,
+    LogLevel,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CommandLineOption {
+    pub name: &'static str,
+    pub kind: CommandLineOptionKind,
+    pub description: &'static str,
+    pub hint: &'static str,
+    pub values: CommandLineOptionValues,
+}
+
+/// The single source of truth for all command line options of the game binary.
+/// The getopts parser, the man page and the shell completions are built from it.
+pub static COMMAND_LINE_OPTIONS: &[CommandLineOption] = &[
+    CommandLineOption {
+        name: "datadir",
+        kind: CommandLineOptionKind::Multi,
+        description: "Set path for data directory",
+        hint: DATA_DIR_OPTION_EXAMPLE,
+        values: CommandLineOptionValues::Directory,
+    },
+    CommandLineOption {
+        name: "mod",
+        kind: CommandLineOptionKind::Multi,
+        description: "Start one of the game modifications. MOD_NAME is the name of modification, e.g. 'from-russia-with-love. See mods folder for possible options'.",
+        hint: "MOD_NAME",
+        values: CommandLineOptionValues::Mod,
+    },
+    CommandLineOption {
+        name: "res",
+        kind: CommandLineOptionKind::Single,
+        description: "Screen resolution, e.g. 800x600. Default value is 640x480",
+        hint: "WIDTHxHEIGHT",
+        values: CommandLineOptionValues::Resolution,
+    },
+    CommandLineOption {
+        name: "resversion",
+        kind: CommandLineOptionKind::Single,
+        description: "Version of the game resources. Possible values: DUTCH, ENGLISH, FRENCH, GERMAN, ITALIAN, POLISH, RUSSIAN, RUSSIAN_GOLD. Default value is ENGLISH. RUSSIAN is for BUKA Agonia Vlasty release. RUSSIAN_GOLD is for Gold release",
+        hint: "RUSSIAN_GOLD",
+        values: CommandLineOptionValues::ResourceVersion,
+    },
+    CommandLineOption {
+        name: "unittests",
+        kind: CommandLineOptionKind::Flag,
+        description: "Perform unit tests. E.g. 'ja2.exe -unittests --gtest_output=\"xml:report.xml\" --gtest_repeat=2'",
+        hint: "",
+        values: CommandLineOptionValues::Nothing,
+    },
+    CommandLineOption {
+        name: "editor",
+        kind: CommandLineOptionKind::Flag,
+        description: "Start the map editor (Editor.slf is required)",
+        hint: "",
+        values: CommandLineOptionValues::Nothing,
+    },
+    CommandLineOption {
+        name: "fullscreen",
+        kind: CommandLineOptionKind::Flag,
+        description: "Start the game in the fullscreen mode",
+        hint: "",
+        values: CommandLineOptionValues::Nothing,
+    },
+    CommandLineOption {
+        name: "nosound",
+        kind: CommandLineOptionKind::Flag,
+        description: "Turn the sound and music off",
+        hint: "",
+        values: CommandLineOptionValues::Nothing,
+    },
+    CommandLineOption {
+        name: "window",
+        kind: CommandLineOptionKind::Flag,
+        description: "Start the game in a window",
+        hint: "",
+        values: CommandLineOptionValues::Nothing,
+    },
+    CommandLineOption {
+        name: "debug",
+        kind: CommandLineOptionKind::Flag,
+        description: "Enable Debug Mode",
+        hint: "",
+        values: CommandLineOptionValues::Nothing,
+    },
+    CommandLineOption {
+        name: "log",
+        kind: CommandLineOptionKind::Multi,
+        description: "Set the log level of all topics or of one, e.g. 'warning' or 'sgp=debug'. Possible levels: debug, info, warning, error, off",
+        hint: "[TOPIC=]LEVEL",
+        values: CommandLineOptionValues::LogLevel,
+    },
+    CommandLineOption {
+        name: "seed",
+        kind: CommandLineOptionKind::Single,
+        description: "Seed the random numbers to replay a game, e.g. a combat. The seed of every start is written to ja2.log",
+        hint: "NUMBER",
+        values: CommandLineOptionValues::Nothing,
+    },
+    CommandLineOption {
+        name: "help",
+        kind: CommandLineOptionKind::Flag,
+        description: "print this help menu",
+        hint: "",
+        values: CommandLineOptionValues::Nothing,
+    },
+];
+
 pub fn get_command_line_options() -> Options {
     let mut opts = Options::new();
 
     opts.long_only(true);
 
-    opts.optmulti(
-        "",
-        "datadir",
-        "Set path for data directory",
-        DATA_DIR_OPTION_EXAMPLE
-    );
-    opts.optmulti(
-        "",
-        "mod",
-        "Start one of the game modifications. MOD_NAME is the name of modification, e.g. 'from-russia-with-love. See mods folder for possible options'.",
-        "MOD_NAME"
-    );
-    opts.optopt(
-        "",
-        "res",
-        "Screen resolution, e.g. 800x600. Default value is 640x480",
-        "WIDTHxHEIGHT"
-    );
-    opts.optopt(
-        "",
-        "resversion",
-        "Version of the game resources. Possible values: DUTCH, ENGLISH, FRENCH, GERMAN, ITALIAN, POLISH, RUSSIAN, RUSSIAN_GOLD. Default value is ENGLISH. RUSSIAN is for BUKA Agonia Vlasty release. RUSSIAN_GOLD is for Gold release",
-        "RUSSIAN_GOLD"
-    );
-    opts.optflag(
-        "",
-        "unittests",
-        "Perform unit tests. E.g. 'ja2.exe -unittests --gtest_output=\"xml:report.xml\" --gtest_repeat=2'");
-    opts.optflag(
-        "",
-        "editor",
-        "Start the map editor (Editor.slf is required)"
-    );
-    opts.optflag(
-        "",
-        "fullscreen",
-        "Start the game in the fullscreen mode"
-    );
-    opts.optflag(
-        "",
-        "nosound",
-        "Turn the sound and music off"
-    );
-    opts.optflag(
-        "",
-        "window",
-        "Start the game in a window"
-    );
-    opts.optflag(
-        "",
-        "debug",
-        "Enable Debug Mode"
-    );
-    opts.optflag(
-        "",
-        "help",
-        "print this help menu"
-    );
+    for option in COMMAND_LINE_OPTIONS {
+        match option.kind {
+            CommandLineOptionKind::Flag => opts.optflag("", option.name, option.description),
+            CommandLineOptionKind::Single => opts.optopt("", option.name, option.description, option.hint),
+            CommandLineOptionKind::Multi => opts.optmulti("", option.name, option.description, option.hint),
+        };
+    }
 
     return opts;
 }
@@ -241,8 +375,9 @@ fn parse_args(engine_options: &mut EngineOptions, args: Vec<String>) -> Option<S
 
     match opts.parse(&args[1..]) {
         Ok(m) => {
-            if m.free.len() > 0 {
-                return Some(format!("Unknown arguments: '{}'.", m.free.join(" ")));
+            match Command::from_args(&m.free) {
+                Ok(command) => engine_options.command = command,
+                Err(str) => return Some(str)
             }
 
             if let Some(s) = m.opt_str("datadir") {
@@ -312,6 +447,19 @@ fn parse_args(engine_options: &mut EngineOptions, args: Vec<String>) -> Option<S
                 engine_options.start_in_debug_mode = true;
             }
 
+            if let Some(s) = m.opt_str("seed") {
+                match s.parse::<u64>() {
+                    Ok(seed) => engine_options.random_seed = Some(seed),
+                    Err(_) => return Some(format!("Invalid seed '{}'.", s))
+                }
+            }
+
+            for switch in m.opt_strs("log") {
+                if let Err(str) = engine_options.log.apply_switch(&switch) {
+                    return Some(str)
+                }
+            }
+
             return None;
         }
         Err(f) => Some(f.to_string())
@@ -409,7 +557,7 @@ pub fn build_engine_options_from_env_and_args(args: Vec<String>) -> Result<Engin
         Some(str) => Err(str)
     }?;
 
-    if engine_options.vanilla_data_dir == PathBuf::from("") {
+    if engine_options.command.requires_data_dir() && engine_options.vanilla_data_dir == PathBuf::from("") {
         return Err(String::from("Vanilla data directory has to be set either in config file or per command line switch"))
     }
 
@@ -437,7 +585,7 @@ pub fn create_engine_options(array: *const *const c_char, length: size_t) -> *mu
         Ok(engine_options) => {
             if engine_options.show_help {
                 let opts = get_command_line_options();
-                let brief = format!("Usage: ja2 [options]");
+                let brief = commands::usage_brief();
                 print!("{}", opts.usage(&brief));
             }
             Box::into_raw(Box::new(engine_options))
@@ -458,7 +606,13 @@ pub fn write_engine_options(ptr: *mut EngineOptions) -> bool {
 #[no_mangle]
 pub fn free_engine_options(ptr: *mut EngineOptions) {
     if ptr.is_null() { return }
-    unsafe { Box::from_raw(ptr); }
+    unsafe { drop(Box::from_raw(ptr)); }
+}
+
+/// The seed of `--seed`, or one from the current time.
+#[no_mangle]
+pub extern fn get_random_seed(ptr: *const EngineOptions) -> u64 {
+    unsafe_from_ptr!(ptr).random_seed.unwrap_or_else(random::seed_from_time)
 }
 
 #[no_mangle]
@@ -569,34 +723,482 @@ pub fn set_start_without_sound(ptr: *mut EngineOptions, val: bool) -> () {
     unsafe_from_ptr_mut!(ptr).start_without_sound = val
 }
 
+#[no_mangle]
+pub fn should_run_command(ptr: *const EngineOptions) -> bool {
+    unsafe_from_ptr!(ptr).command != Command::RunGame
+}
+
+#[no_mangle]
+pub extern fn run_command(ptr: *const EngineOptions, extra_data_dir_ptr: *const c_char) -> bool {
+    let c_str = unsafe { CStr::from_ptr(extra_data_dir_ptr) };
+    let extra_data_dir = c_str.to_string_lossy().into_owned();
+    let stdout = std::io::stdout();
+
+    match commands::run_command(unsafe_from_ptr!(ptr), Path::new(&extra_data_dir), &mut stdout.lock()) {
+        Ok(()) => true,
+        Err(msg) => {
+            println!("{}", msg);
+            false
+        }
+    }
+}
+
 #[no_mangle]
 pub extern fn get_resource_version_string(version: ResourceVersion) -> *mut c_char {
     let c_str_home = CString::new(version.to_string()).unwrap();
     c_str_home.into_raw()
 }
 
+/// Returns the path of the game executable or null if it could not be found.
+/// `executable_name_ptr` may be null to use the default name.
+#[no_mangle]
+pub extern fn find_ja2_executable(launcher_path_ptr: *const c_char, executable_name_ptr: *const c_char) -> *mut c_char {
+    let launcher_path = unsafe { CStr::from_ptr(launcher_path_ptr).to_string_lossy().into_owned() };
+    let executable_name = if executable_name_ptr.is_null() {
+        String::from(launcher::DEFAULT_EXECUTABLE_NAME)
+    } else {
+        unsafe { CStr::from_ptr(executable_name_ptr).to_string_lossy().into_owned() }
+    };
+
+    match launcher::find_ja2_executable(Path::new(&launcher_path), &executable_name) {
+        Ok(path) => CString::new(path.to_string_lossy().into_owned()).unwrap().into_raw(),
+        Err(msg) => {
+            println!("{}", msg);
+            ptr::null_mut()
+        }
+    }
+}
+
+fn error_to_c_string(result: Result<(), String>) -> *mut c_char {
+    match result {
+        Ok(()) => ptr::null_mut(),
+        Err(msg) => CString::new(msg).unwrap().into_raw(),
+    }
+}
+
+#[no_mangle]
+pub extern fn get_number_of_resource_versions() -> u32 {
+    launcher::available_versions().len() as u32
+}
+
+/// Returns ENGLISH if `index` is out of range.
+#[no_mangle]
+pub extern fn get_resource_version_at(index: u32) -> ResourceVersion {
+    launcher::available_versions().get(index as usize).cloned().unwrap_or(ResourceVersion::ENGLISH)
+}
+
+#[no_mangle]
+pub extern fn get_number_of_recommended_resolutions() -> u32 {
+    launcher::RECOMMENDED_RESOLUTIONS.len() as u32
+}
+
+/// Returns 0 if `index` is out of range.
+#[no_mangle]
+pub extern fn get_recommended_resolution_x(index: u32) -> u16 {
+    launcher::RECOMMENDED_RESOLUTIONS.get(index as usize).map(|r| r.0).unwrap_or(0)
+}
+
+/// Returns 0 if `index` is out of range.
+#[no_mangle]
+pub extern fn get_recommended_resolution_y(index: u32) -> u16 {
+    launcher::RECOMMENDED_RESOLUTIONS.get(index as usize).map(|r| r.1).unwrap_or(0)
+}
+
+/// Returns an error message or null if the game can run in the resolution. Values that do not fit a resolution,
+/// e.g. negative ones, are errors.
+#[no_mangle]
+pub extern fn check_resolution(x: i32, y: i32) -> *mut c_char {
+    error_to_c_string(launcher::validate_resolution_values(x, y).map(|_| ()))
+}
+
+/// Returns an error message or null if `resolution_ptr` is a resolution like `800x600` the game can run in.
+#[no_mangle]
+pub extern fn check_resolution_string(resolution_ptr: *const c_char) -> *mut c_char {
+    let resolution = unsafe { CStr::from_ptr(resolution_ptr).to_string_lossy().into_owned() };
+
+    error_to_c_string(launcher::validate_resolution_string(&resolution).map(|_| ()))
+}
+
+/// Sets a resolution like `800x600`. Returns an error message and leaves the resolution unchanged if it is invalid.
+#[no_mangle]
+pub extern fn set_resolution_from_string(ptr: *mut EngineOptions, resolution_ptr: *const c_char) -> *mut c_char {
+    let resolution = unsafe { CStr::from_ptr(resolution_ptr).to_string_lossy().into_owned() };
+
+    error_to_c_string(launcher::validate_resolution_string(&resolution).map(|r| {
+        unsafe_from_ptr_mut!(ptr).resolution = r;
+    }))
+}
+
+/// Returns an error message or null if the directory contains the original game.
+#[no_mangle]
+pub extern fn check_vanilla_data_dir(data_dir_ptr: *const c_char) -> *mut c_char {
+    let data_dir = unsafe { CStr::from_ptr(data_dir_ptr).to_string_lossy().into_owned() };
+
+    error_to_c_string(launcher::validate_vanilla_data_dir(&data_dir).map(|_| ()))
+}
+
 #[no_mangle]
-pub extern fn find_ja2_executable(launcher_path_ptr: *const c_char) -> *const c_char {
-    let launcher_path = unsafe { CStr::from_ptr(launcher_path_ptr).to_string_lossy() };
-    let is_exe = launcher_path.to_lowercase().ends_with(".exe");
-    let end_of_executable_slice = launcher_path.len() - if is_exe { 13 } else { 9 };
-    let mut executable_path = String::from(&launcher_path[0..end_of_executable_slice]);
+pub extern fn build_launch_command(executable_path_ptr: *const c_char, editor: bool) -> *mut c_char {
+    let executable_path = unsafe { CStr::from_ptr(executable_path_ptr).to_string_lossy().into_owned() };
+    let command = launcher::LaunchCommand::new(Path::new(&executable_path)).editor(editor);
 
-    if is_exe {
-        executable_path.push_str(if is_exe { ".exe" } else { "" });
+    CString::new(command.to_command_line()).unwrap().into_raw()
+}
+
+/// Reads `Ja2.set` next to `ja2.json`. Like the game, this falls back to the defaults if the file is missing or invalid.
+#[no_mangle]
+pub extern fn read_game_settings(ptr: *const EngineOptions) -> *mut settings::GameSettings {
+    let path = settings::game_settings_path(unsafe_from_ptr!(ptr));
+    let game_settings = settings::read_game_settings(&path).ok().filter(|s| !s.is_outdated()).unwrap_or_default();
+
+    Box::into_raw(Box::new(game_settings))
+}
+
+#[no_mangle]
+pub extern fn write_game_settings(ptr: *const EngineOptions, settings_ptr: *const settings::GameSettings) -> bool {
+    let path = settings::game_settings_path(unsafe_from_ptr!(ptr));
+
+    match settings::write_game_settings(&path, unsafe_from_ptr!(settings_ptr)) {
+        Ok(()) => true,
+        Err(msg) => {
+            println!("{}", msg);
+            false
+        }
     }
+}
 
-    CString::new(executable_path).unwrap().into_raw()
+#[no_mangle]
+pub extern fn free_game_settings(ptr: *mut settings::GameSettings) {
+    if ptr.is_null() { return }
+    unsafe { drop(Box::from_raw(ptr)); }
+}
+
+#[no_mangle]
+pub extern fn get_number_of_game_settings() -> u32 {
+    settings::all_setting_keys().len() as u32
+}
+
+/// The key of a setting, the options like `subtitles` come first. Returns null if `index` is out of range.
+#[no_mangle]
+pub extern fn get_game_setting_key(index: u32) -> *mut c_char {
+    match settings::all_setting_keys().get(index as usize) {
+        Some(key) => CString::new(*key).unwrap().into_raw(),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Returns the value of a setting as text, e.g. `true` or `63`, or null if the key is unknown.
+#[no_mangle]
+pub extern fn get_game_setting(ptr: *const settings::GameSettings, key_ptr: *const c_char) -> *mut c_char {
+    let key = unsafe { CStr::from_ptr(key_ptr).to_string_lossy().into_owned() };
+
+    match unsafe_from_ptr!(ptr).get(&key) {
+        Some(value) => CString::new(value).unwrap().into_raw(),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Changes a setting to a value given as text. Returns an error message and leaves the settings unchanged if it is invalid.
+#[no_mangle]
+pub extern fn set_game_setting(ptr: *mut settings::GameSettings, key_ptr: *const c_char, value_ptr: *const c_char) -> *mut c_char {
+    let key = unsafe { CStr::from_ptr(key_ptr).to_string_lossy().into_owned() };
+    let value = unsafe { CStr::from_ptr(value_ptr).to_string_lossy().into_owned() };
+
+    error_to_c_string(unsafe_from_ptr_mut!(ptr).set(&key, &value))
 }
 
 #[no_mangle]
 pub fn free_rust_string(s: *mut c_char) {
     unsafe {
         if s.is_null() { return }
-        CString::from_raw(s)
+        drop(CString::from_raw(s))
     };
 }
 
+/// Stores the current game policy and mods next to a saved game, `save_path` is the path of the `.sav` file.
+#[no_mangle]
+pub extern fn write_saved_game_policy(ptr: *const EngineOptions, extra_data_dir_ptr: *const c_char, save_path_ptr: *const c_char) -> bool {
+    let extra_data_dir = unsafe { CStr::from_ptr(extra_data_dir_ptr).to_string_lossy().into_owned() };
+    let save_path = unsafe { CStr::from_ptr(save_path_ptr).to_string_lossy().into_owned() };
+    let result = policy::PolicySnapshot::current(unsafe_from_ptr!(ptr), Path::new(&extra_data_dir))
+        .and_then(|snapshot| policy::write_policy_snapshot(Path::new(&save_path), &snapshot));
+
+    match result {
+        Ok(()) => true,
+        Err(msg) => {
+            println!("{}", msg);
+            false
+        }
+    }
+}
+
+/// Returns a description of the rule changes since a game was saved, or null if the rules are the same or unknown.
+#[no_mangle]
+pub extern fn check_saved_game_policy(ptr: *const EngineOptions, extra_data_dir_ptr: *const c_char, save_path_ptr: *const c_char) -> *mut c_char {
+    let extra_data_dir = unsafe { CStr::from_ptr(extra_data_dir_ptr).to_string_lossy().into_owned() };
+    let save_path = unsafe { CStr::from_ptr(save_path_ptr).to_string_lossy().into_owned() };
+    let saved = match policy::read_policy_snapshot(Path::new(&save_path)) {
+        Ok(Some(saved)) => saved,
+        Ok(None) => return ptr::null_mut(),
+        Err(msg) => return CString::new(msg).unwrap().into_raw(),
+    };
+
+    match policy::PolicySnapshot::current(unsafe_from_ptr!(ptr), Path::new(&extra_data_dir)) {
+        Ok(current) => {
+            let mismatch = policy::compare_snapshots(&saved, &current);
+            if mismatch.is_empty() { ptr::null_mut() } else { CString::new(mismatch.to_string()).unwrap().into_raw() }
+        },
+        Err(msg) => CString::new(msg).unwrap().into_raw(),
+    }
+}
+
+/// Decodes a WAV, Ogg Vorbis or FLAC file and resamples it to `sample_rate`. Returns null and logs the error if the
+/// file is not supported.
+#[no_mangle]
+pub extern fn load_audio(data_ptr: *const u8, length: size_t, sample_rate: u32) -> *mut audio::AudioData {
+    let data = unsafe { slice::from_raw_parts(data_ptr, length) };
+
+    match audio::load_audio(data, sample_rate) {
+        Ok(audio_data) => Box::into_raw(Box::new(audio_data)),
+        Err(msg) => {
+            log_error("sound", &msg);
+            ptr::null_mut()
+        }
+    }
+}
+
+#[no_mangle]
+pub extern fn get_audio_channels(ptr: *const audio::AudioData) -> u16 {
+    unsafe_from_ptr!(ptr).channels
+}
+
+/// Number of 16-bit samples of all channels together.
+#[no_mangle]
+pub extern fn get_audio_number_of_samples(ptr: *const audio::AudioData) -> size_t {
+    unsafe_from_ptr!(ptr).samples.len()
+}
+
+/// The interleaved samples in the byte order of the system, valid until `free_audio_data` is called.
+#[no_mangle]
+pub extern fn get_audio_samples(ptr: *const audio::AudioData) -> *const i16 {
+    unsafe_from_ptr!(ptr).samples.as_ptr()
+}
+
+#[no_mangle]
+pub extern fn free_audio_data(ptr: *mut audio::AudioData) {
+    if ptr.is_null() { return }
+    unsafe { drop(Box::from_raw(ptr)); }
+}
+
+/// Parses a Smacker video from the contents of its file. Returns null and logs the error if it is not a valid video.
+#[no_mangle]
+pub extern fn open_smacker_video(data_ptr: *const u8, length: size_t) -> *mut smacker::SmackerDecoder {
+    let data = unsafe { slice::from_raw_parts(data_ptr, length) };
+
+    match smacker::SmackerVideo::from_bytes(data.to_vec()) {
+        Ok(video) => Box::into_raw(Box::new(smacker::SmackerDecoder::new(video))),
+        Err(msg) => {
+            log_error("smk", &msg);
+            ptr::null_mut()
+        }
+    }
+}
+
+#[no_mangle]
+pub extern fn get_smacker_width(ptr: *const smacker::SmackerDecoder) -> u32 {
+    unsafe_from_ptr!(ptr).video().width
+}
+
+#[no_mangle]
+pub extern fn get_smacker_height(ptr: *const smacker::SmackerDecoder) -> u32 {
+    unsafe_from_ptr!(ptr).video().height
+}
+
+#[no_mangle]
+pub extern fn get_smacker_frame_count(ptr: *const smacker::SmackerDecoder) -> u32 {
+    unsafe_from_ptr!(ptr).video().frame_count as u32
+}
+
+#[no_mangle]
+pub extern fn get_smacker_frame_duration_us(ptr: *const smacker::SmackerDecoder) -> u32 {
+    unsafe_from_ptr!(ptr).video().frame_duration_us
+}
+
+/// Decodes the next frame. Returns false after the last frame, or logs the error and returns false.
+#[no_mangle]
+pub extern fn next_smacker_frame(ptr: *mut smacker::SmackerDecoder) -> bool {
+    match unsafe_from_ptr_mut!(ptr).next_frame() {
+        Ok(decoded) => decoded,
+        Err(msg) => {
+            log_error("smk", &msg);
+            false
+        }
+    }
+}
+
+/// The 256 RGB colors of the current frame, valid until the next frame is decoded.
+#[no_mangle]
+pub extern fn get_smacker_palette(ptr: *const smacker::SmackerDecoder) -> *const u8 {
+    unsafe_from_ptr!(ptr).palette().as_ptr()
+}
+
+/// The width * height palette indexes of the current frame, valid until the next frame is decoded.
+#[no_mangle]
+pub extern fn get_smacker_pixels(ptr: *const smacker::SmackerDecoder) -> *const u8 {
+    unsafe_from_ptr!(ptr).pixels().as_ptr()
+}
+
+/// Decodes an audio track of the video and resamples it to `sample_rate`. Returns null and logs the error if the
+/// track does not exist or can not be decoded. The result is freed with `free_audio_data`.
+#[no_mangle]
+pub extern fn load_smacker_audio(ptr: *const smacker::SmackerDecoder, track: u8, sample_rate: u32) -> *mut audio::AudioData {
+    match unsafe_from_ptr!(ptr).video().decode_audio(track as usize) {
+        Ok(audio_data) => Box::into_raw(Box::new(audio::resample(&audio_data, sample_rate))),
+        Err(msg) => {
+            log_error("smk", &msg);
+            ptr::null_mut()
+        }
+    }
+}
+
+#[no_mangle]
+pub extern fn free_smacker_video(ptr: *mut smacker::SmackerDecoder) {
+    if ptr.is_null() { return }
+    unsafe { drop(Box::from_raw(ptr)); }
+}
+
+fn lock_logger() -> std::sync::MutexGuard<'static, logging::Logger> {
+    logging::LOGGER.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Logs an error of the decoders under the topic of the C++ code that calls them.
+fn log_error(topic: &str, msg: &str) {
+    let message = format!("{}\n", msg);
+    lock_logger().log(std::time::SystemTime::now(), logging::LogLevel::Error, logging::topic_index(topic).unwrap_or(0), &message);
+}
+
+/// `SLOG_Init`, `log_file` may be null.
+#[no_mangle]
+pub extern fn init_logging(console: u32, log_file: *const c_char) {
+    let path = if log_file.is_null() { None } else { Some(PathBuf::from(unsafe { CStr::from_ptr(log_file) }.to_string_lossy().into_owned())) };
+    if let Err(msg) = lock_logger().init(logging::LogConsole::from_slog(console), path.as_deref()) {
+        println!("{}", msg);
+    }
+}
+
+/// `SLOG_Deinit`
+#[no_mangle]
+pub extern fn deinit_logging() {
+    lock_logger().close_file();
+}
+
+/// `SLOG_SetLevel`
+#[no_mangle]
+pub extern fn set_log_levels(console: u32, file: u32) {
+    lock_logger().set_levels(logging::LogLevel::from_slog(console), logging::LogLevel::from_slog(file));
+}
+
+/// `SLOG_EnableTopic` and `SLOG_DisableTopic`
+#[no_mangle]
+pub extern fn set_log_topic_enabled(topic: u32, enabled: bool) {
+    lock_logger().set_topic_enabled(topic as usize, enabled);
+}
+
+/// Whether `SLOG_LogMessage` would write a message, checked before formatting it.
+#[no_mangle]
+pub extern fn is_log_enabled(level: u32, topic: u32) -> bool {
+    lock_logger().is_enabled(logging::LogLevel::from_slog(level), topic as usize)
+}
+
+/// `SLOG_LogMessage` with the formatted message. Returns whether the message was logged.
+#[no_mangle]
+pub extern fn write_log_message(level: u32, topic: u32, message: *const c_char) -> bool {
+    let message = unsafe { CStr::from_ptr(message) }.to_string_lossy();
+    lock_logger().log(std::time::SystemTime::now(), logging::LogLevel::from_slog(level), topic as usize, &message)
+}
+
+/// Applies the `log` options and moves the log file to `file_name` in `stracciatella_home`.
+/// Prints the error and returns false if the file can not be created.
+#[no_mangle]
+pub extern fn configure_logging(ptr: *const EngineOptions, file_name: *const c_char) -> bool {
+    let engine_options = unsafe_from_ptr!(ptr);
+    let path = engine_options.stracciatella_home.join(unsafe { CStr::from_ptr(file_name) }.to_string_lossy().into_owned());
+    match lock_logger().configure(&engine_options.log, &path) {
+        Ok(()) => true,
+        Err(msg) => {
+            println!("{}", msg);
+            false
+        }
+    }
+}
+
+fn lock_random_numbers() -> std::sync::MutexGuard<'static, random::RandomNumbers> {
+    random::RANDOM_NUMBERS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Seeds `Random` and pregenerates the numbers of `PreRandom`. The seed is logged whatever the log levels are.
+#[no_mangle]
+pub extern fn seed_random(seed: u64) {
+    let message = format!("Random seed: {}\n", seed);
+    lock_logger().log_unfiltered(std::time::SystemTime::now(), logging::LogLevel::Info, logging::topic_index("sgp").unwrap_or(0), &message);
+    *lock_random_numbers() = random::RandomNumbers::new(seed);
+}
+
+/// `Random`, a number in `0..range`.
+#[no_mangle]
+pub extern fn random_below(range: u32) -> u32 {
+    lock_random_numbers().random(range)
+}
+
+/// `PreRandom`, the next pregenerated number modulo `range`.
+#[no_mangle]
+pub extern fn pre_random_below(range: u32) -> u32 {
+    lock_random_numbers().pre_random(range)
+}
+
+#[no_mangle]
+pub extern fn get_random_state() -> u64 {
+    lock_random_numbers().generator_state()
+}
+
+#[no_mangle]
+pub extern fn set_random_state(state: u64) {
+    lock_random_numbers().set_generator_state(state);
+}
+
+#[no_mangle]
+pub extern fn get_random_increment() -> u64 {
+    lock_random_numbers().generator_increment()
+}
+
+#[no_mangle]
+pub extern fn set_random_increment(increment: u64) {
+    lock_random_numbers().set_generator_increment(increment);
+}
+
+#[no_mangle]
+pub extern fn get_pre_random_index() -> u32 {
+    lock_random_numbers().pre_random_index()
+}
+
+#[no_mangle]
+pub extern fn set_pre_random_index(index: u32) {
+    lock_random_numbers().set_pre_random_index(index);
+}
+
+/// Copies the `MAX_PREGENERATED_NUMS` pregenerated numbers to `nums`.
+#[no_mangle]
+pub extern fn get_pre_random_nums(nums: *mut u32) {
+    let nums = unsafe { slice::from_raw_parts_mut(nums, random::MAX_PREGENERATED_NUMS) };
+    nums.copy_from_slice(lock_random_numbers().pre_random_nums());
+}
+
+/// Replaces the pregenerated numbers with the `MAX_PREGENERATED_NUMS` numbers of `nums`.
+#[no_mangle]
+pub extern fn set_pre_random_nums(nums: *const u32) {
+    let nums = unsafe { &*(nums as *const [u32; random::MAX_PREGENERATED_NUMS]) };
+    lock_random_numbers().set_pre_random_nums(nums);
+}
 
 #[cfg(test)]
 mod tests {
@@ -610,6 +1212,7 @@ mod tests {
     use std::fs::File;
     use std::io::prelude::*;
     use std::env;
+    use std::ptr;
 
     macro_rules! assert_chars_eq { ($got:expr, $expected:expr) => {
         unsafe {
@@ -668,6 +1271,58 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_args_should_set_the_command() {
+        let mut engine_options: super::EngineOptions = Default::default();
+        let input = vec!(String::from("ja2"), String::from("-debug"), String::from("mods"), String::from("info"), String::from("o-fortuna"));
+        assert_eq!(super::parse_args(&mut engine_options, input), None);
+        assert!(super::should_start_in_debug_mode(&engine_options));
+        assert!(super::should_run_command(&engine_options));
+        assert_eq!(engine_options.command, super::Command::ModsInfo(String::from("o-fortuna")));
+    }
+
+    #[test]
+    fn launcher_model_should_not_panic_on_indexes_out_of_range() {
+        let count = super::get_number_of_recommended_resolutions();
+        assert_ne!(super::get_recommended_resolution_x(count - 1), 0);
+        assert_eq!(super::get_recommended_resolution_x(count), 0);
+        assert_eq!(super::get_recommended_resolution_y(u32::max_value()), 0);
+        assert_eq!(super::get_resource_version_at(super::get_number_of_resource_versions()), super::ResourceVersion::ENGLISH);
+    }
+
+    #[test]
+    fn get_game_setting_key_should_return_null_for_indexes_out_of_range() {
+        let count = super::get_number_of_game_settings();
+        let key = super::get_game_setting_key(count - 1);
+        assert!(!key.is_null());
+        super::free_rust_string(key);
+        assert!(super::get_game_setting_key(count).is_null());
+    }
+
+    #[test]
+    fn parse_args_should_set_the_random_seed() {
+        let mut engine_options: super::EngineOptions = Default::default();
+        let input = vec!(String::from("ja2"), String::from("--seed"), String::from("1234"));
+        assert_eq!(super::parse_args(&mut engine_options, input), None);
+        assert_eq!(engine_options.random_seed, Some(1234));
+        assert_eq!(super::get_random_seed(&engine_options), 1234);
+
+        let input = vec!(String::from("ja2"), String::from("--seed"), String::from("-1"));
+        assert_eq!(super::parse_args(&mut engine_options, input).unwrap(), "Invalid seed '-1'.");
+    }
+
+    #[test]
+    fn parse_args_should_set_the_log_levels() {
+        let mut engine_options: super::EngineOptions = Default::default();
+        let input = vec!(String::from("ja2"), String::from("--log"), String::from("error"), String::from("--log"), String::from("sgp=debug"));
+        assert_eq!(super::parse_args(&mut engine_options, input), None);
+        assert_eq!(engine_options.log.level, Some(super::logging::LogLevel::Error));
+        assert_eq!(engine_options.log.topics.get("sgp"), Some(&super::logging::LogLevel::Debug));
+
+        let input = vec!(String::from("ja2"), String::from("--log"), String::from("gameloop=loud"));
+        assert_eq!(super::parse_args(&mut engine_options, input).unwrap(), "Unknown log level 'loud', possible levels: debug, info, warning, error, off");
+    }
+
     #[test]
     fn parse_args_should_fail_with_unknown_resversion() {
         let mut engine_options: super::EngineOptions = Default::default();
@@ -1012,6 +1667,22 @@ mod tests {
         assert_eq!(engine_options_res, Err(String::from(expected_error_message)));
     }
 
+    #[test]
+    #[cfg(not(windows))]
+    fn build_engine_options_from_env_and_args_should_not_require_datadir_for_commands() {
+        let temp_dir = write_temp_folder_with_ja2_ini(b"{}");
+        let args = vec!(String::from("ja2"), String::from("config"), String::from("validate"));
+        let old_home = env::var("HOME");
+
+        env::set_var("HOME", temp_dir.path());
+        let engine_options_res = super::build_engine_options_from_env_and_args(args);
+        match old_home {
+            Ok(home) => env::set_var("HOME", home),
+            _ => {}
+        }
+        assert_eq!(engine_options_res.unwrap().command, super::Command::ConfigValidate);
+    }
+
     #[test]
     fn write_engine_options_should_write_a_json_file_that_can_be_serialized_again() {
         let mut engine_options = super::EngineOptions::default();
@@ -1051,7 +1722,13 @@ r##"{
   "resversion": "ENGLISH",
   "fullscreen": false,
   "debug": false,
-  "nosound": false
+  "nosound": false,
+  "log": {
+    "topics": {},
+    "max_file_size": 1048576,
+    "max_files": 3,
+    "json": false
+  }
 }"##);
     }
 
@@ -1070,10 +1747,21 @@ r##"{
 
     #[test]
     fn find_ja2_executable_should_determine_game_path_from_launcher_path() {
-        assert_chars_eq!(super::find_ja2_executable(CString::new("/home/test/ja2-launcher").unwrap().as_ptr()), "/home/test/ja2");
-        assert_chars_eq!(super::find_ja2_executable(CString::new("C:\\\\home\\\\test\\\\ja2-launcher.exe").unwrap().as_ptr()), "C:\\\\home\\\\test\\\\ja2.exe");
-        assert_chars_eq!(super::find_ja2_executable(CString::new("ja2-launcher").unwrap().as_ptr()), "ja2");
-        assert_chars_eq!(super::find_ja2_executable(CString::new("ja2-launcher.exe").unwrap().as_ptr()), "ja2.exe");
-        assert_chars_eq!(super::find_ja2_executable(CString::new("JA2-LAUNCHER.EXE").unwrap().as_ptr()), "JA2.exe");
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        let launcher = CString::new(temp_dir.path().join("ja2-launcher.exe").to_str().unwrap()).unwrap();
+        let name = CString::new("ja2-custom").unwrap();
+        File::create(temp_dir.path().join("ja2.exe")).unwrap();
+        File::create(temp_dir.path().join("ja2-custom.exe")).unwrap();
+
+        assert_chars_eq!(super::find_ja2_executable(launcher.as_ptr(), ptr::null()), temp_dir.path().join("ja2.exe").to_str().unwrap());
+        assert_chars_eq!(super::find_ja2_executable(launcher.as_ptr(), name.as_ptr()), temp_dir.path().join("ja2-custom.exe").to_str().unwrap());
+    }
+
+    #[test]
+    fn find_ja2_executable_should_return_null_if_the_game_is_missing() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        let launcher = CString::new(temp_dir.path().join("ja2-launcher").to_str().unwrap()).unwrap();
+
+        assert!(super::find_ja2_executable(launcher.as_ptr(), ptr::null()).is_null());
     }
 }
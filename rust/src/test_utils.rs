@@ -0,0 +1,251 @@
+//! Builders of the binary files the tests of several modules read: SLF libraries, Smacker videos and font STIs.
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use slf::{DIRENTRY_SIZE, FILENAME_SIZE, LIBHEADER_SIZE};
+
+/// Builds an SLF library, entries with `None` as contents are marked as deleted.
+pub fn slf_file(library_path: &str, files: &[(&str, Option<&[u8]>)]) -> Vec<u8> {
+    let mut data = vec!(0u8; LIBHEADER_SIZE);
+    data[0..8].copy_from_slice(b"test.slf");
+    data[FILENAME_SIZE..FILENAME_SIZE + library_path.len()].copy_from_slice(library_path.as_bytes());
+    data[2 * FILENAME_SIZE..2 * FILENAME_SIZE + 4].copy_from_slice(&(files.len() as i32).to_le_bytes());
+
+    let mut directory = Vec::new();
+    for &(name, contents) in files {
+        let mut entry = vec!(0u8; DIRENTRY_SIZE);
+        entry[0..name.len()].copy_from_slice(name.as_bytes());
+        entry[FILENAME_SIZE..FILENAME_SIZE + 4].copy_from_slice(&(data.len() as u32).to_le_bytes());
+        match contents {
+            Some(contents) => {
+                entry[FILENAME_SIZE + 4..FILENAME_SIZE + 8].copy_from_slice(&(contents.len() as u32).to_le_bytes());
+                data.extend_from_slice(contents);
+            },
+            None => entry[FILENAME_SIZE + 8] = 0xFF,
+        }
+        directory.extend(entry);
+    }
+    data.extend(directory);
+    data
+}
+
+/// Packs values with the least significant bit first, like Vorbis and Smacker.
+#[derive(Default)]
+pub struct BitWriter {
+    pub bytes: Vec<u8>,
+    bits: usize,
+}
+
+impl BitWriter {
+    pub fn write(&mut self, value: u32, bits: usize) -> &mut BitWriter {
+        for i in 0..bits {
+            if self.bits % 8 == 0 {
+                self.bytes.push(0);
+            }
+            *self.bytes.last_mut().unwrap() |= (((value >> i) & 1) as u8) << (self.bits % 8);
+            self.bits += 1;
+        }
+        self
+    }
+}
+
+/// A Huffman tree for the tests, built by splitting the values in halves.
+pub struct TestTree {
+    values: Vec<u16>,
+}
+
+impl TestTree {
+    pub fn new(values: &[u16]) -> TestTree {
+        TestTree { values: values.to_vec() }
+    }
+
+    fn write_nodes(bits: &mut BitWriter, values: &[u16], write_leaf: &mut dyn FnMut(&mut BitWriter, u16)) {
+        if values.len() == 1 {
+            bits.write(0, 1);
+            write_leaf(bits, values[0]);
+        } else {
+            bits.write(1, 1);
+            let (zero, one) = values.split_at(values.len() / 2);
+            TestTree::write_nodes(bits, zero, write_leaf);
+            TestTree::write_nodes(bits, one, write_leaf);
+        }
+    }
+
+    fn write_code(bits: &mut BitWriter, values: &[u16], value: u16) {
+        if values.len() > 1 {
+            let (zero, one) = values.split_at(values.len() / 2);
+            let is_one = !zero.contains(&value);
+            bits.write(is_one as u32, 1);
+            TestTree::write_code(bits, if is_one { one } else { zero }, value);
+        }
+    }
+
+    /// Writes a tree of 8 bit values.
+    pub fn write(&self, bits: &mut BitWriter) {
+        bits.write(1, 1);
+        TestTree::write_nodes(bits, &self.values, &mut |bits, value| { bits.write(u32::from(value), 8); });
+        bits.write(0, 1);
+    }
+
+    /// Writes a tree of 16 bit values with the trees of their low and high bytes.
+    fn write_big(&self, bits: &mut BitWriter, escapes: [u16; 3]) {
+        let mut low: Vec<u16> = self.values.iter().map(|v| v & 0xFF).collect();
+        let mut high: Vec<u16> = self.values.iter().map(|v| v >> 8).collect();
+        low.sort();
+        low.dedup();
+        high.sort();
+        high.dedup();
+        bits.write(1, 1);
+        TestTree::new(&low).write(bits);
+        TestTree::new(&high).write(bits);
+        for escape in &escapes {
+            bits.write(u32::from(*escape), 16);
+        }
+        TestTree::write_nodes(bits, &self.values, &mut |bits, value| {
+            TestTree::write_code(bits, &low, value & 0xFF);
+            TestTree::write_code(bits, &high, value >> 8);
+        });
+        bits.write(0, 1);
+    }
+
+    fn encode(&self, bits: &mut BitWriter, value: u16) {
+        TestTree::write_code(bits, &self.values, value);
+    }
+}
+
+const NO_ESCAPES: [u16; 3] = [0xFFF0, 0xFFF1, 0xFFF2];
+const MONO_MAP: u16 = 0x8421;
+const COLORS_ESCAPE: u16 = 0xEEEE;
+
+fn video_trees() -> [TestTree; 4] {
+    [
+        TestTree::new(&[MONO_MAP]),
+        TestTree::new(&[0x0102, COLORS_ESCAPE]),
+        TestTree::new(&[0x0403, 0x0605]),
+        TestTree::new(&[0x0703, 0x0001, 0x0004, 0x000A]),
+    ]
+}
+
+/// An audio chunk with the size in front.
+fn audio_chunk(data: &[u8]) -> Vec<u8> {
+    let mut chunk = vec!(0; 4);
+    LittleEndian::write_u32(&mut chunk, data.len() as u32 + 4);
+    chunk.extend_from_slice(data);
+    chunk
+}
+
+/// 16 bit mono DPCM with deltas of +16 and -16.
+fn dpcm_chunk(first: i16, deltas: &[bool]) -> Vec<u8> {
+    let mut bits = BitWriter::default();
+    bits.write(1, 1).write(0, 1).write(1, 1);
+    TestTree::new(&[0x10, 0xF0]).write(&mut bits);
+    TestTree::new(&[0x00, 0xFF]).write(&mut bits);
+    bits.write(u32::from(first as u16 >> 8), 8).write(u32::from(first as u16 & 0xFF), 8);
+    for &negative in deltas {
+        bits.write(negative as u32, 1).write(negative as u32, 1);
+    }
+    let mut data = vec!(0; 4);
+    LittleEndian::write_u32(&mut data, (deltas.len() as u32 + 1) * 2);
+    data.extend(bits.bytes);
+    audio_chunk(&data)
+}
+
+/// An SMK4 video of 8x8 pixels with a ring frame and two frames that use every kind of block and palette update,
+/// with a 16 bit DPCM audio track and an 8 bit stereo PCM one.
+pub fn test_video() -> Vec<u8> {
+    let trees = video_trees();
+    let [ref mono_map, ref mono_colors, ref full, ref types] = trees;
+    let mut tree_bits = BitWriter::default();
+    for tree in &trees {
+        tree.write_big(&mut tree_bits, if tree.values.contains(&COLORS_ESCAPE) { [COLORS_ESCAPE, 0xEEEF, 0xEEED] } else { NO_ESCAPES });
+    }
+
+    // A solid block, a full block and two mono blocks, the second with the escape for the last colors
+    let mut video = BitWriter::default();
+    types.encode(&mut video, 0x0703);
+    types.encode(&mut video, 0x0001);
+    video.write(0, 2);
+    for _ in 0..4 {
+        full.encode(&mut video, 0x0403);
+        full.encode(&mut video, 0x0605);
+    }
+    types.encode(&mut video, 0x0004);
+    mono_colors.encode(&mut video, 0x0102);
+    mono_map.encode(&mut video, MONO_MAP);
+    mono_colors.encode(&mut video, COLORS_ESCAPE);
+    mono_map.encode(&mut video, MONO_MAP);
+    let mut frame_0 = vec!(3, 0x3F, 0, 0, 0, 0x3F, 0, 0xFF, 0xFD, 0, 0, 0);
+    frame_0.extend(dpcm_chunk(256, &[false, true, false]));
+    frame_0.extend(audio_chunk(&[0x80, 0xFF]));
+    frame_0.extend(video.bytes);
+
+    // Three void blocks and a double full block
+    let mut video = BitWriter::default();
+    types.encode(&mut video, 0x000A);
+    types.encode(&mut video, 0x0001);
+    video.write(1, 1);
+    full.encode(&mut video, 0x0403);
+    full.encode(&mut video, 0x0605);
+    let mut frame_1 = vec!(2, 0, 0, 0x3F, 0x40, 0, 0xFF, 0xFD);
+    frame_1.extend(dpcm_chunk(-16, &[]));
+    frame_1.extend(audio_chunk(&[0x00, 0x81]));
+    frame_1.extend(video.bytes);
+
+    let frames = [frame_0.clone(), frame_1, frame_0];
+    let mut data = b"SMK4".to_vec();
+    let mut header = [0; 100];
+    LittleEndian::write_u32(&mut header[0..4], 8);
+    LittleEndian::write_u32(&mut header[4..8], 8);
+    LittleEndian::write_u32(&mut header[8..12], 2);
+    LittleEndian::write_i32(&mut header[12..16], -6667);
+    LittleEndian::write_u32(&mut header[16..20], 0x01 | 0x02);
+    LittleEndian::write_u32(&mut header[48..52], tree_bits.bytes.len() as u32);
+    LittleEndian::write_u32(&mut header[68..72], 0xE000_0000 | 22050);
+    LittleEndian::write_u32(&mut header[72..76], 0x5000_0000 | 11025);
+    data.extend_from_slice(&header);
+    for (i, frame) in frames.iter().enumerate() {
+        let mut size = [0; 4];
+        LittleEndian::write_u32(&mut size, (frame.len() as u32 + 3) & !3 | (i == 0) as u32);
+        data.extend_from_slice(&size);
+    }
+    data.extend_from_slice(&[0x07; 3]);
+    data.extend(tree_bits.bytes);
+    for frame in &frames {
+        data.extend(frame);
+        data.extend_from_slice(&[0; 3][..(4 - frame.len() % 4) % 4]);
+    }
+    data
+}
+
+/// An ETRLE compressed font STI with 93 glyphs, enough for ASCII in the English table. The glyphs are 4x6
+/// pixels filled with their index + 1, except the space which is 2 pixels wide.
+pub fn test_font() -> Vec<u8> {
+    let mut subimages = Vec::new();
+    let mut pixel_data = Vec::new();
+    for glyph in 0..93u8 {
+        let (width, height) = (if glyph == 92 { 2u16 } else { 4 }, 6u16);
+        let offset = pixel_data.len() as u32;
+        for _ in 0..height {
+            pixel_data.push(width as u8);
+            pixel_data.extend(vec!(glyph + 1; width as usize));
+            pixel_data.push(0);
+        }
+        subimages.extend_from_slice(&offset.to_le_bytes());
+        subimages.extend_from_slice(&(pixel_data.len() as u32 - offset).to_le_bytes());
+        subimages.extend_from_slice(&[0, 0, 0, 0]);
+        subimages.extend_from_slice(&height.to_le_bytes());
+        subimages.extend_from_slice(&width.to_le_bytes());
+    }
+
+    let mut data = vec!(0u8; 64);
+    data[0..4].copy_from_slice(b"STCI");
+    data[8..12].copy_from_slice(&(pixel_data.len() as u32).to_le_bytes());
+    data[16] = 0x28;
+    data[24..28].copy_from_slice(&256u32.to_le_bytes());
+    data[28..30].copy_from_slice(&93u16.to_le_bytes());
+    data[44] = 8;
+    data.extend((0..768).map(|i| (i % 256) as u8));
+    data.extend(subimages);
+    data.extend(pixel_data);
+    data
+}
@@ -0,0 +1,474 @@
+//! Measuring, wrapping and drawing text with the bitmap fonts of the game, like `Font.cc` and `WordWrap.cc`.
+//!
+//! A font is an ETRLE compressed STI with one subimage per glyph, e.g. `fonts/font10arial.sti`. The translation table of
+//! the resource version maps the characters to glyphs, see `TranslationTable.cc`, and the game draws a `?` for a
+//! character without a glyph. The Russian versions ship fonts with their own order of glyphs, so their strings need to
+//! be measured with their fonts.
+//!
+//! `check_string_widths` wraps every string of a resource in `externalized/strings` like `LineWrap` does, so
+//! translators can see without starting the game which strings overflow the box they are drawn into.
+
+use std::fs;
+use std::path::Path;
+
+use serde_json::Value;
+
+use image;
+use image::{Image, IndexedImage};
+use localization;
+use ResourceVersion;
+
+/// Characters `first` to `last` map to the glyphs starting at `glyph`.
+type GlyphRun = (char, char, u8);
+
+/// `CTranslationTableEnglish`, also used by the Dutch, German, Italian and Polish versions.
+static ENGLISH_GLYPHS: &[GlyphRun] = &[
+    (' ', ' ', 92), ('!', '!', 62), ('"', '"', 84), ('#', '%', 64), ('&', '&', 68), ('\'', '\'', 85), ('(', ')', 70),
+    ('*', '*', 69), ('+', '+', 74), (',', ',', 88), ('-', '-', 72), ('.', '.', 89), ('/', '/', 91), ('0', '9', 52),
+    (':', ';', 82), ('<', '<', 86), ('=', '=', 75), ('>', '>', 87), ('?', '?', 90), ('@', '@', 63), ('A', 'Z', 0),
+    ('[', '[', 80), ('\\', '\\', 77), (']', ']', 81), ('^', '^', 67), ('_', '_', 73), ('a', 'z', 26), ('{', '{', 78),
+    ('|', '|', 76), ('}', '}', 79), ('\u{00C0}', '\u{00C2}', 133), ('\u{00C4}', '\u{00C4}', 93),
+    ('\u{00C7}', '\u{00C7}', 136), ('\u{00C8}', '\u{00CA}', 138), ('\u{00CB}', '\u{00CB}', 137),
+    ('\u{00CC}', '\u{00CC}', 168), ('\u{00CF}', '\u{00CF}', 141), ('\u{00D2}', '\u{00D4}', 143),
+    ('\u{00D6}', '\u{00D6}', 94), ('\u{00D9}', '\u{00DB}', 147), ('\u{00DC}', '\u{00DC}', 95),
+    ('\u{00DF}', '\u{00DF}', 99), ('\u{00E0}', '\u{00E2}', 151), ('\u{00E4}', '\u{00E4}', 96),
+    ('\u{00E7}', '\u{00E7}', 154), ('\u{00E8}', '\u{00EA}', 156), ('\u{00EB}', '\u{00EB}', 155),
+    ('\u{00EC}', '\u{00EC}', 169), ('\u{00EF}', '\u{00EF}', 159), ('\u{00F2}', '\u{00F4}', 161),
+    ('\u{00F6}', '\u{00F6}', 97), ('\u{00F9}', '\u{00FB}', 165), ('\u{00FC}', '\u{00FC}', 98),
+    ('\u{0104}', '\u{0104}', 170), ('\u{0105}', '\u{0105}', 179), ('\u{0106}', '\u{0106}', 171),
+    ('\u{0107}', '\u{0107}', 180), ('\u{0118}', '\u{0118}', 172), ('\u{0119}', '\u{0119}', 181),
+    ('\u{0141}', '\u{0141}', 173), ('\u{0142}', '\u{0142}', 182), ('\u{0143}', '\u{0143}', 174),
+    ('\u{0144}', '\u{0144}', 183), ('\u{015A}', '\u{015A}', 176), ('\u{015B}', '\u{015B}', 185),
+    ('\u{0179}', '\u{0179}', 178), ('\u{017A}', '\u{017A}', 187), ('\u{017B}', '\u{017B}', 177),
+    ('\u{017C}', '\u{017C}', 186), ('\u{0401}', '\u{0401}', 106), ('\u{0410}', '\u{0415}', 100),
+    ('\u{0416}', '\u{0429}', 107), ('\u{042A}', '\u{042A}', 128), ('\u{042B}', '\u{042F}', 127),
+    ('\u{0430}', '\u{0435}', 100), ('\u{0436}', '\u{0449}', 107), ('\u{044A}', '\u{044A}', 128),
+    ('\u{044B}', '\u{044F}', 127), ('\u{0451}', '\u{0451}', 106),
+];
+
+/// `CTranslationTableFrench`.
+static FRENCH_GLYPHS: &[GlyphRun] = &[
+    (' ', ' ', 92), ('!', '!', 62), ('"', '"', 84), ('#', '%', 64), ('&', '&', 68), ('\'', '\'', 85), ('(', ')', 70),
+    ('*', '*', 69), ('+', '+', 74), (',', ',', 88), ('-', '-', 72), ('.', '.', 89), ('/', '/', 91), ('0', '9', 52),
+    (':', ';', 82), ('<', '<', 86), ('=', '=', 75), ('>', '>', 87), ('?', '?', 90), ('@', '@', 63), ('A', 'Z', 0),
+    ('[', '[', 80), ('\\', '\\', 77), (']', ']', 81), ('^', '^', 67), ('_', '_', 73), ('a', 'z', 26), ('{', '{', 78),
+    ('|', '|', 76), ('}', '}', 79), ('\u{00C0}', '\u{00C2}', 133), ('\u{00C4}', '\u{00C4}', 93),
+    ('\u{00C7}', '\u{00C7}', 136), ('\u{00C8}', '\u{00CA}', 138), ('\u{00CB}', '\u{00CB}', 137),
+    ('\u{00CC}', '\u{00CC}', 168), ('\u{00CE}', '\u{00CE}', 169), ('\u{00CF}', '\u{00CF}', 141),
+    ('\u{00D2}', '\u{00D4}', 143), ('\u{00D6}', '\u{00D6}', 94), ('\u{00D9}', '\u{00DB}', 147),
+    ('\u{00DC}', '\u{00DC}', 95), ('\u{00DF}', '\u{00DF}', 99), ('\u{00E0}', '\u{00E2}', 151),
+    ('\u{00E4}', '\u{00E4}', 96), ('\u{00E7}', '\u{00E7}', 154), ('\u{00E8}', '\u{00EA}', 156),
+    ('\u{00EB}', '\u{00EB}', 155), ('\u{00EC}', '\u{00EC}', 170), ('\u{00EE}', '\u{00EE}', 171),
+    ('\u{00EF}', '\u{00EF}', 159), ('\u{00F2}', '\u{00F4}', 161), ('\u{00F6}', '\u{00F6}', 97),
+    ('\u{00F9}', '\u{00FB}', 165), ('\u{00FC}', '\u{00FC}', 98), ('\u{0104}', '\u{0104}', 172),
+    ('\u{0105}', '\u{0105}', 181), ('\u{0106}', '\u{0106}', 173), ('\u{0107}', '\u{0107}', 182),
+    ('\u{0118}', '\u{0118}', 174), ('\u{0119}', '\u{0119}', 183), ('\u{0141}', '\u{0141}', 175),
+    ('\u{0142}', '\u{0142}', 184), ('\u{0143}', '\u{0143}', 176), ('\u{0144}', '\u{0144}', 185),
+    ('\u{015A}', '\u{015A}', 178), ('\u{015B}', '\u{015B}', 187), ('\u{0179}', '\u{0179}', 180),
+    ('\u{017A}', '\u{017A}', 189), ('\u{017B}', '\u{017B}', 179), ('\u{017C}', '\u{017C}', 188),
+    ('\u{0401}', '\u{0401}', 106), ('\u{0410}', '\u{0415}', 100), ('\u{0416}', '\u{0429}', 107),
+    ('\u{042A}', '\u{042A}', 128), ('\u{042B}', '\u{042F}', 127), ('\u{0430}', '\u{0435}', 100),
+    ('\u{0436}', '\u{0449}', 107), ('\u{044A}', '\u{044A}', 128), ('\u{044B}', '\u{044F}', 127),
+    ('\u{0451}', '\u{0451}', 106),
+];
+
+/// `CTranslationTableRussian`, the glyphs of the BUKA release follow CP1251 without its gaps.
+static RUSSIAN_GLYPHS: &[GlyphRun] = &[
+    (' ', '\u{007F}', 0), ('\u{0410}', '\u{044F}', 96),
+];
+
+/// `CTranslationTableRussianGold`.
+static RUSSIAN_GOLD_GLYPHS: &[GlyphRun] = &[
+    (' ', ' ', 92), ('!', '!', 62), ('"', '"', 84), ('#', '%', 64), ('&', '&', 68), ('\'', '\'', 85), ('(', ')', 70),
+    ('*', '*', 69), ('+', '+', 74), (',', ',', 88), ('-', '-', 72), ('.', '.', 89), ('/', '/', 91), ('0', '9', 52),
+    (':', ';', 82), ('<', '<', 86), ('=', '=', 75), ('>', '>', 87), ('?', '?', 90), ('@', '@', 63), ('A', 'Z', 0),
+    ('[', '[', 80), ('\\', '\\', 77), (']', ']', 81), ('^', '^', 67), ('_', '_', 73), ('a', 'z', 26), ('{', '{', 78),
+    ('|', '|', 76), ('}', '}', 79), ('\u{00C0}', '\u{00C2}', 165), ('\u{00C4}', '\u{00C4}', 93),
+    ('\u{00C7}', '\u{00C7}', 168), ('\u{00C8}', '\u{00CA}', 170), ('\u{00CB}', '\u{00CB}', 169),
+    ('\u{00CC}', '\u{00CC}', 182), ('\u{00CF}', '\u{00CF}', 173), ('\u{00D2}', '\u{00D2}', 175),
+    ('\u{00D3}', '\u{00D3}', 189), ('\u{00D4}', '\u{00D4}', 177), ('\u{00D6}', '\u{00D6}', 94),
+    ('\u{00D9}', '\u{00DB}', 179), ('\u{00DC}', '\u{00DC}', 95), ('\u{00DF}', '\u{00DF}', 99),
+    ('\u{00E0}', '\u{00E2}', 165), ('\u{00E4}', '\u{00E4}', 96), ('\u{00E7}', '\u{00E7}', 168),
+    ('\u{00E8}', '\u{00EA}', 170), ('\u{00EB}', '\u{00EB}', 169), ('\u{00EC}', '\u{00EC}', 183),
+    ('\u{00EF}', '\u{00EF}', 173), ('\u{00F2}', '\u{00F2}', 175), ('\u{00F3}', '\u{00F3}', 198),
+    ('\u{00F4}', '\u{00F4}', 177), ('\u{00F6}', '\u{00F6}', 97), ('\u{00F9}', '\u{00FB}', 179),
+    ('\u{00FC}', '\u{00FC}', 98), ('\u{0104}', '\u{0104}', 184), ('\u{0105}', '\u{0105}', 193),
+    ('\u{0106}', '\u{0106}', 185), ('\u{0107}', '\u{0107}', 194), ('\u{0118}', '\u{0118}', 186),
+    ('\u{0119}', '\u{0119}', 195), ('\u{0141}', '\u{0141}', 187), ('\u{0142}', '\u{0142}', 196),
+    ('\u{0143}', '\u{0143}', 188), ('\u{0144}', '\u{0144}', 197), ('\u{015A}', '\u{015A}', 190),
+    ('\u{015B}', '\u{015B}', 199), ('\u{0179}', '\u{0179}', 192), ('\u{017A}', '\u{017A}', 201),
+    ('\u{017B}', '\u{017B}', 191), ('\u{017C}', '\u{017C}', 200), ('\u{0410}', '\u{044F}', 100),
+];
+
+/// Maps characters to the glyphs of the fonts of a resource version, see `setResources` in `GameRes.cc`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TranslationTable {
+    runs: &'static [GlyphRun],
+    /// The only character that maps to glyph 0, the table has 0 for all characters without a glyph.
+    zero_glyph_char: char,
+}
+
+impl TranslationTable {
+    pub fn for_version(version: ResourceVersion) -> TranslationTable {
+        let runs = match version {
+            ResourceVersion::FRENCH => FRENCH_GLYPHS,
+            ResourceVersion::RUSSIAN => RUSSIAN_GLYPHS,
+            ResourceVersion::RUSSIAN_GOLD => RUSSIAN_GOLD_GLYPHS,
+            _ => ENGLISH_GLYPHS,
+        };
+        let zero_glyph_char = if version == ResourceVersion::RUSSIAN { ' ' } else { 'A' };
+        TranslationTable { runs, zero_glyph_char }
+    }
+
+    /// The glyph of a character, `None` if the fonts have none like in `IsPrintableChar`.
+    pub fn glyph(&self, c: char) -> Option<u8> {
+        let glyph = self.runs.iter()
+            .find(|&&(first, last, _)| first <= c && c <= last)
+            .map_or(0, |&(first, _, glyph)| glyph + (c as u32 - first as u32) as u8);
+        if glyph != 0 || c == self.zero_glyph_char { Some(glyph) } else { None }
+    }
+}
+
+/// A font with one glyph per subimage, the glyphs keep the palette indices of the STI.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Font {
+    pub palette: Vec<[u8; 4]>,
+    pub glyphs: Vec<IndexedImage>,
+}
+
+impl Font {
+    pub fn from_sti(data: &[u8]) -> Result<Font, String> {
+        let (palette, glyphs) = image::decode_sti_indexed(data)?;
+        if glyphs.is_empty() {
+            return Err(String::from("The font has no glyphs"));
+        }
+        Ok(Font { palette, glyphs })
+    }
+
+    /// The height of the first glyph including its offset, which the game uses as the height of the font.
+    pub fn height(&self) -> u32 {
+        (i32::from(self.glyphs[0].height) + i32::from(self.glyphs[0].offset_y)).max(0) as u32
+    }
+
+    /// The advance of a glyph, 0 for glyphs the font does not have.
+    fn glyph_width(&self, glyph: u8) -> u32 {
+        self.glyphs.get(glyph as usize).map_or(0, |g| (i32::from(g.width) + i32::from(g.offset_x)).max(0) as u32)
+    }
+}
+
+pub fn read_font_file(path: &Path) -> Result<Font, String> {
+    let data = fs::read(path).map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+
+    Font::from_sti(&data).map_err(|e| format!("Error parsing {}: {}", path.display(), e))
+}
+
+/// A font with the translation table of a resource version, measures and draws text like the game.
+#[derive(Debug, Clone, Copy)]
+pub struct TextLayout<'a> {
+    pub font: &'a Font,
+    pub table: TranslationTable,
+}
+
+impl<'a> TextLayout<'a> {
+    pub fn new(font: &'a Font, version: ResourceVersion) -> TextLayout<'a> {
+        TextLayout { font, table: TranslationTable::for_version(version) }
+    }
+
+    /// The glyph the game draws for a character, the one of `?` if there is none, see `GetGlyphIndex`.
+    pub fn glyph(&self, c: char) -> u8 {
+        self.table.glyph(c).or_else(|| self.table.glyph('?')).unwrap_or(0)
+    }
+
+    /// The characters of `text` that have no glyph in the table or the font, each once in order.
+    pub fn missing_glyphs(&self, text: &str) -> Vec<char> {
+        let mut missing: Vec<char> = Vec::new();
+        for c in text.chars() {
+            let has_glyph = self.table.glyph(c).is_some_and(|g| (g as usize) < self.font.glyphs.len());
+            if !has_glyph && !missing.contains(&c) {
+                missing.push(c);
+            }
+        }
+        missing
+    }
+
+    pub fn char_width(&self, c: char) -> u32 {
+        self.font.glyph_width(self.glyph(c))
+    }
+
+    /// The width of `text` in pixels, see `StringPixLength`.
+    pub fn string_width(&self, text: &str) -> u32 {
+        text.chars().map(|c| self.char_width(c)).sum()
+    }
+
+    /// Splits `text` into lines of at most `width` pixels like `LineWrap`: at spaces and after hyphens, words longer
+    /// than a line anywhere. Leading spaces are dropped, as are the spaces where a line ends.
+    pub fn line_wrap(&self, text: &str, width: u32) -> Vec<String> {
+        let chars: Vec<char> = text.chars().collect();
+        let mut lines = Vec::new();
+        let mut i = chars.iter().take_while(|&&c| c == ' ').count();
+        let (mut line_start, mut line_end, mut word_start) = (i, i, i);
+        let (mut line_width, mut word_width) = (0, 0);
+
+        loop {
+            if chars.get(i) == Some(&' ') {
+                line_end = i;
+                while chars.get(i) == Some(&' ') {
+                    line_width += self.char_width(' ');
+                    i += 1;
+                }
+                word_start = i;
+                word_width = 0;
+            }
+            let c = match chars.get(i) {
+                Some(&c) => c,
+                None => {
+                    if line_start != i {
+                        lines.push(chars[line_start..i].iter().collect());
+                    }
+                    return lines;
+                },
+            };
+            let w = self.char_width(c);
+            word_width += w;
+            line_width += w;
+            if line_width > width {
+                if line_start == line_end {
+                    // A single word is longer than a line, the game splits it and does not count this character.
+                    line_end = i;
+                    word_start = i;
+                    word_width = 0;
+                }
+                lines.push(chars[line_start..line_end].iter().collect());
+                line_start = word_start;
+                line_end = word_start;
+                line_width = word_width;
+            }
+            if c == '-' {
+                line_end = i + 1;
+                word_start = i + 1;
+                word_width = 0;
+            }
+            i += 1;
+        }
+    }
+
+    /// Cuts `text` to `width` pixels including the three dots appended to it, see `ReduceStringLength`.
+    pub fn reduce_string_length(&self, text: &str, width: u32) -> String {
+        if self.string_width(text) <= width {
+            return String::from(text);
+        }
+
+        let mut rest = width.saturating_sub(self.string_width("..."));
+        let mut reduced: String = text.chars().take_while(|&c| {
+            let w = self.char_width(c);
+            if w > rest {
+                return false;
+            }
+            rest -= w;
+            true
+        }).collect();
+        reduced.push_str("...");
+        reduced
+    }
+
+    /// Draws `text` with the palette indices of the glyphs into `target` at `x`, `y` like `gprintf`, clipped to the
+    /// target.
+    pub fn draw(&self, text: &str, target: &mut IndexedImage, mut x: i32, y: i32) {
+        for c in text.chars() {
+            let glyph = self.glyph(c);
+            if let Some(image) = self.font.glyphs.get(glyph as usize) {
+                let (left, top) = (x + i32::from(image.offset_x), y + i32::from(image.offset_y));
+                for gy in 0..image.height {
+                    for gx in 0..image.width {
+                        let (tx, ty) = (left + i32::from(gx), top + i32::from(gy));
+                        let index = image.pixel(gx, gy);
+                        if index != 0 && tx >= 0 && ty >= 0 && tx < i32::from(target.width) && ty < i32::from(target.height) {
+                            target.pixels[ty as usize * target.width as usize + tx as usize] = index;
+                        }
+                    }
+                }
+            }
+            x += self.font.glyph_width(glyph) as i32;
+        }
+    }
+
+    /// Wraps `text` to `width` pixels and draws the lines below each other, `gap` pixels apart, like
+    /// `DisplayWrappedString` with left justification. The image is as high as the lines need.
+    pub fn draw_wrapped(&self, text: &str, width: u16, gap: u32) -> IndexedImage {
+        let lines = self.line_wrap(text, u32::from(width));
+        let line_height = self.font.height() + gap;
+        let mut target = IndexedImage::new(width, (lines.len() as u32 * line_height).min(u32::from(u16::MAX)) as u16);
+
+        for (i, line) in lines.iter().enumerate() {
+            self.draw(line, &mut target, 0, (i as u32 * line_height) as i32);
+        }
+        target
+    }
+
+    /// Converts an image drawn with this font to RGBA with the palette of the font.
+    pub fn to_image(&self, drawn: &IndexedImage) -> Image {
+        drawn.to_image(&self.font.palette)
+    }
+}
+
+/// Collects the strings of a string file with their path, e.g. `[3]` or `a.b`, named like `localization` does.
+fn collect_strings<'a>(path: &str, value: &'a Value, strings: &mut Vec<(String, &'a str)>) {
+    match *value {
+        Value::Array(ref values) => {
+            for (index, value) in values.iter().enumerate() {
+                collect_strings(&format!("{}[{}]", path, index), value, strings);
+            }
+        },
+        Value::Object(ref values) => {
+            for (key, value) in values {
+                let entry = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                collect_strings(&entry, value, strings);
+            }
+        },
+        Value::String(ref text) => strings.push((String::from(path), text)),
+        _ => {},
+    }
+}
+
+/// Problems of the strings of one file: characters without a glyph and strings that need more than `max_lines`
+/// lines of `width` pixels, or are wider than `width` if one line fits. Placeholders like `%ls` are measured as
+/// they are written.
+pub fn check_strings(layout: &TextLayout, strings: &Value, width: u32, max_lines: usize) -> Vec<String> {
+    let mut collected = Vec::new();
+    collect_strings("", strings, &mut collected);
+
+    let mut problems = Vec::new();
+    for (path, text) in collected {
+        let missing = layout.missing_glyphs(text);
+        if !missing.is_empty() {
+            let described: Vec<String> = missing.iter().map(|&c| format!("'{}' U+{:04X}", c, c as u32)).collect();
+            problems.push(format!("{}: no glyph for {}, the game draws '?'", path, described.join(", ")));
+        }
+        if max_lines <= 1 {
+            let text_width = layout.string_width(text);
+            if text_width > width {
+                problems.push(format!("{}: '{}' is {} pixels wide, {} fit", path, text, text_width, width));
+            }
+        } else {
+            let lines = layout.line_wrap(text, width).len();
+            if lines > max_lines {
+                problems.push(format!("{}: '{}' needs {} lines of {} pixels, {} fit", path, text, lines, width, max_lines));
+            }
+        }
+    }
+    problems
+}
+
+/// Checks the string resource `name` in `dir`, e.g. `new-strings`, in every resource version with its translation
+/// table, see `check_strings`. The problems start with the file and the version, the Russian versions share a file.
+pub fn check_string_widths(dir: &Path, name: &str, font: &Font, width: u32, max_lines: usize) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    for &version in ResourceVersion::all() {
+        let file_name = format!("{}-{}.json", name, localization::language_suffix(version));
+        match localization::read_json(&dir.join(&file_name)) {
+            Ok(strings) => {
+                let layout = TextLayout::new(font, version);
+                problems.extend(check_strings(&layout, &strings, width, max_lines).into_iter().map(|p| format!("{} ({}) {}", file_name, version, p)));
+            },
+            Err(e) => problems.push(e),
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate tempdir;
+
+    use std::fs;
+
+    use super::{check_string_widths, check_strings, Font, TextLayout, TranslationTable};
+    use test_utils::test_font;
+    use ResourceVersion;
+
+    #[test]
+    fn translation_table_should_follow_the_resource_version() {
+        let english = TranslationTable::for_version(ResourceVersion::GERMAN);
+        assert_eq!(english.glyph('A'), Some(0));
+        assert_eq!(english.glyph('a'), Some(26));
+        assert_eq!(english.glyph('\u{00DF}'), Some(99));
+        assert_eq!(english.glyph('\u{20AC}'), None);
+
+        let russian = TranslationTable::for_version(ResourceVersion::RUSSIAN);
+        assert_eq!(russian.glyph(' '), Some(0));
+        assert_eq!(russian.glyph('A'), Some(33));
+        assert_eq!(russian.glyph('\u{0436}'), Some(134));
+        assert_eq!(russian.glyph('\u{00DF}'), None);
+        assert_eq!(TranslationTable::for_version(ResourceVersion::FRENCH).glyph('\u{00CE}'), Some(169));
+    }
+
+    #[test]
+    fn line_wrap_should_split_at_spaces_and_hyphens() {
+        let font = Font::from_sti(&test_font()).unwrap();
+        let layout = TextLayout::new(&font, ResourceVersion::ENGLISH);
+
+        assert_eq!(font.height(), 6);
+        assert_eq!(layout.string_width("AB CD"), 18);
+        assert_eq!(layout.line_wrap("  Hello world  again", 20), vec!("Hello", "world", "again"));
+        assert_eq!(layout.line_wrap("well-known", 20), vec!("well-", "known"));
+        assert_eq!(layout.line_wrap("abcdefgh", 12), vec!("abc", "defg", "h"));
+        assert!(layout.line_wrap("   ", 20).is_empty());
+    }
+
+    #[test]
+    fn reduce_string_length_should_append_dots() {
+        let font = Font::from_sti(&test_font()).unwrap();
+        let layout = TextLayout::new(&font, ResourceVersion::ENGLISH);
+
+        assert_eq!(layout.reduce_string_length("abcdefgh", 24), "abc...");
+        assert_eq!(layout.reduce_string_length("ab", 24), "ab");
+        assert_eq!(layout.reduce_string_length("abcdefgh", 4), "...");
+    }
+
+    #[test]
+    fn draw_wrapped_should_draw_the_glyphs_line_by_line() {
+        let font = Font::from_sti(&test_font()).unwrap();
+        let layout = TextLayout::new(&font, ResourceVersion::ENGLISH);
+
+        let drawn = layout.draw_wrapped("AB C\u{20AC}", 10, 1);
+
+        assert_eq!((drawn.width, drawn.height), (10, 14));
+        assert_eq!(drawn.pixel(0, 0), 1);
+        assert_eq!(drawn.pixel(4, 5), 2);
+        assert_eq!(drawn.pixel(9, 0), 0);
+        assert_eq!(drawn.pixel(0, 6), 0);
+        assert_eq!(drawn.pixel(0, 7), 3);
+        assert_eq!(drawn.pixel(4, 7), 91);
+        assert_eq!(layout.to_image(&drawn).pixel(0, 7), [9, 10, 11, 255]);
+    }
+
+    #[test]
+    fn check_strings_should_report_overflows_and_missing_glyphs() {
+        let font = Font::from_sti(&test_font()).unwrap();
+        let layout = TextLayout::new(&font, ResourceVersion::ENGLISH);
+        let strings = ::serde_json::from_str(r#"{"short": "Short", "long": ["Much too long", "Euro €"]}"#).unwrap();
+
+        assert_eq!(check_strings(&layout, &strings, 20, 2), vec!(
+            "long[0]: 'Much too long' needs 3 lines of 20 pixels, 2 fit",
+            "long[1]: no glyph for '\u{20AC}' U+20AC, the game draws '?'",
+        ));
+        assert_eq!(check_strings(&layout, &strings, 30, 1)[0], "long[0]: 'Much too long' is 48 pixels wide, 30 fit");
+    }
+
+    #[test]
+    fn check_string_widths_should_check_every_language() {
+        let temp_dir = tempdir::TempDir::new("ja2-tests").unwrap();
+        for suffix in &["dut", "eng", "fr", "ger", "it", "pl"] {
+            fs::write(temp_dir.path().join(format!("test-{}.json", suffix)), r#"["Short", "Much too long"]"#).unwrap();
+        }
+        fs::write(temp_dir.path().join("test-rus.json"), "[\"\u{0416}\u{0443}\u{043A}\"]").unwrap();
+        let font = Font::from_sti(&test_font()).unwrap();
+
+        let problems = check_string_widths(temp_dir.path(), "test", &font, 30, 1);
+
+        assert_eq!(problems.len(), 8);
+        assert_eq!(problems[1], "test-eng.json (ENGLISH) [1]: 'Much too long' is 48 pixels wide, 30 fit");
+        assert_eq!(problems[6], "test-rus.json (RUSSIAN) [0]: no glyph for '\u{0416}' U+0416, '\u{0443}' U+0443, '\u{043A}' U+043A, the game draws '?'");
+    }
+}
@@ -19,6 +19,8 @@ extern "C" {
 	extern char * get_resource_version_string(GameVersion);
 	extern void free_rust_string(char *);
 	extern bool should_show_help(const engine_options_t *);
+	extern bool should_run_command(const engine_options_t *);
+	extern bool run_command(const engine_options_t *, const char *extra_data_dir);
 	extern bool should_run_unittests(const engine_options_t *);
 	extern bool should_run_editor(const engine_options_t *);
 	extern bool should_start_in_fullscreen(const engine_options_t *);
@@ -26,7 +28,74 @@ extern "C" {
 	extern bool should_start_in_window(const engine_options_t *);
 	extern bool should_start_in_debug_mode(const engine_options_t *);
 	extern bool should_start_without_sound(const engine_options_t *);
+	extern uint64_t get_random_seed(const engine_options_t *);
 	extern void set_start_without_sound(const engine_options_t *, bool);
 
-	extern char * find_ja2_executable(const char *);
+	/** Returns NULL if the game executable could not be found. Pass NULL as executable name to use the default one. */
+	extern char * find_ja2_executable(const char *launcher_path, const char *executable_name);
+
+	/* Launcher model, the check_ and set_resolution_from_string functions return an error message or NULL if the input is valid.
+	 * Out of range indexes return GV_ENGLISH from get_resource_version_at and 0 from get_recommended_resolution_x/y. */
+	extern uint32_t get_number_of_resource_versions();
+	extern GameVersion get_resource_version_at(uint32_t index);
+	extern uint32_t get_number_of_recommended_resolutions();
+	extern UINT16 get_recommended_resolution_x(uint32_t index);
+	extern UINT16 get_recommended_resolution_y(uint32_t index);
+	extern char * check_resolution(int32_t x, int32_t y);
+	extern char * check_resolution_string(const char *resolution);
+	extern char * set_resolution_from_string(engine_options_t *, const char *resolution);
+	extern char * check_vanilla_data_dir(const char *data_dir);
+	extern char * build_launch_command(const char *executable_path, bool editor);
+
+	/* In-game options of Ja2.set, set_game_setting returns an error message or NULL if the value is valid.
+	 * get_game_setting_key returns NULL if the index is out of range. */
+	typedef struct game_settings_S game_settings_t;
+	extern game_settings_t* read_game_settings(const engine_options_t *);
+	extern bool write_game_settings(const engine_options_t *, const game_settings_t *);
+	extern void free_game_settings(game_settings_t *);
+	extern uint32_t get_number_of_game_settings();
+	extern char * get_game_setting_key(uint32_t index);
+	extern char * get_game_setting(const game_settings_t *, const char *key);
+	extern char * set_game_setting(game_settings_t *, const char *key, const char *value);
+
+	/* Policy snapshots next to the saved games, check_saved_game_policy returns the rule changes or NULL if there are none. */
+	extern bool write_saved_game_policy(const engine_options_t *, const char *extra_data_dir, const char *save_path);
+	extern char * check_saved_game_policy(const engine_options_t *, const char *extra_data_dir, const char *save_path);
+
+	/* WAV, Ogg Vorbis or FLAC files decoded to signed 16-bit samples in the system byte order, load_audio returns NULL on error. */
+	typedef struct audio_data_S audio_data_t;
+	extern audio_data_t* load_audio(const uint8_t *data, size_t length, uint32_t sample_rate);
+	extern uint16_t get_audio_channels(const audio_data_t *);
+	extern size_t get_audio_number_of_samples(const audio_data_t *);
+	extern const int16_t * get_audio_samples(const audio_data_t *);
+	extern void free_audio_data(audio_data_t *);
+
+	/* Smacker videos decoded frame by frame to palette indexes, next_smacker_frame returns false after the last frame or on error. */
+	typedef struct smacker_video_S smacker_video_t;
+	extern smacker_video_t* open_smacker_video(const uint8_t *data, size_t length);
+	extern uint32_t get_smacker_width(const smacker_video_t *);
+	extern uint32_t get_smacker_height(const smacker_video_t *);
+	extern uint32_t get_smacker_frame_count(const smacker_video_t *);
+	extern uint32_t get_smacker_frame_duration_us(const smacker_video_t *);
+	extern bool next_smacker_frame(smacker_video_t *);
+	extern const uint8_t * get_smacker_palette(const smacker_video_t *);
+	extern const uint8_t * get_smacker_pixels(const smacker_video_t *);
+	extern audio_data_t* load_smacker_audio(const smacker_video_t *, uint8_t track, uint32_t sample_rate);
+	extern void free_smacker_video(smacker_video_t *);
+
+	/* Applies the log levels of ja2.json and --log and moves the SLOG log file to file_name in the stracciatella home. */
+	extern bool configure_logging(const engine_options_t *, const char *file_name);
+
+	/* The numbers of Random and PreRandom, the pregenerated numbers are copied from and to arrays of MAX_PREGENERATED_NUMS. */
+	extern void seed_random(uint64_t seed);
+	extern uint32_t random_below(uint32_t range);
+	extern uint32_t pre_random_below(uint32_t range);
+	extern uint64_t get_random_state(void);
+	extern void set_random_state(uint64_t state);
+	extern uint64_t get_random_increment(void);
+	extern void set_random_increment(uint64_t increment);
+	extern uint32_t get_pre_random_index(void);
+	extern void set_pre_random_index(uint32_t index);
+	extern void get_pre_random_nums(uint32_t *nums);
+	extern void set_pre_random_nums(const uint32_t *nums);
 }
@@ -1,4 +1,5 @@
 #include <stdexcept>
+#include <string>
 
 #include "Buffer.h"
 #include "Directories.h"
@@ -111,6 +112,8 @@
 #include "ContentManager.h"
 #include "GameInstance.h"
 #include "slog/slog.h"
+#include "RustInterface.h"
+#include "SGP.h"
 
 static const char g_quicksave_name[] = "QuickSave";
 static const char g_savegame_name[]  = "SaveGame";
@@ -180,6 +183,7 @@ static void SaveMeanwhileDefsToSaveGameFile(HWFILE);
 static void SaveMercProfiles(HWFILE);
 static void SaveOppListInfoToSavedGame(HWFILE);
 static void SavePreRandomNumbersToSaveGameFile(HWFILE);
+static void SaveRandomStateToSaveGameFile(HWFILE);
 static void SaveSoldierStructure(HWFILE hFile);
 static void SaveTacticalStatusToSavedGame(HWFILE);
 static void SaveWatchedLocsToSavedGame(HWFILE);
@@ -233,6 +237,8 @@ BOOLEAN SaveGame(UINT8 const ubSaveGameID, wchar_t const* GameDesc)
 	// Set the fact that we are saving a game
 	gTacticalStatus.uiFlags |= LOADING_SAVED_GAME;
 
+	// The name of the end turn save alternates with every call, it is created once for the save and the game policy
+	char savegame_name[512];
 	try
 	{
 		//Save the current sectors open temp files to the disk
@@ -255,7 +261,6 @@ BOOLEAN SaveGame(UINT8 const ubSaveGameID, wchar_t const* GameDesc)
 		FileMan::createDir(GCM->getSavedGamesFolder().c_str());
 
 		// Create the save game file
-		char savegame_name[512];
 		CreateSavedGameFileNameFromNumber(ubSaveGameID, savegame_name);
 		AutoSGPFile f(FileMan::openForWriting(savegame_name));
 
@@ -417,6 +422,8 @@ BOOLEAN SaveGame(UINT8 const ubSaveGameID, wchar_t const* GameDesc)
 		SaveLeaveItemList(f);
 
 		NewWayOfSavingBobbyRMailOrdersToSaveGameFile(f);
+
+		SaveRandomStateToSaveGameFile(f);
 	}
 	catch (...)
 	{
@@ -442,6 +449,12 @@ BOOLEAN SaveGame(UINT8 const ubSaveGameID, wchar_t const* GameDesc)
 
 	SaveGameSettings();
 
+	// Store the game policy and mods next to the saved game, loading compares them with the current ones
+	if (GetEngineOptions() && !write_saved_game_policy(GetEngineOptions(), GetExtraDataDir(), savegame_name))
+	{
+		SLOGW(DEBUG_TAG_SAVELOAD, "Could not store the game policy of '%s'", savegame_name);
+	}
+
 	// Display a screen message that the save was succesful (unless we are in Dead is Dead Mode to prevent message spamming)
 	if (ubSaveGameID != SAVE__END_TURN_NUM && gGameOptions.ubGameSaveMode != DIF_DEAD_IS_DEAD)
 	{
@@ -564,10 +577,12 @@ static void LoadGeneralInfo(HWFILE, UINT32 savegame_version);
 static void LoadMeanwhileDefsFromSaveGameFile(HWFILE, UINT32 savegame_version);
 static void LoadOppListInfoFromSavedGame(HWFILE);
 static void LoadPreRandomNumbersFromSaveGameFile(HWFILE);
+static void LoadRandomStateFromSaveGameFile(HWFILE);
 static void LoadSavedMercProfiles(HWFILE, UINT32 savegame_version, bool stracLinuxFormat);
 static void LoadSoldierStructure(HWFILE, UINT32 savegame_version, bool stracLinuxFormat);
 static void LoadTacticalStatusFromSavedGame(HWFILE, bool stracLinuxFormat);
 static void LoadWatchedLocsFromSavedGame(HWFILE);
+static void ReportGamePolicyChanges(char const* savegame_name);
 static void TruncateStrategicGroupSizes(void);
 static void UpdateMercMercContractInfo(void);
 
@@ -927,6 +942,12 @@ void LoadSavedGame(UINT8 const save_slot_id)
 		HandleOldBobbyRMailOrders();
 	}
 
+	// Older games continue with the numbers of the current start
+	if (version >= 101)
+	{
+		LoadRandomStateFromSaveGameFile(f);
+	}
+
 	BAR(1, L"Final Checks...");
 
 	// ATE: Patch? Patch up groups (will only do for old saves)
@@ -1121,6 +1142,8 @@ void LoadSavedGame(UINT8 const save_slot_id)
 	// 2. the ai may ignoring the CallAvailableEnemiesTo(...) because it wasn't
 	//		fully analyzed before
 	CallAvailableTeamEnemiesToAmbush(gMapInformation.sCenterGridNo);
+
+	ReportGamePolicyChanges(zSaveGameName);
 }
 
 
@@ -1944,20 +1967,70 @@ static void LoadGeneralInfo(HWFILE const f, UINT32 const savegame_version)
 static void SavePreRandomNumbersToSaveGameFile(HWFILE const hFile)
 {
 	//Save the Prerandom number index
-	FileWrite(hFile, &guiPreRandomIndex, sizeof(UINT32));
+	UINT32 const index = GetPreRandomIndex();
+	FileWrite(hFile, &index, sizeof(UINT32));
 
-	//Save the Prerandom number index
-	FileWrite(hFile, guiPreRandomNums, sizeof(UINT32) * MAX_PREGENERATED_NUMS);
+	//Save the Prerandom numbers
+	UINT32 nums[MAX_PREGENERATED_NUMS];
+	GetPreRandomNums(nums);
+	FileWrite(hFile, nums, sizeof(UINT32) * MAX_PREGENERATED_NUMS);
 }
 
 
 static void LoadPreRandomNumbersFromSaveGameFile(HWFILE const hFile)
 {
 	//Load the Prerandom number index
-	FileRead(hFile, &guiPreRandomIndex, sizeof(UINT32));
+	UINT32 index;
+	FileRead(hFile, &index, sizeof(UINT32));
+	SetPreRandomIndex(index);
+
+	//Load the Prerandom numbers
+	UINT32 nums[MAX_PREGENERATED_NUMS];
+	FileRead(hFile, nums, sizeof(UINT32) * MAX_PREGENERATED_NUMS);
+	SetPreRandomNums(nums);
+}
 
-	//Load the Prerandom number index
-	FileRead(hFile, guiPreRandomNums, sizeof(UINT32) * MAX_PREGENERATED_NUMS);
+
+static void SaveRandomStateToSaveGameFile(HWFILE const hFile)
+{
+	// Loading the game repeats the numbers of Random that followed the save
+	uint64_t const state     = GetRandomState();
+	uint64_t const increment = GetRandomIncrement();
+	FileWrite(hFile, &state,     sizeof(uint64_t));
+	FileWrite(hFile, &increment, sizeof(uint64_t));
+}
+
+
+static void LoadRandomStateFromSaveGameFile(HWFILE const hFile)
+{
+	uint64_t state;
+	uint64_t increment;
+	FileRead(hFile, &state,     sizeof(uint64_t));
+	FileRead(hFile, &increment, sizeof(uint64_t));
+	SetRandomIncrement(increment);
+	SetRandomState(state);
+}
+
+
+// Shows the changes of the game policy and mods since the game was saved, the game may not play as it did.
+static void ReportGamePolicyChanges(char const* const savegame_name)
+{
+	if (!GetEngineOptions()) return;
+
+	char* const changes = check_saved_game_policy(GetEngineOptions(), GetExtraDataDir(), savegame_name);
+	if (changes == NULL) return;
+
+	SLOGW(DEBUG_TAG_SAVELOAD, "The game policy changed since '%s' was saved:\n%s", savegame_name, changes);
+	ScreenMsg(FONT_MCOLOR_LTYELLOW, MSG_INTERFACE, L"The game policy changed since the game was saved:");
+	std::string const text(changes);
+	free_rust_string(changes);
+	for (size_t start = 0; start < text.size();)
+	{
+		size_t end = text.find('\n', start);
+		if (end == std::string::npos) end = text.size();
+		if (end > start) ScreenMsg(FONT_MCOLOR_LTYELLOW, MSG_INTERFACE, L"%hs", text.substr(start, end - start).c_str());
+		start = end + 1;
+	}
 }
 
 
@@ -134,6 +134,7 @@ struct AUTORESOLVE_STRUCT
 	UINT32 uiTotalElapsedBattleTimeInMilliseconds;
 	UINT32 uiPrevTime, uiCurrTime;
 	UINT32 uiPreRandomIndex;
+	uint64_t uiRandomState;
 
 	SGPBox rect;
 
@@ -451,7 +452,8 @@ void EnterAutoResolveMode( UINT8 ubSectorX, UINT8 ubSectorY )
 	gpAR->uiTotalElapsedBattleTimeInMilliseconds = 0;
 	gpAR->fSound = TRUE;
 	gpAR->fMoraleEventsHandled = FALSE;
-	gpAR->uiPreRandomIndex = guiPreRandomIndex;
+	gpAR->uiPreRandomIndex = GetPreRandomIndex();
+	gpAR->uiRandomState = GetRandomState();
 
 	//Determine who gets the defensive advantage
 	switch( gubEnemyEncounterCode )
@@ -2215,7 +2217,8 @@ static void CreateTempPlayerMerc(void);
 // Debug utilities
 static void ResetAutoResolveInterface(void)
 {
-	guiPreRandomIndex = gpAR->uiPreRandomIndex;
+	SetPreRandomIndex(gpAR->uiPreRandomIndex);
+	SetRandomState(gpAR->uiRandomState);
 
 	RemoveAutoResolveInterface(false);
 
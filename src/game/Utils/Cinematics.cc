@@ -68,10 +68,8 @@ BOOLEAN SmkPollFlics(void)
 			SmackDoFrame(smkobj);
 		}
 
-		// Check to see if the flic is done the last frame
-		//printf ("smk->FrameNum %u\n", smk->FrameNum);
-		// if (smk->FrameNum == smk->Frames - 1)
-		if (i->SmackerStatus == SMK_LAST )
+		// Check to see if the flic is done the last frame, skipped past it or failed
+		if (i->SmackerStatus != SMK_MORE)
 		{
 			if (i->uiFlags & SMK_FLIC_AUTOCLOSE) SmkCloseFlic(i);
 		}
@@ -160,6 +158,8 @@ try
 		return NULL;
 	}
 
+	sf->SmackerStatus = sf->SmackerObject->Frames > 1 ? SMK_MORE : SMK_LAST;
+
 	// Make sure we have a video surface
 	SmkSetupVideo();
 
@@ -1,3 +1,4 @@
+#include <cstdint>
 #include <string>
 #include "FL/Fl_Native_File_Chooser.H"
 #include <FL/Fl_PNG_Image.H>
@@ -10,33 +11,15 @@
 
 #define LAUNCHER_TOPIC DEBUG_TAG_LAUNCHER
 
-#define RESOLUTION_SEPARATOR "x"
-
-
-const char* defaultResolution = "640x480";
-const char* predefinedVersions[] = {
-	"DUTCH",
-	"ENGLISH",
-	"FRENCH",
-	"GERMAN",
-	"ITALIAN",
-	"POLISH",
-	"RUSSIAN",
-	"RUSSIAN_GOLD",
-	NULL
-};
 
 Launcher::Launcher(const std::string exePath, engine_options_t* engine_options) : StracciatellaLauncher() {
 	this->exePath = exePath;
 	this->engine_options = engine_options;
 	this->predefinedResolutions = std::vector< std::pair<int, int> >();
 
-	this->predefinedResolutions.push_back(std::make_pair(640,  480));
-	this->predefinedResolutions.push_back(std::make_pair(800,  600));
-	this->predefinedResolutions.push_back(std::make_pair(1024, 768));
-	this->predefinedResolutions.push_back(std::make_pair(1280, 720));
-	this->predefinedResolutions.push_back(std::make_pair(1600, 900));
-	this->predefinedResolutions.push_back(std::make_pair(1920, 1080));
+	for (uint32_t i = 0; i < get_number_of_recommended_resolutions(); i++) {
+		this->predefinedResolutions.push_back(std::make_pair(get_recommended_resolution_x(i), get_recommended_resolution_y(i)));
+	}
 }
 
 void Launcher::show() {
@@ -83,7 +66,8 @@ void Launcher::initializeInputsFromDefaults() {
 		predefinedResolutionInput->value(resolutionString);
 		enablePredefinedResolutions();
 	} else {
-		predefinedResolutionInput->value(defaultResolution);
+		sprintf(resolutionString, "%dx%d", predefinedResolutions[0].first, predefinedResolutions[0].second);
+		predefinedResolutionInput->value(resolutionString);
 		enableCustomResolutions();
 	}
 
@@ -102,11 +86,11 @@ int Launcher::writeJsonFile() {
 						(int)customResolutionXInput->value(),
 						(int)customResolutionYInput->value());
 	} else {
-		std::string res = predefinedResolutionInput->value();
-		int split_index = res.find(RESOLUTION_SEPARATOR);
-		int x = atoi(res.substr(0, split_index).c_str());
-		int y = atoi(res.substr(split_index+1, res.length()).c_str());
-		set_resolution(this->engine_options, x, y);
+		char* error = set_resolution_from_string(this->engine_options, predefinedResolutionInput->value());
+		if (error != NULL) {
+			SLOGW(LAUNCHER_TOPIC, "%s", error);
+			free_rust_string(error);
+		}
 	}
 
 	set_resource_version(this->engine_options, gameVersionInput->value());
@@ -122,8 +106,10 @@ int Launcher::writeJsonFile() {
 }
 
 void Launcher::populateChoices() {
-	for (int i=0; predefinedVersions[i] != NULL; i++) {
-		gameVersionInput->add(predefinedVersions[i]);
+	for (uint32_t i = 0; i < get_number_of_resource_versions(); i++) {
+		char* version = get_resource_version_string(get_resource_version_at(i));
+		gameVersionInput->add(version);
+		free_rust_string(version);
 	}
 	for (int i=0; i < predefinedResolutions.size(); i++) {
 		char resolutionString[255];
@@ -177,29 +163,44 @@ void Launcher::enableCustomResolutionSelection(Fl_Widget* btn, void* userdata) {
 	window->enableCustomResolutions();
 }
 
-void Launcher::startExecutable(bool asEditor) {
-	// check minimal resolution:
-	if (customResolutionButton->value() &&
-		(customResolutionXInput->value() < 640 ||
-		customResolutionYInput->value() < 480)) {
-		fl_alert("Invalid custom resolution %dx%d.\nJA2 Stracciatella needs a resolution of at least 640x480.",
-			(int) customResolutionXInput->value(),
-			(int) customResolutionYInput->value());
-		return;
-	}
+// Fl_Value_Input has no maximum, clamp before converting so check_resolution sees the out of range value.
+static int32_t resolutionInputValue(const Fl_Value_Input* input) {
+	double const value = input->value();
+	if (!(value >= INT32_MIN)) return INT32_MIN; // also NaN
+	if (value > INT32_MAX) return INT32_MAX;
+	return (int32_t)value;
+}
 
-	std::string cmd("\"" + this->exePath + "\"");
+bool Launcher::validateInputs() {
+	char* error = check_vanilla_data_dir(dataDirectoryInput->value());
+	if (error == NULL) {
+		if (customResolutionButton->value()) {
+			error = check_resolution(resolutionInputValue(customResolutionXInput), resolutionInputValue(customResolutionYInput));
+		} else {
+			error = check_resolution_string(predefinedResolutionInput->value());
+		}
+	}
 
-	if (asEditor) {
-		cmd += std::string(" -editor");
+	if (error != NULL) {
+		fl_alert("%s", error);
+		free_rust_string(error);
+		return false;
 	}
+	return true;
+}
 
-	system(cmd.c_str());
+void Launcher::startExecutable(bool asEditor) {
+	char* cmd = build_launch_command(this->exePath.c_str(), asEditor);
+	system(cmd);
+	free_rust_string(cmd);
 }
 
 void Launcher::startGame(Fl_Widget* btn, void* userdata) {
 	Launcher* window = static_cast< Launcher* >( userdata );
 
+	if (!window->validateInputs()) {
+		return;
+	}
 	window->writeJsonFile();
 	window->startExecutable(false);
 }
@@ -207,6 +208,9 @@ void Launcher::startGame(Fl_Widget* btn, void* userdata) {
 void Launcher::startEditor(Fl_Widget* btn, void* userdata) {
 	Launcher* window = static_cast< Launcher* >( userdata );
 
+	if (!window->validateInputs()) {
+		return;
+	}
 	window->writeJsonFile();
 	window->startExecutable(true);
 }
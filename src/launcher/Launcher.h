@@ -20,6 +20,7 @@ private:
 	void populateChoices();
 	void enablePredefinedResolutions();
 	void enableCustomResolutions();
+	bool validateInputs();
 	void startExecutable(bool asEditor);
 	static void openDataDirectorySelector(Fl_Widget *btn, void *userdata);
 	static void enablePredefinedResolutionSelection(Fl_Widget* btn, void* userdata);
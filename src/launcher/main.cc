@@ -6,10 +6,14 @@
 #include "RustInterface.h"
 
 int main(int argc, char* argv[]) {
-	SLOG_Init(SLOG_STDERR, "stracciatella-launcher.log");
+	SLOG_Init(SLOG_STDERR, NULL);
 	SLOG_SetLevel(SLOG_WARNING, SLOG_WARNING);
 
-	char* rustExePath = find_ja2_executable(argv[0]);
+	char* rustExePath = find_ja2_executable(argv[0], JA2_BINARY_NAME);
+	if (rustExePath == NULL) {
+		SLOGE(DEBUG_TAG_LAUNCHER, "Could not find the game executable '%s'", JA2_BINARY_NAME);
+		return EXIT_FAILURE;
+	}
 	std::string exePath = std::string(rustExePath);
 	free_rust_string(rustExePath);
 
@@ -21,6 +25,7 @@ int main(int argc, char* argv[]) {
 	if (should_show_help(params)) {
 		return EXIT_SUCCESS;
 	}
+	configure_logging(params, "stracciatella-launcher.log");
 
 	Launcher launcher(exePath, params);
 
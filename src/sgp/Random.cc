@@ -1,38 +1,18 @@
 #include "Random.h"
-#include <stdlib.h>
-#include <time.h>
+#include "RustInterface.h"
 
-UINT32 guiPreRandomIndex = 0;
-UINT32 guiPreRandomNums[ MAX_PREGENERATED_NUMS ];
-
-void InitializeRandom(void)
+void InitializeRandom(uint64_t const seed)
 {
-	// Seed the random-number generator with current time so that
-	// the numbers will be different every time we run.
-	srand( (unsigned) time(NULL) );
-	//Pregenerate all of the random numbers.
-	for( guiPreRandomIndex = 0; guiPreRandomIndex < MAX_PREGENERATED_NUMS; guiPreRandomIndex++ )
-	{
-		guiPreRandomNums[ guiPreRandomIndex ] = rand();
-	}
-	guiPreRandomIndex = 0;
+	// The numbers are generated in rust/src/random.rs, the same seed
+	// gives the same numbers on every platform.
+	seed_random(seed);
 }
 
 // Returns a pseudo-random integer between 0 and uiRange
 UINT32 Random(UINT32 uiRange)
 {
-	UINT32 x;
 	// Always return 0, if no range given (it's not an error)
-	if (uiRange == 0)
-		return(0);
-	/* Ensures a correct average value by actually limiting the possible
-	 * set of values to the largest multiple of uiRange and
-	 * discarding [largest multiple of uiRange beneath RAND_MAX,RAND_MAX].
-	 * The rather complex limitation ensures a correct behaviour even
-	 * for very large (close to RAND_MAX) values of uiRange.
-	 */
-	do { x = rand(); } while ( x >= (((RAND_MAX - uiRange + 1)/uiRange+1)*uiRange));
-	return x % uiRange;
+	return random_below(uiRange);
 }
 
 BOOLEAN Chance( UINT32 uiChance )
@@ -42,28 +22,50 @@ BOOLEAN Chance( UINT32 uiChance )
 
 UINT32 PreRandom( UINT32 uiRange )
 {
-	UINT32 uiNum;
-	if( !uiRange )
-		return 0;
-	//Extract the current pregenerated number
-	/* HACK0007 Stop PreRandom always returning 0 or 1
-	 * without ensuring an equal distribution, which
-	 * would be a rather complex task with pregenerated randoms
-	 */
-	uiNum = guiPreRandomNums[ guiPreRandomIndex ] % uiRange;
-	//Replace the current pregenerated number with a new one.
-
-	//This was removed in the name of optimization.  Uncomment if you hate recycling.
-	//guiPreRandomNums[ guiPreRandomIndex ] = rand();
-
-	//Go to the next index.
-	guiPreRandomIndex++;
-	if( guiPreRandomIndex >= (UINT32)MAX_PREGENERATED_NUMS )
-		guiPreRandomIndex = 0;
-	return uiNum;
+	return pre_random_below(uiRange);
 }
 
 BOOLEAN PreChance( UINT32 uiChance )
 {
 	return PreRandom(100) < uiChance;
 }
+
+UINT32 GetPreRandomIndex(void)
+{
+	return get_pre_random_index();
+}
+
+void SetPreRandomIndex(UINT32 const uiIndex)
+{
+	set_pre_random_index(uiIndex);
+}
+
+void GetPreRandomNums(UINT32 nums[MAX_PREGENERATED_NUMS])
+{
+	get_pre_random_nums(nums);
+}
+
+void SetPreRandomNums(const UINT32 nums[MAX_PREGENERATED_NUMS])
+{
+	set_pre_random_nums(nums);
+}
+
+uint64_t GetRandomState(void)
+{
+	return get_random_state();
+}
+
+void SetRandomState(uint64_t const state)
+{
+	set_random_state(state);
+}
+
+uint64_t GetRandomIncrement(void)
+{
+	return get_random_increment();
+}
+
+void SetRandomIncrement(uint64_t const increment)
+{
+	set_random_increment(increment);
+}
@@ -4,7 +4,8 @@
 #include "Types.h"
 
 
-extern void InitializeRandom(void);
+//Seeds Random and PreRandom, the seed of a start can be given with --seed to replay it.
+extern void InitializeRandom(uint64_t seed);
 extern UINT32 Random( UINT32 uiRange );
 
 //Chance( 74 ) returns TRUE 74% of the time.  If uiChance >= 100, then it will always return TRUE.
@@ -18,7 +19,19 @@ extern BOOLEAN PreChance( UINT32 uiChance );
 //IMPORTANT:  Changing this define will invalidate the JA2 save.  If this
 //						is necessary, please ifdef your own value.
 #define MAX_PREGENERATED_NUMS			256
-extern UINT32 guiPreRandomIndex;
-extern UINT32 guiPreRandomNums[ MAX_PREGENERATED_NUMS ];
+
+//The position in and the contents of the pregenerated numbers, stored in the save games.
+extern UINT32 GetPreRandomIndex(void);
+extern void SetPreRandomIndex(UINT32 uiIndex);
+extern void GetPreRandomNums(UINT32 nums[MAX_PREGENERATED_NUMS]);
+extern void SetPreRandomNums(const UINT32 nums[MAX_PREGENERATED_NUMS]);
+
+//The state of Random, restoring it repeats the numbers since it was taken.
+extern uint64_t GetRandomState(void);
+extern void SetRandomState(uint64_t state);
+
+//The increment selects the sequence of Random, it is stored in the save games with the state.
+extern uint64_t GetRandomIncrement(void);
+extern void SetRandomIncrement(uint64_t increment);
 
 #endif
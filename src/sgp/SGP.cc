@@ -260,12 +260,32 @@ static void MainLoop(int msPerGameCycle)
 
 ContentManager *GCM = NULL;
 
+static engine_options_t* g_engine_options = NULL;
+static std::string g_extra_data_dir;
+
+engine_options_t const* GetEngineOptions()
+{
+	return g_engine_options;
+}
+
+char const* GetExtraDataDir()
+{
+	return g_extra_data_dir.c_str();
+}
+
 ////////////////////////////////////////////////////////////
 
 int main(int argc, char* argv[])
 {
 	std::string exeFolder = FileMan::getParentPath(argv[0], true);
 
+	std::string extraDataDir = EXTRA_DATA_DIR;
+	if(extraDataDir.empty())
+	{
+		// use location of the exe file
+		extraDataDir = exeFolder;
+	}
+
 #if defined BROKEN_SWPRINTF
 	if (setlocale(LC_CTYPE, "UTF-8") == NULL)
 	{
@@ -273,8 +293,8 @@ int main(int argc, char* argv[])
 	}
 #endif
 
-	// init logging
-	SLOG_Init(SLOG_STDERR, "ja2.log");
+	// init logging, the log file is opened in the stracciatella home by configure_logging
+	SLOG_Init(SLOG_STDERR, NULL);
 	SLOG_SetLevel(SLOG_WARNING, SLOG_WARNING);
 
 	engine_options_t* params = create_engine_options(argv, argc);
@@ -286,6 +306,13 @@ int main(int argc, char* argv[])
 		return EXIT_SUCCESS;
 	}
 
+	if (should_run_command(params)) {
+		// maintenance commands run entirely in rust, without SDL
+		bool success = run_command(params, extraDataDir.c_str());
+		free_engine_options(params);
+		return success ? EXIT_SUCCESS : EXIT_FAILURE;
+	}
+
 	if (should_start_in_fullscreen(params)) {
 		VideoSetFullScreen(TRUE);
 	} else if (should_start_in_window(params)) {
@@ -296,21 +323,14 @@ int main(int argc, char* argv[])
 		SoundEnableSound(FALSE);
 	}
 
-	// Disable sound when using SDL2 2.0.6:
-	SDL_version sdl_version_linked;
-	SDL_GetVersion(&sdl_version_linked);
-	if (sdl_version_linked.major == 2 && sdl_version_linked.minor == 0 && sdl_version_linked.patch == 6) {
-		SLOGE(DEBUG_TAG_SGP, "Detected SDL2 2.0.6. Disabled sound.\n"
-							 "This version of SDL2 has a fatal bug in the audio conversion routines.\n"
-							 "Either downgrade to version 2.0.5 or upgrade to version 2.0.7 or later.");
-		SoundEnableSound(FALSE);
-	}
-
 	if (should_start_in_debug_mode(params)) {
 		SLOG_SetLevel(SLOG_DEBUG, SLOG_DEBUG);
 		GameState::getInstance()->setDebugging(true);
 	}
 
+	configure_logging(params, "ja2.log");
+	uint64_t const randomSeed = get_random_seed(params);
+
 	if (should_run_editor(params)) {
 		GameState::getInstance()->setEditorMode(false);
 	}
@@ -361,13 +381,6 @@ int main(int argc, char* argv[])
 	free_rust_string(rustConfigFolderPath);
 	free_rust_string(rustResRootPath);
 
-	std::string extraDataDir = EXTRA_DATA_DIR;
-	if(extraDataDir.empty())
-	{
-		// use location of the exe file
-		extraDataDir = exeFolder;
-	}
-
 	std::string externalizedDataPath = FileMan::joinPaths(extraDataDir, "externalized");
 
 	FileMan::switchTmpFolder(configFolderPath);
@@ -410,7 +423,9 @@ int main(int argc, char* argv[])
 		SLOGI(DEBUG_TAG_SGP,"------------------------------------------------------------------------------");
 	}
 
-		free_engine_options(params);
+	// kept for the saved games, they store the rules they were played with
+	g_engine_options = params;
+	g_extra_data_dir = extraDataDir;
 
 	std::vector<std::string> libraries = cm->getListOfGameResources();
 	cm->initGameResouces(configFolderPath, libraries);
@@ -446,8 +461,8 @@ int main(int argc, char* argv[])
 #endif
 
 		SLOGD(DEBUG_TAG_SGP, "Initializing Random");
-		// Initialize random number generator
-		InitializeRandom(); // no Shutdown
+		// Initialize random number generator, the seed is always written to the log
+		InitializeRandom(randomSeed); // no Shutdown
 
 		SLOGD(DEBUG_TAG_SGP, "Initializing Game Manager");
 		// Initialize the Game
@@ -488,6 +503,9 @@ int main(int argc, char* argv[])
 	delete cm;
 	GCM = NULL;
 
+	free_engine_options(g_engine_options);
+	g_engine_options = NULL;
+
 	return EXIT_SUCCESS;
 }
 
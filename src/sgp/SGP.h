@@ -7,4 +7,12 @@
  * Call this function if you want to exit the game. */
 void requestGameExit();
 
+typedef struct engine_options_S engine_options_t;
+
+/** The engine options of this start, valid until the game exits. */
+engine_options_t const* GetEngineOptions();
+
+/** The directory with the externalized data and the mods. */
+char const* GetExtraDataDir();
+
 #endif
@@ -2,161 +2,72 @@
 #if !defined(_MSC_VER)
 	#include <strings.h>
 #endif
+#include "Buffer.h"
 #include "Smack_Stub.h"
 #include "Sound_Control.h"
 #include "SoundMan.h"
 #include "FileMan.h"
-
-extern "C" {
-#include "smacker.h"
-}
+#include "MemMan.h"
 
 #define SMKTRACK 0
 
 typedef unsigned char   UCHAR8;
 
-BOOLEAN SmackCheckStatus(CHAR8 smkstatus)
-{
-	if (smkstatus <0)
-	{
-		printf ("SmackLibrary returned an error!\n");
-		return TRUE;
-	}
-	return FALSE;
-}
-
-BOOLEAN SmkVideoSwitch(smk SmkObj, BOOLEAN sw)
+// Plays the first audio track, decoded and resampled to the rate of the audio device in Rust
+static UINT32 SmackPlayAudio(smacker_video_t* video)
 {
-	return (SmackCheckStatus(smk_enable_video(SmkObj, sw)));
-} 
+	UINT32 const sample_rate = SoundGetSampleRate();
+	if (sample_rate == 0) return NO_SAMPLE;
 
-BOOLEAN SmkAudioSwitch(smk SmkObj, BOOLEAN sw)
-{
-	return SmackCheckStatus(smk_enable_audio(SmkObj, SMKTRACK, sw));
-}
+	audio_data_t* const audio = load_smacker_audio(video, SMKTRACK, sample_rate);
+	if (audio == NULL) return NO_SAMPLE;
 
-void SmackPrintFlickInfo(unsigned long width, unsigned long height, UCHAR8 scale, unsigned long framecount, 
-			DOUBLE usf, UCHAR8 a_channels, UCHAR8 a_depth, UCHAR8 a_rate)
-{
-	printf ("Video -- Frames: %lu Width: %lu Height: %lu Scale: %d \n", framecount, width, height, scale);
-	printf ("Audio -- FPS: %2.2f  Channels: %d Depth: %d Rate %d\n", usf/1000, a_channels, a_depth, a_rate);
-}
-
-// read all smackaudio and convert it to 22050Hz on the fly (44100 originally)
-UINT32 SmackGetAudio(const smk SmkObj, const INT16* audiobuffer)
-{
-	UINT32 n_samples = 0;
-	UINT32 i, index;
-	INT16 *paudio = (INT16*) audiobuffer;
-	if (!audiobuffer)
-		return 0;
-	SmkAudioSwitch(SmkObj, ENABLE);
-	smk_first(SmkObj);
-	do
+	// SoundPlayFromBuffer plays stereo, mono tracks use the same sample for both channels
+	UINT16       const channels = get_audio_channels(audio);
+	UINT32       const frames   = UINT32(get_audio_number_of_samples(audio) / channels);
+	INT16 const* const samples  = get_audio_samples(audio);
+	INT16*       const buffer   = MALLOCN(INT16, frames * 2);
+	for (UINT32 i = 0; i < frames; i++)
 	{
-		const UINT32 smacklen = smk_get_audio_size (SmkObj, SMKTRACK);
-		const INT16 *smackaudio = (INT16*) smk_get_audio (SmkObj, SMKTRACK);
-
-		memcpy(paudio, smackaudio, smacklen);
-		paudio += smacklen / 2;
-
-		n_samples += smacklen / 2;
+		buffer[i * 2]     = samples[i * channels];
+		buffer[i * 2 + 1] = samples[i * channels + channels - 1];
 	}
-	while(smk_next(SmkObj) != SMK_DONE);
-	SmkAudioSwitch(SmkObj, DISABLE);
-	return n_samples;
-}
-
-void SmackWriteAudio(INT16* abuffer, UINT32 size)
-{
-	FILE* fp = fopen("/tmp/smk.raw", "wb");
-	fwrite(abuffer, size, 1, fp);
-	fclose(fp);
-}
+	free_audio_data(audio);
 
-UCHAR8* SmackToMemory(SGPFile* File)
-{
-	UCHAR8* smacktomemory;
-	UINT32 FileSize=FileGetSize(File);
-
-	smacktomemory = (UCHAR8*) malloc (FileSize);
-	if(!smacktomemory)
-		return NULL;
-	FileRead(File, smacktomemory, FileSize);
-	return smacktomemory;
+	if (frames == 0)
+	{
+		MemFree(buffer);
+		return NO_SAMPLE;
+	}
+	return SoundPlayFromBuffer(buffer, frames, MAXVOLUME, 64, 1, NULL, NULL);
 }
 
 
 Smack* SmackOpen(SGPFile* FileHandle, UINT32 Flags, UINT32 ExtraFlag)
 {
-	Smack* flickinfo;
-	// smacklib info types
-	unsigned long frame, framecount, width, height;
-	UCHAR8 scale;
-	DOUBLE usf;
-	CHAR8 smkstatus;
-	/* arrays for audio track metadata */
-	UCHAR8 a_depth[7], a_channels[7];
-	unsigned long a_rate[7];
-	unsigned long audiolen, audiosamples;
-	INT16* audiobuffer;
-	UCHAR8* smackloaded;
-	UINT32 smacksize = FileGetSize(FileHandle);
-	flickinfo = (Smack*) malloc(sizeof(Smack));
-	if(!flickinfo)
-		return NULL;
-
-	smackloaded = SmackToMemory (FileHandle);
-	if (!smackloaded)
-	{
-		free(flickinfo);
-		return NULL;
-	}
-	flickinfo->SmackerInMemory = smackloaded;
-	flickinfo->Smacker = smk_open_memory(smackloaded, smacksize);
-	//open file with given filehandle DISK/MEMORY mode
-	//flickinfo->Smacker = smk_open_generic(1, fp, 0, SMK_MODE_DISK);
-	//flickinfo->Smacker = smk_open_generic(1, fp, 0, SMK_MODE_MEMORY);
-	if(!flickinfo->Smacker)
-	{
-		free(flickinfo);
-		return NULL;
-	}
+	UINT32 const smacksize = FileGetSize(FileHandle);
+	SGP::Buffer<UINT8> smackloaded(smacksize);
+	FileRead(FileHandle, smackloaded, smacksize);
 
-	smkstatus = smk_info_video(flickinfo->Smacker, &width, &height, &scale);
-	SmackCheckStatus(smkstatus);
-	smkstatus = smk_info_all(flickinfo->Smacker, &frame, &framecount, &usf);
-	SmackCheckStatus(smkstatus);
-	smkstatus = smk_info_audio(flickinfo->Smacker, NULL, a_channels, a_depth, a_rate);
-	SmackCheckStatus(smkstatus);
+	smacker_video_t* const video = open_smacker_video(smackloaded, smacksize);
+	if (!video) return NULL;
 
-	SmkVideoSwitch(flickinfo->Smacker, DISABLE);
+	Smack* const flickinfo = new Smack();
+	flickinfo->Smacker         = video;
+	flickinfo->Width           = get_smacker_width(video);
+	flickinfo->Height          = get_smacker_height(video);
+	flickinfo->Frames          = get_smacker_frame_count(video);
+	flickinfo->FramesPerSecond = get_smacker_frame_duration_us(video);
+	flickinfo->SoundTag        = SmackPlayAudio(video);
 
-	flickinfo->Frames=framecount;
-	flickinfo->Height=height;
-	flickinfo->Width=width;
-	flickinfo->FramesPerSecond = usf;
-	// calculated audio memory for downsampling 44100->22050
-	audiosamples = ((flickinfo->Frames / (usf/1000)) * (a_rate[SMKTRACK]) * 16 *  a_channels[SMKTRACK]);
-	audiobuffer = (INT16*) malloc(audiosamples);
-	if (!audiobuffer)
+	// Decode the first frame
+	if (!next_smacker_frame(video))
 	{
-		free(flickinfo);
+		SLOGE(DEBUG_TAG_SMK, "Could not decode the first frame of the video");
+		SmackClose(flickinfo);
 		return NULL;
 	}
-	audiolen = SmackGetAudio(flickinfo->Smacker, audiobuffer);
-	//SmackWriteAudio( audiobuffer, audiolen); // are getting right audio data?
-	// shoot and forget... audiobuffer should be freed by SoundMan
-	if (audiolen > 0)
-		flickinfo->SoundTag = SoundPlayFromBuffer(audiobuffer, audiolen, MAXVOLUME, 64, 1, NULL, NULL);
-	else free(audiobuffer), audiobuffer = NULL;
-	SmkVideoSwitch  (flickinfo->Smacker, ENABLE);
-	if ((smk_first(flickinfo->Smacker) < 0))
-	{
-		printf("First Failed!");
-		return NULL;
-	}
-	smk_first(flickinfo->Smacker);
+	flickinfo->FrameNum = 0;
 	flickinfo->LastTick = SDL_GetTicks();
 	return flickinfo;
 }
@@ -213,9 +124,12 @@ UINT32 SmackDoFrame(Smack* Smk)
 
 CHAR8 SmackNextFrame(Smack* Smk)
 {
-	CHAR8 smkstatus;
-	smkstatus = smk_next(Smk->Smacker);
-	return smkstatus;
+	if (!next_smacker_frame(Smk->Smacker))
+	{
+		return Smk->FrameNum + 1 < Smk->Frames ? SMK_ERROR : SMK_DONE;
+	}
+	Smk->FrameNum++;
+	return Smk->FrameNum + 1 == Smk->Frames ? SMK_LAST : SMK_MORE;
 }
 
 UINT32 SmackWait(Smack* Smk)
@@ -226,21 +140,21 @@ UINT32 SmackWait(Smack* Smk)
 
 void SmackClose(Smack* Smk)
 {
-	if(!SoundStop(Smk->SoundTag))
-		printf("Error in SmackClose SoundStop\n");
-	free(Smk->SmackerInMemory);
-	smk_close(Smk->Smacker); // closes and frees Smacker Object and file
+	// The sound may already have ended
+	if (Smk->SoundTag != NO_SAMPLE) SoundStop(Smk->SoundTag);
+	free_smacker_video(Smk->Smacker);
+	delete Smk;
 }
 
 void SmackToBuffer(Smack* Smk, UINT32 Left, UINT32 Top, UINT32 Pitch, UINT32 DestHeight, UINT32 DestWidth, void* Buf, UINT32 Flags)
 {
-	unsigned char* smackframe, *pframe;
-	unsigned char* smackpal;
+	const unsigned char* smackframe, *pframe;
+	const unsigned char* smackpal;
 	UINT16 i,j,pixel,*buf;
-	UINT8 *rgb;
+	const UINT8 *rgb;
 	UINT32 halfpitch = Pitch / 2;
-	smackframe = smk_get_video(Smk->Smacker);
-	smackpal = smk_get_palette (Smk->Smacker);
+	smackframe = get_smacker_pixels(Smk->Smacker);
+	smackpal = get_smacker_palette(Smk->Smacker);
 	// dump_bmp (smackpal, smackframe, 640, 480, Smk->FrameNum);
 	buf=(UINT16*)Buf;
 	pframe=smackframe;
@@ -3,9 +3,7 @@
 
 #include "Types.h"
 #include "SDL.h"
-extern "C" {
-#include "smacker.h"
-}
+#include "RustInterface.h"
 
 enum
 {
@@ -24,6 +22,15 @@ enum
 	SMACKAUTOBLIT = 0
 };
 
+// Results of SmackNextFrame
+enum
+{
+	SMK_DONE  = 0x00,
+	SMK_MORE  = 0x01,
+	SMK_LAST  = 0x02,
+	SMK_ERROR = -1
+};
+
 enum
 {
 	SMACKFILEHANDLE = 0x01000,
@@ -41,13 +48,13 @@ enum
 
 struct Smack
 {
-	smk Smacker; //object pointer type for libsmacker
-	unsigned char* SmackerInMemory;
+	smacker_video_t* Smacker; // decoder of the Rust library
 	UINT32 SoundTag; // for soundman
 	UINT32 Height;
 	UINT32 Width;
 	UINT32 Frames;
-	UINT32 FramesPerSecond;
+	UINT32 FrameNum; // index of the decoded frame
+	UINT32 FramesPerSecond; // actually microseconds per frame
 	UINT32 LastTick;
 };
 
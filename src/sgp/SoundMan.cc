@@ -18,6 +18,7 @@
 
 #include "ContentManager.h"
 #include "GameInstance.h"
+#include "RustInterface.h"
 #include "slog/slog.h"
 
 
@@ -219,6 +220,11 @@ UINT32 SoundPlayFromBuffer(INT16* pbuffer, UINT32 size, UINT32 volume, UINT32 pa
 	return SoundStartSample(buffertag, channel, volume, pan, loop, end_callback, data);
 }
 
+UINT32 SoundGetSampleRate(void)
+{
+	return fSoundSystemInit ? gTargetAudioSpec.freq : 0;
+}
+
 UINT32 SoundPlayStreamedFile(const char* pFilename, UINT32 volume, UINT32 pan, UINT32 loop, void (*end_callback)(void*), void* data)
 try
 {
@@ -598,45 +604,32 @@ static SAMPLETAG* SoundLoadDisk(const char* pFilename)
 
 	memset(s, 0, sizeof(*s));
 
-	SDL_RWops* rwOps = FileGetRWOps(hFile);
-	SDL_AudioSpec wavSpec;
-	Uint32 wavLength;
-	Uint8 *wavBuffer;
-	SDL_AudioCVT cvt;
+	UINT32 const uiFileSize = FileGetSize(hFile);
+	SGP::Buffer<UINT8> fileData(uiFileSize);
+	FileRead(hFile, fileData, uiFileSize);
 
-	if (SDL_LoadWAV_RW(rwOps, 0,  &wavSpec, &wavBuffer, &wavLength) == NULL) {
-		SLOGE(DEBUG_TAG_SOUND, "Error loading sound file: %s", SDL_GetError());
+	// Decoded and resampled to the rate of the audio device in Rust
+	audio_data_t* const audio = load_audio(fileData, uiFileSize, gTargetAudioSpec.freq);
+	if (audio == NULL) {
+		SLOGE(DEBUG_TAG_SOUND, "Error loading sound file: %s", pFilename);
 		return NULL;
 	}
 
-	SDL_BuildAudioCVT(&cvt, wavSpec.format, wavSpec.channels, wavSpec.freq, gTargetAudioSpec.format, wavSpec.channels, gTargetAudioSpec.freq);
-	cvt.len = wavLength;
-	cvt.buf = MALLOCN(UINT8, cvt.len * cvt.len_mult);
-	memcpy(cvt.buf, wavBuffer, wavLength);
-	SDL_FreeWAV(wavBuffer);
-	SDL_FreeRW(rwOps);
-
-	if (cvt.needed) {
-		if (SDL_ConvertAudio(&cvt) != 0) {
-			SLOGE(DEBUG_TAG_SOUND, "Error converting sound file: %s", SDL_GetError());
-			return NULL;
-		};
-	}
-
-	UINT32 convertedSize = cvt.len * cvt.len_ratio;
+	UINT16 const channels      = get_audio_channels(audio);
+	UINT32 const convertedSize = UINT32(get_audio_number_of_samples(audio) * sizeof(INT16));
 
 	strcpy(s->pName, pFilename);
-	s->n_samples = UINT32(convertedSize / (wavSpec.channels * 2));
+	s->n_samples = UINT32(convertedSize / (channels * 2));
 	s->uiFlags     |= SAMPLE_ALLOCATED;
-	if (wavSpec.channels != 1) {
+	if (channels != 1) {
 		s->uiFlags |= SAMPLE_STEREO;
 	}
 
 	s->uiInstances  = 0;
 	s->pData = MALLOCN(UINT8, convertedSize);
-	memcpy(s->pData, cvt.buf, convertedSize);
+	memcpy(s->pData, get_audio_samples(audio), convertedSize);
 
-	free(cvt.buf);
+	free_audio_data(audio);
 
 	IncreaseSoundMemoryUsedBySample(s);
 
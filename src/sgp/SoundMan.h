@@ -20,8 +20,15 @@ void InitializeSoundManager(void);
 void ShutdownSoundManager(void);
 
 
+/* Plays a buffer of interleaved stereo samples at the rate of the audio
+ * device, size is the number of samples per channel. The buffer is freed by
+ * the sound manager. */
 UINT32 SoundPlayFromBuffer(INT16* pbuffer, UINT32 size, UINT32 volume, UINT32 pan, UINT32 loop, void (*end_callback)(void*), void* data);
 
+/* Returns the sample rate of the audio device, sounds are resampled to it when
+ * they are loaded. 0 if the sound hardware is not started. */
+UINT32 SoundGetSampleRate(void);
+
 
 /* Starts a sample playing. If the sample is not loaded in the cache, it will
  * be found and loaded.
@@ -28,103 +28,32 @@
 #include "slog.h"
 
 #include <stdarg.h>
+#include <stdbool.h>
 #include <stdio.h>
-#include <time.h>
+#include <stdint.h>
 #include <stdlib.h>
 
 #if defined(_MSC_VER)
   /* Visual Studio */
-  #include <io.h>
-  #define write _write
-  #define snprintf(buf, size, format, ...) _snprintf_s(buf, size, _TRUNCATE, format, ##__VA_ARGS__)
   #define vsnprintf(buf, size, format, args) vsnprintf_s(buf, size, _TRUNCATE, format, args)
-  #pragma warning( disable : 4996 )  /* disable some VS warnings, e.g. 'fopen': This function or variable may be unsafe. */
-#else
-  #include <unistd.h>
+  #pragma warning( disable : 4996 )  /* disable some VS warnings */
 #endif
 
 
 /************************************************************
- * Static data
+ * Rust backend
+ *
+ * Filtering, formatting and the log files are implemented in
+ * rust/src/logging.rs, only the printf style formatting of
+ * the messages is done here.
  ************************************************************/
 
-static int s_initialized = 0;
-static int s_consoleFD;         /**< Console file descriptor. 0 - no, 1 - stdout, 2 - stderr. */
-static FILE *s_logFile = NULL;  /**< File for logging. */
-
-static SLOGLevel s_consoleLevel = SLOG_INFO;
-static SLOGLevel s_fileLevel = SLOG_DEBUG;
-
-/* gDebugFlags contains one flag per topic to enable (1) or disable (0) logging
- * default is all on for now. Watch the variable width when adding more topics! */
-static long long gDebugFlags;
-
-static char SLOGTags[NUMBER_OF_TOPICS][TAG_LENGTH + 1] =
-{
-  "Game Loop",
-  "Strategic Map",
-  "AI",
-  "Scheduler",
-  "Path AI",
-  "Animations",
-  "Gap",
-  "Weapons",
-  "Overhead",
-  "Keys",
-  "Points",
-  "Morale",
-  "Merc Hire",
-  "Opp List",
-  "Lighting",
-  "Render World",
-  "Tiles",
-  "Explosion",
-  "Physics",
-  "Ambient",
-  "Save/Load",
-  "Music Control",
-  "Smacker",
-  "Event Pump",
-  "Quests",
-  "Editor",
-  "Resources",
-  "JA2 Screens",
-  "Init",
-  "Font",
-  "SGP",
-  "STCI",
-  "Container",
-  "Video",
-  "VSurface",
-  "Sound",
-  "Memory Manager",
-  "Game Screen",
-  "Bobby Ray",
-  "Strategic AI",
-  "Air Raid",
-  "Bullets",
-  "Handle Items",
-  "Interface",
-  "Line of Sight",
-  "Tactical Save",
-  "Team Turns",
-  "World Def",
-  "Himage",
-  "Library DB",
-  "File Man",
-  "Mod Pack",
-  "Default CM",
-  "Soldier",
-  "Asserts",
-  "Queen Command",
-  "NPC",
-  "Campaign",
-  "Loyalty",
-  "Auto Resolve",
-  "Corpses",
-  "Fixme",
-  "Launcher"
-};
+extern void init_logging(uint32_t console, const char *logFile);
+extern void deinit_logging(void);
+extern void set_log_levels(uint32_t console, uint32_t file);
+extern void set_log_topic_enabled(uint32_t topic, bool enabled);
+extern bool is_log_enabled(uint32_t level, uint32_t topic);
+extern bool write_log_message(uint32_t level, uint32_t topic, const char *message);
 
 /************************************************************
  * Function implementation
@@ -132,125 +61,53 @@ static char SLOGTags[NUMBER_OF_TOPICS][TAG_LENGTH + 1] =
 
 void SLOG_Init(SLOGConsole console, const char *logFile)
 {
-  if(!s_initialized)
-  {
-    gDebugFlags = 0xFFFFFFFFFFFFFFFF;
-    switch(console)
-    {
-    case SLOG_STDOUT:
-      s_consoleFD = 1;
-      break;
-    case SLOG_STDERR:
-      s_consoleFD = 2;
-      break;
-    default:
-      s_consoleFD = 0;
-    }
-
-    if(logFile)
-    {
-      s_logFile = fopen(logFile, "wt");
-      /* ignoring any errors */
-    }
-
-    s_initialized = 1;
-  }
+  init_logging(console, logFile);
 }
 
-/** @brief Library deinitialization.
- *
- * This function should be called before program exit only once. */
 void SLOG_Deinit()
 {
-  if(s_logFile)
-  {
-    fclose(s_logFile);
-    s_logFile = NULL;
-  }
+  deinit_logging();
 }
 
-
 void SLOG_SetLevel(SLOGLevel console, SLOGLevel file)
 {
-  s_consoleLevel = console;
-  s_fileLevel = file;
+  set_log_levels(console, file);
 }
 
 void SLOG_EnableTopic (SLOGTopics topic)
 {
-  gDebugFlags |= (1 << topic);
+  set_log_topic_enabled(topic, true);
 }
 
 void SLOG_DisableTopic (SLOGTopics topic)
 {
-  gDebugFlags &= ~(1 << topic);
-}
-
-/** Get level name for including into the log. */
-static const char* getLevelName(SLOGLevel level)
-{
-  switch(level)
-  {
-  case SLOG_DEBUG:      return "DEBUG";
-  case SLOG_INFO:       return "INFO";
-  case SLOG_WARNING:    return "WARNING";
-  case SLOG_ERROR:      return "ERROR";
-  }
-  return "";
+  set_log_topic_enabled(topic, false);
 }
 
 void SLOG_LogMessage(SLOGLevel level, SLOGTopics tag, const char *format, ...)
 {
-  int logToConsole = (s_consoleFD != 0) && (level >= s_consoleLevel);
-  int logToFile = (s_logFile != NULL) && (level >= s_fileLevel);
+  va_list args;
+  char buf[1024];
+  bool logged;
 
-  if ( !(gDebugFlags & (1 << tag))) return;
-
-  if(logToConsole || logToFile)
+  /* Most messages are below the levels, skip formatting them */
+  if (!is_log_enabled(level, tag))
   {
-    struct tm *localTime;
-    time_t currentTime;
-    va_list args;
-    char buf[256];
-    int size = 0;
-
-    currentTime = time(NULL);
-    localTime = localtime(&currentTime);
-
-    /* print time, level and tag */
-    size += snprintf(buf + size, sizeof(buf) - size, "%4d/%02d/%02d %02d:%02d:%02d %-7s [%-16s] ",
-                     localTime->tm_year + 1900, localTime->tm_mon, localTime->tm_mday,
-                     localTime->tm_hour, localTime->tm_min, localTime->tm_sec,
-                     getLevelName(level), SLOGTags[tag]);
-
-    /* print message */
-    va_start(args, format);
-    size += vsnprintf(buf + size, sizeof(buf) - size, format, args);
-    va_end(args);
-
-    if(size >= (int)sizeof(buf))
-    {
-      /* there were not enough space in the buf */
-      size = sizeof(buf) - 1;
-    }
-
-    buf[size] = 0;
+    return;
+  }
 
-    if(logToConsole)
-    {
-      write(s_consoleFD, buf, size);
-    }
+  va_start(args, format);
+  vsnprintf(buf, sizeof(buf), format, args);
+  va_end(args);
+  buf[sizeof(buf) - 1] = 0;
 
-    if(logToFile)
-    {
-      fputs(buf, s_logFile);
-      fflush(s_logFile);
-    }
+  logged = write_log_message(level, tag, buf);
 #ifdef ENABLE_ASSERTS
-    if (tag == DEBUG_TAG_ASSERTS)
-    {
-      abort();
-    }
-#endif
+  if (logged && tag == DEBUG_TAG_ASSERTS)
+  {
+    abort();
   }
+#else
+  (void)logged;
+#endif
 }